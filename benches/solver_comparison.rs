@@ -1,5 +1,9 @@
+#[path = "solver_comparison/results.rs"]
+mod results;
+
 use batsat::{dimacs::parse, lbool, BasicCallbacks, Solver as BatSatSolver, SolverInterface};
 use prettytable::{format, Cell, Row, Table};
+use results::{compare_with_baseline, results_to_json, MeasurementResult, Status};
 use screwsat::solver::Solver as ScrewSatSolver;
 use splr::{SolveIF, Solver as SplrSolver};
 use std::{
@@ -13,31 +17,12 @@ use std::{
 use varisat::Solver as VariSatSolver;
 
 const FILE_PATH: &str = "tests/test_data/random";
-
-#[derive(Default, Debug)]
-pub(crate) enum Status {
-    #[default]
-    UNSAT,
-    SAT,
-}
-
-impl ToString for Status {
-    fn to_string(&self) -> String {
-        match self {
-            Status::UNSAT => "UNSAT",
-            Status::SAT => "SAT",
-        }
-        .to_string()
-    }
-}
-
-#[derive(Default, Debug)]
-pub(crate) struct MeasurementResult {
-    pub solver_name: String,
-    pub setup_time: u64,
-    pub solve_time: u64,
-    pub status: Status,
-}
+// Path to a `results.json` of an earlier run. When set, the run is compared
+// against it and fails if any measurement regressed.
+const BASELINE_ENV: &str = "BENCH_BASELINE";
+// Allowed slowdown relative to the baseline, in percent.
+const THRESHOLD_ENV: &str = "BENCH_THRESHOLD";
+const DEFAULT_THRESHOLD: u64 = 20;
 
 #[derive(Default)]
 struct ScrewSat {
@@ -211,7 +196,7 @@ fn for_each_dimacs_file<C: CNFSolverMeasurement>(
                 res.setup_time = C::measured_setup(solver, path.as_path()).unwrap();
                 (res.solve_time, res.status) = C::measured_solve(solver).unwrap();
 
-                results.entry(file_stem).or_insert_with(Vec::new).push(res);
+                results.entry(file_stem).or_default().push(res);
             }
         }
     }
@@ -243,7 +228,10 @@ fn output_results(results: &BTreeMap<OsString, Vec<MeasurementResult>>) -> std::
 
     table.printstd();
     let mut result_table = File::create("results_table.txt")?;
-    write!(result_table, "{}", table)
+    write!(result_table, "{}", table)?;
+
+    let mut result_json = File::create("results.json")?;
+    write!(result_json, "{:#}", results_to_json(results))
 }
 
 fn main() -> std::io::Result<()> {
@@ -260,5 +248,27 @@ fn main() -> std::io::Result<()> {
     for_each_dimacs_file::<Splr>(FILE_PATH, &mut Splr::default(), &mut results)?;
     for_each_dimacs_file::<BatSat>(FILE_PATH, &mut BatSat::default(), &mut results)?;
 
-    output_results(&results)
+    output_results(&results)?;
+
+    if let Ok(baseline_path) = std::env::var(BASELINE_ENV) {
+        let threshold = std::env::var(THRESHOLD_ENV)
+            .ok()
+            .and_then(|t| t.parse().ok())
+            .unwrap_or(DEFAULT_THRESHOLD);
+        let baseline = std::fs::read_to_string(&baseline_path)?;
+        let regressions = compare_with_baseline(&results, &baseline, threshold)?;
+        if !regressions.is_empty() {
+            eprintln!(
+                "Regressions beyond {}% against {}:",
+                threshold, baseline_path
+            );
+            regressions.iter().for_each(|r| eprintln!("  {}", r));
+            std::process::exit(1);
+        }
+        println!(
+            "No regressions beyond {}% against {}",
+            threshold, baseline_path
+        );
+    }
+    Ok(())
 }
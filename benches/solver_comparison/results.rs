@@ -0,0 +1,99 @@
+// The results of the solver comparison bench, and their comparison against a
+// baseline. Kept apart from the bench, whose harness runs no tests, so that
+// its tests can include it, see `results_tests.rs`.
+
+use serde_json::{json, Value};
+use std::{collections::BTreeMap, ffi::OsString};
+
+// Measurements faster than this (in usecs) are too noisy to compare.
+const NOISE_FLOOR: u64 = 1000;
+
+#[allow(clippy::upper_case_acronyms)]
+#[derive(Default, Debug)]
+pub(crate) enum Status {
+    #[default]
+    UNSAT,
+    SAT,
+}
+
+impl std::fmt::Display for Status {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Status::UNSAT => write!(f, "UNSAT"),
+            Status::SAT => write!(f, "SAT"),
+        }
+    }
+}
+
+#[derive(Default, Debug)]
+pub(crate) struct MeasurementResult {
+    pub solver_name: String,
+    pub setup_time: u64,
+    pub solve_time: u64,
+    pub status: Status,
+}
+
+pub(crate) fn results_to_json(results: &BTreeMap<OsString, Vec<MeasurementResult>>) -> Value {
+    let mut entries = vec![];
+    for (file_name, measurements) in results {
+        for measurement in measurements {
+            entries.push(json!({
+                "file_name": file_name.to_string_lossy(),
+                "solver_name": measurement.solver_name,
+                "setup_time": measurement.setup_time,
+                "solve_time": measurement.solve_time,
+                "status": measurement.status.to_string(),
+            }));
+        }
+    }
+    Value::Array(entries)
+}
+
+// Compares the total (setup + solve) time of every measurement with the one
+// in `baseline`, the `results.json` of an earlier run, returning a
+// description of each regression beyond `threshold` percent.
+pub(crate) fn compare_with_baseline(
+    results: &BTreeMap<OsString, Vec<MeasurementResult>>,
+    baseline: &str,
+    threshold: u64,
+) -> std::io::Result<Vec<String>> {
+    let baseline: Value = serde_json::from_str(baseline)
+        .map_err(|e| std::io::Error::new(std::io::ErrorKind::InvalidData, e))?;
+    let text = |value: &Value| value.as_str().unwrap_or_default().to_string();
+    let mut baseline_times = BTreeMap::new();
+    for entry in baseline.as_array().into_iter().flatten() {
+        let key = (text(&entry["file_name"]), text(&entry["solver_name"]));
+        let time = entry["setup_time"].as_u64().unwrap_or_default()
+            + entry["solve_time"].as_u64().unwrap_or_default();
+        baseline_times.insert(key, (time, text(&entry["status"])));
+    }
+
+    let mut regressions = vec![];
+    for (file_name, measurements) in results {
+        for measurement in measurements {
+            let key = (
+                file_name.to_string_lossy().to_string(),
+                measurement.solver_name.clone(),
+            );
+            let Some((baseline_time, baseline_status)) = baseline_times.get(&key) else {
+                continue;
+            };
+            if *baseline_status != measurement.status.to_string() {
+                regressions.push(format!(
+                    "{} {}: status changed from {} to {}",
+                    key.0, key.1, baseline_status, measurement.status
+                ));
+            }
+            let time = measurement.setup_time + measurement.solve_time;
+            if time.max(*baseline_time) >= NOISE_FLOOR
+                && time * 100 > baseline_time * (100 + threshold)
+            {
+                regressions.push(format!(
+                    "{} {}: {} usecs, baseline {} usecs",
+                    key.0, key.1, time, baseline_time
+                ));
+            }
+        }
+    }
+    Ok(regressions)
+}
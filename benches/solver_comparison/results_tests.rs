@@ -0,0 +1,62 @@
+// The tests of the solver comparison bench results, a target of their own
+// as the bench runs without the test harness.
+
+#[path = "results.rs"]
+mod results;
+
+use results::{compare_with_baseline, results_to_json, MeasurementResult, Status};
+use std::{collections::BTreeMap, ffi::OsString};
+
+#[test]
+fn test_compare_with_baseline() -> Result<(), Box<dyn std::error::Error>> {
+    let measurement = |solver_name: &str, setup_time, solve_time, status| MeasurementResult {
+        solver_name: solver_name.to_string(),
+        setup_time,
+        solve_time,
+        status,
+    };
+    let results = |batsat_solve_time, splr_status| {
+        BTreeMap::from([(
+            OsString::from("network_a"),
+            vec![
+                measurement("BatSat", 1000, batsat_solve_time, Status::UNSAT),
+                measurement("Splr", 10, 20, splr_status),
+            ],
+        )])
+    };
+    let baseline = results_to_json(&results(9000, Status::SAT));
+    assert_eq!(baseline.as_array().map(Vec::len), Some(2));
+    assert_eq!(baseline[0]["file_name"], "network_a");
+    assert_eq!(baseline[0]["solver_name"], "BatSat");
+    assert_eq!(baseline[0]["setup_time"], 1000);
+    assert_eq!(baseline[0]["solve_time"], 9000);
+    assert_eq!(baseline[0]["status"], "UNSAT");
+    let baseline = serde_json::to_string_pretty(&baseline)?;
+
+    assert!(compare_with_baseline(&results(9000, Status::SAT), &baseline, 20)?.is_empty());
+    // 20% slower in total, and faster
+    assert!(compare_with_baseline(&results(11000, Status::SAT), &baseline, 20)?.is_empty());
+    assert!(compare_with_baseline(&results(10, Status::SAT), &baseline, 20)?.is_empty());
+    assert_eq!(
+        compare_with_baseline(&results(11001, Status::SAT), &baseline, 20)?,
+        vec!["network_a BatSat: 12001 usecs, baseline 10000 usecs"]
+    );
+    assert!(compare_with_baseline(&results(11001, Status::SAT), &baseline, 21)?.is_empty());
+    // the Splr measurement is below the noise floor, whatever its slowdown
+    let mut slow_splr = results(9000, Status::SAT);
+    slow_splr.get_mut(&OsString::from("network_a")).unwrap()[1].solve_time = 900;
+    assert!(compare_with_baseline(&slow_splr, &baseline, 20)?.is_empty());
+    assert_eq!(
+        compare_with_baseline(&results(9000, Status::UNSAT), &baseline, 20)?,
+        vec!["network_a Splr: status changed from SAT to UNSAT"]
+    );
+    // measurements missing from the baseline are skipped
+    let mut new_network = results(9000, Status::SAT);
+    new_network.insert(
+        OsString::from("network_b"),
+        vec![measurement("BatSat", 1, 1_000_000, Status::SAT)],
+    );
+    assert!(compare_with_baseline(&new_network, &baseline, 20)?.is_empty());
+    assert!(compare_with_baseline(&new_network, "not json", 20).is_err());
+    Ok(())
+}
@@ -0,0 +1,74 @@
+use crate::{
+    fbas::FbasError, AnalysisReport, Callbacks, FbasAnalyzer, MultiNetworkReport, TrustReport,
+};
+use std::collections::BTreeMap;
+
+// Aliases map validator keys to display names. They only apply when results
+// are rendered: analyses keep identifying validators by key, and results can
+// be fed back to the analyzer until aliases are applied to them. Validators
+// without an alias keep their key. Aliases need not be unique, so a renamed
+// result can't always be mapped back.
+
+fn alias(aliases: &BTreeMap<String, String>, v: &mut String) {
+    if let Some(name) = aliases.get(v.as_str()) {
+        v.clone_from(name);
+    }
+}
+
+impl<Cb: Callbacks> FbasAnalyzer<Cb> {
+    /// Returns the display names the input gives its nodes, e.g. the `name`
+    /// fields of the stellarbeats format, to pass to `get_potential_split_aliased`
+    /// or the reports' `apply_aliases`, possibly amended.
+    pub fn aliases(&self) -> BTreeMap<String, String> {
+        self.fbas
+            .metadata
+            .iter()
+            .filter_map(|(key, meta)| Some((key.clone(), meta.name.clone()?)))
+            .collect()
+    }
+
+    /// Same as `get_potential_split`, displaying validators by their alias
+    /// in `aliases` if they have one.
+    pub fn get_potential_split_aliased(
+        &self,
+        aliases: &BTreeMap<String, String>,
+    ) -> Result<(Vec<String>, Vec<String>), FbasError> {
+        let (mut qa, mut qb) = self.get_potential_split()?;
+        for v in qa.iter_mut().chain(qb.iter_mut()) {
+            alias(aliases, v);
+        }
+        Ok((qa, qb))
+    }
+}
+
+impl AnalysisReport {
+    /// Displays the validators of the potential split by their alias in
+    /// `aliases`, if they have one.
+    pub fn apply_aliases(&mut self, aliases: &BTreeMap<String, String>) {
+        let (qa, qb) = &mut self.potential_split;
+        for v in qa.iter_mut().chain(qb.iter_mut()) {
+            alias(aliases, v);
+        }
+    }
+}
+
+impl TrustReport {
+    /// Displays the validators of the asymmetric relations by their alias in
+    /// `aliases`, if they have one.
+    pub fn apply_aliases(&mut self, aliases: &BTreeMap<String, String>) {
+        for (truster, trusted) in self.asymmetric.iter_mut() {
+            alias(aliases, truster);
+            alias(aliases, trusted);
+        }
+    }
+}
+
+impl MultiNetworkReport {
+    /// Displays the validators in the report of every network by their alias
+    /// in `aliases`, if they have one.
+    pub fn apply_aliases(&mut self, aliases: &BTreeMap<String, String>) {
+        for report in self.networks.values_mut() {
+            report.apply_aliases(aliases);
+        }
+    }
+}
@@ -0,0 +1,233 @@
+use crate::{fbas::FbasError, FbasAnalyzer, SolveStatus};
+use batsat::callbacks::Basic;
+use serde_json::{json, Map, Value};
+use std::collections::BTreeMap;
+
+// Analyses are looked up by name and configured with JSON, so that a front
+// end (a command line tool, a monitoring service, a plan of analyses read
+// from a file) can run the ones it is asked for, its own and those of other
+// crates alike, without knowing any of them. Their results are JSON too, to
+// be reported as they come. An analysis that needs more than the qsets says
+// so with its required inputs, checked before it runs.
+//
+// The built-in analyses wrap the methods of the analyzer of the same name,
+// solving with the default callbacks. They are registered by
+// `AnalysisRegistry::with_builtins`.
+
+/// What an `Analysis` needs from the network besides its qsets.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum AnalysisInput {
+    /// The organizations running the validators, see
+    /// `FbasAnalyzer::organizations`.
+    Organizations,
+}
+
+impl AnalysisInput {
+    fn is_available(self, analyzer: &FbasAnalyzer<Basic>) -> bool {
+        match self {
+            AnalysisInput::Organizations => analyzer.organizations().orgs().next().is_some(),
+        }
+    }
+}
+
+/// An analysis run by name with a JSON configuration, e.g. from a plan of
+/// analyses, see `AnalysisRegistry`.
+pub trait Analysis: Send + Sync {
+    /// The name the analysis is registered and looked up by.
+    fn name(&self) -> &str;
+
+    /// The inputs the analysis needs besides the qsets. It isn't run on
+    /// networks without them.
+    fn required_inputs(&self) -> &[AnalysisInput] {
+        &[]
+    }
+
+    /// Runs the analysis on the network of `analyzer`, with the settings in
+    /// `config` (`Value::Null` for the defaults), and returns its result.
+    /// Invalid settings are reported as `FbasError::InvalidAnalysis`.
+    fn run(&self, analyzer: &mut FbasAnalyzer<Basic>, config: &Value) -> Result<Value, FbasError>;
+}
+
+/// Analyses by name, to run the ones asked for by name.
+#[derive(Default)]
+pub struct AnalysisRegistry {
+    analyses: BTreeMap<String, Box<dyn Analysis>>,
+}
+
+impl AnalysisRegistry {
+    /// A registry with no analyses.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// A registry with the analyses of this crate: "intersection",
+    /// "top_tier", "minimal_blocking_sets", "minimal_splitting_sets" and
+    /// "critical_organizations".
+    pub fn with_builtins() -> Self {
+        let mut registry = Self::new();
+        registry.register(Intersection);
+        registry.register(TopTier);
+        registry.register(MinimalBlockingSets);
+        registry.register(MinimalSplittingSets);
+        registry.register(CriticalOrganizations);
+        registry
+    }
+
+    /// Adds `analysis`, replacing any analysis of the same name, built-in
+    /// ones included.
+    pub fn register(&mut self, analysis: impl Analysis + 'static) {
+        self.analyses
+            .insert(analysis.name().to_string(), Box::new(analysis));
+    }
+
+    pub fn get(&self, name: &str) -> Option<&dyn Analysis> {
+        self.analyses.get(name).map(|analysis| analysis.as_ref())
+    }
+
+    /// The names of the analyses, in name order.
+    pub fn names(&self) -> impl Iterator<Item = &str> {
+        self.analyses.keys().map(|name| name.as_str())
+    }
+
+    /// Runs the analysis named `name`, see `Analysis::run`. Fails with
+    /// `FbasError::InvalidAnalysis` if there is none, or if the network
+    /// lacks one of its required inputs.
+    pub fn run(
+        &self,
+        name: &str,
+        analyzer: &mut FbasAnalyzer<Basic>,
+        config: &Value,
+    ) -> Result<Value, FbasError> {
+        let analysis = self
+            .get(name)
+            .ok_or_else(|| FbasError::InvalidAnalysis(format!("unknown analysis \"{}\"", name)))?;
+        if let Some(input) = analysis
+            .required_inputs()
+            .iter()
+            .find(|input| !input.is_available(analyzer))
+        {
+            return Err(FbasError::InvalidAnalysis(format!(
+                "analysis \"{}\" requires {:?}, which the network lacks",
+                name, input
+            )));
+        }
+        analysis.run(analyzer, config)
+    }
+
+    /// Runs every analysis whose required inputs the network has, each with
+    /// its entry of `config` (an object keyed by analysis name), and returns
+    /// their results keyed by analysis name.
+    pub fn run_all(
+        &self,
+        analyzer: &mut FbasAnalyzer<Basic>,
+        config: &Value,
+    ) -> Result<Value, FbasError> {
+        let mut results = Map::new();
+        for (name, analysis) in &self.analyses {
+            if analysis
+                .required_inputs()
+                .iter()
+                .all(|input| input.is_available(analyzer))
+            {
+                let result = analysis.run(analyzer, &config[name.as_str()])?;
+                results.insert(name.clone(), result);
+            }
+        }
+        Ok(Value::Object(results))
+    }
+}
+
+// The size limit of set enumerations, from `config["max_size"]`.
+fn max_size(config: &Value, default: usize) -> Result<usize, FbasError> {
+    match &config["max_size"] {
+        Value::Null => Ok(default),
+        value => value
+            .as_u64()
+            .and_then(|size| size.try_into().ok())
+            .ok_or_else(|| FbasError::InvalidAnalysis(format!("invalid max_size {}", value))),
+    }
+}
+
+// Whether the network has quorum intersection, with the potential split if
+// it doesn't.
+struct Intersection;
+
+impl Analysis for Intersection {
+    fn name(&self) -> &str {
+        "intersection"
+    }
+
+    fn run(&self, analyzer: &mut FbasAnalyzer<Basic>, _: &Value) -> Result<Value, FbasError> {
+        let status = match analyzer.try_solve()? {
+            SolveStatus::SAT(_) => "SAT",
+            SolveStatus::UNSAT => "UNSAT",
+            SolveStatus::UNKNOWN => "UNKNOWN",
+        };
+        let (quorum_a, quorum_b) = analyzer.get_minimal_split()?;
+        Ok(json!({ "status": status, "split": [quorum_a, quorum_b] }))
+    }
+}
+
+// `FbasAnalyzer::top_tier`, null if not found.
+struct TopTier;
+
+impl Analysis for TopTier {
+    fn name(&self) -> &str {
+        "top_tier"
+    }
+
+    fn run(&self, analyzer: &mut FbasAnalyzer<Basic>, _: &Value) -> Result<Value, FbasError> {
+        Ok(json!(analyzer.top_tier(Basic::default)?))
+    }
+}
+
+// `FbasAnalyzer::minimal_blocking_sets`, up to 3 validators by default.
+struct MinimalBlockingSets;
+
+impl Analysis for MinimalBlockingSets {
+    fn name(&self) -> &str {
+        "minimal_blocking_sets"
+    }
+
+    fn run(&self, analyzer: &mut FbasAnalyzer<Basic>, config: &Value) -> Result<Value, FbasError> {
+        let sets = analyzer.minimal_blocking_sets(max_size(config, 3)?)?;
+        Ok(json!(sets))
+    }
+}
+
+// `FbasAnalyzer::minimal_splitting_sets`, up to 2 validators by default.
+struct MinimalSplittingSets;
+
+impl Analysis for MinimalSplittingSets {
+    fn name(&self) -> &str {
+        "minimal_splitting_sets"
+    }
+
+    fn run(&self, analyzer: &mut FbasAnalyzer<Basic>, config: &Value) -> Result<Value, FbasError> {
+        let found = analyzer.minimal_splitting_sets(max_size(config, 2)?, Basic::default)?;
+        Ok(json!({ "sets": found.sets, "complete": found.complete }))
+    }
+}
+
+// `FbasAnalyzer::critical_organizations` of the organizations of the network.
+struct CriticalOrganizations;
+
+impl Analysis for CriticalOrganizations {
+    fn name(&self) -> &str {
+        "critical_organizations"
+    }
+
+    fn required_inputs(&self) -> &[AnalysisInput] {
+        &[AnalysisInput::Organizations]
+    }
+
+    fn run(&self, analyzer: &mut FbasAnalyzer<Basic>, _: &Value) -> Result<Value, FbasError> {
+        let critical =
+            analyzer.critical_organizations(&analyzer.organizations(), Basic::default)?;
+        Ok(json!({
+            "availability": critical.availability,
+            "intersection": critical.intersection,
+            "unknown": critical.unknown,
+        }))
+    }
+}
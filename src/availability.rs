@@ -0,0 +1,52 @@
+use crate::{fbas::FbasError, FbasAnalyzer};
+use batsat::Callbacks;
+use std::collections::BTreeSet;
+
+// Crashed validators are simply absent, so this is the liveness counterpart
+// of the intersection check and needs no SAT call: the validators that can
+// still reach agreement are those of the largest quorum avoiding the crashed
+// ones, see `Fbas::max_quorum`, as the union of quorums is a quorum.
+
+/// Outcome of `FbasAnalyzer::availability`.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Availability {
+    /// Whether the validators that didn't crash still contain a quorum.
+    pub quorum_exists: bool,
+    /// The validators that didn't crash and are in some quorum of those,
+    /// i.e. can still externalize, in validator order.
+    pub available: Vec<String>,
+    /// The validators that didn't crash but are in no such quorum, in
+    /// validator order.
+    pub blocked: Vec<String>,
+}
+
+impl<Cb: Callbacks> FbasAnalyzer<Cb> {
+    /// Checks which validators can still make progress if the `crashed`
+    /// validators are down. Returns an error for validators unknown to the
+    /// network.
+    pub fn availability(&self, crashed: &[&str]) -> Result<Availability, FbasError> {
+        let crashed = crashed
+            .iter()
+            .map(|v| self.fbas.try_get_validator_index(v))
+            .collect::<Result<BTreeSet<_>, _>>()?;
+        let quorum = self.fbas.max_quorum(&crashed);
+        let mut availability = Availability {
+            quorum_exists: !quorum.is_empty(),
+            ..Default::default()
+        };
+        for ni in self
+            .fbas
+            .validators
+            .iter()
+            .filter(|ni| !crashed.contains(ni))
+        {
+            let name = self.fbas.try_get_validator_string(ni)?;
+            if quorum.contains(ni) {
+                availability.available.push(name);
+            } else {
+                availability.blocked.push(name);
+            }
+        }
+        Ok(availability)
+    }
+}
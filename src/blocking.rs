@@ -0,0 +1,255 @@
+use crate::{fbas::FbasError, pruning::main_component, FbasAnalyzer};
+use batsat::Callbacks;
+use itertools::Itertools;
+use petgraph::graph::NodeIndex;
+use std::collections::{BTreeMap, BTreeSet};
+
+// A blocking set is a set of validators whose failure leaves the others
+// without any quorum, i.e. it blocks liveness. Being blocking is upward
+// closed, so enumerating candidates by increasing size and skipping the
+// supersets of sets already found yields exactly the minimal ones. Each
+// candidate is checked with `Fbas::max_quorum`, no SAT call is needed.
+//
+// Every minimal quorum lies within a strongly connected component, and a set is
+// blocking iff it intersects every minimal quorum, so when a single component
+// contains a quorum (see `main_component`), only its validators are
+// candidates. Otherwise all validators are. The number of candidates still
+// grows exponentially with the size of the sets, hence the size limit, and
+// the callbacks checked between batches.
+//
+// The candidates of one size are independent, so with the `rayon` feature
+// they are checked in parallel, batch by batch. Results are kept in
+// enumeration order either way, so the output doesn't depend on scheduling.
+
+// Candidates collected at a time, bounding the memory held in a batch.
+const CANDIDATES_PER_BATCH: usize = 4096;
+
+#[cfg(feature = "rayon")]
+use self::parallel_blocking_in_batch as blocking_in_batch;
+#[cfg(not(feature = "rayon"))]
+use self::sequential_blocking_in_batch as blocking_in_batch;
+
+// Returns the indices of the first `limit` blocking candidates in `batch`.
+// Also built with `rayon` in tests, to check the parallel version against.
+#[cfg(any(not(feature = "rayon"), test))]
+pub(crate) fn sequential_blocking_in_batch(
+    is_blocking: impl Fn(&BTreeSet<NodeIndex>) -> bool,
+    batch: &[BTreeSet<NodeIndex>],
+    limit: usize,
+) -> Vec<usize> {
+    batch
+        .iter()
+        .enumerate()
+        .filter(|(_, candidate)| is_blocking(candidate))
+        .map(|(i, _)| i)
+        .take(limit)
+        .collect()
+}
+
+// Same as the sequential version. Once `limit` blocking candidates are
+// known, the largest of their indices is shared as a cutoff beyond which
+// candidates are no longer checked. The cutoff only decreases, and anything
+// beyond it can't be among the first `limit`.
+#[cfg(feature = "rayon")]
+pub(crate) fn parallel_blocking_in_batch(
+    is_blocking: impl Fn(&BTreeSet<NodeIndex>) -> bool + Sync,
+    batch: &[BTreeSet<NodeIndex>],
+    limit: usize,
+) -> Vec<usize> {
+    use rayon::prelude::*;
+    use std::sync::{
+        atomic::{AtomicUsize, Ordering},
+        Mutex,
+    };
+
+    let cutoff = AtomicUsize::new(usize::MAX);
+    let found = Mutex::new(BTreeSet::new());
+    batch.par_iter().enumerate().for_each(|(i, candidate)| {
+        if i > cutoff.load(Ordering::Relaxed) || !is_blocking(candidate) {
+            return;
+        }
+        let mut found = found.lock().expect("blocking set search panicked");
+        found.insert(i);
+        if let Some(last) = found.iter().nth(limit.saturating_sub(1)) {
+            cutoff.fetch_min(*last, Ordering::Relaxed);
+        }
+    });
+    let found = found.into_inner().expect("blocking set search panicked");
+    found.into_iter().take(limit).collect()
+}
+
+impl<Cb: Callbacks> FbasAnalyzer<Cb> {
+    /// Returns all minimal blocking sets with at most `max_size` validators,
+    /// ordered by size and then by validator order. If no quorum exists at
+    /// all, the only minimal blocking set is the empty set.
+    pub fn minimal_blocking_sets(&self, max_size: usize) -> Result<Vec<Vec<String>>, FbasError> {
+        self.minimal_blocking_sets_limited(max_size, usize::MAX)
+    }
+
+    /// Same as `minimal_blocking_sets`, stopping once `max_sets` sets are
+    /// found. The result is the first `max_sets` sets of the full
+    /// enumeration.
+    pub fn minimal_blocking_sets_limited(
+        &self,
+        max_size: usize,
+        max_sets: usize,
+    ) -> Result<Vec<Vec<String>>, FbasError> {
+        let members = self.blocking_candidates();
+        let mut found: Vec<BTreeSet<NodeIndex>> = vec![];
+        for size in 0..=max_size.min(members.len()) {
+            if found.len() >= max_sets {
+                break;
+            }
+            self.extend_minimal_blocking_sets(&members, size, &mut found, max_sets, || false);
+        }
+        self.validator_names(&found)
+    }
+
+    /// Returns the smallest blocking sets of at most `max_size` validators,
+    /// i.e. all sets of the fewest validators whose failure blocks every
+    /// quorum, ordered by validator order. Their size is the number of
+    /// failures the network can't survive, while the larger minimal blocking
+    /// sets of `minimal_blocking_sets` show the other ways it can be blocked.
+    /// Empty if every blocking set has more than `max_size` validators.
+    /// `cb` is checked along the way; `None` if it stopped the search.
+    pub fn find_minimal_blocking_sets<C: Callbacks>(
+        &self,
+        max_size: usize,
+        cb: C,
+    ) -> Result<Option<Vec<Vec<String>>>, FbasError> {
+        let members = self.blocking_candidates();
+        let mut found: Vec<BTreeSet<NodeIndex>> = vec![];
+        for size in 0..=max_size.min(members.len()) {
+            let stop = || cb.stop();
+            if !self.extend_minimal_blocking_sets(&members, size, &mut found, usize::MAX, stop) {
+                return Ok(None);
+            }
+            if !found.is_empty() {
+                break;
+            }
+        }
+        self.validator_names(&found).map(Some)
+    }
+
+    // Same as `minimal_blocking_sets`, with `cb` checked along the way.
+    // `None` if it stopped the search.
+    pub(crate) fn minimal_blocking_sets_with<C: Callbacks>(
+        &self,
+        max_size: usize,
+        cb: C,
+    ) -> Result<Option<Vec<Vec<String>>>, FbasError> {
+        let members = self.blocking_candidates();
+        let mut found: Vec<BTreeSet<NodeIndex>> = vec![];
+        for size in 0..=max_size.min(members.len()) {
+            let stop = || cb.stop();
+            if !self.extend_minimal_blocking_sets(&members, size, &mut found, usize::MAX, stop) {
+                return Ok(None);
+            }
+        }
+        self.validator_names(&found).map(Some)
+    }
+
+    // The validators minimal blocking sets are made of, see the top of this
+    // file.
+    fn blocking_candidates(&self) -> Vec<NodeIndex> {
+        match main_component(&self.fbas) {
+            Some(main) => main.into_iter().collect(),
+            None => self.fbas.validators.clone(),
+        }
+    }
+
+    // Adds the minimal blocking sets of `size` validators out of `members` to
+    // `found`, which holds all the smaller ones, up to a total of `max_sets`.
+    // Returns false if `stop` interrupted it.
+    fn extend_minimal_blocking_sets(
+        &self,
+        members: &[NodeIndex],
+        size: usize,
+        found: &mut Vec<BTreeSet<NodeIndex>>,
+        max_sets: usize,
+        stop: impl Fn() -> bool,
+    ) -> bool {
+        let fbas = &self.fbas;
+        let smaller = found.clone();
+        let mut candidates = members
+            .iter()
+            .copied()
+            .combinations(size)
+            .map(|candidate| candidate.into_iter().collect::<BTreeSet<_>>())
+            .filter(|candidate| !smaller.iter().any(|set| set.is_subset(candidate)));
+        while found.len() < max_sets {
+            if stop() {
+                return false;
+            }
+            let batch: Vec<_> = candidates.by_ref().take(CANDIDATES_PER_BATCH).collect();
+            if batch.is_empty() {
+                break;
+            }
+            let limit = max_sets - found.len();
+            let blocking = match self.minimal_quorums.get() {
+                Some(quorums) => blocking_in_batch(
+                    |candidate| quorums.iter().all(|quorum| !quorum.is_disjoint(candidate)),
+                    &batch,
+                    limit,
+                ),
+                None => blocking_in_batch(
+                    |candidate| fbas.max_quorum(candidate).is_empty(),
+                    &batch,
+                    limit,
+                ),
+            };
+            for i in blocking {
+                found.push(batch[i].clone());
+            }
+        }
+        true
+    }
+
+    pub(crate) fn validator_names(
+        &self,
+        sets: &[BTreeSet<NodeIndex>],
+    ) -> Result<Vec<Vec<String>>, FbasError> {
+        sets.iter()
+            .map(|set| {
+                set.iter()
+                    .map(|ni| self.fbas.try_get_validator_string(ni))
+                    .collect()
+            })
+            .collect()
+    }
+
+    /// Ranks the minimal blocking sets with at most `max_size` validators by
+    /// the probability of all their validators being down at once, most
+    /// likely first. `uptime` maps validators to the fraction of time they are
+    /// up, e.g. from historical statistics; validators without data are
+    /// assumed to be up `default_uptime` of the time. Failures are assumed to
+    /// be independent, so the probability of a set is the product of the
+    /// downtimes of its validators.
+    pub fn rank_blocking_sets_by_uptime(
+        &self,
+        max_size: usize,
+        uptime: &BTreeMap<String, f64>,
+        default_uptime: f64,
+    ) -> Result<Vec<(Vec<String>, f64)>, FbasError> {
+        let mut ranked: Vec<(Vec<String>, f64)> = self
+            .minimal_blocking_sets(max_size)?
+            .into_iter()
+            .map(|set| {
+                let p = set
+                    .iter()
+                    .map(|v| {
+                        1.0 - uptime
+                            .get(v)
+                            .copied()
+                            .unwrap_or(default_uptime)
+                            .clamp(0.0, 1.0)
+                    })
+                    .product();
+                (set, p)
+            })
+            .collect();
+        // stable, so equally likely sets keep the enumeration order
+        ranked.sort_by(|(_, a), (_, b)| b.total_cmp(a));
+        Ok(ranked)
+    }
+}
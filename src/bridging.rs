@@ -0,0 +1,128 @@
+use crate::{
+    fbas::{Fbas, FbasError, InternalScpQuorumSet, QuorumSetMap},
+    Callbacks, DiagnosticsSink, FbasAnalyzer, Organizations, SolveStatus,
+};
+use itertools::Itertools;
+use std::{collections::BTreeSet, sync::Arc};
+
+// Adding validators to a qset under the same threshold only adds slices, and
+// so quorums; it can't restore intersection. Raising the threshold along
+// with it isn't enough either, as the slack may let the validator's current
+// quorums stand. A trust edge therefore makes an organization a required
+// member of a validator's qset: the qset becomes the inner set of an
+// all-of qset, next to a simple majority of the organization's validators,
+// the usual organization-level qset. Further edges of the same validator
+// join that all-of qset.
+//
+// Candidate edges go from validators of the organizations to the other
+// organizations. Combinations of them are tried by increasing size, each on a
+// rebuilt network solved from scratch, so the search is exhaustive but only
+// practical for a handful of edges.
+
+/// An organization to add to a validator's qset, see
+/// `FbasAnalyzer::bridging_edges`.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct TrustEdge {
+    pub validator: String,
+    pub org: String,
+}
+
+/// Outcome of `FbasAnalyzer::bridging_edges`.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct BridgingEdges {
+    /// A smallest set of edges restoring quorum intersection, in validator
+    /// and then organization order, if there is one of at most the
+    /// requested size. Empty if the network already enjoys quorum
+    /// intersection.
+    pub edges: Option<Vec<TrustEdge>>,
+    /// Whether no solver was interrupted by its callbacks. Otherwise a set
+    /// of fewer edges may have been missed, or any at all if `edges` is
+    /// `None`.
+    pub complete: bool,
+}
+
+// Applies `edges` to the qsets of `qsm`.
+fn add_edges(
+    qsm: &QuorumSetMap,
+    orgs: &Organizations,
+    edges: &[&TrustEdge],
+) -> Result<QuorumSetMap, FbasError> {
+    let mut bridged = qsm.clone();
+    let mut wrapped = BTreeSet::new();
+    for edge in edges {
+        let members = orgs
+            .validators_of(&edge.org)
+            .ok_or(FbasError::InternalError("Unknown organization"))?;
+        let qset = bridged
+            .get_mut(&edge.validator)
+            .ok_or(FbasError::InternalError("Validator has no qset"))?;
+        let qset = Arc::make_mut(qset);
+        if wrapped.insert(&edge.validator) {
+            *qset = InternalScpQuorumSet {
+                threshold: 1,
+                threshold_percent: None,
+                validators: vec![],
+                inner_sets: vec![qset.clone()],
+            };
+        }
+        qset.threshold += 1;
+        qset.inner_sets.push(InternalScpQuorumSet {
+            threshold: members.len() as u32 / 2 + 1,
+            threshold_percent: None,
+            validators: members.to_vec(),
+            inner_sets: vec![],
+        });
+    }
+    Ok(bridged)
+}
+
+impl<Cb: Callbacks> FbasAnalyzer<Cb> {
+    /// Finds a smallest set of at most `max_edges` trust edges whose
+    /// addition restores quorum intersection: each edge adds a majority of
+    /// an organization of `orgs` to the qset of a validator of another one,
+    /// as a member its slices require. This gives a concrete proposal of
+    /// which operators should take which organizations into their
+    /// configuration. Every combination is solved by a separate solver
+    /// driven by callbacks from `new_cb`.
+    pub fn bridging_edges<C: Callbacks>(
+        &self,
+        orgs: &Organizations,
+        max_edges: usize,
+        mut new_cb: impl FnMut() -> C,
+    ) -> Result<BridgingEdges, FbasError> {
+        let qsm = self.fbas.quorum_set_map()?;
+        let mut candidates = vec![];
+        for vi in self.fbas.validators.iter() {
+            let validator = self.fbas.try_get_validator_string(vi)?;
+            let Some(own) = orgs.org_of(&validator) else {
+                continue;
+            };
+            candidates.extend(orgs.orgs().filter(|org| *org != own).map(|org| TrustEdge {
+                validator: validator.clone(),
+                org: org.to_string(),
+            }));
+        }
+
+        let mut bridging = BridgingEdges {
+            edges: None,
+            complete: true,
+        };
+        for k in 0..=max_edges.min(candidates.len()) {
+            for edges in candidates.iter().combinations(k) {
+                let bridged = add_edges(&qsm, orgs, &edges)?;
+                let fbas = Fbas::from_quorum_set_map(&bridged)?;
+                let mut analyzer = FbasAnalyzer::from_fbas(fbas, new_cb())?
+                    .with_diagnostics_sink(DiagnosticsSink::Silent);
+                match analyzer.solve() {
+                    SolveStatus::UNSAT => {
+                        bridging.edges = Some(edges.into_iter().cloned().collect());
+                        return Ok(bridging);
+                    }
+                    SolveStatus::UNKNOWN => bridging.complete = false,
+                    SolveStatus::SAT(_) => {}
+                }
+            }
+        }
+        Ok(bridging)
+    }
+}
@@ -0,0 +1,47 @@
+use crate::{fbas::FbasError, Callbacks, FbasAnalyzer, SolveStatus};
+use std::collections::BTreeSet;
+
+/// The largest number of validators `solve_brute_force` accepts. Every subset
+/// of validators is enumerated, so the running time doubles with each one.
+pub const BRUTE_FORCE_MAX_VALIDATORS: usize = 20;
+
+impl<Cb: Callbacks> FbasAnalyzer<Cb> {
+    /// Checks quorum intersection by exhaustive enumeration instead of the SAT
+    /// solver. Every subset of validators is tested for being a quorum directly
+    /// on the graph, and for each quorum found we look for another quorum among
+    /// the remaining validators. The result is exact, and is stored like the
+    /// result of `solve` (so `get_potential_split` works after it), which makes
+    /// it usable both as an alternative backend for small networks and as an
+    /// oracle to test the SAT encoding against.
+    pub fn solve_brute_force(&mut self) -> Result<SolveStatus, FbasError> {
+        self.flush_diagnostics();
+        let validators = &self.fbas.validators;
+        if validators.len() > BRUTE_FORCE_MAX_VALIDATORS {
+            return Err(FbasError::NetworkTooLarge(
+                "brute-force mode supports at most 20 validators",
+            ));
+        }
+
+        self.status = SolveStatus::UNSAT;
+        for mask in 1u32..(1 << validators.len()) {
+            let quorum_a: BTreeSet<_> = validators
+                .iter()
+                .enumerate()
+                .filter(|(i, _)| mask & (1 << i) != 0)
+                .map(|(_, ni)| *ni)
+                .collect();
+            if !self.fbas.is_quorum(&quorum_a) {
+                continue;
+            }
+            let quorum_b = self.fbas.max_quorum(&quorum_a);
+            if !quorum_b.is_empty() {
+                self.status = SolveStatus::SAT((
+                    quorum_a.into_iter().collect(),
+                    quorum_b.into_iter().collect(),
+                ));
+                break;
+            }
+        }
+        Ok(self.status.clone())
+    }
+}
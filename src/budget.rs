@@ -0,0 +1,83 @@
+use batsat::Callbacks;
+use std::time::{Duration, Instant};
+
+// The instant `duration` from now, or a century from now if that can't be
+// represented, e.g. for `Duration::MAX` as no deadline at all.
+fn after(duration: Duration) -> Instant {
+    let now = Instant::now();
+    now.checked_add(duration)
+        .or_else(|| now.checked_add(Duration::from_secs(100 * 365 * 24 * 60 * 60)))
+        .unwrap_or(now)
+}
+
+/// A deadline for one analysis, to be passed as the analyzer's callbacks: the
+/// search stops at the deadline, leaving the result UNKNOWN. Building the
+/// formula can't be interrupted, but its time counts toward the deadline.
+#[derive(Debug, Clone, Copy)]
+pub struct Deadline(Instant);
+
+impl Deadline {
+    pub fn at(instant: Instant) -> Self {
+        Self(instant)
+    }
+
+    pub fn after(duration: Duration) -> Self {
+        Self(after(duration))
+    }
+
+    pub fn instant(&self) -> Instant {
+        self.0
+    }
+}
+
+impl Callbacks for Deadline {
+    fn stop(&self) -> bool {
+        Instant::now() >= self.0
+    }
+}
+
+/// Splits one overall time budget across a number of analyses run one after
+/// the other, so that the first one can't eat the whole budget. Each analysis
+/// gets an even share of the time remaining when it starts, so time left over
+/// by quick analyses goes to the later ones. Once the budget is used up, the
+/// remaining analyses are skipped.
+#[derive(Debug, Clone)]
+pub struct TimeBudget {
+    end: Instant,
+    pending: usize,
+}
+
+impl TimeBudget {
+    /// A budget of `total` from now, for `analyses` analyses.
+    pub fn new(total: Duration, analyses: usize) -> Self {
+        Self {
+            end: after(total),
+            pending: analyses,
+        }
+    }
+
+    pub fn remaining(&self) -> Duration {
+        self.end.saturating_duration_since(Instant::now())
+    }
+
+    /// Number of analyses that haven't been allocated time yet.
+    pub fn pending(&self) -> usize {
+        self.pending
+    }
+
+    /// Allocates the deadline of the next analysis, or returns `None` if it
+    /// should be skipped because no time (or no pending analysis) is left.
+    /// Skipped analyses count as allocated.
+    pub fn next_deadline(&mut self) -> Option<Deadline> {
+        if self.pending == 0 {
+            return None;
+        }
+        // beyond u32::MAX pending analyses, no share is worth allocating
+        let share = u32::try_from(self.pending).map_or(Duration::ZERO, |n| self.remaining() / n);
+        self.pending -= 1;
+        if share.is_zero() {
+            return None;
+        }
+        Some(Deadline::after(share))
+    }
+}
@@ -0,0 +1,228 @@
+use crate::{
+    fbas::{Fbas, FbasError, InternalScpQuorumSet, QuorumSetMap},
+    Callbacks, DiagnosticsSink, FbasAnalyzer,
+};
+
+// Snapshots are compared as seen by the analysis: qsets without unknown
+// validators, and in canonical order, so that reordering members or
+// dropping a validator nobody knows doesn't show up as a change. The impact
+// is the quorum intersection of each snapshot, solved from scratch so that
+// the analyzers compared are left untouched.
+//
+// When the later snapshot loses intersection, each change is reverted alone
+// in it and the result solved again: the changes whose revert restores
+// intersection are the culprits. A split needing several changes together
+// has none, since reverting any one of them leaves it in place; finding the
+// smallest sets of changes to revert would take exponentially many solves.
+// Changes of the top tier are attributed the same way, the culprits being
+// the changes whose revert brings back the previous top tier.
+//
+// Snapshots are also told apart by hash, e.g. to skip analyzing one again.
+// Snapshot hashes must stay the same across runs and versions of the crate
+// for stored ones to be compared with new ones, so they are computed with
+// FNV-1a over a rendering of the qsets, rather than with the std hasher.
+
+/// A validator whose qset differs between two snapshots, each rendered by
+/// `InternalScpQuorumSet::pretty`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct QsetChange {
+    pub validator: String,
+    pub before: String,
+    pub after: String,
+}
+
+/// One change between two snapshots, see `FbasDiff`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SnapshotChange {
+    /// The validator was added.
+    Added(String),
+    /// The validator was removed.
+    Removed(String),
+    /// The qset of the validator changed.
+    QsetChanged(String),
+}
+
+impl std::fmt::Display for SnapshotChange {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SnapshotChange::Added(v) => write!(f, "{} added", v),
+            SnapshotChange::Removed(v) => write!(f, "{} removed", v),
+            SnapshotChange::QsetChanged(v) => write!(f, "qset of {} changed", v),
+        }
+    }
+}
+
+/// The differences between two snapshots of a network, see `Fbas::diff` and
+/// `FbasAnalyzer::diff`. Each list is in validator order.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct FbasDiff {
+    /// Validators only in the later snapshot.
+    pub added: Vec<String>,
+    /// Validators only in the earlier snapshot.
+    pub removed: Vec<String>,
+    /// Validators in both whose qset changed.
+    pub changed: Vec<QsetChange>,
+    /// Whether quorums intersect in the earlier snapshot, `None` if not
+    /// solved or if the solver didn't reach a conclusion.
+    pub intersecting_before: Option<bool>,
+    /// Same as `intersecting_before`, for the later snapshot.
+    pub intersecting_after: Option<bool>,
+    /// If the change loses quorum intersection (see `breaks_intersection`),
+    /// the changes that each restore it when reverted alone, in the order
+    /// of the lists above. Empty otherwise, or if the split only goes away
+    /// by reverting several changes together.
+    pub culprits: Vec<SnapshotChange>,
+}
+
+impl FbasDiff {
+    /// Whether the snapshots have the same validators and qsets.
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.removed.is_empty() && self.changed.is_empty()
+    }
+
+    /// Whether the change loses quorum intersection, the risky kind of
+    /// change monitoring should flag.
+    pub fn breaks_intersection(&self) -> bool {
+        self.intersecting_before == Some(true) && self.intersecting_after == Some(false)
+    }
+}
+
+fn is_intersecting<C: Callbacks>(fbas: &Fbas, cb: C) -> Result<Option<bool>, FbasError> {
+    qsets_intersect(&fbas.quorum_set_map()?, cb)
+}
+
+fn qsets_intersect<C: Callbacks>(qsm: &QuorumSetMap, cb: C) -> Result<Option<bool>, FbasError> {
+    let fbas = Fbas::from_quorum_set_map(qsm)?;
+    let mut analyzer =
+        FbasAnalyzer::from_fbas(fbas, cb)?.with_diagnostics_sink(DiagnosticsSink::Silent);
+    Ok(analyzer.solve().intersecting())
+}
+
+// Appends a rendering of `qset`, with its members in canonical order.
+fn render_qset(qset: &InternalScpQuorumSet, out: &mut String) {
+    out.push_str(&format!("{}(", qset.threshold));
+    for validator in qset.validators.iter() {
+        out.push_str(validator);
+        out.push(',');
+    }
+    for inner in qset.inner_sets.iter() {
+        render_qset(inner, out);
+        out.push(',');
+    }
+    out.push(')');
+}
+
+impl<Cb: Callbacks> FbasAnalyzer<Cb> {
+    /// Compares this network to a later snapshot `other` (see `Fbas::diff`),
+    /// and checks quorum intersection of both, to flag risky configuration
+    /// changes. Each snapshot is solved by a separate solver driven by
+    /// callbacks from `new_cb`.
+    pub fn diff<C: Callbacks, D: Callbacks>(
+        &self,
+        other: &FbasAnalyzer<D>,
+        mut new_cb: impl FnMut() -> C,
+    ) -> Result<FbasDiff, FbasError> {
+        let mut diff = self.fbas.diff(&other.fbas)?;
+        diff.intersecting_before = is_intersecting(&self.fbas, new_cb())?;
+        diff.intersecting_after = is_intersecting(&other.fbas, new_cb())?;
+        diff.culprits = find_culprits(&self.fbas, &other.fbas, &diff, new_cb)?;
+        Ok(diff)
+    }
+
+    /// A hash identifying the network analyzed, the same for networks with
+    /// the same validators and qsets (as seen by the analysis, order aside)
+    /// whatever the run or version of the crate, e.g. to be recorded along
+    /// with reports in a `ReportStore`, or to skip unchanged snapshots as a
+    /// `Watcher` does.
+    pub fn snapshot_hash(&self) -> Result<String, FbasError> {
+        let mut rendered = String::new();
+        for (validator, qset) in self.fbas.quorum_set_map()?.iter() {
+            rendered.push_str(validator);
+            rendered.push('=');
+            render_qset(&qset.canonical(), &mut rendered);
+            rendered.push('\n');
+        }
+        // FNV-1a
+        let hash = rendered.bytes().fold(0xcbf29ce484222325u64, |hash, byte| {
+            (hash ^ byte as u64).wrapping_mul(0x100000001b3)
+        });
+        Ok(format!("{:016x}", hash))
+    }
+}
+
+// The changes of `diff`, from `before` to `after`, whose revert alone in
+// `after` gives qsets that `restores`.
+fn culprits_by_revert(
+    before: &Fbas,
+    after: &Fbas,
+    diff: &FbasDiff,
+    mut restores: impl FnMut(&QuorumSetMap) -> Result<bool, FbasError>,
+) -> Result<Vec<SnapshotChange>, FbasError> {
+    let (before, after) = (before.quorum_set_map()?, after.quorum_set_map()?);
+    let changes = diff
+        .added
+        .iter()
+        .map(|v| SnapshotChange::Added(v.clone()))
+        .chain(
+            diff.removed
+                .iter()
+                .map(|v| SnapshotChange::Removed(v.clone())),
+        )
+        .chain(
+            diff.changed
+                .iter()
+                .map(|change| SnapshotChange::QsetChanged(change.validator.clone())),
+        );
+    let mut culprits = vec![];
+    for change in changes {
+        let mut reverted = after.clone();
+        match &change {
+            SnapshotChange::Added(v) => {
+                reverted.remove(v);
+            }
+            SnapshotChange::Removed(v) | SnapshotChange::QsetChanged(v) => {
+                let qset = before
+                    .get(v)
+                    .ok_or(FbasError::InternalError("changed validator not found"))?;
+                reverted.insert(v.clone(), qset.clone());
+            }
+        }
+        if restores(&reverted)? {
+            culprits.push(change);
+        }
+    }
+    Ok(culprits)
+}
+
+// The culprits of `diff`, the changes from `before` to `after`, see the top
+// of this file. Nothing is solved unless `diff` breaks intersection.
+pub(crate) fn find_culprits<C: Callbacks>(
+    before: &Fbas,
+    after: &Fbas,
+    diff: &FbasDiff,
+    mut new_cb: impl FnMut() -> C,
+) -> Result<Vec<SnapshotChange>, FbasError> {
+    if !diff.breaks_intersection() {
+        return Ok(vec![]);
+    }
+    culprits_by_revert(before, after, diff, |qsm| {
+        Ok(qsets_intersect(qsm, new_cb())? == Some(true))
+    })
+}
+
+// The changes of `diff`, from `before` to `after`, that each bring back
+// `top_tier`, the top tier of `before`, when reverted alone. A reverted
+// snapshot whose top tier is interrupted doesn't count.
+pub(crate) fn find_top_tier_culprits<C: Callbacks>(
+    before: &Fbas,
+    after: &Fbas,
+    diff: &FbasDiff,
+    top_tier: &[String],
+    mut new_cb: impl FnMut() -> C,
+) -> Result<Vec<SnapshotChange>, FbasError> {
+    culprits_by_revert(before, after, diff, |qsm| {
+        let analyzer = FbasAnalyzer::from_fbas(Fbas::from_quorum_set_map(qsm)?, new_cb())?
+            .with_diagnostics_sink(DiagnosticsSink::Silent);
+        Ok(analyzer.top_tier(&mut new_cb)?.as_deref() == Some(top_tier))
+    })
+}
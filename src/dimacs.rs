@@ -0,0 +1,241 @@
+use crate::{
+    fbas::{FbasError, Vertex},
+    fbas_analyze::FbasLitsWrapper,
+    telemetry::Span,
+    FbasAnalyzer, Quorum, SolvePhase, SolveStats,
+};
+use batsat::{
+    intmap::AsIndex, lbool, theory::Theory, Callbacks, Lit, Solver, SolverInterface, Var,
+};
+use std::io::{BufRead, ErrorKind, Write};
+
+// DIMACS export of the formula `solve` solves. The formula is encoded again
+// into a `ClauseRecorder`, which keeps the variables and clauses as they are
+// added instead of solving them, so the export is the original formula
+// regardless of what the analyzer's solver has learnt or simplified since, or
+// whether it was released. Variables are numbered as in the solver: the
+// vertices of the graph in quorum A, then in quorum B (see
+// `FbasLitsWrapper`), then the auxiliary variables of the encoding. Comment
+// lines map the former back to validators and qsets.
+//
+// The import goes the other way, loading any DIMACS formula into a solver set
+// up as the analyzer's, so the same callbacks bound the search, and external
+// tools can compare against the analyzer on the exact same instances.
+
+#[derive(Default)]
+struct ClauseRecorder {
+    num_vars: u32,
+    clauses: Vec<Vec<Lit>>,
+}
+
+// Only what the encoding uses is meaningful; the recorder never solves, so
+// solving returns `UNDEF` and there is no model or core.
+impl SolverInterface for ClauseRecorder {
+    fn num_vars(&self) -> u32 {
+        self.num_vars
+    }
+    fn num_clauses(&self) -> u64 {
+        self.clauses.len() as u64
+    }
+    fn num_conflicts(&self) -> u64 {
+        0
+    }
+    fn num_propagations(&self) -> u64 {
+        0
+    }
+    fn num_decisions(&self) -> u64 {
+        0
+    }
+    fn num_restarts(&self) -> u64 {
+        0
+    }
+    fn is_ok(&self) -> bool {
+        true
+    }
+    fn reset(&mut self) {
+        *self = Self::default();
+    }
+    fn print_stats(&self) {}
+    fn new_var(&mut self, _upol: lbool, _dvar: bool) -> Var {
+        self.new_var_default()
+    }
+    fn new_var_default(&mut self) -> Var {
+        self.num_vars += 1;
+        Var::from_index(self.num_vars as usize - 1)
+    }
+    fn var_of_int(&mut self, i: u32) -> Var {
+        self.num_vars = self.num_vars.max(i + 1);
+        Var::from_index(i as usize)
+    }
+    fn add_clause_reuse(&mut self, clause: &mut Vec<Lit>) -> bool {
+        self.clauses.push(clause.clone());
+        true
+    }
+    fn simplify_th<Th: Theory>(&mut self, _th: &mut Th) -> bool {
+        true
+    }
+    fn solve_limited_preserving_trail_th<Th: Theory>(
+        &mut self,
+        _th: &mut Th,
+        _assumps: &[Lit],
+    ) -> lbool {
+        lbool::UNDEF
+    }
+    fn pop_model<Th: Theory>(&mut self, _th: &mut Th) {}
+    fn raw_value_lit(&self, _l: Lit) -> lbool {
+        lbool::UNDEF
+    }
+    fn proved_at_lvl_0(&self) -> &[Lit] {
+        &[]
+    }
+    fn get_model(&self) -> &[lbool] {
+        &[]
+    }
+    fn value_var(&self, _v: Var) -> lbool {
+        lbool::UNDEF
+    }
+    fn value_lit(&self, _lit: Lit) -> lbool {
+        lbool::UNDEF
+    }
+    fn value_lvl_0(&self, _lit: Lit) -> lbool {
+        lbool::UNDEF
+    }
+    fn unsat_core(&self) -> &[Lit] {
+        &[]
+    }
+    fn unsat_core_contains_lit(&self, _lit: Lit) -> bool {
+        false
+    }
+    fn unsat_core_contains_var(&self, _v: Var) -> bool {
+        false
+    }
+}
+
+// The DIMACS form of `lit`, variables being numbered from 1.
+fn dimacs_lit(lit: Lit) -> i64 {
+    let var = lit.var().idx() as i64 + 1;
+    match lit.sign() {
+        true => var,
+        false => -var,
+    }
+}
+
+impl<Cb: Callbacks> FbasAnalyzer<Cb> {
+    /// Writes the formula `solve` solves to `writer` in the DIMACS CNF
+    /// format, e.g. to run it through an external solver or archive it. It is
+    /// satisfiable iff there are two disjoint quorums. Comment lines map the
+    /// variable of each validator and qset in either quorum, e.g.
+    /// `c 1 A GABC...` and `c 40 B qset 39 (2 of 3)`; the remaining variables
+    /// are auxiliary.
+    pub fn write_dimacs(&self, mut writer: impl Write) -> Result<(), FbasError> {
+        let mut recorder = ClauseRecorder::default();
+        self.encode_formula(&mut recorder)?;
+
+        let io_err = |_| FbasError::IoError("fail to write DIMACS");
+        let graph = &self.fbas.graph;
+        let fbas_lits = FbasLitsWrapper::new(graph.node_count());
+        writeln!(writer, "c SAT iff two disjoint quorums exist").map_err(io_err)?;
+        for quorum in [Quorum::A, Quorum::B] {
+            let q = match quorum {
+                Quorum::A => "A",
+                Quorum::B => "B",
+            };
+            for ni in graph.node_indices() {
+                let var = dimacs_lit(fbas_lits.in_quorum(&ni, quorum));
+                match &graph[ni] {
+                    Vertex::Validator(name) | Vertex::Watcher(name) => {
+                        writeln!(writer, "c {} {} {}", var, q, name)
+                    }
+                    Vertex::QSet(_) => writeln!(
+                        writer,
+                        "c {} {} qset {} ({} of {})",
+                        var,
+                        q,
+                        ni.index(),
+                        graph[ni].get_threshold(),
+                        graph.neighbors(ni).count()
+                    ),
+                }
+                .map_err(io_err)?;
+            }
+        }
+        writeln!(
+            writer,
+            "p cnf {} {}",
+            recorder.num_vars,
+            recorder.clauses.len()
+        )
+        .map_err(io_err)?;
+        for clause in &recorder.clauses {
+            for lit in clause {
+                write!(writer, "{} ", dimacs_lit(*lit)).map_err(io_err)?;
+            }
+            writeln!(writer, "0").map_err(io_err)?;
+        }
+        writer.flush().map_err(io_err)
+    }
+}
+
+/// The result of `solve_dimacs`.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct DimacsSolution {
+    /// Whether the formula is satisfiable, `None` if the callbacks stopped
+    /// the search first.
+    pub satisfiable: Option<bool>,
+    /// The model if satisfiable: the value of variable `i` (numbered from 1,
+    /// as in the file) at index `i - 1`. Empty otherwise.
+    pub model: Vec<bool>,
+    pub stats: SolveStats,
+}
+
+/// Loads a formula in the DIMACS CNF format from `reader`, e.g. one written
+/// by `FbasAnalyzer::write_dimacs`, into a solver set up as the analyzer's,
+/// and solves it. The callbacks apply as they do to the analyzer, e.g. a
+/// `Deadline` bounds the search. The number of clauses must match the header.
+pub fn solve_dimacs<Cb: Callbacks>(
+    mut reader: impl BufRead,
+    cb: Cb,
+) -> Result<DimacsSolution, FbasError> {
+    let mut span = Span::start("solve_dimacs");
+    let mut solver = Solver::new(Default::default(), cb);
+    batsat::dimacs::parse(&mut reader, &mut solver, true, false).map_err(|e| match e.kind() {
+        ErrorKind::InvalidInput => FbasError::ParseError("fail to parse DIMACS"),
+        _ => FbasError::IoError("fail to read DIMACS"),
+    })?;
+    span.set_int("num_vars", solver.num_vars() as u64);
+    span.set_int("num_clauses", solver.num_clauses());
+    let result = solver.solve_limited(&[]);
+    let satisfiable = match result {
+        r if r == lbool::TRUE => Some(true),
+        r if r == lbool::FALSE => Some(false),
+        _ => None,
+    };
+    let model = match satisfiable {
+        Some(true) => solver
+            .get_model()
+            .iter()
+            .map(|v| *v == lbool::TRUE)
+            .collect(),
+        _ => vec![],
+    };
+    span.set_str("status", || {
+        match satisfiable {
+            Some(true) => "SAT",
+            Some(false) => "UNSAT",
+            None => "UNKNOWN",
+        }
+        .to_string()
+    });
+    Ok(DimacsSolution {
+        satisfiable,
+        model,
+        stats: SolveStats {
+            phase: SolvePhase::Search,
+            num_vars: solver.num_vars(),
+            num_clauses: solver.num_clauses(),
+            num_conflicts: solver.num_conflicts(),
+            num_decisions: solver.num_decisions(),
+            num_propagations: solver.num_propagations(),
+        },
+    })
+}
@@ -0,0 +1,140 @@
+use crate::{fbas::FbasError, FbasAnalyzer};
+use batsat::Callbacks;
+use itertools::Itertools;
+use petgraph::graph::NodeIndex;
+use std::collections::BTreeSet;
+
+// A dispensable set (DSet, see the SCP whitepaper) is a set `B` of validators
+// the others can do without:
+//
+// - quorum availability despite `B`: the validators outside `B` are a quorum,
+//   or there are none, and
+// - quorum intersection despite `B`: with `B` deleted, i.e. byzantine, the
+//   others still enjoy quorum intersection, see `solve_excluding`.
+//
+// The intersection of two DSets is a DSet, so for a set `I` of ill-behaved
+// validators there is a smallest DSet containing it. Its validators are
+// befouled, the others intact: they are the ones the protocol guarantees
+// safety and liveness to despite `I`.
+//
+// Any DSet containing `I` also contains every validator outside the largest
+// quorum avoiding `I`, as it is outside the largest quorum avoiding the DSet,
+// which must be everything else. The search starts from that closure and adds
+// the other validators in sets of increasing size, so its cost grows with the
+// number of befouled validators that aren't forced that way.
+//
+// That closure is the befoulment cascade: a validator whose qset is v-blocked
+// by befouled validators is befouled in turn, until the survivors are a
+// quorum (or none are left). It only accounts for availability, so the
+// validators it leaves are a superset of the intact ones, and it takes no
+// solver.
+
+/// Outcome of `FbasAnalyzer::intact_validators` and
+/// `FbasAnalyzer::befoulment_cascade`, each list in validator order.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct IntactValidators {
+    pub intact: Vec<String>,
+    /// The smallest DSet containing the ill-behaved validators, or for
+    /// `befoulment_cascade` the validators transitively v-blocked by them.
+    pub befouled: Vec<String>,
+}
+
+impl<Cb: Callbacks> FbasAnalyzer<Cb> {
+    /// Returns whether `validators` are a dispensable set, i.e. the others
+    /// are a quorum (or there are none) that enjoys quorum intersection even
+    /// if `validators` are byzantine. `None` if the solver, driven by `cb`,
+    /// was interrupted. Returns an error for validators unknown to the
+    /// network.
+    pub fn is_dispensable_set<C: Callbacks>(
+        &self,
+        validators: &[&str],
+        cb: C,
+    ) -> Result<Option<bool>, FbasError> {
+        let set = validators
+            .iter()
+            .map(|v| self.fbas.try_get_validator_index(v))
+            .collect::<Result<BTreeSet<_>, _>>()?;
+        self.is_dset(&set, cb)
+    }
+
+    /// Splits the validators into intact and befouled ones given the
+    /// `ill_behaved` ones, which are befouled themselves. The search is
+    /// exponential in the number of befouled validators that stay in the
+    /// largest quorum avoiding the ill-behaved ones, each candidate taking a
+    /// solve by a separate solver driven by callbacks from `new_cb`. `None`
+    /// if one of them was interrupted. Returns an error for validators
+    /// unknown to the network.
+    pub fn intact_validators<C: Callbacks>(
+        &self,
+        ill_behaved: &[&str],
+        mut new_cb: impl FnMut() -> C,
+    ) -> Result<Option<IntactValidators>, FbasError> {
+        let fbas = &self.fbas;
+        let ill_behaved = ill_behaved
+            .iter()
+            .map(|v| fbas.try_get_validator_index(v))
+            .collect::<Result<BTreeSet<_>, _>>()?;
+        let quorum = fbas.max_quorum(&ill_behaved);
+        let forced = fbas.validators_outside(&quorum);
+        let others: Vec<NodeIndex> = quorum.iter().copied().collect();
+        // all validators are a DSet, so this finds one
+        for size in 0..=others.len() {
+            for extra in others.iter().copied().combinations(size) {
+                let candidate: BTreeSet<_> = forced.iter().copied().chain(extra).collect();
+                match self.is_dset(&candidate, new_cb())? {
+                    Some(true) => return self.split_intact(&candidate).map(Some),
+                    Some(false) => {}
+                    None => return Ok(None),
+                }
+            }
+        }
+        Err(FbasError::InternalError(
+            "The set of all validators is not a DSet",
+        ))
+    }
+
+    /// Propagates befoulment from the `faulty` validators, which are
+    /// befouled themselves: a validator is befouled once befouled validators
+    /// v-block its qset, transitively. The remaining validators are the
+    /// largest quorum avoiding `faulty`, and a superset of the intact ones of
+    /// `intact_validators`, as quorum intersection among them isn't checked.
+    /// Returns an error for validators unknown to the network.
+    pub fn befoulment_cascade(&self, faulty: &[&str]) -> Result<IntactValidators, FbasError> {
+        let fbas = &self.fbas;
+        let faulty = faulty
+            .iter()
+            .map(|v| fbas.try_get_validator_index(v))
+            .collect::<Result<BTreeSet<_>, _>>()?;
+        self.split_intact(&fbas.validators_outside(&fbas.max_quorum(&faulty)))
+    }
+
+    fn is_dset<C: Callbacks>(
+        &self,
+        set: &BTreeSet<NodeIndex>,
+        cb: C,
+    ) -> Result<Option<bool>, FbasError> {
+        let others = self.fbas.validators_outside(set);
+        if self.fbas.max_quorum(set) != others {
+            return Ok(Some(false));
+        }
+        let names = set
+            .iter()
+            .map(|ni| self.fbas.try_get_validator_string(ni))
+            .collect::<Result<Vec<_>, _>>()?;
+        let names: Vec<&str> = names.iter().map(String::as_str).collect();
+        Ok(self.solve_excluding(&names, cb)?.intersecting())
+    }
+
+    fn split_intact(&self, befouled: &BTreeSet<NodeIndex>) -> Result<IntactValidators, FbasError> {
+        let mut split = IntactValidators::default();
+        for ni in self.fbas.validators.iter() {
+            let name = self.fbas.try_get_validator_string(ni)?;
+            if befouled.contains(ni) {
+                split.befouled.push(name);
+            } else {
+                split.intact.push(name);
+            }
+        }
+        Ok(split)
+    }
+}
@@ -0,0 +1,192 @@
+use crate::fbas::{FbasError, InternalScpQuorumSet, QuorumSetMap, QUORUM_SET_MAX_DEPTH};
+use std::{collections::BTreeMap, sync::Arc};
+
+// A compact textual syntax for networks, handy in tests and bug reports. Each
+// non-empty line assigns a qset to one or more validators:
+//
+//     a, b: 2-of(a, b, c)
+//     c: 3-of(a, b, 2-of(c, d, e))
+//
+// Members of a qset are validator names or nested qsets. Validator names are
+// any run of characters other than whitespace and `,():#`. Everything from a
+// `#` to the end of the line is a comment.
+//
+// Qsets are parsed recursively, so their nesting is capped to keep any input
+// from overflowing the stack. The cap is past the depth networks can have,
+// so that qsets nested too deep are still parsed and linted as such.
+
+const DSL_MAX_DEPTH: u32 = 2 * QUORUM_SET_MAX_DEPTH;
+
+struct Parser<'a> {
+    input: &'a str,
+    pos: usize,
+    // nesting of the qset being parsed, 1 for the top-level one
+    depth: u32,
+}
+
+impl<'a> Parser<'a> {
+    fn skip_whitespace(&mut self) {
+        let rest = &self.input[self.pos..];
+        self.pos += rest.len() - rest.trim_start().len();
+    }
+
+    fn peek(&mut self) -> Option<char> {
+        self.skip_whitespace();
+        self.input[self.pos..].chars().next()
+    }
+
+    fn expect(&mut self, token: &str, err: &'static str) -> Result<(), FbasError> {
+        self.skip_whitespace();
+        if !self.input[self.pos..].starts_with(token) {
+            return Err(FbasError::ParseError(err));
+        }
+        self.pos += token.len();
+        Ok(())
+    }
+
+    fn name(&mut self) -> Result<&'a str, FbasError> {
+        self.skip_whitespace();
+        let rest = &self.input[self.pos..];
+        let len = rest
+            .find(|c: char| c.is_whitespace() || ",():#".contains(c))
+            .unwrap_or(rest.len());
+        if len == 0 {
+            return Err(FbasError::ParseError("Expected a validator name"));
+        }
+        self.pos += len;
+        Ok(&rest[..len])
+    }
+
+    // whether the upcoming member is a nested qset rather than a validator
+    fn at_qset(&mut self) -> bool {
+        self.skip_whitespace();
+        let rest = &self.input[self.pos..];
+        let digits = rest.len() - rest.trim_start_matches(|c: char| c.is_ascii_digit()).len();
+        digits > 0 && rest[digits..].starts_with("-of")
+    }
+
+    fn qset(&mut self) -> Result<InternalScpQuorumSet, FbasError> {
+        if self.depth == DSL_MAX_DEPTH {
+            return Err(FbasError::MaxDepthExceeded);
+        }
+        self.depth += 1;
+        let qset = self.qset_members();
+        self.depth -= 1;
+        qset
+    }
+
+    fn qset_members(&mut self) -> Result<InternalScpQuorumSet, FbasError> {
+        self.skip_whitespace();
+        let rest = &self.input[self.pos..];
+        let digits = rest.len() - rest.trim_start_matches(|c: char| c.is_ascii_digit()).len();
+        let threshold = rest[..digits]
+            .parse()
+            .map_err(|_| FbasError::ParseError("Expected a threshold"))?;
+        self.pos += digits;
+        self.expect("-of", "Expected '-of' after the threshold")?;
+        self.expect("(", "Expected '(' after '-of'")?;
+
+        let mut qset = InternalScpQuorumSet {
+            threshold,
+            threshold_percent: None,
+            validators: vec![],
+            inner_sets: vec![],
+        };
+        if self.peek() == Some(')') {
+            self.pos += 1;
+            return Ok(qset);
+        }
+        loop {
+            if self.at_qset() {
+                qset.inner_sets.push(self.qset()?);
+            } else {
+                qset.validators.push(self.name()?.to_string());
+            }
+            match self.peek() {
+                Some(',') => self.pos += 1,
+                Some(')') => {
+                    self.pos += 1;
+                    return Ok(qset);
+                }
+                _ => return Err(FbasError::ParseError("Expected ',' or ')' in qset")),
+            }
+        }
+    }
+
+    fn line(&mut self) -> Result<(Vec<&'a str>, InternalScpQuorumSet), FbasError> {
+        let mut validators = vec![self.name()?];
+        while self.peek() == Some(',') {
+            self.pos += 1;
+            validators.push(self.name()?);
+        }
+        self.expect(":", "Expected ':' after the validator names")?;
+        let qset = self.qset()?;
+        if self.peek().is_some() {
+            return Err(FbasError::ParseError("Unexpected input after qset"));
+        }
+        Ok((validators, qset))
+    }
+}
+
+// The validators and qset of every non-empty line, in order.
+fn parse_lines(dsl: &str) -> Result<Vec<(Vec<&str>, InternalScpQuorumSet)>, FbasError> {
+    dsl.lines()
+        .map(|line| line.split('#').next().unwrap_or_default())
+        .filter(|line| !line.trim().is_empty())
+        .map(|line| {
+            Parser {
+                input: line,
+                pos: 0,
+                depth: 0,
+            }
+            .line()
+        })
+        .collect()
+}
+
+pub(crate) fn quorum_set_map_from_dsl(dsl: &str) -> Result<QuorumSetMap, FbasError> {
+    let mut qsm = QuorumSetMap::new();
+    for (validators, qset) in parse_lines(dsl)? {
+        let qset = Arc::new(qset);
+        for v in validators {
+            if qsm.insert(v.to_string(), qset.clone()).is_some() {
+                return Err(FbasError::ParseError(
+                    "Validator is assigned multiple qsets",
+                ));
+            }
+        }
+    }
+    Ok(qsm)
+}
+
+// Same syntax, but a validator may be assigned several qsets, which are
+// collected as alternatives in order of appearance. Repeated qsets are kept
+// once.
+pub(crate) fn qset_alternatives_from_dsl(
+    dsl: &str,
+) -> Result<BTreeMap<String, Vec<InternalScpQuorumSet>>, FbasError> {
+    let mut alternatives: BTreeMap<String, Vec<InternalScpQuorumSet>> = BTreeMap::new();
+    for (validators, qset) in parse_lines(dsl)? {
+        for v in validators {
+            let qsets = alternatives.entry(v.to_string()).or_default();
+            if !qsets.contains(&qset) {
+                qsets.push(qset.clone());
+            }
+        }
+    }
+    Ok(alternatives)
+}
+
+// A single qset in the same syntax, e.g. `2-of(a, b, c)`.
+pub(crate) fn qset_from_dsl(dsl: &str) -> Result<InternalScpQuorumSet, FbasError> {
+    let mut parser = Parser {
+        input: dsl,
+        pos: 0,
+        depth: 0,
+    };
+    let qset = parser.qset()?;
+    if parser.peek().is_some() {
+        return Err(FbasError::ParseError("Unexpected input after qset"));
+    }
+    Ok(qset)
+}
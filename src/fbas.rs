@@ -1,14 +1,26 @@
+use crate::diff::{FbasDiff, QsetChange};
+#[cfg(any(feature = "json", test))]
+use crate::json_parser::{NodeCollector, NodeRecord};
+use crate::lint::{lint_quorum_set_map, QsetLint};
+use crate::organization::{org_quorum_set_map, NodeMetadata, Organizations};
 use petgraph::graph::{DiGraph, NodeIndex};
 use std::{
+    cmp::Ordering,
     collections::{BTreeMap, BTreeSet},
     fmt::Debug,
-    rc::Rc,
+    hash::{Hash, Hasher},
+    sync::Arc,
 };
-use stellar_xdr::curr::{Limits, NodeId, PublicKey, ReadXdr, ScpQuorumSet};
+#[cfg(feature = "xdr")]
+use stellar_xdr::curr::{Limited, Limits, NodeId, PublicKey, ReadXdr, ScpQuorumSet};
 
-const QUORUM_SET_MAX_DEPTH: u32 = 4;
+/// The maximum number of nesting levels allowed in a qset, counting the top
+/// level, as in stellar-core.
+pub(crate) const QUORUM_SET_MAX_DEPTH: u32 = 4;
 
-pub(crate) type QuorumSetMap = BTreeMap<String, Rc<InternalScpQuorumSet>>;
+/// Parsed quorum sets by validator. The sets are reference counted atomically
+/// so that a parsed map can be shared by threads building their own `Fbas`.
+pub type QuorumSetMap = BTreeMap<String, Arc<InternalScpQuorumSet>>;
 
 /// This is the internal representation of a quorum set. The Qset structure must
 /// be explicitly specified (by validator's declaration). You can't say my inner
@@ -19,9 +31,11 @@ pub(crate) type QuorumSetMap = BTreeMap<String, Rc<InternalScpQuorumSet>>;
 /// validators). Such transitive structure is described by the graph in `Fbas`.
 /// A leaf in a `Qset` can only contain 1. validator or 2. vacuous qset (qset
 /// with a threshold but empty validator list and inner-qset).
-#[derive(Debug, Clone, PartialEq, PartialOrd, Eq, Ord, Default)]
-pub(crate) struct Qset {
+#[derive(Debug, Clone, Default)]
+pub struct Qset {
     pub threshold: u32,
+    // The percentage `threshold` was given as, see `InternalScpQuorumSet`.
+    pub threshold_percent: Option<u32>,
     // Stores index of validators that have been parsed and already exists in
     // the graph.
     pub validators: BTreeSet<NodeIndex>,
@@ -33,23 +47,160 @@ pub(crate) struct Qset {
 /// Same as `ScpQuorumSet` except it identifies validators with String instead
 /// of `NodeId`, because we want to make it easier for testing by allowing nodes
 /// to be random strings instead of requiring valid stellar strkeys
-#[derive(Clone, Debug, Hash, PartialEq, Eq, PartialOrd, Ord)]
-pub(crate) struct InternalScpQuorumSet {
+#[derive(Clone, Debug)]
+pub struct InternalScpQuorumSet {
     pub threshold: u32,
+    /// The percentage of members `threshold` was computed from, if the input
+    /// gave it that way (`thresholdPercent` in JSON). Only kept for display, and
+    /// left out of comparisons.
+    pub threshold_percent: Option<u32>,
     pub validators: Vec<String>,
     pub inner_sets: Vec<InternalScpQuorumSet>,
 }
 
-#[derive(Debug)]
-pub(crate) enum Vertex {
+// Qsets are compared without `threshold_percent`, which only tells how the
+// threshold was written: qsets with the same threshold and members are the
+// same to the analysis, share a vertex and are linted once.
+impl Qset {
+    fn identity(&self) -> (u32, &BTreeSet<NodeIndex>, &BTreeSet<NodeIndex>) {
+        (self.threshold, &self.validators, &self.inner_qsets)
+    }
+}
+
+impl PartialEq for Qset {
+    fn eq(&self, other: &Self) -> bool {
+        self.identity() == other.identity()
+    }
+}
+
+impl Eq for Qset {}
+
+impl PartialOrd for Qset {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Qset {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.identity().cmp(&other.identity())
+    }
+}
+
+impl InternalScpQuorumSet {
+    fn identity(&self) -> (u32, &Vec<String>, &Vec<InternalScpQuorumSet>) {
+        (self.threshold, &self.validators, &self.inner_sets)
+    }
+}
+
+impl PartialEq for InternalScpQuorumSet {
+    fn eq(&self, other: &Self) -> bool {
+        self.identity() == other.identity()
+    }
+}
+
+impl Eq for InternalScpQuorumSet {}
+
+impl Hash for InternalScpQuorumSet {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.identity().hash(state)
+    }
+}
+
+impl PartialOrd for InternalScpQuorumSet {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for InternalScpQuorumSet {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.identity().cmp(&other.identity())
+    }
+}
+
+impl InternalScpQuorumSet {
+    // The same qset with validators and inner sets sorted, at every level.
+    // Qsets differing only in these orders are the same to the analysis, and
+    // build the same graph in this form, see `Fbas`.
+    pub(crate) fn canonical(&self) -> Self {
+        let mut inner_sets: Vec<_> = self.inner_sets.iter().map(Self::canonical).collect();
+        inner_sets.sort();
+        let mut validators = self.validators.clone();
+        validators.sort();
+        Self {
+            threshold: self.threshold,
+            threshold_percent: self.threshold_percent,
+            validators,
+            inner_sets,
+        }
+    }
+
+    /// Renders the qset as an indented outline, e.g.
+    ///
+    /// ```text
+    /// 3 of:
+    ///   v1, v2
+    ///   67% (2) of: v3, v4, v5
+    /// ```
+    ///
+    /// Validators are displayed by their alias if they have one, and
+    /// thresholds given as a percentage with both forms.
+    pub fn pretty(&self, aliases: &BTreeMap<String, String>) -> String {
+        let mut out = String::new();
+        self.write_pretty(aliases, 0, &mut out);
+        out
+    }
+
+    fn write_pretty(&self, aliases: &BTreeMap<String, String>, indent: usize, out: &mut String) {
+        let validators = self
+            .validators
+            .iter()
+            .map(|v| aliases.get(v).unwrap_or(v).as_str())
+            .collect::<Vec<_>>()
+            .join(", ");
+        out.push_str(&format!(
+            "{:indent$}{} of:",
+            "",
+            threshold_label(self.threshold, self.threshold_percent),
+            indent = indent
+        ));
+        if self.inner_sets.is_empty() {
+            if !validators.is_empty() {
+                out.push_str(&format!(" {}", validators));
+            }
+            out.push('\n');
+            return;
+        }
+        out.push('\n');
+        if !validators.is_empty() {
+            out.push_str(&format!(
+                "{:indent$}{}\n",
+                "",
+                validators,
+                indent = indent + 2
+            ));
+        }
+        for inner in &self.inner_sets {
+            inner.write_pretty(aliases, indent + 2, out);
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub enum Vertex {
     Validator(String),
     QSet(Qset),
+    /// A node known to the network without a qset, e.g. an observer. It has
+    /// no edges and can't be in any quorum, but is kept for reporting.
+    Watcher(String),
 }
 
 impl Vertex {
     pub fn get_threshold(&self) -> u32 {
         match self {
-            Vertex::Validator(_) => 1,
+            // a watcher would need a qset, which it doesn't have
+            Vertex::Validator(_) | Vertex::Watcher(_) => 1,
             Vertex::QSet(qset) => qset.threshold,
         }
     }
@@ -61,6 +212,17 @@ pub enum FbasError {
     MaxDepthExceeded,
     XdrDecodingError(&'static str),
     InternalError(&'static str),
+    NetworkTooLarge(&'static str),
+    IoError(&'static str),
+    /// Json input that doesn't match the expected format, with what is
+    /// wrong with it.
+    JsonError(String),
+    /// An analysis run by name that can't be, see `AnalysisRegistry::run`:
+    /// unknown, missing some of its inputs or given invalid settings.
+    InvalidAnalysis(String),
+    /// A panic caught at an entry point of the analyzer, with the context it
+    /// happened in.
+    Internal(Box<crate::fbas_analyze::InternalFailure>),
 }
 
 impl std::error::Error for FbasError {}
@@ -72,23 +234,94 @@ impl std::fmt::Display for FbasError {
             FbasError::MaxDepthExceeded => write!(f, "Maximum quorum set depth exceeded"),
             FbasError::XdrDecodingError(msg) => write!(f, "XDR decoding error: {}", msg),
             FbasError::InternalError(msg) => write!(f, "Internal error (likely a bug): {}", msg),
+            FbasError::NetworkTooLarge(msg) => write!(f, "Network too large: {}", msg),
+            FbasError::IoError(msg) => write!(f, "I/O error: {}", msg),
+            FbasError::JsonError(msg) => write!(f, "JSON error: {}", msg),
+            FbasError::InvalidAnalysis(msg) => write!(f, "Invalid analysis: {}", msg),
+            FbasError::Internal(failure) => write!(
+                f,
+                "Internal error (likely a bug): {} panicked in phase {:?} with {} validators, \
+                 {} vertices, {} variables and {} clauses: {}",
+                failure.entry_point,
+                failure.stats.phase,
+                failure.num_validators,
+                failure.num_vertices,
+                failure.stats.num_vars,
+                failure.stats.num_clauses,
+                failure.message
+            ),
+        }
+    }
+}
+
+/// Non-fatal issues found in the network that affect the analysis results.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum FbasWarning {
+    /// A qset's threshold exceeds the number of its (known) members, so it can
+    /// never be satisfied. `validators` are the validators using it as their
+    /// top-level qset, which can therefore never be in a quorum.
+    ThresholdExceedsMembers {
+        threshold: u32,
+        /// The percentage `threshold` was given as, if any.
+        threshold_percent: Option<u32>,
+        members: usize,
+        validators: Vec<String>,
+    },
+    /// The qset of `validator` can never be satisfied because some of its
+    /// inner sets can't be, although its own threshold doesn't exceed its
+    /// members (see `ThresholdExceedsMembers`).
+    UnsatisfiableQset { validator: String },
+}
+
+// A threshold as the input gave it, e.g. `3`, or `67% (3)` for a percentage.
+pub(crate) fn threshold_label(threshold: u32, percent: Option<u32>) -> String {
+    match percent {
+        Some(percent) => format!("{}% ({})", percent, threshold),
+        None => threshold.to_string(),
+    }
+}
+
+impl std::fmt::Display for FbasWarning {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            FbasWarning::ThresholdExceedsMembers {
+                threshold,
+                threshold_percent,
+                members,
+                validators,
+            } => write!(
+                f,
+                "Qset threshold {} exceeds its {} members, validators {:?} can never be in a quorum",
+                threshold_label(*threshold, *threshold_percent),
+                members,
+                validators
+            ),
+            FbasWarning::UnsatisfiableQset { validator } => write!(
+                f,
+                "Qset of validator {} has unsatisfiable inner sets, it can never be in a quorum",
+                validator
+            ),
         }
     }
 }
 
+// The public key of `node` in strkey form, e.g. `GABC...`.
+#[cfg(feature = "xdr")]
+fn node_id_to_string(node: &NodeId) -> String {
+    match &node.0 {
+        PublicKey::PublicKeyTypeEd25519(key) => {
+            stellar_strkey::ed25519::PublicKey(key.0).to_string()
+        }
+    }
+}
+
+#[cfg(feature = "xdr")]
 impl From<ScpQuorumSet> for InternalScpQuorumSet {
     fn from(qset: ScpQuorumSet) -> Self {
         InternalScpQuorumSet {
             threshold: qset.threshold,
-            validators: qset
-                .validators
-                .iter()
-                .map(|node_id| match &node_id.0 {
-                    PublicKey::PublicKeyTypeEd25519(key) => {
-                        stellar_strkey::ed25519::PublicKey(key.0).to_string()
-                    }
-                })
-                .collect(),
+            threshold_percent: None,
+            validators: qset.validators.iter().map(node_id_to_string).collect(),
             inner_sets: qset
                 .inner_sets
                 .iter()
@@ -98,13 +331,80 @@ impl From<ScpQuorumSet> for InternalScpQuorumSet {
     }
 }
 
-#[derive(Default, Debug)]
-pub(crate) struct Fbas {
+/// The network as a graph of validators and qsets. Its numbering is
+/// canonical, so that everything derived from it (variable numbering,
+/// exports, seeds, the order of results) is stable:
+/// - Validators come first, sorted by key, with `validators` in that order.
+/// - Qset vertices follow in order of first use, going through the qsets of
+///   the validators in key order, each with its validators and inner sets
+///   sorted, inner sets before the qset using them.
+///
+/// The order in which qsets list their members, or the network its
+/// validators, therefore makes no difference.
+#[derive(Default, Debug, Clone)]
+pub struct Fbas {
     pub graph: DiGraph<Vertex, ()>,
     pub validators: Vec<NodeIndex>,
+    // non-fatal issues found while building the graph, see `DiagnosticsSink`
+    pub(crate) diagnostics: Vec<String>,
+    // the number of distinct validators dropped from qsets because they have
+    // no qset themselves, recorded in reports
+    pub(crate) unknown_validators: usize,
+    // findings of the qset lints on the qsets as parsed, see
+    // `FbasAnalyzer::qset_lints`
+    pub(crate) lints: Vec<QsetLint>,
+    // what the input tells about the nodes besides their qsets, by key
+    pub(crate) metadata: BTreeMap<String, NodeMetadata>,
+    // arbitrary attributes of the nodes (e.g. ISP, country), by attribute
+    // name and then key, see `FbasAnalyzer::set_node_attribute`
+    pub(crate) attributes: BTreeMap<String, BTreeMap<String, String>>,
 }
 
 impl Fbas {
+    // Puts diagnostics raised before the graph was built (i.e. while parsing)
+    // ahead of the ones raised while building it.
+    fn with_earlier_diagnostics(mut self, mut diagnostics: Vec<String>) -> Self {
+        diagnostics.append(&mut self.diagnostics);
+        self.diagnostics = diagnostics;
+        self
+    }
+
+    // Adds the watchers after the validators and qsets, so that their indices
+    // are unaffected. Watchers referenced in qsets are reported as such
+    // rather than as unknown.
+    pub(crate) fn with_watchers(mut self, watchers: Vec<String>) -> Self {
+        for w in watchers {
+            let unknown = format!("Validator {} is unknown", w);
+            if let Some(diagnostic) = self.diagnostics.iter_mut().find(|d| **d == unknown) {
+                *diagnostic = format!("Validator {} is a watcher without a qset", w);
+            }
+            self.graph.add_node(Vertex::Watcher(w));
+        }
+        self
+    }
+
+    #[cfg(any(feature = "json", test))]
+    pub(crate) fn with_metadata(mut self, metadata: BTreeMap<String, NodeMetadata>) -> Self {
+        self.metadata = metadata;
+        self
+    }
+
+    #[cfg(any(feature = "json", test))]
+    pub(crate) fn with_attributes(
+        mut self,
+        attributes: BTreeMap<String, BTreeMap<String, String>>,
+    ) -> Self {
+        self.attributes = attributes;
+        self
+    }
+
+    pub(crate) fn watchers(&self) -> impl Iterator<Item = &str> {
+        self.graph.node_weights().filter_map(|vertex| match vertex {
+            Vertex::Watcher(w) => Some(w.as_str()),
+            _ => None,
+        })
+    }
+
     fn add_validator(&mut self, v: String) -> NodeIndex {
         let idx = self.graph.add_node(Vertex::Validator(v));
         self.validators.push(idx);
@@ -118,24 +418,420 @@ impl Fbas {
         }
     }
 
-    fn from_quorum_set_map(qsm: QuorumSetMap) -> Result<Self, FbasError> {
-        let mut fbas = Fbas::default();
+    pub(crate) fn try_get_validator_index(&self, v: &str) -> Result<NodeIndex, FbasError> {
+        self.validators
+            .iter()
+            .find(|ni| matches!(self.graph.node_weight(**ni), Some(Vertex::Validator(s)) if s == v))
+            .copied()
+            .ok_or(FbasError::InternalError("Validator not found"))
+    }
+
+    /// Checks the consistency of the graph, see
+    /// `FbasAnalyzer::check_invariants`.
+    pub(crate) fn check_invariants(&self) -> Result<(), FbasError> {
+        let listed: BTreeSet<NodeIndex> = self.validators.iter().copied().collect();
+        if listed.len() != self.validators.len() {
+            return Err(FbasError::InternalError("Validator listed twice"));
+        }
+        let mut names = BTreeSet::new();
+        for ni in self.graph.node_indices() {
+            let successors: BTreeSet<NodeIndex> = self.graph.neighbors(ni).collect();
+            if successors.len() != self.graph.neighbors(ni).count() {
+                return Err(FbasError::InternalError("Parallel edges in the graph"));
+            }
+            match &self.graph[ni] {
+                Vertex::Validator(name) => {
+                    if !listed.contains(&ni) {
+                        return Err(FbasError::InternalError("Validator vertex not listed"));
+                    }
+                    if !names.insert(name) {
+                        return Err(FbasError::InternalError("Validator name not unique"));
+                    }
+                    let mut qsets = successors.iter();
+                    match (qsets.next(), qsets.next()) {
+                        (Some(qi), None) if matches!(self.graph[*qi], Vertex::QSet(_)) => {}
+                        _ => {
+                            return Err(FbasError::InternalError(
+                                "Validator doesn't have exactly one qset",
+                            ))
+                        }
+                    }
+                }
+                Vertex::QSet(qset) => {
+                    if qset
+                        .validators
+                        .union(&qset.inner_qsets)
+                        .ne(successors.iter())
+                        || !qset.validators.is_disjoint(&qset.inner_qsets)
+                    {
+                        return Err(FbasError::InternalError(
+                            "Qset members don't match its edges",
+                        ));
+                    }
+                    let members_match = qset
+                        .validators
+                        .iter()
+                        .all(|vi| matches!(self.graph[*vi], Vertex::Validator(_)))
+                        && qset
+                            .inner_qsets
+                            .iter()
+                            .all(|qi| matches!(self.graph[*qi], Vertex::QSet(_)));
+                    if !members_match {
+                        return Err(FbasError::InternalError("Qset member of the wrong kind"));
+                    }
+                }
+                Vertex::Watcher(name) => {
+                    if !names.insert(name) {
+                        return Err(FbasError::InternalError("Watcher name not unique"));
+                    }
+                    let has_edges = self.graph.neighbors_undirected(ni).next().is_some();
+                    if has_edges {
+                        return Err(FbasError::InternalError("Watcher has edges"));
+                    }
+                }
+            }
+        }
+        if listed
+            .iter()
+            .any(|vi| !matches!(self.graph.node_weight(*vi), Some(Vertex::Validator(_))))
+        {
+            return Err(FbasError::InternalError(
+                "Listed validator is not a validator vertex",
+            ));
+        }
+        Ok(())
+    }
+
+    /// Returns whether the validators in `set` form a quorum, i.e. `set` is
+    /// non-empty and every qset of its members is satisfied within `set`.
+    pub fn is_quorum(&self, set: &BTreeSet<NodeIndex>) -> bool {
+        !set.is_empty() && self.max_quorum(&self.validators_outside(set)) == *set
+    }
+
+    /// Returns the validators `vi` transitively depends on: those in its
+    /// qset, those in theirs, and so on, including inner sets. `vi` itself
+    /// is included only if reachable this way, e.g. through its own qset.
+    pub fn transitive_dependencies(&self, vi: NodeIndex) -> BTreeSet<NodeIndex> {
+        let mut dependencies = BTreeSet::new();
+        let mut visited = BTreeSet::new();
+        let mut stack: Vec<_> = self.graph.neighbors(vi).collect();
+        while let Some(ni) = stack.pop() {
+            if !visited.insert(ni) {
+                continue;
+            }
+            if matches!(self.graph[ni], Vertex::Validator(_)) {
+                dependencies.insert(ni);
+            }
+            stack.extend(self.graph.neighbors(ni));
+        }
+        dependencies
+    }
+
+    /// Returns the validator-level trust graph: one node per validator,
+    /// weighted by its key and in the order of `validators` (so node `i` is
+    /// `validators[i]`), with an edge to every other validator in its qset,
+    /// inner sets included. This is the graph with the qset vertices
+    /// contracted, for centrality computations and visualization; qset
+    /// thresholds are lost, and so are unknown validators.
+    pub fn trust_graph(&self) -> Result<DiGraph<String, ()>, FbasError> {
+        let mut trust = DiGraph::with_capacity(self.validators.len(), self.validators.len());
+        let mut positions = BTreeMap::new();
+        for vi in self.validators.iter() {
+            positions.insert(*vi, trust.add_node(self.try_get_validator_string(vi)?));
+        }
+        for vi in self.validators.iter() {
+            for t in self.trusted_validators(*vi) {
+                trust.add_edge(positions[vi], positions[&t], ());
+            }
+        }
+        Ok(trust)
+    }
+
+    /// Compares this snapshot of a network to a later one, `other`: the
+    /// validators added and removed, and those whose qset changed, as seen by
+    /// the analysis (without unknown validators, order aside). The impact on
+    /// quorum intersection is left out, see `FbasAnalyzer::diff`.
+    pub fn diff(&self, other: &Fbas) -> Result<FbasDiff, FbasError> {
+        let (before, after) = (self.quorum_set_map()?, other.quorum_set_map()?);
+        let aliases = BTreeMap::new();
+        let mut diff = FbasDiff::default();
+        for (validator, qset) in before.iter() {
+            match after.get(validator) {
+                None => diff.removed.push(validator.clone()),
+                Some(changed) if changed != qset => diff.changed.push(QsetChange {
+                    validator: validator.clone(),
+                    before: qset.pretty(&aliases),
+                    after: changed.pretty(&aliases),
+                }),
+                Some(_) => {}
+            }
+        }
+        diff.added = after
+            .keys()
+            .filter(|v| !before.contains_key(*v))
+            .cloned()
+            .collect();
+        Ok(diff)
+    }
+
+    /// Returns whether `set` contains a quorum, i.e. the validators in `set`
+    /// can reach agreement among themselves should the others fail.
+    pub fn contains_quorum(&self, set: &BTreeSet<NodeIndex>) -> bool {
+        !self.max_quorum(&self.validators_outside(set)).is_empty()
+    }
+
+    pub(crate) fn validators_outside(&self, set: &BTreeSet<NodeIndex>) -> BTreeSet<NodeIndex> {
+        self.validators
+            .iter()
+            .filter(|ni| !set.contains(ni))
+            .copied()
+            .collect()
+    }
+
+    /// Returns whether `set` is a minimal quorum, i.e. a quorum none of whose
+    /// proper subsets is a quorum. It suffices that no member can be left out
+    /// with a quorum remaining among the others.
+    pub fn is_minimal_quorum(&self, set: &BTreeSet<NodeIndex>) -> bool {
+        let outside = self.validators_outside(set);
+        self.is_quorum(set)
+            && set.iter().all(|vi| {
+                let mut failed = outside.clone();
+                failed.insert(*vi);
+                self.max_quorum(&failed).is_empty()
+            })
+    }
+
+    /// Shrinks `set` to a minimal quorum within it, or to the empty set if it
+    /// contains no quorum: starting from the largest quorum within `set`,
+    /// members are dropped one by one, in index order, as long as a quorum
+    /// remains among the others. Which minimal quorum is found depends on
+    /// that order.
+    pub fn minimize_quorum(&self, set: &BTreeSet<NodeIndex>) -> BTreeSet<NodeIndex> {
+        let mut failed = self.validators_outside(set);
+        let mut quorum = self.max_quorum(&failed);
+        for vi in quorum.clone() {
+            if !quorum.contains(&vi) {
+                continue;
+            }
+            failed.insert(vi);
+            let smaller = self.max_quorum(&failed);
+            if smaller.is_empty() {
+                failed.remove(&vi);
+            } else {
+                quorum = smaller;
+            }
+        }
+        quorum
+    }
+
+    /// Reconstructs the qset of validator `vi` as seen by the analysis, i.e.
+    /// without unknown validators.
+    pub(crate) fn validator_qset(&self, vi: NodeIndex) -> Result<InternalScpQuorumSet, FbasError> {
+        let qi = self
+            .graph
+            .neighbors(vi)
+            .next()
+            .ok_or(FbasError::InternalError("Validator has no qset"))?;
+        self.internal_qset(qi)
+    }
+
+    /// Reconstructs the qsets of all validators as seen by the analysis, see
+    /// `validator_qset`.
+    pub(crate) fn quorum_set_map(&self) -> Result<QuorumSetMap, FbasError> {
+        self.validators
+            .iter()
+            .map(|vi| {
+                Ok((
+                    self.try_get_validator_string(vi)?,
+                    Arc::new(self.validator_qset(*vi)?),
+                ))
+            })
+            .collect()
+    }
+
+    pub(crate) fn internal_qset(&self, qi: NodeIndex) -> Result<InternalScpQuorumSet, FbasError> {
+        let Vertex::QSet(qset) = &self.graph[qi] else {
+            return Err(FbasError::InternalError("Node index is not a qset"));
+        };
+        Ok(InternalScpQuorumSet {
+            threshold: qset.threshold,
+            threshold_percent: qset.threshold_percent,
+            validators: qset
+                .validators
+                .iter()
+                .map(|ni| self.try_get_validator_string(ni))
+                .collect::<Result<_, _>>()?,
+            inner_sets: qset
+                .inner_qsets
+                .iter()
+                .map(|ni| self.internal_qset(*ni))
+                .collect::<Result<_, _>>()?,
+        })
+    }
+
+    /// Returns the validators appearing anywhere in `vi`'s qset, including
+    /// nested inner sets, but excluding `vi` itself.
+    pub(crate) fn trusted_validators(&self, vi: NodeIndex) -> BTreeSet<NodeIndex> {
+        let mut trusted = BTreeSet::new();
+        let mut stack: Vec<NodeIndex> = self.graph.neighbors(vi).collect();
+        while let Some(ni) = stack.pop() {
+            match self.graph[ni] {
+                Vertex::Validator(_) => {
+                    trusted.insert(ni);
+                }
+                Vertex::QSet(_) => stack.extend(self.graph.neighbors(ni)),
+                // watchers have no incoming edges
+                Vertex::Watcher(_) => {}
+            }
+        }
+        trusted.remove(&vi);
+        trusted
+    }
+
+    /// Finds qset vertices whose threshold exceeds the number of successors.
+    /// The encoding silently renders such vertices (and validators depending
+    /// on them) unusable, so they are surfaced as warnings instead.
+    pub(crate) fn find_unsatisfiable_qsets(&self) -> Result<Vec<FbasWarning>, FbasError> {
+        let mut warnings = vec![];
+        for ni in self.graph.node_indices() {
+            let Vertex::QSet(qset) = &self.graph[ni] else {
+                continue;
+            };
+            let members = self.graph.neighbors(ni).count();
+            if qset.threshold as usize <= members {
+                continue;
+            }
+            let validators = self
+                .graph
+                .neighbors_directed(ni, petgraph::Direction::Incoming)
+                .filter(|vi| matches!(self.graph[*vi], Vertex::Validator(_)))
+                .map(|vi| self.try_get_validator_string(&vi))
+                .collect::<Result<BTreeSet<_>, _>>()?;
+            warnings.push(FbasWarning::ThresholdExceedsMembers {
+                threshold: qset.threshold,
+                threshold_percent: qset.threshold_percent,
+                members,
+                validators: validators.into_iter().collect(),
+            });
+        }
+
+        let mut satisfiable = BTreeMap::new();
+        for vi in self.validators.iter() {
+            let Some(qi) = self.graph.neighbors(*vi).next() else {
+                continue;
+            };
+            let members = self.graph.neighbors(qi).count();
+            if self.graph[qi].get_threshold() as usize <= members
+                && !self.is_qset_satisfiable(qi, &mut satisfiable)
+            {
+                warnings.push(FbasWarning::UnsatisfiableQset {
+                    validator: self.try_get_validator_string(vi)?,
+                });
+            }
+        }
+        Ok(warnings)
+    }
+
+    // Whether the qset vertex `qi` can be satisfied at all, assuming all
+    // validators are available: its threshold must be met by its validators
+    // and satisfiable inner sets.
+    fn is_qset_satisfiable(&self, qi: NodeIndex, memo: &mut BTreeMap<NodeIndex, bool>) -> bool {
+        if let Some(satisfiable) = memo.get(&qi) {
+            return *satisfiable;
+        }
+        let available = self
+            .graph
+            .neighbors(qi)
+            .filter(|ni| match self.graph[*ni] {
+                Vertex::Validator(_) => true,
+                Vertex::QSet(_) => self.is_qset_satisfiable(*ni, memo),
+                Vertex::Watcher(_) => false,
+            })
+            .count();
+        let satisfiable = available >= self.graph[qi].get_threshold() as usize;
+        memo.insert(qi, satisfiable);
+        satisfiable
+    }
+
+    /// Returns the validators of the largest quorum that does not contain any
+    /// of the `failed` validators. Since the union of two quorums is again a
+    /// quorum, this is computed as a fixpoint: starting with all vertices,
+    /// repeatedly drop those whose threshold can no longer be met by the
+    /// remaining ones. The result is empty iff no such quorum exists.
+    pub(crate) fn max_quorum(&self, failed: &BTreeSet<NodeIndex>) -> BTreeSet<NodeIndex> {
+        let alive = self.alive_vertices(failed);
+        self.validators
+            .iter()
+            .filter(|ni| alive.contains(ni))
+            .copied()
+            .collect()
+    }
+
+    /// Returns the vertices (validators and qsets) that can still be satisfied
+    /// when the `failed` validators are out, see `max_quorum`. Any other
+    /// vertex can't be part of a quorum.
+    pub(crate) fn alive_vertices(&self, failed: &BTreeSet<NodeIndex>) -> BTreeSet<NodeIndex> {
+        let mut alive: BTreeSet<NodeIndex> = self
+            .graph
+            .node_indices()
+            .filter(|ni| !failed.contains(ni))
+            .collect();
+        loop {
+            let dead: Vec<NodeIndex> = alive
+                .iter()
+                .filter(|ni| {
+                    let threshold = self.graph[**ni].get_threshold() as usize;
+                    self.graph
+                        .neighbors(**ni)
+                        .filter(|nb| alive.contains(nb))
+                        .count()
+                        < threshold
+                })
+                .copied()
+                .collect();
+            if dead.is_empty() {
+                break;
+            }
+            dead.iter().for_each(|ni| {
+                alive.remove(ni);
+            });
+        }
+        alive
+    }
+
+    pub(crate) fn from_quorum_set_map(qsm: &QuorumSetMap) -> Result<Self, FbasError> {
+        let mut fbas = Fbas {
+            lints: lint_quorum_set_map(qsm),
+            ..Default::default()
+        };
         let mut known_validators = BTreeMap::new();
         let mut known_qsets = BTreeMap::new();
+        // one vertex and edge per validator, plus at least one qset vertex
+        fbas.graph.reserve_nodes(qsm.len() + 1);
+        fbas.graph.reserve_edges(qsm.len());
+        fbas.validators.reserve(qsm.len());
+
+        // in key order whatever the map's, see `Fbas`
+        let mut sorted: Vec<_> = qsm.iter().collect();
+        sorted.sort_by_key(|(key, _)| *key);
 
         // First pass: add all validators
-        for (node_str, _) in qsm.iter() {
-            let idx = fbas.add_validator(node_str.clone());
-            known_validators.insert(node_str, idx);
+        for (node_str, _) in sorted.iter() {
+            let idx = fbas.add_validator((*node_str).clone());
+            known_validators.insert(*node_str, idx);
         }
 
         // Second pass: process quorum sets and create connections
-        for (node_str, qset) in qsm.iter() {
+        for (node_str, qset) in sorted {
             let v_idx = known_validators
                 .get(node_str)
-                .ok_or_else(|| FbasError::InternalError("key not found"))?;
-            let q_idx =
-                fbas.process_scp_quorum_set(qset, 0, &known_validators, &mut known_qsets)?;
+                .ok_or(FbasError::InternalError("key not found"))?;
+            let q_idx = fbas.process_scp_quorum_set(
+                &qset.canonical(),
+                0,
+                &known_validators,
+                &mut known_qsets,
+            )?;
             let _ = fbas.graph.add_edge(*v_idx, q_idx, ());
         }
 
@@ -153,15 +849,23 @@ impl Fbas {
             return Err(FbasError::MaxDepthExceeded);
         }
 
-        let mut new_qset = Qset::default();
-        new_qset.threshold = qset.threshold;
+        let mut new_qset = Qset {
+            threshold: qset.threshold,
+            threshold_percent: qset.threshold_percent,
+            ..Default::default()
+        };
 
         // Add validators
         for validator in &qset.validators {
             if let Some(&idx) = known_validators.get(validator) {
                 new_qset.validators.insert(idx);
             } else {
-                eprintln!("Validator {} is unknown", validator);
+                // the same qset is usually shared by several validators
+                let diagnostic = format!("Validator {} is unknown", validator);
+                if !self.diagnostics.contains(&diagnostic) {
+                    self.diagnostics.push(diagnostic);
+                    self.unknown_validators += 1;
+                }
             }
         }
 
@@ -196,37 +900,349 @@ impl Fbas {
         Ok(idx)
     }
 
+    #[cfg(feature = "xdr")]
     pub fn from_quorum_set_map_buf<T: AsRef<[u8]>, I: ExactSizeIterator<Item = T>>(
         nodes: I,
         quorum_set: I,
     ) -> Result<Self, FbasError> {
-        assert_eq!(nodes.len(), quorum_set.len());
-        let mut quorum_set_map = QuorumSetMap::new();
-
-        for (node_buf, qset_buf) in nodes.zip(quorum_set) {
-            let node = NodeId::from_xdr(node_buf, Limits::none())
-                .map_err(|_| FbasError::XdrDecodingError("NodeId cannot be decoded from xdr"))?;
-            let node_str = match &node.0 {
-                PublicKey::PublicKeyTypeEd25519(key) => {
-                    stellar_strkey::ed25519::PublicKey(key.0).to_string()
-                }
-            };
-            if !qset_buf.as_ref().is_empty() {
-                let qset = ScpQuorumSet::from_xdr(qset_buf, Limits::none()).map_err(|_| {
-                    FbasError::XdrDecodingError("ScpQuorumSet cannot be decoded from xdr")
-                })?;
-                quorum_set_map.insert(node_str, Rc::new(qset.into()));
-            } else {
-                eprintln!("Validator {} is unknown", node_str);
-            }
+        let mut diagnostics = vec![];
+        let quorum_set_map = quorum_set_map_from_buf(nodes, quorum_set, &mut diagnostics)?;
+        Ok(Self::from_quorum_set_map(&quorum_set_map)?.with_earlier_diagnostics(diagnostics))
+    }
+
+    /// Builds the `Fbas` from a directory with one file per validator, named
+    /// after its public key with any extension (e.g. `GABC....xdr`), holding
+    /// its `ScpQuorumSet` in XDR, binary or base64. An empty file is a
+    /// validator whose qset is unknown, as in `from_quorum_set_map_buf`.
+    /// Subdirectories and hidden files are ignored.
+    #[cfg(feature = "xdr")]
+    pub fn from_quorum_set_dir(dir: &str) -> Result<Self, FbasError> {
+        let mut diagnostics = vec![];
+        let quorum_set_map = quorum_set_map_from_dir(dir, &mut diagnostics)?;
+        Ok(Self::from_quorum_set_map(&quorum_set_map)?.with_earlier_diagnostics(diagnostics))
+    }
+
+    /// Builds the `Fbas` from a stream of validators and their
+    /// `ScpQuorumSet`s, e.g. dumped from a captive core database, in either
+    /// of two forms told apart by the first byte:
+    /// - text, one `<public key> <base64 XDR qset>` per line, the qset left
+    ///   out for a validator whose qset is unknown;
+    /// - binary, the XDR of each validator's `NodeId` followed by that of its
+    ///   qset, back to back.
+    ///
+    /// A validator listed more than once takes its last qset.
+    #[cfg(feature = "xdr")]
+    pub fn from_quorum_set_stream(reader: impl std::io::BufRead) -> Result<Self, FbasError> {
+        let mut diagnostics = vec![];
+        let quorum_set_map = quorum_set_map_from_stream(reader, &mut diagnostics)?;
+        Ok(Self::from_quorum_set_map(&quorum_set_map)?.with_earlier_diagnostics(diagnostics))
+    }
+
+    /// Same as `from_quorum_set_map_buf`, but builds the organization-level
+    /// `Fbas` where every organization in `orgs` is a single node.
+    #[cfg(feature = "xdr")]
+    pub fn from_quorum_set_map_buf_org_level<T: AsRef<[u8]>, I: ExactSizeIterator<Item = T>>(
+        nodes: I,
+        quorum_set: I,
+        orgs: &Organizations,
+    ) -> Result<Self, FbasError> {
+        let mut diagnostics = vec![];
+        let quorum_set_map = quorum_set_map_from_buf(nodes, quorum_set, &mut diagnostics)?;
+        let org_map = org_quorum_set_map(&quorum_set_map, orgs, &mut diagnostics)?;
+        Ok(Self::from_quorum_set_map(&org_map)?.with_earlier_diagnostics(diagnostics))
+    }
+
+    /// Collapses the validators of every organization of `orgs` into a
+    /// single node named after it, whose qset is the organization-level qset
+    /// of its validators (see `OrgQuorumSet`): inner sets made up of one
+    /// organization's validators become that organization, whatever their
+    /// threshold. Validators without an organization stay as they are. The
+    /// result has one node per organization, which makes organization-level
+    /// analyses faster, and is built the same way as
+    /// `from_json_path_org_level`, from this network rather than an input
+    /// file. Unknown validators are left out, as they are in the analysis.
+    /// Returns an error if the validators of an organization don't agree on
+    /// their qset.
+    pub fn collapse_organizations(&self, orgs: &Organizations) -> Result<Self, FbasError> {
+        let qsm = self.quorum_set_map()?;
+        let orgs = orgs.with_singletons(qsm.keys());
+        let mut diagnostics = vec![];
+        let org_map = org_quorum_set_map(&qsm, &orgs, &mut diagnostics)?;
+        Ok(Self::from_quorum_set_map(&org_map)?.with_earlier_diagnostics(diagnostics))
+    }
+
+    /// Builds the sub-network induced by `validators`: their qsets with every
+    /// member outside `validators` absent, i.e. unknown, as if it had
+    /// failed. Thresholds are kept, so qsets relying on the others may become
+    /// unsatisfiable. Node metadata and attributes of `validators` carry over.
+    pub fn induced_subnetwork(&self, validators: &BTreeSet<NodeIndex>) -> Result<Self, FbasError> {
+        let mut qsm = self.quorum_set_map()?;
+        let keep = validators
+            .iter()
+            .map(|vi| self.try_get_validator_string(vi))
+            .collect::<Result<BTreeSet<_>, _>>()?;
+        qsm.retain(|v, _| keep.contains(v));
+        let mut sub = Self::from_quorum_set_map(&qsm)?;
+        sub.metadata = self.metadata.clone();
+        sub.metadata.retain(|v, _| keep.contains(v));
+        sub.attributes = self.attributes.clone();
+        for values in sub.attributes.values_mut() {
+            values.retain(|v, _| keep.contains(v));
         }
+        Ok(sub)
+    }
 
-        Self::from_quorum_set_map(quorum_set_map)
+    /// Replaces the qset of `node` with `qset`, to preview a configuration
+    /// change. The network is rebuilt from its qsets with `node`'s replaced,
+    /// so it is numbered canonically (see `Fbas`) as if loaded that way.
+    /// Members of `qset` unknown to the network are dropped as usual, and the
+    /// lints of `node` are redone; diagnostics, watchers, metadata and
+    /// attributes carry over. Returns an error if `node` is not a validator or
+    /// `qset` is nested too deeply.
+    pub fn with_replaced_qset(
+        self,
+        node: &str,
+        qset: &InternalScpQuorumSet,
+    ) -> Result<Self, FbasError> {
+        self.try_get_validator_index(node)?;
+        let mut qsm = self.quorum_set_map()?;
+        qsm.insert(node.to_string(), Arc::new(qset.clone()));
+        let watchers = self.watchers().map(str::to_string).collect();
+        let rebuilt = Self::from_quorum_set_map(&qsm)?.with_watchers(watchers);
+
+        // the other qsets are already stripped of unknown members, so only
+        // `qset` can raise new diagnostics
+        let new: Vec<String> = rebuilt
+            .diagnostics
+            .into_iter()
+            .filter(|d| !self.diagnostics.contains(d))
+            .collect();
+        let unknown_validators = self.unknown_validators + new.len();
+        let mut diagnostics = self.diagnostics;
+        diagnostics.extend(new);
+        // the lints are of the qsets as parsed, which the rebuild no longer has
+        let mut lints = self.lints;
+        let replaced = QuorumSetMap::from([(node.to_string(), Arc::new(qset.clone()))]);
+        lints.retain(|lint| lint.validator != node);
+        lints.extend(lint_quorum_set_map(&replaced));
+        lints.sort_by(|a, b| a.validator.cmp(&b.validator));
+        Ok(Fbas {
+            graph: rebuilt.graph,
+            validators: rebuilt.validators,
+            diagnostics,
+            unknown_validators,
+            lints,
+            metadata: self.metadata,
+            attributes: self.attributes,
+        })
+    }
+
+    /// Builds the `Fbas` from the compact text syntax, e.g.
+    /// `a, b, c: 2-of(a, b, c)`, one line per group of validators sharing a
+    /// qset. See `dsl.rs` for the full syntax.
+    pub fn from_dsl(dsl: &str) -> Result<Self, FbasError> {
+        let quorum_set_map = crate::dsl::quorum_set_map_from_dsl(dsl)?;
+        Self::from_quorum_set_map(&quorum_set_map)
     }
 
     #[cfg(any(feature = "json", test))]
     pub fn from_json_path(path: &str) -> Result<Self, FbasError> {
+        Self::from_json_path_with_min_uptime(path, 0)
+    }
+
+    /// Builds the `Fbas` from json in any of the formats `from_json_path`
+    /// accepts, read from `reader` as a stream, e.g. an HTTP response body.
+    #[cfg(any(feature = "json", test))]
+    pub fn from_json_reader(reader: impl std::io::Read) -> Result<Self, FbasError> {
+        Self::from_node_collector(crate::json_parser::collect_nodes_from_reader(reader, 0)?)
+    }
+
+    /// Same as `from_json_reader`, for json already in memory.
+    #[cfg(any(feature = "json", test))]
+    pub fn from_json_str(json: &str) -> Result<Self, FbasError> {
+        Self::from_json_reader(json.as_bytes())
+    }
+
+    /// Builds the organization-level `Fbas` where every organization (as given
+    /// by the mapping in `orgs_path`) is a single node.
+    #[cfg(any(feature = "json", test))]
+    pub fn from_json_path_org_level(path: &str, orgs_path: &str) -> Result<Self, FbasError> {
         let quorum_set_map = crate::json_parser::quorum_set_map_from_json(path)?;
-        Self::from_quorum_set_map(quorum_set_map)
+        let orgs = crate::json_parser::organizations_from_json(orgs_path)?;
+        let mut diagnostics = vec![];
+        let org_map = org_quorum_set_map(&quorum_set_map, &orgs, &mut diagnostics)?;
+        Ok(Self::from_quorum_set_map(&org_map)?.with_earlier_diagnostics(diagnostics))
+    }
+
+    #[cfg(any(feature = "json", test))]
+    pub fn from_json_path_with_min_uptime(path: &str, min_uptime: u32) -> Result<Self, FbasError> {
+        let reader = crate::json_parser::open_json(path)?;
+        let collector = crate::json_parser::collect_nodes_from_reader(reader, min_uptime)?;
+        Self::from_node_collector(collector)
+    }
+
+    /// Builds the `Fbas` from nodes in the stellarbeats format, e.g. fetched
+    /// from a source of one's own or constructed programmatically, along with
+    /// their metadata and attributes. Nodes without a qset are watchers.
+    #[cfg(any(feature = "json", test))]
+    pub fn from_node_records(records: &[NodeRecord]) -> Result<Self, FbasError> {
+        Self::from_node_records_with_min_uptime(records, 0)
+    }
+
+    // Same as `from_node_records`, leaving out the nodes below `min_uptime`,
+    // see `quorum_set_map_from_json_with_min_uptime`.
+    #[cfg(any(feature = "json", test))]
+    pub(crate) fn from_node_records_with_min_uptime(
+        records: &[NodeRecord],
+        min_uptime: u32,
+    ) -> Result<Self, FbasError> {
+        let mut collector = NodeCollector::new(min_uptime);
+        for node in records {
+            collector.add(node)?;
+        }
+        Self::from_node_collector(collector)
+    }
+
+    #[cfg(any(feature = "json", test))]
+    pub(crate) fn from_node_collector(collector: NodeCollector) -> Result<Self, FbasError> {
+        Ok(Self::from_quorum_set_map(&collector.quorum_set_map)?
+            .with_watchers(collector.watchers)
+            .with_metadata(collector.metadata)
+            .with_attributes(collector.attributes)
+            .with_earlier_diagnostics(collector.diagnostics))
+    }
+}
+
+#[cfg(feature = "xdr")]
+pub(crate) fn quorum_set_map_from_buf<T: AsRef<[u8]>, I: ExactSizeIterator<Item = T>>(
+    nodes: I,
+    quorum_set: I,
+    diagnostics: &mut Vec<String>,
+) -> Result<QuorumSetMap, FbasError> {
+    assert_eq!(nodes.len(), quorum_set.len());
+    let mut quorum_set_map = QuorumSetMap::new();
+
+    for (node_buf, qset_buf) in nodes.zip(quorum_set) {
+        let node = NodeId::from_xdr(node_buf, Limits::none())
+            .map_err(|_| FbasError::XdrDecodingError("NodeId cannot be decoded from xdr"))?;
+        let node_str = node_id_to_string(&node);
+        if !qset_buf.as_ref().is_empty() {
+            let qset = ScpQuorumSet::from_xdr(qset_buf, Limits::none()).map_err(|_| {
+                FbasError::XdrDecodingError("ScpQuorumSet cannot be decoded from xdr")
+            })?;
+            quorum_set_map.insert(node_str, Arc::new(qset.into()));
+        } else {
+            diagnostics.push(format!("Validator {} is unknown", node_str));
+        }
+    }
+    Ok(quorum_set_map)
+}
+
+// Binary XDR of a qset starts with the high byte of its threshold, which is
+// zero for any real threshold, whereas base64 text never contains zero bytes.
+#[cfg(feature = "xdr")]
+fn is_binary_xdr(buf: &[u8]) -> bool {
+    buf.first() == Some(&0)
+}
+
+#[cfg(feature = "xdr")]
+fn decode_quorum_set(buf: &[u8]) -> Result<ScpQuorumSet, FbasError> {
+    let qset = match is_binary_xdr(buf) {
+        true => ScpQuorumSet::from_xdr(buf, Limits::none()),
+        false => ScpQuorumSet::from_xdr_base64(buf.trim_ascii(), Limits::none()),
+    };
+    qset.map_err(|_| FbasError::XdrDecodingError("ScpQuorumSet cannot be decoded from xdr"))
+}
+
+#[cfg(feature = "xdr")]
+fn validate_public_key(key: &str) -> Result<(), FbasError> {
+    stellar_strkey::ed25519::PublicKey::from_string(key)
+        .map(|_| ())
+        .map_err(|_| FbasError::ParseError("invalid validator public key"))
+}
+
+// Adds `node` with the qset in `qset_buf` to `quorum_set_map`, or as unknown
+// if the buffer is empty.
+#[cfg(feature = "xdr")]
+fn insert_quorum_set(
+    quorum_set_map: &mut QuorumSetMap,
+    node: String,
+    qset_buf: &[u8],
+    diagnostics: &mut Vec<String>,
+) -> Result<(), FbasError> {
+    if qset_buf.trim_ascii().is_empty() {
+        diagnostics.push(format!("Validator {} is unknown", node));
+        quorum_set_map.remove(&node);
+    } else {
+        let qset = decode_quorum_set(qset_buf)?;
+        quorum_set_map.insert(node, Arc::new(qset.into()));
+    }
+    Ok(())
+}
+
+#[cfg(feature = "xdr")]
+pub(crate) fn quorum_set_map_from_dir(
+    dir: &str,
+    diagnostics: &mut Vec<String>,
+) -> Result<QuorumSetMap, FbasError> {
+    let mut paths: Vec<_> = std::fs::read_dir(dir)
+        .map_err(|_| FbasError::IoError("fail to read directory"))?
+        .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+        .filter(|path| path.is_file())
+        .collect();
+    paths.sort();
+    let mut quorum_set_map = QuorumSetMap::new();
+    for path in paths {
+        let Some(node) = path.file_stem().and_then(|stem| stem.to_str()) else {
+            continue;
+        };
+        if node.starts_with('.') {
+            continue;
+        }
+        validate_public_key(node)?;
+        let buf = std::fs::read(&path).map_err(|_| FbasError::IoError("fail to read file"))?;
+        insert_quorum_set(&mut quorum_set_map, node.to_string(), &buf, diagnostics)?;
+    }
+    Ok(quorum_set_map)
+}
+
+#[cfg(feature = "xdr")]
+pub(crate) fn quorum_set_map_from_stream(
+    mut reader: impl std::io::BufRead,
+    diagnostics: &mut Vec<String>,
+) -> Result<QuorumSetMap, FbasError> {
+    let read_err = |_| FbasError::IoError("fail to read stream");
+    let mut quorum_set_map = QuorumSetMap::new();
+    if !is_binary_xdr(reader.fill_buf().map_err(read_err)?) {
+        for line in reader.lines() {
+            let line = line.map_err(read_err)?;
+            let mut fields = line.split_whitespace();
+            let Some(node) = fields.next() else {
+                continue;
+            };
+            let qset = fields.next().unwrap_or_default();
+            if fields.next().is_some() {
+                return Err(FbasError::ParseError(
+                    "expected a public key and a qset per line",
+                ));
+            }
+            validate_public_key(node)?;
+            insert_quorum_set(
+                &mut quorum_set_map,
+                node.to_string(),
+                qset.as_bytes(),
+                diagnostics,
+            )?;
+        }
+        return Ok(quorum_set_map);
+    }
+    let mut limited = Limited::new(reader, Limits::none());
+    while !limited.inner.fill_buf().map_err(read_err)?.is_empty() {
+        let node = NodeId::read_xdr(&mut limited)
+            .map_err(|_| FbasError::XdrDecodingError("NodeId cannot be decoded from xdr"))?;
+        let qset = ScpQuorumSet::read_xdr(&mut limited)
+            .map_err(|_| FbasError::XdrDecodingError("ScpQuorumSet cannot be decoded from xdr"))?;
+        quorum_set_map.insert(node_id_to_string(&node), Arc::new(qset.into()));
     }
+    Ok(quorum_set_map)
 }
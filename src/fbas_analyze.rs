@@ -1,10 +1,24 @@
-use crate::fbas::{Fbas, FbasError};
+use crate::fbas::{Fbas, FbasError, FbasWarning, Vertex};
+#[cfg(any(feature = "json", test))]
+use crate::json_parser::NodeRecord;
+use crate::lint::QsetLint;
+use crate::organization::{NodeMetadata, Organizations};
+use crate::pruning::{main_component, DefaultPruning, PruningStrategy};
+use crate::telemetry::{NetworkTag, Span};
 use batsat::{
     interface::SolveResult, intmap::AsIndex, lbool, theory, Callbacks, Lit, Solver,
-    SolverInterface, Var,
+    SolverInterface, SolverOpts, Var,
+};
+use petgraph::{
+    csr::IndexType,
+    graph::{DiGraph, NodeIndex},
+    Direction,
+};
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    panic::AssertUnwindSafe,
+    sync::OnceLock,
 };
-use itertools::Itertools;
-use petgraph::{csr::IndexType, graph::NodeIndex};
 
 // Two imaginary quorums A and B, and we have FBAS system with V vertices. Note
 // the a quorum contain validators, whereas a vertex can be either a validator
@@ -33,35 +47,1095 @@ use petgraph::{csr::IndexType, graph::NodeIndex};
 // satisfiable result (result == SAT), that means a disjoint quorum has been
 // found.
 
-struct FbasLitsWrapper {
+pub(crate) struct FbasLitsWrapper {
     vertex_count: usize,
 }
 
 impl FbasLitsWrapper {
-    fn new(vcount: usize) -> Self {
+    pub(crate) fn new(vcount: usize) -> Self {
         Self {
             vertex_count: vcount,
         }
     }
 
-    fn in_quorum_a(&self, ni: &NodeIndex) -> Lit {
+    pub(crate) fn in_quorum_a(&self, ni: &NodeIndex) -> Lit {
         Lit::new(Var::from_index(ni.index()), true)
     }
 
-    fn in_quorum_b(&self, ni: &NodeIndex) -> Lit {
+    pub(crate) fn in_quorum_b(&self, ni: &NodeIndex) -> Lit {
         Lit::new(Var::from_index(ni.index() + self.vertex_count), true)
     }
 
-    fn new_proposition<Solver: SolverInterface>(&self, solver: &mut Solver) -> Lit {
-        Lit::new(solver.new_var_default(), true)
+    pub(crate) fn in_quorum(&self, ni: &NodeIndex, quorum: Quorum) -> Lit {
+        match quorum {
+            Quorum::A => self.in_quorum_a(ni),
+            Quorum::B => self.in_quorum_b(ni),
+        }
     }
 }
 
+/// The qset of a validator annotated with a SAT model, see
+/// `FbasAnalyzer::qset_membership`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct QsetMembership {
+    pub threshold: u32,
+    /// Whether the qset is satisfied by the quorum in the model.
+    pub in_quorum: bool,
+    /// The validators of the qset and whether each is in the quorum.
+    pub validators: Vec<(String, bool)>,
+    pub inner_sets: Vec<QsetMembership>,
+}
+
+/// Identifies one of the two imaginary quorums in the encoding.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Quorum {
+    A,
+    B,
+}
+
+// Memoization of slice propositions. Identical (threshold, elements) patterns
+// across vertices would otherwise generate identical clause structures. A
+// pattern that occurs more than once gets its slice propositions defined
+// independently of any vertex (so they can be shared), whereas a pattern that
+// occurs only once keeps the regular encoding where all clauses are guarded by
+// the vertex literal, which the solver tends to handle better.
+#[derive(Default)]
+pub(crate) struct SliceMemo {
+    counts: BTreeMap<(u32, Vec<Lit>), usize>,
+    shared: BTreeMap<(u32, Vec<Lit>), usize>,
+    cardinality: CardinalityEncoding,
+}
+
+// Qsets with at least this many members are encoded with a chunked counter
+// instead of one slice per combination, see `counter_clauses`.
+const CHUNKED_CARDINALITY_MIN_MEMBERS: usize = 32;
+
+// Members per chunk of the chunked counter. Smaller chunks mean fewer
+// variables and clauses inside the chunks but a longer chain of registers
+// between them, i.e. more propagation steps to derive a count; larger chunks
+// the opposite. Around 8 works well for flat qsets of a few hundred members.
+const CARDINALITY_CHUNK_SIZE: usize = 8;
+
+// When and how threshold relations are encoded with a chunked counter.
+#[derive(Clone, Copy, Debug)]
+pub(crate) struct CardinalityEncoding {
+    pub(crate) min_members: usize,
+    pub(crate) chunk_size: usize,
+}
+
+impl Default for CardinalityEncoding {
+    fn default() -> Self {
+        Self {
+            min_members: CHUNKED_CARDINALITY_MIN_MEMBERS,
+            chunk_size: CARDINALITY_CHUNK_SIZE,
+        }
+    }
+}
+
+impl SliceMemo {
+    pub(crate) fn with_cardinality(cardinality: CardinalityEncoding) -> Self {
+        Self {
+            cardinality,
+            ..Default::default()
+        }
+    }
+
+    fn key(threshold: u32, elems: &[Lit]) -> (u32, Vec<Lit>) {
+        let mut elems = elems.to_vec();
+        elems.sort_unstable();
+        (threshold, elems)
+    }
+
+    // Registers an occurrence of a pattern, must be called for every pattern
+    // before clauses are added.
+    pub(crate) fn count(&mut self, threshold: u32, elems: &[Lit]) {
+        *self.counts.entry(Self::key(threshold, elems)).or_default() += 1;
+    }
+
+    // Allocates (or looks up, for shared patterns) the slice propositions of
+    // `lit -> (threshold out of elems)`.
+    fn slice_vars<S: SolverInterface>(
+        &mut self,
+        solver: &mut S,
+        threshold: u32,
+        elems: &[Lit],
+    ) -> SliceVars {
+        let new_vars = |count: usize| {
+            let first = solver.num_vars() as usize;
+            for i in 0..count {
+                let v = solver.new_var_default();
+                debug_assert_eq!(v.idx() as usize, first + i);
+            }
+            first
+        };
+        self.allocate(new_vars, threshold, elems)
+    }
+
+    // Same as `slice_vars`, with `new_vars(count)` allocating `count`
+    // consecutive variables and returning the first.
+    fn allocate(
+        &mut self,
+        mut new_vars: impl FnMut(usize) -> usize,
+        threshold: u32,
+        elems: &[Lit],
+    ) -> SliceVars {
+        // a threshold of 0 or of all elements has a single slice, and the
+        // number of combinations only explodes in between
+        let chunk_size = self.cardinality.chunk_size.max(1);
+        if elems.len() >= self.cardinality.min_members
+            && threshold > 0
+            && (threshold as usize) < elems.len()
+        {
+            let num_vars = counter_num_vars(elems.len(), threshold as usize, chunk_size);
+            return SliceVars::Counter {
+                first: new_vars(num_vars),
+                chunk_size,
+            };
+        }
+        let num_slices = num_combinations(elems.len(), threshold as usize);
+        let key = Self::key(threshold, elems);
+        if self.counts.get(&key).copied().unwrap_or_default() <= 1 {
+            return SliceVars::Guarded(new_vars(num_slices));
+        }
+        match self.shared.get(&key) {
+            Some(first) => SliceVars::Shared {
+                first: *first,
+                define: false,
+            },
+            None => {
+                let first = new_vars(num_slices);
+                self.shared.insert(key, first);
+                SliceVars::Shared {
+                    first,
+                    define: true,
+                }
+            }
+        }
+    }
+}
+
+// The slice propositions of one threshold relation, one per combination of
+// `threshold` elements, as consecutive variables starting at `first`.
+#[derive(Clone, Copy)]
+enum SliceVars {
+    // owned by the relation, with every clause guarded by the vertex literal
+    Guarded(usize),
+    // shared with the other relations of the same pattern, defined (without
+    // a guard) along with the first of them
+    Shared { first: usize, define: bool },
+    // not slices but the registers of a chunked counter, see `counter_clauses`
+    Counter { first: usize, chunk_size: usize },
+}
+
+// Calls `f(j, positions)` for the `j`-th combination of `k` out of `n`
+// positions, in lexicographic order (the order of `Itertools::combinations`),
+// reusing a single buffer for the positions.
+pub(crate) fn for_each_combination(n: usize, k: usize, mut f: impl FnMut(usize, &[usize])) {
+    if k > n {
+        return;
+    }
+    let mut positions: Vec<usize> = (0..k).collect();
+    for j in 0.. {
+        f(j, &positions);
+        // the last position that can still move right
+        let Some(i) = (0..k).rev().find(|i| positions[*i] < n - k + i) else {
+            return;
+        };
+        positions[i] += 1;
+        for l in i + 1..k {
+            positions[l] = positions[l - 1] + 1;
+        }
+    }
+}
+
+fn slice_lit(first: usize, j: usize) -> Lit {
+    Lit::new(Var::from_index(first + j), true)
+}
+
+// Number of ways to choose `k` out of `n`, saturating at `usize::MAX`.
+fn num_combinations(n: usize, k: usize) -> usize {
+    if k > n {
+        return 0;
+    }
+    let mut acc: u128 = 1;
+    for i in 0..k.min(n - k) {
+        acc = acc * (n - i) as u128 / (i + 1) as u128;
+        if acc > usize::MAX as u128 {
+            return usize::MAX;
+        }
+    }
+    acc as usize
+}
+
+// Rough solver memory per variable (assignment, activity, watch lists, ...)
+// and per clause (header and watchers), on top of 4 bytes per literal.
+const BYTES_PER_VAR: u64 = 72;
+const BYTES_PER_CLAUSE: u64 = 20;
+
+/// Projected size of the SAT encoding of a network, see
+/// `FbasAnalyzer::estimate_encoding`. Counts saturate at `u64::MAX`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct EncodingEstimate {
+    pub num_vars: u64,
+    /// Clauses generated by the encoding. The solver drops some of them
+    /// (e.g. unit clauses), so it may end up holding fewer.
+    pub num_clauses: u64,
+    pub num_literals: u64,
+    /// A rough estimate of the solver memory holding the formula, in bytes.
+    pub memory_bytes: u64,
+}
+
+impl EncodingEstimate {
+    fn update_memory(&mut self) {
+        self.memory_bytes = self
+            .num_vars
+            .saturating_mul(BYTES_PER_VAR)
+            .saturating_add(self.num_clauses.saturating_mul(BYTES_PER_CLAUSE))
+            .saturating_add(self.num_literals.saturating_mul(4));
+    }
+
+    fn add_clauses(&mut self, count: u64, literals_each: u64) {
+        self.num_clauses = self.num_clauses.saturating_add(count);
+        self.num_literals = self
+            .num_literals
+            .saturating_add(count.saturating_mul(literals_each));
+    }
+
+    // Counts the clauses `threshold_clauses` generates for a relation over
+    // `n` elements, without generating them.
+    fn add_relation(&mut self, threshold: usize, n: usize, slices: SliceVars) {
+        let t = threshold as u64;
+        let num_slices = num_combinations(n, threshold) as u64;
+        match slices {
+            SliceVars::Guarded(_) => {
+                self.add_clauses(num_slices.saturating_mul(t), 3);
+                self.add_clauses(num_slices, t + 2);
+                self.add_clauses(1, num_slices.saturating_add(1));
+            }
+            SliceVars::Shared { define, .. } => {
+                if define {
+                    self.add_clauses(num_slices.saturating_mul(t), 2);
+                }
+                self.add_clauses(1, num_slices.saturating_add(1));
+            }
+            SliceVars::Counter { chunk_size, .. } => {
+                for (g, start) in (0..n).step_by(chunk_size).enumerate() {
+                    let len = chunk_size.min(n - start);
+                    let regs = len.min(threshold) as u64;
+                    // within the chunk, see `counter_clauses`
+                    self.add_clauses(regs, 2);
+                    self.add_clauses(regs - 1, 1);
+                    self.add_clauses((len as u64 - 1) * regs, 3);
+                    self.add_clauses((len as u64 - 1) * (regs - 1), 2);
+                    // between chunks
+                    let prev = u64::from(g > 0);
+                    for j in 0..threshold {
+                        let ks = len.min(j) as u64 + 1;
+                        let with_chunk = ks.min(len as u64);
+                        self.add_clauses(with_chunk, 2 + prev);
+                        self.add_clauses(ks - with_chunk, 1 + prev);
+                    }
+                }
+                self.add_clauses(1, 2);
+            }
+        }
+    }
+}
+
+// Computes the size of the formula `construct_formula` would generate for
+// `fbas` with the `pruned` vertices, without generating it.
+pub(crate) fn estimate_pruned_encoding(
+    fbas: &Fbas,
+    pruned: &BTreeSet<NodeIndex>,
+) -> Result<EncodingEstimate, FbasError> {
+    let mut estimate = estimate_encoding(
+        fbas,
+        |ni| pruned.contains(ni),
+        CardinalityEncoding::default(),
+    )?;
+    // the pruned vertices are fixed to false, see `construct_formula`
+    estimate.add_clauses(pruned.len() as u64 * 2, 1);
+    estimate.update_memory();
+    Ok(estimate)
+}
+
+// Computes the size of the formula `encode_fbas` would generate, without
+// generating it.
+pub(crate) fn estimate_encoding(
+    fbas: &Fbas,
+    skip_relation: impl Fn(&NodeIndex) -> bool,
+    cardinality: CardinalityEncoding,
+) -> Result<EncodingEstimate, FbasError> {
+    let num_validators = fbas.validators.len() as u64;
+    let mut estimate = EncodingEstimate::default();
+    let mut num_vars = fbas.graph.node_count().saturating_mul(2);
+    // formulas 1 and 2
+    estimate.add_clauses(2, num_validators);
+    estimate.add_clauses(num_validators, 2);
+
+    let relations = qset_relations(fbas, &skip_relation)?;
+    let mut memo = SliceMemo::with_cardinality(cardinality);
+    for (_, threshold, elems) in relations.iter() {
+        memo.count(threshold, elems);
+    }
+    for (_, threshold, elems) in relations.iter() {
+        let new_vars = |count: usize| {
+            let first = num_vars;
+            num_vars = num_vars.saturating_add(count);
+            first
+        };
+        let slices = memo.allocate(new_vars, threshold, elems);
+        estimate.add_relation(threshold as usize, elems.len(), slices);
+    }
+    estimate.num_vars = num_vars as u64;
+    estimate.update_memory();
+    Ok(estimate)
+}
+
+// Generated clauses, stored back to back in one buffer instead of one vector
+// each. Encoding a large network generates millions of clauses of two or
+// three literals, so this takes most of the load off the allocator and the
+// global memory cap. They are handed to the solver through a single reused
+// vector, which it copies from anyway.
+#[derive(Debug, Default)]
+pub(crate) struct ClauseArena {
+    lits: Vec<Lit>,
+    // the end of each clause in `lits`
+    ends: Vec<usize>,
+}
+
+impl ClauseArena {
+    pub(crate) fn push(&mut self, clause: impl IntoIterator<Item = Lit>) {
+        self.lits.extend(clause);
+        self.ends.push(self.lits.len());
+    }
+
+    pub(crate) fn iter(&self) -> impl Iterator<Item = &[Lit]> {
+        let starts = std::iter::once(0).chain(self.ends.iter().copied());
+        starts
+            .zip(self.ends.iter())
+            .map(|(start, end)| &self.lits[start..*end])
+    }
+
+    pub(crate) fn add_to<S: SolverInterface>(&self, solver: &mut S) {
+        let mut clause = vec![];
+        for lits in self.iter() {
+            clause.clear();
+            clause.extend_from_slice(lits);
+            solver.add_clause_reuse(&mut clause);
+        }
+    }
+}
+
+// Number of registers of the chunked counter over `n` elements.
+fn counter_num_vars(n: usize, threshold: usize, chunk_size: usize) -> usize {
+    let within_chunks: usize = (0..n)
+        .step_by(chunk_size)
+        .map(|start| {
+            let len = chunk_size.min(n - start);
+            len * len.min(threshold)
+        })
+        .sum();
+    within_chunks + n.div_ceil(chunk_size) * threshold
+}
+
+// Generates the clauses for `lit -> (threshold out of elems)` for large qsets,
+// where one slice per combination is infeasible. The elements are split into
+// chunks, each with a sequential counter (Sinz, 2005) whose register
+// `r_{i,k}` implies that at least `k + 1` of its first `i + 1` elements are
+// true. Between chunks, register `S_{g,j}` implies that at least `j + 1`
+// elements of the first `g + 1` chunks are true: if chunk `g` has at most `k`
+// true elements, the earlier chunks must have at least `j + 1 - k`. Registers
+// only ever imply counts, so setting them all false satisfies every clause
+// but `lit -> S_{last,threshold-1}`, which is the only guarded one.
+fn counter_clauses(
+    lit: Lit,
+    threshold: usize,
+    elems: &[Lit],
+    first: usize,
+    chunk_size: usize,
+    clauses: &mut ClauseArena,
+) {
+    let mut next = first;
+    let mut alloc = |count: usize| {
+        let start = next;
+        next += count;
+        start
+    };
+
+    // `at_least[g][k]` is the register implying that at least `k + 1`
+    // elements of chunk `g` are true, for `k` below the chunk length
+    let mut at_least: Vec<Vec<Lit>> = vec![];
+    for chunk in elems.chunks(chunk_size) {
+        // counts beyond the threshold are never needed
+        let regs = chunk.len().min(threshold);
+        let start = alloc(chunk.len() * regs);
+        let r = |i: usize, k: usize| slice_lit(start, i * regs + k);
+        for (i, x) in chunk.iter().enumerate() {
+            for k in 0..regs {
+                if i == 0 {
+                    clauses.push([!r(i, k), *x]);
+                    if k > 0 {
+                        clauses.push([!r(i, k)]);
+                    }
+                    continue;
+                }
+                clauses.push([!r(i, k), r(i - 1, k), *x]);
+                if k > 0 {
+                    clauses.push([!r(i, k), r(i - 1, k - 1)]);
+                }
+            }
+        }
+        at_least.push((0..regs).map(|k| r(chunk.len() - 1, k)).collect());
+    }
+
+    let start = alloc(at_least.len() * threshold);
+    let s = |g: usize, j: usize| slice_lit(start, g * threshold + j);
+    for (g, chunk) in elems.chunks(chunk_size).enumerate() {
+        for j in 0..threshold {
+            // either chunk `g` has more than `k` true elements, or the earlier
+            // chunks have at least `j + 1 - k`; for `k > j` this always holds
+            for k in 0..=chunk.len().min(j) {
+                let earlier = (g > 0).then(|| s(g - 1, j - k));
+                // missing iff `k` is the chunk length
+                let within = at_least[g].get(k).copied();
+                clauses.push([Some(!s(g, j)), earlier, within].into_iter().flatten());
+            }
+        }
+    }
+    debug_assert_eq!(
+        next,
+        first + counter_num_vars(elems.len(), threshold, chunk_size)
+    );
+    clauses.push([!lit, s(at_least.len() - 1, threshold - 1)]);
+}
+
+// Generates the Tseitin-transformed clauses for `lit -> (threshold out of
+// elems)`. Each combination of `threshold` elements has a proposition `x_j`,
+// which implies all of its elements being true, and `lit` implies at least one
+// of the `x_j`. See `SliceMemo` for how the `x_j` are shared between vertices.
+// Clauses only refer to variables allocated beforehand, so they can be
+// generated on any thread.
+fn threshold_clauses(
+    lit: Lit,
+    threshold: u32,
+    elems: &[Lit],
+    slices: SliceVars,
+    clauses: &mut ClauseArena,
+) {
+    let mut third_term = vec![!lit];
+    match slices {
+        SliceVars::Shared { first, define } => {
+            let (_, sorted_elems) = SliceMemo::key(threshold, elems);
+            for_each_combination(sorted_elems.len(), threshold as usize, |j, q_slice| {
+                let xi_j = slice_lit(first, j);
+                if define {
+                    for e in q_slice {
+                        clauses.push([!xi_j, sorted_elems[*e]]);
+                    }
+                }
+                third_term.push(xi_j);
+            });
+        }
+        SliceVars::Guarded(first) => {
+            for_each_combination(elems.len(), threshold as usize, |j, q_slice| {
+                let xi_j = slice_lit(first, j);
+
+                for e in q_slice {
+                    // this is the first part of the equation
+                    clauses.push([!lit, !xi_j, elems[*e]]);
+                }
+                // this is the second part in the qsat_i^{A} equation
+                let neg_pi_j = q_slice.iter().map(|e| !elems[*e]);
+                clauses.push([!lit, xi_j].into_iter().chain(neg_pi_j));
+
+                third_term.push(xi_j);
+            });
+        }
+        SliceVars::Counter { first, chunk_size } => {
+            counter_clauses(lit, threshold as usize, elems, first, chunk_size, clauses);
+            return;
+        }
+    }
+    clauses.push(third_term);
+}
+
+// Adds the clauses for `lit -> (threshold out of elems)` to `solver`, see
+// `threshold_clauses`.
+pub(crate) fn add_threshold_clauses<S: SolverInterface>(
+    solver: &mut S,
+    lit: Lit,
+    threshold: u32,
+    elems: &[Lit],
+    memo: &mut SliceMemo,
+) {
+    let slices = memo.slice_vars(solver, threshold, elems);
+    let mut clauses = ClauseArena::default();
+    threshold_clauses(lit, threshold, elems, slices, &mut clauses);
+    clauses.add_to(solver);
+}
+
+// Adds clauses restricting at most `bound` of `lits` to be true, using the
+// sequential counter encoding (Sinz, 2005). Register `r_{i,j}` is true if at
+// least `j + 1` of the first `i + 1` literals are true.
+pub(crate) fn add_at_most_clauses<S: SolverInterface>(solver: &mut S, lits: &[Lit], bound: usize) {
+    if lits.len() <= bound {
+        return;
+    }
+    if bound == 0 {
+        for l in lits {
+            solver.add_clause_reuse(&mut vec![!*l]);
+        }
+        return;
+    }
+    let mut prev: Vec<Lit> = vec![];
+    for (i, &x) in lits.iter().enumerate() {
+        if i == lits.len() - 1 {
+            solver.add_clause_reuse(&mut vec![!x, !prev[bound - 1]]);
+            break;
+        }
+        let curr: Vec<Lit> = (0..bound)
+            .map(|_| Lit::new(solver.new_var_default(), true))
+            .collect();
+        solver.add_clause_reuse(&mut vec![!x, curr[0]]);
+        if i == 0 {
+            for r in &curr[1..] {
+                solver.add_clause_reuse(&mut vec![!*r]);
+            }
+        } else {
+            solver.add_clause_reuse(&mut vec![!prev[0], curr[0]]);
+            for j in 1..bound {
+                solver.add_clause_reuse(&mut vec![!x, !prev[j - 1], curr[j]]);
+                solver.add_clause_reuse(&mut vec![!prev[j], curr[j]]);
+            }
+            solver.add_clause_reuse(&mut vec![!x, !prev[bound - 1]]);
+        }
+        prev = curr;
+    }
+}
+
+// Enumerates the minimal sets of true `selectors` over the models of the
+// formula built by `encode`, which returns a fresh solver with the selectors.
+// Sizes are searched in increasing order with at most `size` selectors true,
+// and every set found is excluded together with its supersets by a clause.
+// Any smaller set in a model would then contain a minimal one already found,
+// so every model at a size is a new minimal set of exactly that size.
+// Returns the sets as positions in the selectors, ordered by size and then
+// lexicographically, and whether they are complete, i.e. neither `max_sets`
+// was reached nor a solver interrupted.
+pub(crate) fn enumerate_minimal_sets<C: Callbacks>(
+    max_size: usize,
+    max_sets: usize,
+    mut encode: impl FnMut() -> Result<(Solver<C>, Vec<Lit>), FbasError>,
+) -> Result<(Vec<Vec<usize>>, bool), FbasError> {
+    let mut found: Vec<Vec<usize>> = vec![];
+    for size in 0..=max_size {
+        // every other set is a superset of the empty one
+        if found.first().is_some_and(Vec::is_empty) {
+            break;
+        }
+        let (mut solver, selectors) = encode()?;
+        add_at_most_clauses(&mut solver, &selectors, size);
+        let exclusion = |set: &[usize]| set.iter().map(|i| !selectors[*i]).collect::<Vec<_>>();
+        for set in &found {
+            solver.add_clause_reuse(&mut exclusion(set));
+        }
+        let first_of_size = found.len();
+        let mut th = theory::EmptyTheory::new();
+        loop {
+            if found.len() >= max_sets {
+                found[first_of_size..].sort();
+                return Ok((found, false));
+            }
+            let set: Vec<usize> = match solver.solve_limited_th_full(&mut th, &[]) {
+                SolveResult::Sat(model) => (0..selectors.len())
+                    .filter(|i| model.value_lit(selectors[*i]) == lbool::TRUE)
+                    .collect(),
+                SolveResult::Unsat(_) => break,
+                SolveResult::Unknown(_) => {
+                    found[first_of_size..].sort();
+                    return Ok((found, false));
+                }
+            };
+            solver.add_clause_reuse(&mut exclusion(&set));
+            found.push(set);
+        }
+        found[first_of_size..].sort();
+    }
+    Ok((found, true))
+}
+
+// Number of relations whose clauses one thread generates at a time.
+const RELATIONS_PER_THREAD: usize = 64;
+
+// The qset relations `lit -> (threshold out of elems)` of all vertices not
+// skipped, first for quorum A then for quorum B. The elements of all
+// relations are stored back to back (CSR layout), so that encoding large
+// networks doesn't allocate a vector per relation.
+struct QsetRelations {
+    heads: Vec<(Lit, u32)>,
+    // the elements of relation `i` are `elems[offsets[i]..offsets[i + 1]]`
+    offsets: Vec<usize>,
+    elems: Vec<Lit>,
+}
+
+impl QsetRelations {
+    fn iter(&self) -> impl Iterator<Item = (Lit, u32, &[Lit])> {
+        self.heads
+            .iter()
+            .zip(self.offsets.windows(2))
+            .map(|((lit, threshold), range)| (*lit, *threshold, &self.elems[range[0]..range[1]]))
+    }
+}
+
+fn qset_relations(
+    fbas: &Fbas,
+    skip_relation: &impl Fn(&NodeIndex) -> bool,
+) -> Result<QsetRelations, FbasError> {
+    let fbas_lits = FbasLitsWrapper::new(fbas.graph.node_count());
+    // the thresholds and successors of the vertices, walking the graph's
+    // adjacency lists once for both quorums
+    let mut vertices = vec![];
+    let mut succ_offsets = vec![0];
+    let mut succs = vec![];
+    for ni in fbas.graph.node_indices().filter(|ni| !skip_relation(ni)) {
+        let nd = fbas
+            .graph
+            .node_weight(ni)
+            .ok_or(FbasError::InternalError("Node index not found"))?;
+        vertices.push((ni, nd.get_threshold()));
+        succs.extend(fbas.graph.neighbors(ni));
+        succ_offsets.push(succs.len());
+    }
+
+    let mut relations = QsetRelations {
+        heads: Vec::with_capacity(vertices.len() * 2),
+        offsets: Vec::with_capacity(vertices.len() * 2 + 1),
+        elems: Vec::with_capacity(succs.len() * 2),
+    };
+    relations.offsets.push(0);
+    for quorum in [Quorum::A, Quorum::B] {
+        for (i, (ni, threshold)) in vertices.iter().enumerate() {
+            relations
+                .heads
+                .push((fbas_lits.in_quorum(ni, quorum), *threshold));
+            relations.elems.extend(
+                succs[succ_offsets[i]..succ_offsets[i + 1]]
+                    .iter()
+                    .map(|elem| fbas_lits.in_quorum(elem, quorum)),
+            );
+            relations.offsets.push(relations.elems.len());
+        }
+    }
+    Ok(relations)
+}
+
+// Encodes the three constraints described at the top of this file into
+// `solver`. The qset relation (formula 3) of vertices for which `skip_relation`
+// returns true is left out, for callers that need to encode it differently.
+pub(crate) fn encode_fbas<S: SolverInterface>(
+    fbas: &Fbas,
+    solver: &mut S,
+    skip_relation: impl Fn(&NodeIndex) -> bool,
+) -> Result<(), FbasError> {
+    encode_fbas_with(
+        fbas,
+        solver,
+        skip_relation,
+        default_num_threads(),
+        CardinalityEncoding::default(),
+        |_| lbool::UNDEF,
+    )
+}
+
+pub(crate) fn default_num_threads() -> usize {
+    std::thread::available_parallelism().map_or(1, |n| n.get())
+}
+
+// Same as `encode_fbas`, generating the qset relation clauses on up to
+// `num_threads` threads and encoding large qsets as configured by
+// `cardinality`. The variables of each vertex (in both quorums) get the
+// preferred polarity `polarity` returns, `lbool::UNDEF` for none. The
+// resulting formula doesn't depend on `num_threads`.
+pub(crate) fn encode_fbas_with<S: SolverInterface>(
+    fbas: &Fbas,
+    solver: &mut S,
+    skip_relation: impl Fn(&NodeIndex) -> bool,
+    num_threads: usize,
+    cardinality: CardinalityEncoding,
+    polarity: impl Fn(&NodeIndex) -> lbool,
+) -> Result<(), FbasError> {
+    let fbas_lits = FbasLitsWrapper::new(fbas.graph.node_count());
+
+    // for each vertex in the graph, we add a variable representing it
+    // belonging to quorum A and quorum B
+    for _ in [Quorum::A, Quorum::B] {
+        for ni in fbas.graph.node_indices() {
+            solver.new_var(polarity(&ni), true);
+        }
+    }
+    debug_assert!(solver.num_vars() as usize == fbas.graph.node_count() * 2);
+
+    // formula 1: both quorums are non-empty -- at least one validator must
+    // exist in each quorum
+    let mut quorums_not_empty: (Vec<Lit>, Vec<Lit>) = fbas
+        .validators
+        .iter()
+        .map(|ni| (fbas_lits.in_quorum_a(ni), fbas_lits.in_quorum_b(ni)))
+        .collect();
+    solver.add_clause_reuse(&mut quorums_not_empty.0);
+    solver.add_clause_reuse(&mut quorums_not_empty.1);
+
+    // formula 2: two quorums do not intersect -- no validator can appear in
+    // both quorums
+    fbas.validators.iter().for_each(|ni| {
+        solver.add_clause_reuse(&mut vec![
+            !fbas_lits.in_quorum_a(ni),
+            !fbas_lits.in_quorum_b(ni),
+        ]);
+    });
+
+    // formula 3: qset relation for each vertex must be satisfied
+    encode_qset_relations(fbas, solver, skip_relation, num_threads, cardinality)
+}
+
+// Breaks the symmetry between quorums A and B: swapping them in a model gives
+// another one, so the search may as well only consider the models where the
+// first validator (in `fbas.validators` order) of either quorum is in A.
+// Auxiliary variable `p_i` implies that one of the first `i + 1` validators is
+// in a quorum, and
+//
+//     p_i -> p_{i-1} OR v_i^A OR v_i^B,    v_i^B -> p_{i-1}
+//
+// with `p_{-1}` false. The `p_i` are only ever implied, so any model of the
+// regular encoding with the quorums in the right order extends to these.
+// Assumptions on the literals of one quorum alone are no longer sound under
+// it, since a split may only be found with A and B the other way around.
+pub(crate) fn break_quorum_symmetry<S: SolverInterface>(fbas: &Fbas, solver: &mut S) {
+    let fbas_lits = FbasLitsWrapper::new(fbas.graph.node_count());
+    let mut prev: Option<Lit> = None;
+    for ni in fbas.validators.iter() {
+        let (a, b) = (fbas_lits.in_quorum_a(ni), fbas_lits.in_quorum_b(ni));
+        let p = Lit::new(solver.new_var_default(), true);
+        solver.add_clause_reuse(
+            &mut [Some(!p), prev, Some(a), Some(b)]
+                .into_iter()
+                .flatten()
+                .collect(),
+        );
+        solver.add_clause_reuse(&mut [Some(!b), prev].into_iter().flatten().collect());
+        prev = Some(p);
+    }
+}
+
+// Adds formula 3 of `encode_fbas_with` alone, for analyses that need their
+// own non-emptiness and disjointness constraints. The vertex variables must
+// already be allocated.
+pub(crate) fn encode_qset_relations<S: SolverInterface>(
+    fbas: &Fbas,
+    solver: &mut S,
+    skip_relation: impl Fn(&NodeIndex) -> bool,
+    num_threads: usize,
+    cardinality: CardinalityEncoding,
+) -> Result<(), FbasError> {
+    let relations = qset_relations(fbas, &skip_relation)?;
+    let mut memo = SliceMemo::with_cardinality(cardinality);
+    for (_, threshold, elems) in relations.iter() {
+        memo.count(threshold, elems);
+    }
+
+    // Variables are allocated up front, in the same order as adding the
+    // relations one by one would, so the clauses (which dominate the encoding
+    // time for large networks) can be generated in parallel. They are added to
+    // the solver in their original order, wave by wave to bound the memory
+    // held in the buffers.
+    let relations: Vec<_> = relations
+        .iter()
+        .map(|(aq_i, threshold, elems)| {
+            let slices = memo.slice_vars(solver, threshold, elems);
+            (aq_i, threshold, elems, slices)
+        })
+        .collect();
+    let generate = |chunk: &[(Lit, u32, &[Lit], SliceVars)]| {
+        let mut clauses = ClauseArena::default();
+        for (aq_i, threshold, elems, slices) in chunk {
+            threshold_clauses(*aq_i, *threshold, elems, *slices, &mut clauses);
+        }
+        clauses
+    };
+    for wave in relations.chunks(num_threads * RELATIONS_PER_THREAD) {
+        if wave.len() <= RELATIONS_PER_THREAD {
+            // not worth spawning a thread
+            generate(wave).add_to(solver);
+            continue;
+        }
+        let buffers: Vec<ClauseArena> = std::thread::scope(|scope| {
+            let handles: Vec<_> = wave
+                .chunks(RELATIONS_PER_THREAD)
+                .map(|chunk| scope.spawn(|| generate(chunk)))
+                .collect();
+            handles
+                .into_iter()
+                .map(|h| h.join().expect("clause generation panicked"))
+                .collect()
+        });
+        for clauses in buffers {
+            clauses.add_to(solver);
+        }
+    }
+    Ok(())
+}
+
 #[derive(Default)]
 pub struct FbasAnalyzer<Cb: Callbacks> {
-    fbas: Fbas,
-    solver: Solver<Cb>,
-    status: SolveStatus,
+    pub(crate) fbas: Fbas,
+    // `None` once released by `shrink`
+    solver: Option<Solver<Cb>>,
+    pub(crate) status: SolveStatus,
+    phase: SolvePhase,
+    // whether the configured topology contains any quorum
+    pub(crate) quorum_exists: bool,
+    warnings: Vec<FbasWarning>,
+    // qset vertices in quorum A and B in the latest SAT model
+    qsets_in_quorum: (BTreeSet<NodeIndex>, BTreeSet<NodeIndex>),
+    // vertices fixed out of both quorums, see `PruningStrategy`
+    pruned: BTreeSet<NodeIndex>,
+    heuristic: BranchingHeuristic,
+    diagnostics_sink: DiagnosticsSink,
+    // diagnostics not yet emitted, or all of them for `DiagnosticsSink::Collected`
+    diagnostics: Vec<String>,
+    // see `with_symmetry_breaking`
+    symmetry_breaking: bool,
+    // all minimal quorums, once enumerated in full, shared by the analyses
+    // deriving from them, see `enumerate_minimal_quorums`
+    pub(crate) minimal_quorums: OnceLock<Vec<BTreeSet<NodeIndex>>>,
+    // what the spans record about `fbas`, computed once up front
+    network: NetworkTag,
+}
+
+/// Where an analyzer's diagnostics go. Diagnostics are informational messages
+/// about the input, such as validators referenced in a qset but unknown to the
+/// network.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum DiagnosticsSink {
+    /// Diagnostics are dropped.
+    Silent,
+    /// Diagnostics are kept, to be retrieved with `take_diagnostics`.
+    Collected,
+    /// Diagnostics are printed to stderr.
+    #[default]
+    Stderr,
+    /// Diagnostics are emitted as `tracing` warnings.
+    #[cfg(feature = "tracing")]
+    Tracing,
+}
+
+/// Domain knowledge seeding the solver's choice of values when branching, see
+/// `FbasAnalyzer::with_branching_heuristic`. The formula and thus the result
+/// are the same whatever the heuristic, only the time to find it differs.
+/// `batsat` doesn't expose variable activities, so which variables are
+/// branched on first is always left to the solver.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum BranchingHeuristic {
+    /// The solver's own choices.
+    #[default]
+    Solver,
+    /// Validators appearing in some qset, and qsets, are tried in the
+    /// quorums first, whereas watchers (validators in no qset, which other
+    /// validators never depend on) are tried out of them.
+    TopTier,
+}
+
+impl BranchingHeuristic {
+    // The preferred polarity of the variables of each vertex, by index.
+    fn polarities(&self, fbas: &Fbas) -> Vec<lbool> {
+        fbas.graph
+            .node_indices()
+            .map(|ni| match self {
+                BranchingHeuristic::Solver => lbool::UNDEF,
+                BranchingHeuristic::TopTier => {
+                    let watcher = match fbas.graph[ni] {
+                        Vertex::Validator(_) => fbas
+                            .graph
+                            .neighbors_directed(ni, Direction::Incoming)
+                            .next()
+                            .is_none(),
+                        Vertex::QSet(_) => false,
+                        Vertex::Watcher(_) => true,
+                    };
+                    if watcher {
+                        lbool::FALSE
+                    } else {
+                        lbool::TRUE
+                    }
+                }
+            })
+            .collect()
+    }
+}
+
+/// The phase of `solve` at which the result was determined.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum SolvePhase {
+    #[default]
+    NotSolved,
+    /// The graph-level pre-check found that no quorum exists at all, so two
+    /// disjoint quorums can't exist either.
+    PreCheck,
+    /// The result was found by the full CDCL search.
+    Search,
+}
+
+/// Statistics of the solver after the latest `solve`.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SolveStats {
+    pub phase: SolvePhase,
+    pub num_vars: u32,
+    pub num_clauses: u64,
+    pub num_conflicts: u64,
+    pub num_decisions: u64,
+    pub num_propagations: u64,
+}
+
+/// Where a panic caught by one of the `try_*` entry points or constructors of
+/// `FbasAnalyzer` happened, see `FbasError::Internal`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct InternalFailure {
+    /// The entry point that panicked, e.g. `"solve"`.
+    pub entry_point: &'static str,
+    /// The solver statistics when the panic was caught, including the phase.
+    pub stats: SolveStats,
+    /// The size of the network, 0 if the panic happened while parsing it.
+    pub num_validators: usize,
+    pub num_vertices: usize,
+    /// The panic message, if it was a string.
+    pub message: String,
+}
+
+impl InternalFailure {
+    fn into_error(
+        entry_point: &'static str,
+        payload: Box<dyn std::any::Any + Send>,
+        stats: SolveStats,
+        (num_validators, num_vertices): (usize, usize),
+    ) -> FbasError {
+        let message = match payload.downcast::<String>() {
+            Ok(message) => *message,
+            Err(payload) => match payload.downcast::<&'static str>() {
+                Ok(message) => message.to_string(),
+                Err(_) => "panic without a message".to_string(),
+            },
+        };
+        FbasError::Internal(Box::new(InternalFailure {
+            entry_point,
+            stats,
+            num_validators,
+            num_vertices,
+            message,
+        }))
+    }
+}
+
+// Runs `f` before any solve, turning a panic into `FbasError::Internal` for a
+// network of the given `sizes` (validators and vertices).
+pub(crate) fn catch_internal<T>(
+    entry_point: &'static str,
+    sizes: (usize, usize),
+    f: impl FnOnce() -> Result<T, FbasError>,
+) -> Result<T, FbasError> {
+    std::panic::catch_unwind(AssertUnwindSafe(f)).unwrap_or_else(|payload| {
+        Err(InternalFailure::into_error(
+            entry_point,
+            payload,
+            SolveStats::default(),
+            sizes,
+        ))
+    })
+}
+
+/// Outcome of `FbasAnalyzer::enumerate_splits`.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct DisjointQuorums {
+    /// The pairs of disjoint quorums found, each as in `get_potential_split`.
+    pub pairs: Vec<(Vec<String>, Vec<String>)>,
+    /// Whether `pairs` are all the pairs of disjoint quorums, i.e. neither
+    /// the cap was reached nor the solver interrupted by its callbacks.
+    pub complete: bool,
+}
+
+/// The outcome of an analysis, detached from the analyzer. See
+/// `FbasAnalyzer::into_report`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct AnalysisReport {
+    /// The version of the JSON format the report was read from, or the
+    /// current one, see `REPORT_SCHEMA_VERSION`.
+    pub schema_version: u32,
+    pub status: SolveStatus,
+    pub potential_split: (Vec<String>, Vec<String>),
+    pub stats: SolveStats,
+    pub warnings: Vec<FbasWarning>,
+    pub provenance: ReportProvenance,
+    /// As passed to `FbasAnalyzer::into_report`.
+    pub robustness: Option<crate::RobustnessScore>,
+}
+
+/// How a report was produced, so that it can be judged and reproduced long
+/// after the fact. Reports read from JSON written before these were recorded
+/// have the defaults.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ReportProvenance {
+    /// The version of this crate.
+    pub crate_version: String,
+    /// The SAT solver, always `batsat`.
+    pub solver: String,
+    /// The random seed of the solver.
+    pub solver_seed: f64,
+    pub branching_heuristic: BranchingHeuristic,
+    /// Whether the encoding broke the symmetry between the two quorums, see
+    /// `FbasAnalyzer::with_symmetry_breaking`.
+    pub symmetry_breaking: bool,
+    /// Whether the search was stopped by the callbacks (e.g. a deadline)
+    /// before reaching a conclusion.
+    pub interrupted: bool,
+    /// The number of vertices left out of the formula by pruning. Pruning
+    /// preserves the result.
+    pub pruned_vertices: usize,
+    /// The number of validators dropped from qsets because their own qset is
+    /// unknown, which changes the network analyzed.
+    pub unknown_validators: usize,
+}
+
+// The random seed of the solvers created by this crate.
+pub(crate) fn solver_seed() -> f64 {
+    SolverOpts::default().random_seed
+}
+
+/// The version of the JSON format written by `AnalysisReport::to_json`
+/// (with the `json` feature).
+///
+/// The format evolves as follows, so that consumers of stored reports don't
+/// silently break on upgrades:
+/// - Adding a field, or a new kind of warning, keeps the version. Readers
+///   ignore what they don't know and default what is missing.
+/// - Removing or renaming a field, or changing its meaning, bumps the
+///   version. `AnalysisReport::from_json` keeps reading every older version,
+///   which is checked against a fixture per version in
+///   `tests/test_data/reports`.
+/// - Reports of a newer version than the crate knows are rejected.
+pub const REPORT_SCHEMA_VERSION: u32 = 1;
+
+impl Default for AnalysisReport {
+    fn default() -> Self {
+        Self {
+            schema_version: REPORT_SCHEMA_VERSION,
+            status: Default::default(),
+            potential_split: Default::default(),
+            stats: Default::default(),
+            warnings: Default::default(),
+            provenance: Default::default(),
+            robustness: None,
+        }
+    }
 }
 
 #[derive(Clone, Default, PartialEq)]
@@ -84,116 +1158,602 @@ impl std::fmt::Debug for SolveStatus {
     }
 }
 
+impl SolveStatus {
+    // Whether quorums intersect, as concluded from the status of an
+    // intersection check: UNSAT if they do. `None` if the solver didn't
+    // reach a conclusion.
+    pub(crate) fn intersecting(&self) -> Option<bool> {
+        match self {
+            SolveStatus::UNSAT => Some(true),
+            SolveStatus::SAT(_) => Some(false),
+            SolveStatus::UNKNOWN => None,
+        }
+    }
+}
+
 impl std::fmt::Display for SolveStatus {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        <Self as std::fmt::Debug>::fmt(&self, f)
+        <Self as std::fmt::Debug>::fmt(self, f)
     }
 }
 
 impl<Cb: Callbacks> FbasAnalyzer<Cb> {
+    #[cfg(feature = "xdr")]
     pub fn from_quorum_set_map_buf<T: AsRef<[u8]>, I: ExactSizeIterator<Item = T>>(
         nodes: I,
         quorum_set: I,
         cb: Cb,
     ) -> Result<Self, FbasError> {
-        let fbas = Fbas::from_quorum_set_map_buf(nodes, quorum_set)?;
-        Self::from_fbas(fbas, cb)
+        Self::build("from_quorum_set_map_buf", cb, || {
+            Fbas::from_quorum_set_map_buf(nodes, quorum_set)
+        })
+    }
+
+    /// Constructs the analyzer from a directory of XDR qsets, one file per
+    /// validator, see `Fbas::from_quorum_set_dir`.
+    #[cfg(feature = "xdr")]
+    pub fn from_quorum_set_dir(dir: &str, cb: Cb) -> Result<Self, FbasError> {
+        Self::build("from_quorum_set_dir", cb, || Fbas::from_quorum_set_dir(dir))
+    }
+
+    /// Constructs the analyzer from a stream of validators and their XDR
+    /// qsets, as text or binary, see `Fbas::from_quorum_set_stream`.
+    #[cfg(feature = "xdr")]
+    pub fn from_quorum_set_stream(
+        reader: impl std::io::BufRead,
+        cb: Cb,
+    ) -> Result<Self, FbasError> {
+        Self::build("from_quorum_set_stream", cb, || {
+            Fbas::from_quorum_set_stream(reader)
+        })
+    }
+
+    /// Constructs an analyzer operating natively on organizations, see
+    /// `from_json_path_org_level`.
+    #[cfg(feature = "xdr")]
+    pub fn from_quorum_set_map_buf_org_level<T: AsRef<[u8]>, I: ExactSizeIterator<Item = T>>(
+        nodes: I,
+        quorum_set: I,
+        orgs: &Organizations,
+        cb: Cb,
+    ) -> Result<Self, FbasError> {
+        Self::build("from_quorum_set_map_buf_org_level", cb, || {
+            Fbas::from_quorum_set_map_buf_org_level(nodes, quorum_set, orgs)
+        })
+    }
+
+    /// Constructs an analyzer of this network with the validators of every
+    /// organization of `orgs` collapsed into a single node, see
+    /// `Fbas::collapse_organizations`. Results of the new analyzer are
+    /// expressed in organization names, and validator keys for validators
+    /// without an organization.
+    pub fn collapse_organizations<C: Callbacks>(
+        &self,
+        orgs: &Organizations,
+        cb: C,
+    ) -> Result<FbasAnalyzer<C>, FbasError> {
+        FbasAnalyzer::from_fbas(self.fbas.collapse_organizations(orgs)?, cb)
+    }
+
+    /// Constructs an analyzer of the sub-network induced by `validators`,
+    /// e.g. the top tier or the validators one trusts, see
+    /// `Fbas::induced_subnetwork`: members of their qsets outside the subset
+    /// are absent. Returns an error for validators unknown to the network.
+    pub fn subnetwork<C: Callbacks>(
+        &self,
+        validators: &[&str],
+        cb: C,
+    ) -> Result<FbasAnalyzer<C>, FbasError> {
+        let validators = self.validator_set(validators)?;
+        FbasAnalyzer::from_fbas(self.fbas.induced_subnetwork(&validators)?, cb)
+    }
+
+    /// Constructs an analyzer of this network with the qset of `node`
+    /// replaced by `qset`, written in the compact text syntax (e.g.
+    /// `2-of(a, b, c)`, see `from_dsl`), to preview the effect of the change
+    /// before deploying it. See `Fbas::with_replaced_qset`; this analyzer is
+    /// left untouched.
+    pub fn with_replaced_qset<C: Callbacks>(
+        &self,
+        node: &str,
+        qset: &str,
+        cb: C,
+    ) -> Result<FbasAnalyzer<C>, FbasError> {
+        let qset = crate::dsl::qset_from_dsl(qset)?;
+        FbasAnalyzer::from_fbas(self.fbas.clone().with_replaced_qset(node, &qset)?, cb)
     }
 
+    /// Constructs the analyzer from the compact text syntax, see
+    /// `Fbas::from_dsl`.
+    pub fn from_dsl(dsl: &str, cb: Cb) -> Result<Self, FbasError> {
+        Self::build("from_dsl", cb, || Fbas::from_dsl(dsl))
+    }
+
+    /// Constructs the analyzer from nodes in the stellarbeats format, see
+    /// `Fbas::from_node_records`.
+    #[cfg(any(feature = "json", test))]
+    pub fn from_node_records(records: &[NodeRecord], cb: Cb) -> Result<Self, FbasError> {
+        Self::build("from_node_records", cb, || Fbas::from_node_records(records))
+    }
+
+    /// Constructs the analyzer from a json file in the regular or the
+    /// stellarbeats format, or saved from a live stellar-core node's
+    /// `quorum?transitive=true` or `scp` HTTP endpoint.
     #[cfg(any(feature = "json", test))]
     pub fn from_json_path(path: &str, cb: Cb) -> Result<Self, FbasError> {
-        let fbas = Fbas::from_json_path(path)?;
-        Self::from_fbas(fbas, cb)
+        Self::build("from_json_path", cb, || Fbas::from_json_path(path))
+    }
+
+    /// Constructs the analyzer from json read from `reader`, see
+    /// `Fbas::from_json_reader`.
+    #[cfg(any(feature = "json", test))]
+    pub fn from_json_reader(reader: impl std::io::Read, cb: Cb) -> Result<Self, FbasError> {
+        Self::build("from_json_reader", cb, || Fbas::from_json_reader(reader))
+    }
+
+    /// Constructs the analyzer from json already in memory, see
+    /// `Fbas::from_json_str`.
+    #[cfg(any(feature = "json", test))]
+    pub fn from_json_str(json: &str, cb: Cb) -> Result<Self, FbasError> {
+        Self::build("from_json_str", cb, || Fbas::from_json_str(json))
+    }
+
+    /// Constructs an analyzer operating natively on organizations: each
+    /// organization in the mapping at `orgs_path` becomes a single node whose
+    /// qset is the organization-level qset of its validators. Results (e.g.
+    /// `get_potential_split`) are therefore expressed in organization names.
+    #[cfg(any(feature = "json", test))]
+    pub fn from_json_path_org_level(
+        path: &str,
+        orgs_path: &str,
+        cb: Cb,
+    ) -> Result<Self, FbasError> {
+        Self::build("from_json_path_org_level", cb, || {
+            Fbas::from_json_path_org_level(path, orgs_path)
+        })
+    }
+
+    /// Constructs the analyzer from a json file, excluding validators whose
+    /// 30-day validating percentage is below `min_uptime`. Excluded validators
+    /// are treated as unknown, so they can't count toward any quorum.
+    #[cfg(any(feature = "json", test))]
+    pub fn from_json_path_with_min_uptime(
+        path: &str,
+        min_uptime: u32,
+        cb: Cb,
+    ) -> Result<Self, FbasError> {
+        Self::build("from_json_path_with_min_uptime", cb, || {
+            Fbas::from_json_path_with_min_uptime(path, min_uptime)
+        })
+    }
+
+    // Parses the network with `parse`, catching panics like the encoding in
+    // `from_fbas_with_strategy` does.
+    fn build(
+        entry_point: &'static str,
+        cb: Cb,
+        parse: impl FnOnce() -> Result<Fbas, FbasError>,
+    ) -> Result<Self, FbasError> {
+        let mut span = Span::start("parse");
+        span.set_str("entry_point", || entry_point.to_string());
+        let fbas = catch_internal(entry_point, (0, 0), parse)?;
+        let network = NetworkTag::of(&fbas);
+        span.set_network(&network);
+        drop(span);
+        let sizes = (fbas.validators.len(), fbas.graph.node_count());
+        catch_internal("from_fbas", sizes, || {
+            Self::from_fbas_with_pruning_unguarded(fbas, &DefaultPruning, Some(network), cb)
+        })
     }
 
     pub(crate) fn from_fbas(fbas: Fbas, cb: Cb) -> Result<Self, FbasError> {
+        Self::from_fbas_with_strategy(fbas, &DefaultPruning, cb)
+    }
+
+    /// Constructs the analyzer with a custom reduction of the network applied
+    /// before encoding it, instead of `DefaultPruning`. Results are always in
+    /// terms of the original network, see `PruningStrategy` for what the
+    /// strategy must guarantee for them to be sound. A panic while pruning or
+    /// encoding is returned as `FbasError::Internal`.
+    #[cfg(feature = "internals")]
+    pub fn from_fbas_with_pruning(
+        fbas: Fbas,
+        strategy: &dyn PruningStrategy,
+        cb: Cb,
+    ) -> Result<Self, FbasError> {
+        Self::from_fbas_with_strategy(fbas, strategy, cb)
+    }
+
+    // `from_fbas_with_pruning`, for use within the crate whether or not the
+    // `internals` feature is enabled.
+    pub(crate) fn from_fbas_with_strategy(
+        fbas: Fbas,
+        strategy: &dyn PruningStrategy,
+        cb: Cb,
+    ) -> Result<Self, FbasError> {
+        let sizes = (fbas.validators.len(), fbas.graph.node_count());
+        catch_internal("from_fbas", sizes, || {
+            Self::from_fbas_with_pruning_unguarded(fbas, strategy, None, cb)
+        })
+    }
+
+    // `network` is computed from `fbas` unless the caller already has it.
+    fn from_fbas_with_pruning_unguarded(
+        mut fbas: Fbas,
+        strategy: &dyn PruningStrategy,
+        network: Option<NetworkTag>,
+        cb: Cb,
+    ) -> Result<Self, FbasError> {
+        let pruned = strategy.prune(&fbas);
+        if pruned
+            .iter()
+            .any(|ni| fbas.graph.node_weight(*ni).is_none())
+        {
+            return Err(FbasError::InternalError("Pruned vertex not in the graph"));
+        }
+        let diagnostics = std::mem::take(&mut fbas.diagnostics);
+        let network = network.unwrap_or_else(|| NetworkTag::of(&fbas));
         let mut analyzer = Self {
             fbas,
-            solver: Solver::new(Default::default(), cb),
+            solver: Some(Solver::new(Default::default(), cb)),
             status: SolveStatus::UNKNOWN,
+            phase: SolvePhase::NotSolved,
+            quorum_exists: false,
+            warnings: vec![],
+            qsets_in_quorum: Default::default(),
+            pruned,
+            heuristic: BranchingHeuristic::default(),
+            symmetry_breaking: false,
+            diagnostics_sink: DiagnosticsSink::default(),
+            diagnostics,
+            minimal_quorums: OnceLock::new(),
+            network,
         };
+        analyzer.warnings = analyzer.fbas.find_unsatisfiable_qsets()?;
+        analyzer.quorum_exists = !analyzer.fbas.max_quorum(&BTreeSet::new()).is_empty();
         analyzer.construct_formula()?;
+        debug_assert!(analyzer.check_invariants().is_ok());
         Ok(analyzer)
     }
 
     fn construct_formula(&mut self) -> Result<(), FbasError> {
-        let fbas = &self.fbas;
-        let fbas_lits = FbasLitsWrapper::new(fbas.graph.node_count());
-
-        // for each vertex in the graph, we add a variable representing it
-        // belonging to quorum A and quorum B
-        fbas.graph.node_indices().for_each(|_| {
-            self.solver.new_var_default();
-            self.solver.new_var_default();
-        });
-        debug_assert!(self.solver.num_vars() as usize == fbas.graph.node_count() * 2);
+        let mut span = Span::start("encode");
+        span.set_network(&self.network);
+        span.set_int("pruned_vertices", self.pruned.len() as u64);
+        let mut solver = self
+            .solver
+            .take()
+            .ok_or(FbasError::InternalError("Solver has been released"))?;
+        let encoded = self.encode_formula(&mut solver);
+        span.set_int("num_vars", solver.num_vars() as u64);
+        span.set_int("num_clauses", solver.num_clauses());
+        self.solver = Some(solver);
+        encoded
+    }
 
-        // formula 1: both quorums are non-empty -- at least one validator must
-        // exist in each quorum
-        let mut quorums_not_empty: (Vec<Lit>, Vec<Lit>) = fbas
-            .validators
-            .iter()
-            .map(|ni| (fbas_lits.in_quorum_a(ni), fbas_lits.in_quorum_b(ni)))
-            .collect();
-        self.solver.add_clause_reuse(&mut quorums_not_empty.0);
-        self.solver.add_clause_reuse(&mut quorums_not_empty.1);
-
-        // formula 2: two quorums do not intersect -- no validator can appear in
-        // both quorums
-        fbas.validators.iter().for_each(|ni| {
-            self.solver.add_clause_reuse(&mut vec![
-                !fbas_lits.in_quorum_a(ni),
-                !fbas_lits.in_quorum_b(ni),
-            ]);
-        });
+    // Encodes the formula `solve` solves into `solver`, which must be empty.
+    pub(crate) fn encode_formula<S: SolverInterface>(
+        &self,
+        solver: &mut S,
+    ) -> Result<(), FbasError> {
+        // Pruned vertices are fixed to false instead of encoding their qset
+        // relation, which shrinks the formula.
+        let pruned = &self.pruned;
+        let polarity = self.heuristic.polarities(&self.fbas);
+        encode_fbas_with(
+            &self.fbas,
+            solver,
+            |ni| pruned.contains(ni),
+            default_num_threads(),
+            CardinalityEncoding::default(),
+            |ni| polarity[ni.index()],
+        )?;
+        if self.symmetry_breaking {
+            break_quorum_symmetry(&self.fbas, solver);
+        }
+        let fbas_lits = FbasLitsWrapper::new(self.fbas.graph.node_count());
+        for ni in pruned {
+            solver.add_clause_reuse(&mut vec![!fbas_lits.in_quorum_a(ni)]);
+            solver.add_clause_reuse(&mut vec![!fbas_lits.in_quorum_b(ni)]);
+        }
+        Ok(())
+    }
 
-        // formula 3: qset relation for each vertex must be satisfied
-        let mut add_clauses_for_quorum_relations =
-            |in_quorum: &dyn Fn(&NodeIndex) -> Lit| -> Result<(), FbasError> {
-                fbas.graph.node_indices().try_for_each(|ni| {
-                    let aq_i = in_quorum(&ni);
-                    let nd = fbas
-                        .graph
-                        .node_weight(ni)
-                        .ok_or_else(|| FbasError::InternalError("Node index not found"))?;
-                    let threshold = nd.get_threshold();
-                    let neighbors = fbas.graph.neighbors(ni);
-                    let qset = neighbors.into_iter().combinations(threshold as usize);
-
-                    let mut third_term = vec![];
-                    third_term.push(!aq_i);
-                    for (_j, q_slice) in qset.enumerate() {
-                        // create a new proposition as per Tseitin transformation
-                        let xi_j = fbas_lits.new_proposition(&mut self.solver);
-
-                        // this is the second part in the qsat_i^{A} equation
-                        let mut neg_pi_j = vec![];
-                        neg_pi_j.push(!aq_i);
-                        neg_pi_j.push(xi_j);
-                        for (_k, elem) in q_slice.iter().enumerate() {
-                            // get lit for elem
-                            let elit = in_quorum(elem);
-                            neg_pi_j.push(!elit);
-                            // this is the first part of the equation
-                            self.solver.add_clause_reuse(&mut vec![!aq_i, !xi_j, elit]);
-                        }
-                        self.solver.add_clause_reuse(&mut neg_pi_j);
-
-                        third_term.push(xi_j);
-                    }
-                    self.solver.add_clause_reuse(&mut third_term);
-                    Ok(())
-                })
-            };
+    /// Computes the size of the SAT encoding of this network from the graph,
+    /// without generating it: the number of variables, clauses and literals,
+    /// and a rough estimate of the memory they take in the solver. Useful to
+    /// warn before committing to a heavy solve.
+    pub fn estimate_encoding(&self) -> Result<EncodingEstimate, FbasError> {
+        estimate_pruned_encoding(&self.fbas, &self.pruned)
+    }
 
-        add_clauses_for_quorum_relations(&|ni| fbas_lits.in_quorum_a(ni))?;
-        add_clauses_for_quorum_relations(&|ni| fbas_lits.in_quorum_b(ni))?;
-        Ok(())
+    /// Re-encodes the network with the solver seeded by `heuristic`,
+    /// discarding the result of any earlier solve.
+    pub fn with_branching_heuristic(
+        mut self,
+        heuristic: BranchingHeuristic,
+    ) -> Result<Self, FbasError> {
+        self.heuristic = heuristic;
+        self.reencode()
+    }
+
+    /// Re-encodes the network with or without a constraint ruling out one of
+    /// each pair of models that only differ by swapping quorums A and B, so
+    /// that the search covers half the space. The potential split then
+    /// always has the first validator, in network order, of either quorum in
+    /// its first quorum. Off by default, as it makes assumptions on the
+    /// literals of a single quorum (see `literal_for`) unsound. Discards the
+    /// result of any earlier solve.
+    pub fn with_symmetry_breaking(mut self, enabled: bool) -> Result<Self, FbasError> {
+        self.symmetry_breaking = enabled;
+        self.reencode()
+    }
+
+    fn reencode(mut self) -> Result<Self, FbasError> {
+        self.solver
+            .as_mut()
+            .ok_or(FbasError::InternalError("Solver has been released"))?
+            .reset();
+        self.status = SolveStatus::UNKNOWN;
+        self.phase = SolvePhase::NotSolved;
+        self.qsets_in_quorum = Default::default();
+        self.construct_formula()?;
+        Ok(self)
+    }
+
+    /// Releases the solver, keeping the graph for further graph-only queries
+    /// (e.g. `trust_reciprocity`) and the latest result. Solving afterwards
+    /// returns the latest result without searching again, and `get_stats`
+    /// only reports the phase.
+    pub fn shrink(&mut self) {
+        self.solver = None;
+    }
+
+    /// Consumes the analyzer, keeping only the outcome of the latest solve
+    /// and `robustness`. The latter is not computed here, as it takes far
+    /// more than the intersection check: pass the result of
+    /// `robustness_score` to include it.
+    pub fn into_report(
+        self,
+        robustness: Option<crate::RobustnessScore>,
+    ) -> Result<AnalysisReport, FbasError> {
+        let stats = self.get_stats();
+        let provenance = ReportProvenance {
+            crate_version: env!("CARGO_PKG_VERSION").to_string(),
+            solver: "batsat".to_string(),
+            solver_seed: solver_seed(),
+            branching_heuristic: self.heuristic,
+            symmetry_breaking: self.symmetry_breaking,
+            interrupted: stats.phase == SolvePhase::Search && self.status == SolveStatus::UNKNOWN,
+            pruned_vertices: self.pruned.len(),
+            unknown_validators: self.fbas.unknown_validators,
+        };
+        Ok(AnalysisReport {
+            schema_version: REPORT_SCHEMA_VERSION,
+            potential_split: self.get_potential_split()?,
+            stats,
+            status: self.status,
+            warnings: self.warnings,
+            provenance,
+            robustness,
+        })
+    }
+
+    /// Sets where diagnostics go. Diagnostics raised while constructing the
+    /// analyzer are held back until the first solve, so they go to this sink
+    /// as well.
+    pub fn with_diagnostics_sink(mut self, sink: DiagnosticsSink) -> Self {
+        self.diagnostics_sink = sink;
+        self
+    }
+
+    /// Returns the diagnostics kept by `DiagnosticsSink::Collected`.
+    pub fn take_diagnostics(&mut self) -> Vec<String> {
+        if self.diagnostics_sink != DiagnosticsSink::Collected {
+            return vec![];
+        }
+        std::mem::take(&mut self.diagnostics)
+    }
+
+    pub(crate) fn flush_diagnostics(&mut self) {
+        match self.diagnostics_sink {
+            DiagnosticsSink::Silent => self.diagnostics.clear(),
+            DiagnosticsSink::Collected => {}
+            DiagnosticsSink::Stderr => self.diagnostics.drain(..).for_each(|d| eprintln!("{}", d)),
+            #[cfg(feature = "tracing")]
+            DiagnosticsSink::Tracing => self
+                .diagnostics
+                .drain(..)
+                .for_each(|d| tracing::warn!("{}", d)),
+        }
+    }
+
+    /// Same as `solve`, but a panic (e.g. of the callbacks or the solver) is
+    /// returned as `FbasError::Internal` instead of unwinding into the
+    /// caller, e.g. across an FFI boundary. The solver is released after a
+    /// panic as its state is unknown, see `shrink`. Aborts, such as on a
+    /// failed allocation, can't be caught.
+    pub fn try_solve(&mut self) -> Result<SolveStatus, FbasError> {
+        self.catch_internal("solve", Self::solve)
+    }
+
+    /// Same as `solve_with_unreachable`, with panics caught as in
+    /// `try_solve`.
+    pub fn try_solve_with_unreachable(
+        &mut self,
+        unreachable: &[&str],
+    ) -> Result<SolveStatus, FbasError> {
+        self.catch_internal("solve_with_unreachable", |analyzer| {
+            analyzer.solve_with_unreachable(unreachable)
+        })
+    }
+
+    // Unlike `catch_internal`, the context is taken from the analyzer after
+    // the panic, so that it shows how far the solve got.
+    fn catch_internal(
+        &mut self,
+        entry_point: &'static str,
+        solve: impl FnOnce(&mut Self) -> SolveStatus,
+    ) -> Result<SolveStatus, FbasError> {
+        let payload = match std::panic::catch_unwind(AssertUnwindSafe(|| solve(self))) {
+            Ok(status) => return Ok(status),
+            Err(payload) => payload,
+        };
+        let stats = self.get_stats();
+        self.solver = None;
+        self.status = SolveStatus::UNKNOWN;
+        self.qsets_in_quorum = Default::default();
+        let sizes = (self.fbas.validators.len(), self.fbas.graph.node_count());
+        Err(InternalFailure::into_error(
+            entry_point,
+            payload,
+            stats,
+            sizes,
+        ))
     }
 
     pub fn solve(&mut self) -> SolveStatus {
+        self.traced_solve(|analyzer| {
+            analyzer.flush_diagnostics();
+            if !analyzer.quorum_exists {
+                return analyzer.set_pre_check_unsat();
+            }
+            analyzer.solve_with_assumptions(&[])
+        })
+    }
+
+    /// Solves for quorum intersection treating the `unreachable` validators as
+    /// failed, i.e. they can not be part of either quorum. This reflects the
+    /// "effective" network as experienced from the operator's vantage point,
+    /// as opposed to the configured topology analyzed by `solve`. The overlay
+    /// is passed as solver assumptions, so both analyses can be run on the same
+    /// analyzer. Validators unknown to the network are ignored.
+    pub fn solve_with_unreachable(&mut self, unreachable: &[&str]) -> SolveStatus {
+        self.traced_solve(|analyzer| {
+            let fbas_lits = FbasLitsWrapper::new(analyzer.fbas.graph.node_count());
+            let mut failed = BTreeSet::new();
+            let mut assumptions = vec![];
+            for v in unreachable {
+                match analyzer.fbas.try_get_validator_index(v) {
+                    Ok(ni) => {
+                        failed.insert(ni);
+                        assumptions.push(!fbas_lits.in_quorum_a(&ni));
+                        assumptions.push(!fbas_lits.in_quorum_b(&ni));
+                    }
+                    Err(_) => analyzer
+                        .diagnostics
+                        .push(format!("Validator {} is unknown", v)),
+                }
+            }
+            analyzer.flush_diagnostics();
+            if analyzer.fbas.max_quorum(&failed).is_empty() {
+                return analyzer.set_pre_check_unsat();
+            }
+            analyzer.solve_with_assumptions(&assumptions)
+        })
+    }
+
+    fn traced_solve(&mut self, solve: impl FnOnce(&mut Self) -> SolveStatus) -> SolveStatus {
+        let mut span = Span::start("solve");
+        span.set_network(&self.network);
+        let status = solve(self);
+        let stats = self.get_stats();
+        span.set_str("status", || {
+            match status {
+                SolveStatus::SAT(_) => "SAT",
+                SolveStatus::UNSAT => "UNSAT",
+                SolveStatus::UNKNOWN => "UNKNOWN",
+            }
+            .to_string()
+        });
+        span.set_str("phase", || format!("{:?}", stats.phase));
+        span.set_int("num_conflicts", stats.num_conflicts);
+        span.set_int("num_decisions", stats.num_decisions);
+        status
+    }
+
+    /// Enumerates distinct pairs of disjoint quorums, up to `max_solutions`
+    /// of them, for networks lacking quorum intersection. Pairs that only
+    /// differ by order count once. The first pair is the potential split of
+    /// `solve`, which this runs first and whose result is kept. Each further
+    /// pair takes another solve, with clauses excluding the pairs found so
+    /// far; these are disabled afterwards, so later solves are unaffected.
+    /// Pairs need not be minimal, so there can be a great many. A cap of 0
+    /// still runs `solve` but returns no pairs.
+    pub fn enumerate_splits(&mut self, max_solutions: usize) -> Result<DisjointQuorums, FbasError> {
+        let mut splits = DisjointQuorums::default();
+        let SolveStatus::SAT(first) = self.solve() else {
+            splits.complete = self.status == SolveStatus::UNSAT;
+            return Ok(splits);
+        };
+        if max_solutions == 0 {
+            return Ok(splits);
+        }
+        let latest = (self.status.clone(), self.qsets_in_quorum.clone());
+        let fbas_lits = FbasLitsWrapper::new(self.fbas.graph.node_count());
+        let guard = match self.solver.as_mut() {
+            Some(solver) => Lit::new(solver.new_var_default(), true),
+            None => return Err(FbasError::InternalError("Solver has been released")),
+        };
+        let mut pair = first;
+        loop {
+            splits.pairs.push((
+                self.validator_names(&[pair.0.iter().copied().collect()])?
+                    .remove(0),
+                self.validator_names(&[pair.1.iter().copied().collect()])?
+                    .remove(0),
+            ));
+            if splits.pairs.len() >= max_solutions {
+                break;
+            }
+            let solver = self
+                .solver
+                .as_mut()
+                .ok_or(FbasError::InternalError("Solver has been released"))?;
+            for (a, b) in [(&pair.0, &pair.1), (&pair.1, &pair.0)] {
+                let mut clause = vec![!guard];
+                for vi in self.fbas.validators.iter() {
+                    clause.push(fbas_lits.in_quorum_a(vi) ^ a.contains(vi));
+                    clause.push(fbas_lits.in_quorum_b(vi) ^ b.contains(vi));
+                }
+                solver.add_clause_reuse(&mut clause);
+            }
+            match self.solve_with_assumptions(&[guard]) {
+                SolveStatus::SAT(next) => pair = next,
+                status => {
+                    splits.complete = status == SolveStatus::UNSAT;
+                    break;
+                }
+            }
+        }
+        if let Some(solver) = self.solver.as_mut() {
+            solver.add_clause_reuse(&mut vec![!guard]);
+        }
+        (self.status, self.qsets_in_quorum) = latest;
+        Ok(splits)
+    }
+
+    // Solving happens in two phases. The first phase is a cheap graph-level
+    // check of whether any quorum exists at all (done by the callers). In the
+    // common case where it doesn't, e.g. because no validator has a
+    // satisfiable qset, one quorum candidate is necessarily empty and we bail
+    // out with UNSAT without ever entering the search. Otherwise the second
+    // phase runs the full search.
+    fn set_pre_check_unsat(&mut self) -> SolveStatus {
+        self.phase = SolvePhase::PreCheck;
+        self.status = SolveStatus::UNSAT;
+        self.qsets_in_quorum = Default::default();
+        self.status.clone()
+    }
+
+    fn solve_with_assumptions(&mut self, assumptions: &[Lit]) -> SolveStatus {
+        let Some(solver) = self.solver.as_mut() else {
+            return self.status.clone();
+        };
+        self.phase = SolvePhase::Search;
         let mut th = theory::EmptyTheory::new();
-        let result = self.solver.solve_limited_th_full(&mut th, &[]);
+        let result = solver.solve_limited_th_full(&mut th, assumptions);
+        self.qsets_in_quorum = Default::default();
         self.status = match result {
             SolveResult::Sat(model) => {
                 let fbas_lits = FbasLitsWrapper::new(self.fbas.graph.node_count());
@@ -209,6 +1769,17 @@ impl<Cb: Callbacks> FbasAnalyzer<Cb> {
                         quorum_b.push(*ni);
                     }
                 });
+                for ni in self.fbas.graph.node_indices() {
+                    if !matches!(self.fbas.graph[ni], Vertex::QSet(_)) {
+                        continue;
+                    }
+                    if model.value_lit(fbas_lits.in_quorum_a(&ni)) == lbool::TRUE {
+                        self.qsets_in_quorum.0.insert(ni);
+                    }
+                    if model.value_lit(fbas_lits.in_quorum_b(&ni)) == lbool::TRUE {
+                        self.qsets_in_quorum.1.insert(ni);
+                    }
+                }
                 SolveStatus::SAT((quorum_a, quorum_b))
             }
             SolveResult::Unsat(_) => SolveStatus::UNSAT,
@@ -217,6 +1788,243 @@ impl<Cb: Callbacks> FbasAnalyzer<Cb> {
         self.status.clone()
     }
 
+    /// Checks the internal consistency of the analyzer, for fuzzers and
+    /// integration tests:
+    /// - the validator list matches the validator vertices of the graph, and
+    ///   names are unique,
+    /// - every validator has exactly one qset, whose edges match its members,
+    /// - watchers have no edges,
+    /// - every vertex has two distinct variables (one per quorum) in the
+    ///   solver, and every pruned vertex is in the graph.
+    ///
+    /// Returns `FbasError::InternalError` describing the first violation.
+    pub fn check_invariants(&self) -> Result<(), FbasError> {
+        self.fbas.check_invariants()?;
+        let node_count = self.fbas.graph.node_count();
+        if self.pruned.iter().any(|ni| ni.index() >= node_count) {
+            return Err(FbasError::InternalError("Pruned vertex not in the graph"));
+        }
+        let fbas_lits = FbasLitsWrapper::new(node_count);
+        let mut vars = BTreeSet::new();
+        for ni in self.fbas.graph.node_indices() {
+            vars.insert(fbas_lits.in_quorum_a(&ni).var());
+            vars.insert(fbas_lits.in_quorum_b(&ni).var());
+        }
+        if vars.len() != node_count * 2 {
+            return Err(FbasError::InternalError("Vertex literals are not distinct"));
+        }
+        if let Some(solver) = &self.solver {
+            if vars.last().is_some_and(|v| v.idx() >= solver.num_vars()) {
+                return Err(FbasError::InternalError("Vertex literal not in the solver"));
+            }
+        }
+        Ok(())
+    }
+
+    /// Returns the non-fatal issues found while constructing the formula.
+    pub fn get_warnings(&self) -> &[FbasWarning] {
+        &self.warnings
+    }
+
+    /// Returns the findings of stellar-core's qset sanity checks and a few
+    /// more (see `QsetLintKind`) on the qsets as given, in validator order.
+    /// They are computed when the network is built.
+    pub fn qset_lints(&self) -> &[QsetLint] {
+        &self.fbas.lints
+    }
+
+    /// Returns the nodes known without a qset, which are in no quorum.
+    pub fn get_watchers(&self) -> Vec<String> {
+        self.fbas.watchers().map(str::to_string).collect()
+    }
+
+    /// Returns the metadata of a node (validator or watcher), if the input
+    /// had any for it. Only the stellarbeats format carries metadata.
+    pub fn node_metadata(&self, node: &str) -> Option<&NodeMetadata> {
+        self.fbas.metadata.get(node)
+    }
+
+    /// Groups the nodes by the home domain of their metadata, for analyses
+    /// and reports aggregating per organization (e.g.
+    /// `critical_organizations`). Empty if the input had no metadata.
+    pub fn organizations(&self) -> Organizations {
+        Organizations::from_metadata(&self.fbas.metadata)
+    }
+
+    pub fn get_stats(&self) -> SolveStats {
+        let Some(solver) = &self.solver else {
+            return SolveStats {
+                phase: self.phase,
+                ..Default::default()
+            };
+        };
+        SolveStats {
+            phase: self.phase,
+            num_vars: solver.num_vars(),
+            num_clauses: solver.num_clauses(),
+            num_conflicts: solver.num_conflicts(),
+            num_decisions: solver.num_decisions(),
+            num_propagations: solver.num_propagations(),
+        }
+    }
+
+    /// Returns the literal representing `validator` being in `quorum` in the
+    /// encoding. The literal is read-only information, useful for mapping
+    /// between validators and SAT variables (e.g. when interpreting a DIMACS
+    /// export or constructing assumptions).
+    pub fn literal_for(&self, validator: &str, quorum: Quorum) -> Result<Lit, FbasError> {
+        let ni = self.fbas.try_get_validator_index(validator)?;
+        let fbas_lits = FbasLitsWrapper::new(self.fbas.graph.node_count());
+        Ok(fbas_lits.in_quorum(&ni, quorum))
+    }
+
+    /// Returns the validators outside the main strongly connected component
+    /// of the trust graph, in validator order, or `None` if there isn't a
+    /// single component containing a quorum. As in stellar-core, the
+    /// intersection check is restricted to that component (see
+    /// `DefaultPruning`), so these are structurally detached from it: they
+    /// at most follow it, and none of them is in any minimal quorum.
+    pub fn validators_outside_main_scc(&self) -> Result<Option<Vec<String>>, FbasError> {
+        let Some(main) = main_component(&self.fbas) else {
+            return Ok(None);
+        };
+        self.fbas
+            .validators_outside(&main)
+            .iter()
+            .map(|ni| self.fbas.try_get_validator_string(ni))
+            .collect::<Result<_, _>>()
+            .map(Some)
+    }
+
+    /// Returns the validators `validator` transitively depends on through
+    /// qsets, in name order, see `Fbas::transitive_dependencies`. These are
+    /// the only ones whose qsets matter to whether it can be in a quorum.
+    pub fn transitive_dependencies(&self, validator: &str) -> Result<BTreeSet<String>, FbasError> {
+        let vi = self.fbas.try_get_validator_index(validator)?;
+        self.fbas
+            .transitive_dependencies(vi)
+            .iter()
+            .map(|ni| self.fbas.try_get_validator_string(ni))
+            .collect()
+    }
+
+    /// Returns the validator-level trust graph, with an edge from each
+    /// validator to every other validator in its qset, see
+    /// `Fbas::trust_graph`. Nodes are weighted by validator key.
+    pub fn trust_graph(&self) -> Result<DiGraph<String, ()>, FbasError> {
+        self.fbas.trust_graph()
+    }
+
+    /// Renders the qset of `validator` as an indented outline (see
+    /// `InternalScpQuorumSet::pretty`), displaying validators by their alias in
+    /// `aliases` if they have one. Unknown validators are left out, as they are
+    /// in the analysis.
+    pub fn describe_qset(
+        &self,
+        validator: &str,
+        aliases: &BTreeMap<String, String>,
+    ) -> Result<String, FbasError> {
+        let vi = self.fbas.try_get_validator_index(validator)?;
+        Ok(self.fbas.validator_qset(vi)?.pretty(aliases))
+    }
+
+    /// After a SAT result, returns how the qset of `validator` relates to
+    /// `quorum` in the model: whether each (inner) qset was considered
+    /// satisfied by the quorum and which of its validators are in the quorum.
+    /// This is what the solver used to justify the quorum, so e.g. the inner
+    /// sets carrying it can be read off without a graph search. Returns `None`
+    /// if the latest result is not SAT.
+    pub fn qset_membership(
+        &self,
+        validator: &str,
+        quorum: Quorum,
+    ) -> Result<Option<QsetMembership>, FbasError> {
+        let SolveStatus::SAT((quorum_a, quorum_b)) = &self.status else {
+            return Ok(None);
+        };
+        let (validators, qsets) = match quorum {
+            Quorum::A => (quorum_a, &self.qsets_in_quorum.0),
+            Quorum::B => (quorum_b, &self.qsets_in_quorum.1),
+        };
+        let vi = self.fbas.try_get_validator_index(validator)?;
+        let qi = self
+            .fbas
+            .graph
+            .neighbors(vi)
+            .next()
+            .ok_or(FbasError::InternalError("Validator has no qset"))?;
+        self.qset_membership_of(qi, validators, qsets).map(Some)
+    }
+
+    fn qset_membership_of(
+        &self,
+        qi: NodeIndex,
+        validators: &[NodeIndex],
+        qsets: &BTreeSet<NodeIndex>,
+    ) -> Result<QsetMembership, FbasError> {
+        let Vertex::QSet(qset) = &self.fbas.graph[qi] else {
+            return Err(FbasError::InternalError("Node index is not a qset"));
+        };
+        Ok(QsetMembership {
+            threshold: qset.threshold,
+            in_quorum: qsets.contains(&qi),
+            validators: qset
+                .validators
+                .iter()
+                .map(|ni| {
+                    Ok((
+                        self.fbas.try_get_validator_string(ni)?,
+                        validators.contains(ni),
+                    ))
+                })
+                .collect::<Result<_, FbasError>>()?,
+            inner_sets: qset
+                .inner_qsets
+                .iter()
+                .map(|ni| self.qset_membership_of(*ni, validators, qsets))
+                .collect::<Result<_, _>>()?,
+        })
+    }
+
+    /// Returns whether `validators` form a quorum, evaluated on the graph
+    /// without solving, see `Fbas::is_quorum`. Useful to sanity-check a
+    /// result, e.g. each side of `get_potential_split`.
+    pub fn is_quorum(&self, validators: &[&str]) -> Result<bool, FbasError> {
+        Ok(self.fbas.is_quorum(&self.validator_set(validators)?))
+    }
+
+    /// Returns whether `validators` contain a quorum, evaluated on the graph
+    /// without solving, see `Fbas::contains_quorum`.
+    pub fn contains_quorum(&self, validators: &[&str]) -> Result<bool, FbasError> {
+        Ok(self.fbas.contains_quorum(&self.validator_set(validators)?))
+    }
+
+    /// Returns whether `validators` form a minimal quorum, see
+    /// `Fbas::is_minimal_quorum`.
+    pub fn is_minimal_quorum(&self, validators: &[&str]) -> Result<bool, FbasError> {
+        Ok(self
+            .fbas
+            .is_minimal_quorum(&self.validator_set(validators)?))
+    }
+
+    /// Shrinks `validators` to a minimal quorum within them, e.g. to tell
+    /// which members of a quorum of a potential split actually matter. The
+    /// result is empty if they contain no quorum. See `Fbas::minimize_quorum`.
+    pub fn minimize_quorum(&self, validators: &[&str]) -> Result<Vec<String>, FbasError> {
+        self.fbas
+            .minimize_quorum(&self.validator_set(validators)?)
+            .iter()
+            .map(|ni| self.fbas.try_get_validator_string(ni))
+            .collect()
+    }
+
+    fn validator_set(&self, validators: &[&str]) -> Result<BTreeSet<NodeIndex>, FbasError> {
+        validators
+            .iter()
+            .map(|v| self.fbas.try_get_validator_index(v))
+            .collect()
+    }
+
     pub fn get_potential_split(&self) -> Result<(Vec<String>, Vec<String>), FbasError> {
         match &self.status {
             SolveStatus::SAT((quorum_a, quorum_b)) => {
@@ -233,4 +2041,24 @@ impl<Cb: Callbacks> FbasAnalyzer<Cb> {
             _ => Ok((vec![], vec![])),
         }
     }
+
+    /// Same as `get_potential_split`, with each quorum shrunk to a minimal
+    /// quorum within it, see `Fbas::minimize_quorum`. The solver returns
+    /// whichever model it runs into, so the quorums of the potential split
+    /// often hold many validators that don't matter for it; the minimal ones
+    /// only keep the validators whose qsets cause the split, and are still
+    /// disjoint, being subsets of the original ones.
+    pub fn get_minimal_split(&self) -> Result<(Vec<String>, Vec<String>), FbasError> {
+        let SolveStatus::SAT((quorum_a, quorum_b)) = &self.status else {
+            return Ok((vec![], vec![]));
+        };
+        let minimize = |quorum: &Vec<NodeIndex>| {
+            self.fbas
+                .minimize_quorum(&quorum.iter().copied().collect())
+                .iter()
+                .map(|ni| self.fbas.try_get_validator_string(ni))
+                .collect::<Result<Vec<_>, _>>()
+        };
+        Ok((minimize(quorum_a)?, minimize(quorum_b)?))
+    }
 }
@@ -0,0 +1,62 @@
+//! Conversions to and from the types of the `fbas_analyzer` crate, so that
+//! projects built on it can adopt the SAT-based intersection check
+//! incrementally.
+
+use crate::{
+    fbas::{Fbas, FbasError, InternalScpQuorumSet, QuorumSetMap},
+    json_parser::nodes_to_json,
+    FbasAnalyzer,
+};
+use batsat::Callbacks;
+use fbas_analyzer::{NodeIdSet, QuorumSet};
+use std::sync::Arc;
+
+fn internal_qset(qset: &QuorumSet, fbas: &fbas_analyzer::Fbas) -> InternalScpQuorumSet {
+    InternalScpQuorumSet {
+        threshold: qset.threshold as u32,
+        threshold_percent: None,
+        validators: fbas_analyzer::to_public_keys(qset.validators.iter().copied(), fbas),
+        inner_sets: qset
+            .inner_quorum_sets
+            .iter()
+            .map(|inner| internal_qset(inner, fbas))
+            .collect(),
+    }
+}
+
+/// Returns the ids in `fbas` of the given validators, ignoring validators
+/// `fbas` doesn't know.
+pub fn to_node_id_set(validators: &[String], fbas: &fbas_analyzer::Fbas) -> NodeIdSet {
+    validators
+        .iter()
+        .filter_map(|v| fbas.get_node_id(v))
+        .collect()
+}
+
+/// Returns the validators with the given ids in `fbas`.
+pub fn from_node_id_set(node_ids: &NodeIdSet, fbas: &fbas_analyzer::Fbas) -> Vec<String> {
+    fbas_analyzer::to_public_keys(node_ids.iter(), fbas)
+}
+
+impl<Cb: Callbacks> FbasAnalyzer<Cb> {
+    /// Constructs the analyzer from an `fbas_analyzer` network.
+    pub fn from_fbas_analyzer(fbas: &fbas_analyzer::Fbas, cb: Cb) -> Result<Self, FbasError> {
+        let qsm: QuorumSetMap = (0..fbas.number_of_nodes())
+            .filter_map(|id| {
+                let qset = fbas.get_quorum_set(id)?;
+                let public_key = fbas_analyzer::to_public_keys([id], fbas).pop()?;
+                Some((public_key, Arc::new(internal_qset(&qset, fbas))))
+            })
+            .collect();
+        let fbas = Fbas::from_quorum_set_map(&qsm)?;
+        Self::from_fbas(fbas, cb)
+    }
+
+    /// Converts the analyzed network to an `fbas_analyzer` network. Unknown
+    /// validators are left out of the qsets, as they are in the analysis.
+    pub fn to_fbas_analyzer(&self) -> Result<fbas_analyzer::Fbas, FbasError> {
+        Ok(fbas_analyzer::Fbas::from_json_str(
+            &nodes_to_json(&self.fbas)?.to_string(),
+        ))
+    }
+}
@@ -0,0 +1,76 @@
+use crate::{
+    fbas::FbasError, Callbacks, CriticalOrganizations, FbasAnalyzer, Organizations,
+    ResilienceReport,
+};
+use std::collections::BTreeMap;
+
+// Attributes are free-form strings attached to nodes under a name, e.g. the
+// ISP or country of a validator. Grouping the validators by the value of an
+// attribute gives an `Organizations` mapping, each value standing for an
+// organization, so that every organization-level analysis runs at that
+// granularity as well. Validators without the attribute are in no group, and
+// therefore never fail along with one.
+
+impl<Cb: Callbacks> FbasAnalyzer<Cb> {
+    /// Sets the `attribute` of `node` (a validator or a watcher) to `value`,
+    /// replacing the previous value if any. Networks in the stellarbeats
+    /// format come with the `isp` and `country` attributes. Returns an error
+    /// for nodes unknown to the network.
+    pub fn set_node_attribute(
+        &mut self,
+        node: &str,
+        attribute: &str,
+        value: &str,
+    ) -> Result<(), FbasError> {
+        if !self.fbas.watchers().any(|w| w == node) {
+            self.fbas.try_get_validator_index(node)?;
+        }
+        self.fbas
+            .attributes
+            .entry(attribute.to_string())
+            .or_default()
+            .insert(node.to_string(), value.to_string());
+        Ok(())
+    }
+
+    /// Returns the `attribute` of `node`, if set.
+    pub fn node_attribute(&self, node: &str, attribute: &str) -> Option<&str> {
+        let values = self.fbas.attributes.get(attribute)?;
+        values.get(node).map(String::as_str)
+    }
+
+    /// Groups the validators by their value of `attribute`, each value
+    /// naming a group. Watchers, being in no quorum, are left out.
+    pub fn group_by_attribute(&self, attribute: &str) -> Organizations {
+        let mut groups: BTreeMap<String, Vec<String>> = BTreeMap::new();
+        for (node, value) in self.fbas.attributes.get(attribute).into_iter().flatten() {
+            if self.fbas.try_get_validator_index(node).is_ok() {
+                groups.entry(value.clone()).or_default().push(node.clone());
+            }
+        }
+        Organizations::new(groups)
+    }
+
+    /// Same as `org_failure_resilience`, with the validators grouped by their
+    /// value of `attribute`, e.g. "does the network survive the loss of any
+    /// two countries".
+    pub fn group_failure_resilience<C: Callbacks>(
+        &self,
+        attribute: &str,
+        max_failed: usize,
+        new_cb: impl FnMut() -> C,
+    ) -> Result<ResilienceReport, FbasError> {
+        self.org_failure_resilience(&self.group_by_attribute(attribute), max_failed, new_cb)
+    }
+
+    /// Same as `critical_organizations`, with the validators grouped by
+    /// their value of `attribute`: the values (e.g. ISPs) whose validators
+    /// failing alone break the network.
+    pub fn critical_groups<C: Callbacks>(
+        &self,
+        attribute: &str,
+        new_cb: impl FnMut() -> C,
+    ) -> Result<CriticalOrganizations, FbasError> {
+        self.critical_organizations(&self.group_by_attribute(attribute), new_cb)
+    }
+}
@@ -0,0 +1,93 @@
+use crate::{fbas::FbasError, FbasAnalyzer};
+use batsat::Callbacks;
+use petgraph::graph::NodeIndex;
+use std::collections::BTreeSet;
+
+// Influence is the Shapley-Shubik index of the simple game in which a set of
+// validators wins iff it contains a quorum. Validators join in a random order,
+// and the one whose arrival first makes a quorum possible is the pivot of that
+// ordering. A validator's score is the fraction of orderings in which it is
+// the pivot. Exact computation is exponential, so orderings are sampled.
+//
+// Containing a quorum is monotone, so the pivot of an ordering is found by a
+// binary search over its prefixes, each checked with `Fbas::max_quorum`.
+
+// splitmix64, good enough for shuffling and keeps the results reproducible
+// from the seed.
+pub(crate) struct SplitMix64(pub(crate) u64);
+
+impl SplitMix64 {
+    pub(crate) fn next(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9e3779b97f4a7c15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58476d1ce4e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d049bb133111eb);
+        z ^ (z >> 31)
+    }
+
+    pub(crate) fn shuffle<T>(&mut self, v: &mut [T]) {
+        for i in (1..v.len()).rev() {
+            let j = (self.next() % (i as u64 + 1)) as usize;
+            v.swap(i, j);
+        }
+    }
+}
+
+impl<Cb: Callbacks> FbasAnalyzer<Cb> {
+    /// Estimates each validator's influence on quorum formation from `samples`
+    /// random orderings drawn from `seed`: the fraction of orderings in which
+    /// the validator is the one completing the first quorum. Scores sum to 1
+    /// (or are all 0 if no quorum exists). Returned in decreasing order of
+    /// score.
+    pub fn influence_scores(
+        &self,
+        samples: u32,
+        seed: u64,
+    ) -> Result<Vec<(String, f64)>, FbasError> {
+        let fbas = &self.fbas;
+        let mut pivots = vec![0u32; fbas.graph.node_count()];
+        let mut rng = SplitMix64(seed);
+        let mut order = fbas.validators.clone();
+        let contains_quorum = |prefix: &[NodeIndex]| {
+            let joined: BTreeSet<_> = prefix.iter().collect();
+            let failed = fbas
+                .validators
+                .iter()
+                .filter(|ni| !joined.contains(ni))
+                .copied()
+                .collect();
+            !fbas.max_quorum(&failed).is_empty()
+        };
+        if self.quorum_exists {
+            for _ in 0..samples {
+                rng.shuffle(&mut order);
+                // smallest `len` such that order[..len] contains a quorum
+                let (mut lo, mut hi) = (1, order.len());
+                while lo < hi {
+                    let mid = (lo + hi) / 2;
+                    if contains_quorum(&order[..mid]) {
+                        hi = mid;
+                    } else {
+                        lo = mid + 1;
+                    }
+                }
+                pivots[order[lo - 1].index()] += 1;
+            }
+        }
+
+        let mut scores = fbas
+            .validators
+            .iter()
+            .map(|vi| {
+                let score = if samples == 0 {
+                    0.0
+                } else {
+                    pivots[vi.index()] as f64 / samples as f64
+                };
+                Ok((fbas.try_get_validator_string(vi)?, score))
+            })
+            .collect::<Result<Vec<_>, FbasError>>()?;
+        scores.sort_by(|a, b| b.1.total_cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+        Ok(scores)
+    }
+}
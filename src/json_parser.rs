@@ -1,151 +1,620 @@
-use crate::fbas::{FbasError, InternalScpQuorumSet, QuorumSetMap};
-use json::{object::Object, JsonValue};
-use std::{fs::File, io::Read, rc::Rc};
+use crate::{
+    fbas::{Fbas, FbasError, InternalScpQuorumSet, QuorumSetMap},
+    organization::{NodeMetadata, Organizations},
+};
+use serde::{
+    de::{self, DeserializeSeed, IgnoredAny, MapAccess, SeqAccess, Visitor},
+    Deserialize, Deserializer, Serialize,
+};
+use serde_json::{Map, Value};
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    fs::File,
+    io::{BufReader, Read},
+    marker::PhantomData,
+    sync::Arc,
+};
+
+// Every json format is read into `NodeRecord`s, the nodes of the stellarbeats
+// format, which then give the quorum set map, the node metadata and the
+// attributes alike. The regular format, and stellar-core's, only have keys
+// and qsets, so their records have no metadata.
+
+/// A qset in the stellarbeats format. Its threshold is either absolute or a
+/// percentage of its members (validators and inner sets), rounded up as
+/// stellar-core does; the absolute one wins if both are given.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct QuorumSetJson {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub threshold: Option<u32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub threshold_percent: Option<u32>,
+    #[serde(default)]
+    pub validators: Vec<String>,
+    #[serde(default)]
+    pub inner_quorum_sets: Vec<QuorumSetJson>,
+}
+
+/// Where a node is, as given by the stellarbeats format.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GeoData {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub country_code: Option<String>,
+}
+
+/// How a node has been doing, as given by the stellarbeats format.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct NodeStatistics {
+    /// The percentage of the last 30 days the node was validating.
+    #[serde(
+        default,
+        rename = "validating30DaysPercentage",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub validating_30_days_percentage: Option<f64>,
+}
+
+/// A node in the stellarbeats format, see `Fbas::from_node_records`. Fields
+/// the analysis doesn't use are ignored when reading.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct NodeRecord {
+    pub public_key: String,
+    /// The qset of the node, `None` for a watcher.
+    #[serde(default)]
+    pub quorum_set: Option<QuorumSetJson>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub home_domain: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub organization_id: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub isp: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub geo_data: Option<GeoData>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub statistics: Option<NodeStatistics>,
+}
+
+// The regular format: `{"nodes": [{"node": ..., "qset": {"t": ..., "v":
+// [...]}}]}`, where the members of a qset are validators or inner qsets, in
+// any order.
+#[derive(Deserialize)]
+struct RegularNetwork {
+    nodes: Vec<RegularNode>,
+}
+
+#[derive(Deserialize)]
+struct RegularNode {
+    node: String,
+    #[serde(default)]
+    qset: Option<RegularQuorumSet>,
+}
+
+#[derive(Deserialize)]
+struct RegularQuorumSet {
+    #[serde(default)]
+    t: Option<u32>,
+    #[serde(default, rename = "thresholdPercent")]
+    threshold_percent: Option<u32>,
+    v: Vec<RegularMember>,
+}
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum RegularMember {
+    Validator(String),
+    QuorumSet(RegularQuorumSet),
+}
+
+impl From<RegularQuorumSet> for QuorumSetJson {
+    fn from(qset: RegularQuorumSet) -> Self {
+        let mut json = QuorumSetJson {
+            threshold: qset.t,
+            threshold_percent: qset.threshold_percent,
+            ..Default::default()
+        };
+        for member in qset.v {
+            match member {
+                RegularMember::Validator(v) => json.validators.push(v),
+                RegularMember::QuorumSet(inner) => json.inner_quorum_sets.push(inner.into()),
+            }
+        }
+        json
+    }
+}
+
+impl From<RegularNode> for NodeRecord {
+    fn from(node: RegularNode) -> Self {
+        NodeRecord {
+            public_key: node.node,
+            quorum_set: node.qset.map(QuorumSetJson::from),
+            ..Default::default()
+        }
+    }
+}
+
+// Converts a threshold expressed as a percentage of the qset size into an
+// absolute count, following stellar-core's rounding rule (round up).
+fn threshold_from_percent(percent: u32, size: usize) -> Result<u32, FbasError> {
+    if percent > 100 {
+        return Err(FbasError::ParseError(
+            "threshold percent must be between 0 and 100",
+        ));
+    }
+    let total = size as u64 * percent as u64;
+    if total == 0 {
+        return Ok(0);
+    }
+    Ok((1 + (total - 1) / 100) as u32)
+}
+
+impl QuorumSetJson {
+    pub(crate) fn to_internal(&self) -> Result<InternalScpQuorumSet, FbasError> {
+        let size = self.validators.len() + self.inner_quorum_sets.len();
+        let threshold = match (self.threshold, self.threshold_percent) {
+            (Some(threshold), _) => threshold,
+            (None, Some(percent)) => threshold_from_percent(percent, size)?,
+            (None, None) => return Err(FbasError::ParseError("threshold field missing")),
+        };
+        Ok(InternalScpQuorumSet {
+            threshold,
+            threshold_percent: self.threshold_percent,
+            validators: self.validators.clone(),
+            inner_sets: self
+                .inner_quorum_sets
+                .iter()
+                .map(QuorumSetJson::to_internal)
+                .collect::<Result<_, _>>()?,
+        })
+    }
+
+    pub(crate) fn from_internal(qset: &InternalScpQuorumSet) -> Self {
+        QuorumSetJson {
+            threshold: Some(qset.threshold),
+            threshold_percent: qset.threshold_percent,
+            validators: qset.validators.clone(),
+            inner_quorum_sets: qset.inner_sets.iter().map(Self::from_internal).collect(),
+        }
+    }
+}
+
+fn from_value<T: serde::de::DeserializeOwned>(value: Value) -> Result<T, FbasError> {
+    serde_json::from_value(value).map_err(|e| FbasError::JsonError(e.to_string()))
+}
+
+pub(crate) fn json_from_path(path: &str) -> Result<Value, FbasError> {
+    let data =
+        std::fs::read_to_string(path).map_err(|_| FbasError::ParseError("fail to read file"))?;
+    serde_json::from_str(&data).map_err(|_| FbasError::ParseError("fail to parse to json"))
+}
 
 pub(crate) fn quorum_set_map_from_json(path: &str) -> Result<QuorumSetMap, FbasError> {
-    let mut file = File::open(path).map_err(|_| FbasError::ParseError("fail to open file"))?;
-    let mut data = String::new();
-    file.read_to_string(&mut data)
-        .map_err(|_| FbasError::ParseError("fail to read file"))?;
-    let json_data =
-        json::parse(&data).map_err(|_| FbasError::ParseError("fail to parse to json"))?;
+    // nothing is excluded, so there are no diagnostics
+    quorum_set_map_from_json_with_min_uptime(path, 0, &mut vec![], &mut vec![])
+}
+
+/// Same as `quorum_set_map_from_json`, except nodes whose 30-day validating
+/// percentage (from stellarbeats statistics) is below `min_uptime` are left
+/// out, making them unknown (i.e. failed) to the rest of the network. Nodes
+/// without statistics, and all nodes in the regular format, are kept.
+/// Excluded nodes are reported in `diagnostics`. Nodes without a qset are
+/// watchers, which are collected in `watchers`.
+pub(crate) fn quorum_set_map_from_json_with_min_uptime(
+    path: &str,
+    min_uptime: u32,
+    diagnostics: &mut Vec<String>,
+    watchers: &mut Vec<String>,
+) -> Result<QuorumSetMap, FbasError> {
+    let mut collector = collect_nodes_from_reader(open_json(path)?, min_uptime)?;
+    diagnostics.append(&mut collector.diagnostics);
+    watchers.append(&mut collector.watchers);
+    Ok(collector.quorum_set_map)
+}
 
+/// Reads the nodes of a json document in any of the formats: the
+/// stellarbeats one (an array of nodes), the regular one, or stellar-core's.
+pub(crate) fn node_records_from_json_value(json_data: Value) -> Result<Vec<NodeRecord>, FbasError> {
     match json_data {
-        JsonValue::Object(root) => try_parse_quorum_set_map_from_json_regular(root),
-        JsonValue::Array(nodes) => try_parse_quorum_set_map_from_stellarbeats_json(nodes),
+        Value::Object(root) if is_stellar_core_json(&root) => {
+            node_records_from_stellar_core_json(root)
+        }
+        Value::Object(root) => {
+            let network: RegularNetwork = from_value(Value::Object(root))?;
+            Ok(network.nodes.into_iter().map(NodeRecord::from).collect())
+        }
+        nodes @ Value::Array(_) => from_value(nodes),
         _ => Err(FbasError::ParseError(
             "root is neither an object nor an array",
         )),
     }
 }
 
-fn try_parse_quorum_set_map_from_json_regular(root: Object) -> Result<QuorumSetMap, FbasError> {
-    let nodes = match root.get("nodes") {
-        Some(JsonValue::Array(nodes)) => nodes,
-        _ => return Err(FbasError::ParseError("nodes field missing or not an array")),
-    };
+// Files are read as a stream: the document is deserialized straight into a
+// `NodeCollector`, one node at a time, so that crawler dumps of hundreds of
+// megabytes never need to be held in memory, as text or as a json tree. Only
+// the output of stellar-core, which is small, is read into a tree first.
 
-    let mut quorum_map = QuorumSetMap::new();
-    for node in nodes {
-        let node = match node {
-            JsonValue::Object(n) => n,
-            _ => return Err(FbasError::ParseError("node is not an object")),
-        };
+// The nodes of an array, each read as a `T` and added to the collector. A
+// node the collector rejects stops the stream, with the error in `failure`.
+struct NodeSeq<'a, T> {
+    collector: &'a mut NodeCollector,
+    failure: &'a mut Option<FbasError>,
+    node: PhantomData<T>,
+}
 
-        let public_key = node
-            .get("node")
-            .and_then(|n| n.as_str())
-            .ok_or_else(|| FbasError::ParseError("node field missing or not a string"))?
-            .to_string();
+impl<'de, T: Deserialize<'de> + Into<NodeRecord>> Visitor<'de> for NodeSeq<'_, T> {
+    type Value = ();
 
-        let qset = parse_internal_quorum_set(&node["qset"])?;
-        quorum_map.insert(public_key, Rc::new(qset));
+    fn expecting(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        f.write_str("an array of nodes")
     }
 
-    Ok(quorum_map)
+    fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<(), A::Error> {
+        while let Some(node) = seq.next_element::<T>()? {
+            if let Err(e) = self.collector.add(&node.into()) {
+                *self.failure = Some(e);
+                return Err(de::Error::custom("invalid node"));
+            }
+        }
+        Ok(())
+    }
 }
 
-fn parse_internal_quorum_set(json_qset: &JsonValue) -> Result<InternalScpQuorumSet, FbasError> {
-    let threshold = json_qset["t"]
-        .as_u32()
-        .ok_or_else(|| FbasError::ParseError("threshold field missing or not a number"))?;
+impl<'de, T: Deserialize<'de> + Into<NodeRecord>> DeserializeSeed<'de> for NodeSeq<'_, T> {
+    type Value = ();
 
-    let v = match &json_qset["v"] {
-        JsonValue::Array(v) => v,
-        _ => return Err(FbasError::ParseError("v field missing or not an array")),
-    };
+    fn deserialize<D: Deserializer<'de>>(self, deserializer: D) -> Result<(), D::Error> {
+        deserializer.deserialize_seq(self)
+    }
+}
 
-    let mut validators = vec![];
-    let mut inner_sets = vec![];
+// The root of a document in any of the formats, see
+// `node_records_from_json_value`.
+struct Root<'a> {
+    collector: &'a mut NodeCollector,
+    failure: &'a mut Option<FbasError>,
+}
 
-    for item in v {
-        match item {
-            JsonValue::String(validator) => {
-                validators.push(validator.to_string());
-            }
-            JsonValue::Object(obj) if obj.get("t").is_some() => {
-                inner_sets.push(parse_internal_quorum_set(item)?);
-            }
-            _ => {
-                return Err(FbasError::ParseError(
-                    "validator entry must be either a string (PublicKey) or an object (QuorumSet)",
-                ))
+impl<'de> Visitor<'de> for Root<'_> {
+    type Value = ();
+
+    fn expecting(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        f.write_str("an array of nodes or an object")
+    }
+
+    fn visit_seq<A: SeqAccess<'de>>(self, seq: A) -> Result<(), A::Error> {
+        NodeSeq::<NodeRecord> {
+            collector: self.collector,
+            failure: self.failure,
+            node: PhantomData,
+        }
+        .visit_seq(seq)
+    }
+
+    fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<(), A::Error> {
+        let mut has_nodes = false;
+        let mut stellar_core = Map::new();
+        while let Some(key) = map.next_key::<String>()? {
+            match key.as_str() {
+                "nodes" => {
+                    map.next_value_seed(NodeSeq::<RegularNode> {
+                        collector: &mut *self.collector,
+                        failure: &mut *self.failure,
+                        node: PhantomData,
+                    })?;
+                    has_nodes = true;
+                }
+                "transitive" | "scp" => {
+                    stellar_core.insert(key, map.next_value()?);
+                }
+                _ => {
+                    map.next_value::<IgnoredAny>()?;
+                }
             }
         }
+        if stellar_core.is_empty() {
+            return match has_nodes {
+                true => Ok(()),
+                false => Err(de::Error::missing_field("nodes")),
+            };
+        }
+        let added = node_records_from_stellar_core_json(stellar_core)
+            .and_then(|records| records.iter().try_for_each(|node| self.collector.add(node)));
+        added.map_err(|e| {
+            *self.failure = Some(e);
+            de::Error::custom("invalid node")
+        })
     }
+}
 
-    Ok(InternalScpQuorumSet {
-        threshold,
-        validators,
-        inner_sets,
-    })
+/// Reads a network in any of the json formats from `reader`, as a stream,
+/// see `NodeCollector`.
+pub(crate) fn collect_nodes_from_reader(
+    reader: impl Read,
+    min_uptime: u32,
+) -> Result<NodeCollector, FbasError> {
+    let mut collector = NodeCollector::new(min_uptime);
+    let mut failure = None;
+    let mut deserializer = serde_json::Deserializer::from_reader(reader);
+    let root = Root {
+        collector: &mut collector,
+        failure: &mut failure,
+    };
+    let res = deserializer
+        .deserialize_any(root)
+        .and_then(|()| deserializer.end());
+    match (failure, res) {
+        (Some(e), _) => Err(e),
+        (None, Ok(())) => Ok(collector),
+        (None, Err(e)) if e.is_data() => Err(FbasError::JsonError(e.to_string())),
+        (None, Err(e)) if e.is_io() => Err(FbasError::IoError("fail to read file")),
+        (None, Err(_)) => Err(FbasError::ParseError("fail to parse to json")),
+    }
 }
 
-fn parse_stellarbeats_internal_quorum_set(
-    json_qset: &JsonValue,
-) -> Result<InternalScpQuorumSet, FbasError> {
-    let threshold = json_qset["threshold"]
-        .as_u32()
-        .ok_or_else(|| FbasError::ParseError("threshold field missing or not a number"))?;
+pub(crate) fn open_json(path: &str) -> Result<BufReader<File>, FbasError> {
+    let file = File::open(path).map_err(|_| FbasError::ParseError("fail to open file"))?;
+    Ok(BufReader::new(file))
+}
 
-    let mut validators = vec![];
-    let mut inner_sets = vec![];
+/// What the analysis takes from the nodes of a network, collected one node
+/// at a time so that the nodes themselves needn't all be held in memory.
+/// Nodes whose 30-day validating percentage is below `min_uptime` are
+/// excluded, see `quorum_set_map_from_json_with_min_uptime`, but their
+/// metadata and attributes are kept.
+#[derive(Default)]
+pub(crate) struct NodeCollector {
+    min_uptime: u32,
+    pub(crate) quorum_set_map: QuorumSetMap,
+    pub(crate) watchers: Vec<String>,
+    pub(crate) diagnostics: Vec<String>,
+    pub(crate) metadata: BTreeMap<String, NodeMetadata>,
+    pub(crate) attributes: BTreeMap<String, BTreeMap<String, String>>,
+    // one copy of each distinct qset, most being shared by many nodes
+    qsets: BTreeSet<Arc<InternalScpQuorumSet>>,
+}
 
-    match &json_qset["validators"] {
-        JsonValue::Array(validator_arr) => {
-            for validator in validator_arr {
-                match validator.as_str() {
-                    Some(validator_str) => validators.push(validator_str.to_string()),
-                    None => return Err(FbasError::ParseError("validator entry must be a string")),
-                }
+impl NodeCollector {
+    pub(crate) fn new(min_uptime: u32) -> Self {
+        NodeCollector {
+            min_uptime,
+            ..Default::default()
+        }
+    }
+
+    pub(crate) fn add(&mut self, node: &NodeRecord) -> Result<(), FbasError> {
+        let meta = NodeMetadata {
+            home_domain: node.home_domain.clone(),
+            organization_id: node.organization_id.clone(),
+            name: node.name.clone(),
+        };
+        if meta != NodeMetadata::default() {
+            self.metadata.insert(node.public_key.clone(), meta);
+        }
+        let country = node.geo_data.as_ref().and_then(|g| g.country_code.as_ref());
+        for (attribute, value) in [("isp", node.isp.as_ref()), ("country", country)] {
+            if let Some(value) = value {
+                self.attributes
+                    .entry(attribute.to_string())
+                    .or_default()
+                    .insert(node.public_key.clone(), value.clone());
             }
         }
-        _ => {
-            return Err(FbasError::ParseError(
-                "validators field missing or not an array",
-            ))
+
+        let uptime = node
+            .statistics
+            .as_ref()
+            .and_then(|s| s.validating_30_days_percentage);
+        if let Some(uptime) = uptime.filter(|uptime| *uptime < self.min_uptime as f64) {
+            self.diagnostics.push(format!(
+                "Validator {} excluded, uptime {}% is below {}%",
+                node.public_key, uptime, self.min_uptime
+            ));
+            return Ok(());
         }
+        let Some(qset) = &node.quorum_set else {
+            self.watchers.push(node.public_key.clone());
+            return Ok(());
+        };
+        let qset = qset.to_internal()?;
+        let qset = match self.qsets.get(&qset) {
+            Some(shared) => shared.clone(),
+            None => {
+                let qset = Arc::new(qset);
+                self.qsets.insert(qset.clone());
+                qset
+            }
+        };
+        self.quorum_set_map.insert(node.public_key.clone(), qset);
+        Ok(())
+    }
+}
+
+/// Same as `quorum_set_map_from_json_with_min_uptime`, for nodes already
+/// read.
+pub(crate) fn quorum_set_map_from_node_records(
+    records: &[NodeRecord],
+    min_uptime: u32,
+    diagnostics: &mut Vec<String>,
+    watchers: &mut Vec<String>,
+) -> Result<QuorumSetMap, FbasError> {
+    let mut collector = NodeCollector::new(min_uptime);
+    for node in records {
+        collector.add(node)?;
+    }
+    diagnostics.append(&mut collector.diagnostics);
+    watchers.append(&mut collector.watchers);
+    Ok(collector.quorum_set_map)
+}
+
+// stellar-core's HTTP endpoints give the network as seen by a live node, in
+// one of two shapes:
+//
+// - `quorum?transitive=true`: the nodes of the node's transitive quorum under
+//   `transitive.nodes`, each with its qset in the regular format, except
+//   nodes whose qset the node hasn't heard of.
+// - `scp`: the latest slots under `scp`, by slot index, each with the SCP
+//   statements heard as strings (`{ENV@<node> | i: <slot> | ... | D: <hash>
+//   ...}`) and the qsets they reference under `quorum_sets`, by abbreviated
+//   hash. A node's qset is the one of its statement in the latest slot.
+//
+// Nodes are named as the endpoint names them, which is by strkey only with
+// `fullkeys=true`.
+
+/// Whether `root` is the output of stellar-core's `quorum` or `scp` endpoint.
+pub(crate) fn is_stellar_core_json(root: &Map<String, Value>) -> bool {
+    root.contains_key("transitive") || root.contains_key("scp")
+}
+
+fn node_records_from_stellar_core_json(
+    mut root: Map<String, Value>,
+) -> Result<Vec<NodeRecord>, FbasError> {
+    if let Some(transitive) = root.remove("transitive") {
+        let network: RegularNetwork = from_value(transitive)?;
+        return Ok(network.nodes.into_iter().map(NodeRecord::from).collect());
     }
+    let Some(Value::Object(slots)) = root.remove("scp") else {
+        return Err(FbasError::ParseError("scp field is not an object"));
+    };
+    let mut slots: Vec<(u64, Value)> = slots
+        .into_iter()
+        .filter_map(|(index, slot)| Some((index.parse().ok()?, slot)))
+        .collect();
+    slots.sort_by_key(|(index, _)| *index);
 
-    match &json_qset["innerQuorumSets"] {
-        JsonValue::Array(inner_arr) => {
-            for inner_qset in inner_arr {
-                inner_sets.push(parse_stellarbeats_internal_quorum_set(inner_qset)?);
+    let mut qsets = BTreeMap::new();
+    for (_, slot) in slots {
+        let statements = slot["statements"].as_array().into_iter().flatten();
+        for statement in statements {
+            // [time, statement, validated] entries, or the bare statement
+            let statement = match statement {
+                Value::Array(fields) => fields.iter().find_map(Value::as_str),
+                _ => statement.as_str(),
+            };
+            let Some((node, hash)) = statement.and_then(parse_scp_statement) else {
+                continue;
+            };
+            if let Some(json_qset) = slot["quorum_sets"].get(hash) {
+                let qset: RegularQuorumSet = from_value(json_qset.clone())?;
+                qsets.insert(node.to_string(), QuorumSetJson::from(qset));
             }
         }
-        _ => {
-            return Err(FbasError::ParseError(
-                "innerQuorumSets field missing or not an array",
-            ))
-        }
     }
+    if qsets.is_empty() {
+        return Err(FbasError::ParseError("no qset found in scp statements"));
+    }
+    Ok(qsets
+        .into_iter()
+        .map(|(public_key, qset)| NodeRecord {
+            public_key,
+            quorum_set: Some(qset),
+            ..Default::default()
+        })
+        .collect())
+}
+
+// The node and qset hash of an SCP statement as printed by stellar-core. The
+// hash follows `D: `, or `(lastD): ` for EXTERNALIZE statements.
+fn parse_scp_statement(statement: &str) -> Option<(&str, &str)> {
+    let rest = statement.split_once("{ENV@")?.1;
+    let node = rest.split_once(' ')?.0;
+    let hash = rest
+        .split_once("| D: ")
+        .or_else(|| rest.split_once("(lastD): "))?
+        .1;
+    let end = hash
+        .find(|c: char| !c.is_ascii_hexdigit())
+        .unwrap_or(hash.len());
+    Some((node, &hash[..end]))
+}
+
+/// Extracts the node list, in the stellarbeats format, from a response of a
+/// stellarbeats-like API. The first version of the schema is the list
+/// itself; later ones wrap it in an object under `nodes`, next to other data
+/// such as organizations. Anything else is an unsupported schema.
+#[cfg(any(feature = "net", test))]
+pub(crate) fn nodes_from_api_response(response: Value) -> Result<Vec<NodeRecord>, FbasError> {
+    let nodes = match response {
+        Value::Object(mut root) => root.remove("nodes").unwrap_or(Value::Null),
+        nodes => nodes,
+    };
+    // unlike the nodes of the regular format, which are keyed by `node`
+    let is_stellarbeats = |node: &Value| node["publicKey"].is_string();
+    match &nodes {
+        Value::Array(list) if list.iter().all(is_stellarbeats) => from_value(nodes),
+        _ => Err(FbasError::ParseError("unsupported API response schema")),
+    }
+}
 
-    Ok(InternalScpQuorumSet {
-        threshold,
-        validators,
-        inner_sets,
-    })
+/// Writes the validators of `fbas` with their qsets (as seen by the analysis,
+/// i.e. without unknown validators), followed by the watchers without a qset,
+/// in the stellarbeats format.
+pub(crate) fn nodes_to_json(fbas: &Fbas) -> Result<Value, FbasError> {
+    let mut nodes = fbas
+        .validators
+        .iter()
+        .map(|vi| {
+            Ok(NodeRecord {
+                public_key: fbas.try_get_validator_string(vi)?,
+                quorum_set: Some(QuorumSetJson::from_internal(&fbas.validator_qset(*vi)?)),
+                ..Default::default()
+            })
+        })
+        .collect::<Result<Vec<_>, FbasError>>()?;
+    nodes.extend(fbas.watchers().map(|w| NodeRecord {
+        public_key: w.to_string(),
+        ..Default::default()
+    }));
+    serde_json::to_value(nodes).map_err(|e| FbasError::JsonError(e.to_string()))
 }
 
-fn try_parse_quorum_set_map_from_stellarbeats_json(
-    nodes: Vec<JsonValue>,
+/// Parses nodes in the stellarbeats format, see `nodes_to_json`.
+pub(crate) fn quorum_set_map_from_nodes_json(
+    nodes: &Value,
+    watchers: &mut Vec<String>,
 ) -> Result<QuorumSetMap, FbasError> {
-    let mut quorum_map = QuorumSetMap::new();
-    for node in nodes {
-        let node = match node {
-            JsonValue::Object(n) => n,
-            _ => return Err(FbasError::ParseError("node is not an object")),
-        };
+    if !nodes.is_array() {
+        return Err(FbasError::ParseError("nodes is not an array"));
+    }
+    let records: Vec<NodeRecord> = from_value(nodes.clone())?;
+    quorum_set_map_from_node_records(&records, 0, &mut vec![], watchers)
+}
 
-        let public_key = node
-            .get("publicKey")
-            .and_then(|n| n.as_str())
-            .ok_or_else(|| FbasError::ParseError("publicKey field missing or not a string"))?
-            .to_string();
+#[derive(Deserialize)]
+struct OrganizationJson {
+    name: String,
+    validators: Vec<String>,
+}
 
-        let qset = parse_stellarbeats_internal_quorum_set(&node["quorumSet"])?;
-        quorum_map.insert(public_key, Rc::new(qset));
+/// Parses an organization mapping, given as an array of objects each with a
+/// `name` and a `validators` array.
+pub(crate) fn organizations_from_json(path: &str) -> Result<Organizations, FbasError> {
+    let json_data = json_from_path(path)?;
+    if !json_data.is_array() {
+        return Err(FbasError::ParseError("root is not an array"));
     }
+    let orgs: Vec<OrganizationJson> = from_value(json_data)?;
+    Ok(Organizations::new(
+        orgs.into_iter()
+            .map(|org| (org.name, org.validators))
+            .collect(),
+    ))
+}
 
-    Ok(quorum_map)
+/// Groups the validators of a network in the stellarbeats format by their
+/// `homeDomain`. Nodes without one belong to no organization.
+pub(crate) fn organizations_from_home_domains(path: &str) -> Result<Organizations, FbasError> {
+    let json_data = json_from_path(path)?;
+    if !json_data.is_array() {
+        return Err(FbasError::ParseError("root is not an array"));
+    }
+    let mut collector = NodeCollector::default();
+    for node in from_value::<Vec<NodeRecord>>(json_data)? {
+        collector.add(&node)?;
+    }
+    Ok(Organizations::from_metadata(&collector.metadata))
 }
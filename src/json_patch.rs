@@ -0,0 +1,196 @@
+use crate::{
+    fbas::{Fbas, FbasError},
+    json_parser::{
+        json_from_path, node_records_from_json_value, quorum_set_map_from_node_records, NodeRecord,
+    },
+    FbasAnalyzer,
+};
+use batsat::Callbacks;
+use serde_json::Value;
+
+// Patches are applied to the json document rather than to the quorum set map,
+// so they are written against the snapshot exactly as it was pushed (e.g.
+// `/3/quorumSet/threshold` in the stellarbeats format), and validators that
+// are unknown to the network can be added or referenced later. Only the
+// subset of RFC 6902 that is unambiguous on `serde_json::Value` is needed, so
+// it is implemented here rather than pulling in a dependency.
+
+/// A network snapshot in one of the json formats of
+/// `FbasAnalyzer::from_json_path`, kept in memory so that monitoring
+/// pipelines can push small diffs instead of full dumps, see `apply_patch`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct JsonSnapshot {
+    doc: Value,
+}
+
+// Splits a JSON pointer (RFC 6901) into its unescaped reference tokens.
+fn pointer_tokens(pointer: &str) -> Result<Vec<String>, FbasError> {
+    if pointer.is_empty() {
+        return Ok(vec![]);
+    }
+    let Some(tokens) = pointer.strip_prefix('/') else {
+        return Err(FbasError::ParseError("JSON pointer must start with /"));
+    };
+    Ok(tokens
+        .split('/')
+        .map(|token| token.replace("~1", "/").replace("~0", "~"))
+        .collect())
+}
+
+fn array_index(token: &str, len: usize) -> Result<usize, FbasError> {
+    match token.parse::<usize>() {
+        Ok(i) if i <= len && (token == "0" || !token.starts_with('0')) => Ok(i),
+        _ => Err(FbasError::ParseError("JSON pointer index out of bounds")),
+    }
+}
+
+fn get<'a>(doc: &'a Value, tokens: &[String]) -> Result<&'a Value, FbasError> {
+    tokens.iter().try_fold(doc, |value, token| {
+        let child = match value {
+            Value::Object(object) => object.get(token),
+            Value::Array(array) => array.get(array_index(token, array.len())?),
+            _ => None,
+        };
+        child.ok_or(FbasError::ParseError("JSON pointer target not found"))
+    })
+}
+
+fn get_mut<'a>(doc: &'a mut Value, tokens: &[String]) -> Result<&'a mut Value, FbasError> {
+    tokens.iter().try_fold(doc, |value, token| {
+        let child = match value {
+            Value::Object(object) => object.get_mut(token),
+            Value::Array(array) => {
+                let i = array_index(token, array.len())?;
+                array.get_mut(i)
+            }
+            _ => None,
+        };
+        child.ok_or(FbasError::ParseError("JSON pointer target not found"))
+    })
+}
+
+fn add(doc: &mut Value, tokens: &[String], value: Value) -> Result<(), FbasError> {
+    let Some((last, parent)) = tokens.split_last() else {
+        *doc = value;
+        return Ok(());
+    };
+    match get_mut(doc, parent)? {
+        Value::Object(object) => {
+            object.insert(last.clone(), value);
+        }
+        Value::Array(array) if last == "-" => array.push(value),
+        Value::Array(array) => {
+            let i = array_index(last, array.len())?;
+            array.insert(i, value);
+        }
+        _ => return Err(FbasError::ParseError("JSON pointer target not found")),
+    }
+    Ok(())
+}
+
+fn remove(doc: &mut Value, tokens: &[String]) -> Result<Value, FbasError> {
+    let Some((last, parent)) = tokens.split_last() else {
+        return Ok(std::mem::replace(doc, Value::Null));
+    };
+    let removed = match get_mut(doc, parent)? {
+        Value::Object(object) => object.remove(last),
+        Value::Array(array) => {
+            let i = array_index(last, array.len())?;
+            (i < array.len()).then(|| array.remove(i))
+        }
+        _ => None,
+    };
+    removed.ok_or(FbasError::ParseError("JSON pointer target not found"))
+}
+
+fn apply_operation(doc: &mut Value, operation: &Value) -> Result<(), FbasError> {
+    let pointer = |key: &str| {
+        operation[key]
+            .as_str()
+            .ok_or(FbasError::ParseError("JSON patch operation without a path"))
+            .and_then(pointer_tokens)
+    };
+    let value = || match &operation["value"] {
+        Value::Null if operation.get("value").is_none() => Err(FbasError::ParseError(
+            "JSON patch operation without a value",
+        )),
+        value => Ok(value.clone()),
+    };
+    let path = pointer("path")?;
+    match operation["op"].as_str() {
+        Some("add") => add(doc, &path, value()?),
+        Some("remove") => remove(doc, &path).map(|_| ()),
+        Some("replace") => {
+            *get_mut(doc, &path)? = value()?;
+            Ok(())
+        }
+        Some("move") => {
+            let from = pointer("from")?;
+            if path.len() > from.len() && path.starts_with(&from) {
+                return Err(FbasError::ParseError(
+                    "JSON patch moves a value into itself",
+                ));
+            }
+            let moved = remove(doc, &from)?;
+            add(doc, &path, moved)
+        }
+        Some("copy") => {
+            let copied = get(doc, &pointer("from")?)?.clone();
+            add(doc, &path, copied)
+        }
+        Some("test") if *get(doc, &path)? == value()? => Ok(()),
+        Some("test") => Err(FbasError::ParseError("JSON patch test failed")),
+        _ => Err(FbasError::ParseError("Invalid JSON patch operation")),
+    }
+}
+
+impl JsonSnapshot {
+    pub fn from_json_path(path: &str) -> Result<Self, FbasError> {
+        let snapshot = Self {
+            doc: json_from_path(path)?,
+        };
+        snapshot.node_records()?;
+        Ok(snapshot)
+    }
+
+    /// Applies an RFC 6902 JSON Patch (a json array of operations) to the
+    /// snapshot. The patch is applied atomically: if any operation fails,
+    /// including a `test`, or the patched document is no longer a valid
+    /// network, the snapshot is left unchanged.
+    pub fn apply_patch(&mut self, patch: &str) -> Result<(), FbasError> {
+        let patch: Value = serde_json::from_str(patch)
+            .map_err(|_| FbasError::ParseError("fail to parse to json"))?;
+        let Some(operations) = patch.as_array() else {
+            return Err(FbasError::ParseError("JSON patch is not an array"));
+        };
+        let mut doc = self.doc.clone();
+        for operation in operations {
+            apply_operation(&mut doc, operation)?;
+        }
+        let patched = Self { doc };
+        patched.node_records()?;
+        *self = patched;
+        Ok(())
+    }
+
+    // The nodes of the snapshot, checking that their qsets are valid.
+    fn node_records(&self) -> Result<Vec<NodeRecord>, FbasError> {
+        let records = node_records_from_json_value(self.doc.clone())?;
+        quorum_set_map_from_node_records(&records, 0, &mut vec![], &mut vec![])?;
+        Ok(records)
+    }
+
+    /// Serializes the snapshot, e.g. to persist it between runs.
+    pub fn to_json(&self) -> String {
+        self.doc.to_string()
+    }
+}
+
+impl<Cb: Callbacks> FbasAnalyzer<Cb> {
+    /// Constructs the analyzer from the current state of `snapshot`. The
+    /// formula is encoded from scratch, so after a patch this costs the same
+    /// as loading the full network, minus reading and parsing it.
+    pub fn from_json_snapshot(snapshot: &JsonSnapshot, cb: Cb) -> Result<Self, FbasError> {
+        Self::from_fbas(Fbas::from_node_records(&snapshot.node_records()?)?, cb)
+    }
+}
@@ -1,14 +1,159 @@
+// Without any input format enabled there is no way to construct a network.
+#![cfg_attr(
+    not(any(feature = "xdr", feature = "json", test)),
+    allow(dead_code, unused_imports)
+)]
+
+mod aliases;
 mod allocator;
+#[cfg(any(feature = "json", test))]
+mod analysis;
+mod availability;
+mod blocking;
+mod bridging;
+mod brute_force;
+mod budget;
+mod diff;
+mod dimacs;
+mod dset;
+mod dsl;
 
 pub(crate) mod fbas;
 pub(crate) mod fbas_analyze;
+mod grouping;
+mod influence;
+mod limits;
+mod lint;
+mod local;
+mod metrics;
+mod minimal_quorums;
+mod multi_network;
+mod ordering;
+mod organization;
+mod pool;
+mod pruning;
+#[cfg(feature = "xdr")]
+mod qset_inference;
+mod reciprocity;
+mod red_team;
+mod resilience;
+mod robustness;
+mod sensitivity;
+mod smtlib;
+mod splitting;
+mod sweep;
+mod telemetry;
+mod timeline;
+mod uncertainty;
 
 #[cfg(any(feature = "json", test))]
 pub(crate) mod json_parser;
 
+#[cfg(any(feature = "json", test))]
+mod json_patch;
+
+#[cfg(any(feature = "json", test))]
+mod load;
+
+#[cfg(feature = "net")]
+mod net;
+
+#[cfg(any(feature = "json", test))]
+mod repro;
+
+#[cfg(any(feature = "json", test))]
+mod report_json;
+
+#[cfg(any(feature = "json", test))]
+mod report_sink;
+
+#[cfg(feature = "store")]
+mod store;
+
+#[cfg(any(feature = "json", test))]
+mod watcher;
+
+#[cfg(feature = "fbas_analyzer")]
+pub mod fbas_analyzer_compat;
+
 #[cfg(test)]
 mod test;
 
+#[cfg(any(feature = "json", test))]
+pub use analysis::{Analysis, AnalysisInput, AnalysisRegistry};
+pub use availability::Availability;
 pub use batsat::callbacks::Callbacks;
-pub use fbas::FbasError;
-pub use fbas_analyze::{FbasAnalyzer, SolveStatus};
+pub use batsat::Lit;
+pub use bridging::{BridgingEdges, TrustEdge};
+pub use brute_force::BRUTE_FORCE_MAX_VALIDATORS;
+pub use budget::{Deadline, TimeBudget};
+pub use diff::{FbasDiff, QsetChange, SnapshotChange};
+pub use dimacs::{solve_dimacs, DimacsSolution};
+pub use dset::IntactValidators;
+pub use fbas::{FbasError, FbasWarning};
+pub use fbas_analyze::{
+    AnalysisReport, BranchingHeuristic, DiagnosticsSink, DisjointQuorums, EncodingEstimate,
+    FbasAnalyzer, InternalFailure, QsetMembership, Quorum, ReportProvenance, SolvePhase,
+    SolveStats, SolveStatus, REPORT_SCHEMA_VERSION,
+};
+#[cfg(any(feature = "json", test))]
+pub use json_parser::{GeoData, NodeRecord, NodeStatistics, QuorumSetJson};
+#[cfg(any(feature = "json", test))]
+pub use json_patch::JsonSnapshot;
+pub use limits::{LimitedOutcome, LimitedStatus, ResourceLimits};
+pub use lint::{LintSeverity, QsetLint, QsetLintKind};
+#[cfg(any(feature = "json", test))]
+pub use load::InputFormat;
+pub use local::LocalView;
+pub use metrics::ValidatorMetrics;
+pub use minimal_quorums::MinimalQuorums;
+pub use multi_network::MultiNetworkReport;
+pub use ordering::ValidatorOrder;
+pub use organization::{NodeMetadata, OrgQuorumSet, Organizations};
+pub use petgraph::graph::DiGraph;
+pub use pool::AnalyzerPool;
+pub use reciprocity::TrustReport;
+pub use red_team::RedTeamResult;
+#[cfg(feature = "net")]
+pub use report_sink::WebhookSink;
+#[cfg(any(feature = "json", test))]
+pub use report_sink::{FileSink, ReportSink, StdoutSink};
+pub use resilience::{CriticalOrganizations, ResilienceReport, ResilienceRow};
+pub use robustness::{RobustnessScore, RobustnessWeights};
+pub use sensitivity::ThresholdPerturbation;
+pub use splitting::SplittingSets;
+/// The XDR types expected by the `from_quorum_set_map_buf*` constructors,
+/// re-exported so callers don't have to match the `stellar-xdr` version.
+#[cfg(feature = "xdr")]
+pub use stellar_xdr::curr::{Limits, NodeId, ScpQuorumSet};
+#[cfg(feature = "store")]
+pub use store::{ReportStore, StoredReport, TopTierSize};
+pub use sweep::{ThresholdSweep, ThresholdSweepRow};
+pub use timeline::{Timeline, TimelineEntry, TopTierAlert};
+pub use uncertainty::{Resolution, UncertainQsets, UncertaintyReport, UNCERTAINTY_MAX_RESOLUTIONS};
+#[cfg(any(feature = "json", test))]
+pub use watcher::Watcher;
+
+#[cfg(feature = "xdr")]
+pub use qset_inference::{infer_quorum_sets, InferenceMethod, InferredQset};
+
+/// The stable, high-level API of this crate.
+///
+/// There is no separate configuration type: the analyzer is configured with
+/// its `with_*` methods (`with_branching_heuristic`, `with_symmetry_breaking`,
+/// `with_diagnostics_sink`), whose argument types are exported here.
+pub mod prelude {
+    pub use crate::{
+        AnalysisReport, BranchingHeuristic, Callbacks, DiagnosticsSink, FbasAnalyzer, FbasError,
+        Quorum, SolvePhase, SolveStats, SolveStatus, REPORT_SCHEMA_VERSION,
+    };
+}
+
+/// Internal graph and quorum set types. These are exposed for tooling and
+/// experimentation only and may change between any two releases.
+#[cfg(feature = "internals")]
+pub mod internals {
+    pub use crate::fbas::{Fbas, InternalScpQuorumSet, Qset, QuorumSetMap, Vertex};
+    pub use crate::lint::lint_quorum_set_map;
+    pub use crate::pruning::{DefaultPruning, PruningStrategy};
+}
@@ -0,0 +1,162 @@
+use crate::{
+    fbas::{Fbas, FbasError},
+    fbas_analyze::{catch_internal, estimate_pruned_encoding},
+    pruning::{DefaultPruning, PruningStrategy},
+    Callbacks, Deadline, EncodingEstimate, FbasAnalyzer, SolveStatus,
+};
+use petgraph::graph::NodeIndex;
+use std::{collections::BTreeSet, time::Duration};
+
+// The encoding can't be interrupted, so the clause and memory limits are
+// checked against `estimate_encoding` before encoding, from the pruned
+// network, which is then encoded without pruning it again. The time limit is
+// a `Deadline` covering the whole call; the analyzer gets it as its callbacks
+// and is checked between the steps, so a call overruns it by at most one
+// parse or encoding.
+
+/// Resources one call of `FbasAnalyzer::solve_dsl_within` (or of the other
+/// `solve_*_within` functions) may use. Exceeding any of them ends the call
+/// with the matching `LimitedStatus` rather than an error, so a host never
+/// has to kill the process to bound the analyzer.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ResourceLimits {
+    /// Wall time of the whole call: parsing, encoding and solving.
+    pub max_time: Duration,
+    /// Limit on the estimated memory of the formula, see
+    /// `EncodingEstimate::memory_bytes`. What the solver learns while
+    /// searching comes on top, and is bounded by `max_time` only.
+    pub max_memory_bytes: u64,
+    /// Limit on the number of clauses of the formula, see
+    /// `EncodingEstimate::num_clauses`.
+    pub max_clauses: u64,
+}
+
+impl Default for ResourceLimits {
+    /// 30 seconds and the 2 GiB the crate's allocator allows, with the
+    /// clauses only bounded by the memory.
+    fn default() -> Self {
+        Self {
+            max_time: Duration::from_secs(30),
+            max_memory_bytes: 2 * 1024 * 1024 * 1024,
+            max_clauses: u64::MAX,
+        }
+    }
+}
+
+/// Status of a call with `ResourceLimits`. The discriminants are stable, to
+/// be passed on as status codes across an FFI boundary.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[repr(i32)]
+pub enum LimitedStatus {
+    /// The network enjoys quorum intersection.
+    Unsat = 0,
+    /// The network has two disjoint quorums, see `LimitedOutcome`.
+    Sat = 1,
+    /// `max_time` passed before the analysis completed.
+    TimeLimit = 2,
+    /// The formula would exceed `max_memory_bytes`; nothing was encoded.
+    MemoryLimit = 3,
+    /// The formula would exceed `max_clauses`; nothing was encoded.
+    ClauseLimit = 4,
+}
+
+impl LimitedStatus {
+    pub fn code(self) -> i32 {
+        self as i32
+    }
+}
+
+/// Outcome of a call with `ResourceLimits`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LimitedOutcome {
+    pub status: LimitedStatus,
+    /// The disjoint quorums if `Sat`, as in `get_potential_split`, and
+    /// empty otherwise.
+    pub potential_split: (Vec<String>, Vec<String>),
+    /// The size of the formula, which the clause and memory limits were
+    /// checked against. Default if the time ran out while parsing.
+    pub estimate: EncodingEstimate,
+}
+
+impl LimitedOutcome {
+    fn new(status: LimitedStatus, estimate: EncodingEstimate) -> Self {
+        Self {
+            status,
+            potential_split: Default::default(),
+            estimate,
+        }
+    }
+}
+
+// Hands the vertices pruned for the estimate on to the analyzer.
+struct Pruned(BTreeSet<NodeIndex>);
+
+impl PruningStrategy for Pruned {
+    fn prune(&self, _: &Fbas) -> BTreeSet<NodeIndex> {
+        self.0.clone()
+    }
+}
+
+impl FbasAnalyzer<Deadline> {
+    /// Same as `from_quorum_set_map_buf` followed by `try_solve`, within
+    /// `limits`.
+    #[cfg(feature = "xdr")]
+    pub fn solve_quorum_set_map_buf_within<T: AsRef<[u8]>, I: ExactSizeIterator<Item = T>>(
+        nodes: I,
+        quorum_set: I,
+        limits: &ResourceLimits,
+    ) -> Result<LimitedOutcome, FbasError> {
+        Self::solve_within("from_quorum_set_map_buf", limits, || {
+            Fbas::from_quorum_set_map_buf(nodes, quorum_set)
+        })
+    }
+
+    /// Same as `from_dsl` followed by `try_solve`, within `limits`.
+    pub fn solve_dsl_within(
+        dsl: &str,
+        limits: &ResourceLimits,
+    ) -> Result<LimitedOutcome, FbasError> {
+        Self::solve_within("from_dsl", limits, || Fbas::from_dsl(dsl))
+    }
+
+    // Invalid networks and panics are still errors, as in `try_solve`.
+    fn solve_within(
+        entry_point: &'static str,
+        limits: &ResourceLimits,
+        parse: impl FnOnce() -> Result<Fbas, FbasError>,
+    ) -> Result<LimitedOutcome, FbasError> {
+        let deadline = Deadline::after(limits.max_time);
+        let fbas = catch_internal(entry_point, (0, 0), parse)?;
+        if deadline.stop() {
+            return Ok(LimitedOutcome::new(
+                LimitedStatus::TimeLimit,
+                Default::default(),
+            ));
+        }
+        let pruned = DefaultPruning.prune(&fbas);
+        let estimate = estimate_pruned_encoding(&fbas, &pruned)?;
+        if estimate.num_clauses > limits.max_clauses {
+            return Ok(LimitedOutcome::new(LimitedStatus::ClauseLimit, estimate));
+        }
+        if estimate.memory_bytes > limits.max_memory_bytes {
+            return Ok(LimitedOutcome::new(LimitedStatus::MemoryLimit, estimate));
+        }
+        let mut analyzer = Self::from_fbas_with_strategy(fbas, &Pruned(pruned), deadline)?;
+        // the deadline only interrupts the search
+        let status = if deadline.stop() {
+            SolveStatus::UNKNOWN
+        } else {
+            analyzer.try_solve()?
+        };
+        let mut outcome = LimitedOutcome::new(
+            match status {
+                SolveStatus::SAT(_) => LimitedStatus::Sat,
+                SolveStatus::UNSAT => LimitedStatus::Unsat,
+                SolveStatus::UNKNOWN => LimitedStatus::TimeLimit,
+            },
+            estimate,
+        );
+        outcome.potential_split = analyzer.get_potential_split()?;
+        Ok(outcome)
+    }
+}
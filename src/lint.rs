@@ -0,0 +1,208 @@
+use crate::fbas::{InternalScpQuorumSet, QuorumSetMap, QUORUM_SET_MAX_DEPTH};
+use std::collections::{BTreeMap, BTreeSet};
+
+// The checks follow stellar-core's quorum set sanity checker, which rejects
+// qsets with a threshold of 0 or above their number of members, with a
+// validator listed twice anywhere in the qset, or nested too deeply, and in
+// its extra checks warns about thresholds below a simple majority. On top of
+// these, validators missing from their own qset (stellar-core adds them
+// implicitly) and single validators able to block a qset alone are
+// reported.
+//
+// Members of a qset are its validators and inner sets, as listed: unknown
+// validators and duplicates count, unlike in the analysis, since this is
+// about the configuration as written. Most validators share their qset with
+// others, so findings are computed once per distinct qset.
+
+/// How serious a `QsetLint` is.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum LintSeverity {
+    /// Worth knowing, not a problem in itself.
+    Info,
+    /// Legal, but weakens safety or liveness.
+    Warning,
+    /// Rejected by stellar-core's sanity checks.
+    Error,
+}
+
+/// The issue a `QsetLint` is about. Thresholds and members are those of the
+/// (possibly inner) qset at fault.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum QsetLintKind {
+    /// The threshold is 0 (an error, as the qset is vacuous) or below a
+    /// simple majority of the members (a warning, as two disjoint subsets
+    /// can then satisfy it).
+    ThresholdTooLow { threshold: u32, members: usize },
+    /// The threshold exceeds the number of members, so the qset can never be
+    /// satisfied.
+    ThresholdTooHigh { threshold: u32, members: usize },
+    /// `member` is listed more than once in the qset, inner sets included.
+    DuplicateMember { member: String },
+    /// The qset is nested `depth` levels deep, counting the top level, more
+    /// than the `max` allowed. Building the network fails with
+    /// `FbasError::MaxDepthExceeded` in that case.
+    ExcessiveDepth { depth: u32, max: u32 },
+    /// The validator is not in its own qset.
+    MissingSelf,
+    /// `member` failing alone blocks the qset (it is v-blocking by itself),
+    /// so the validator's liveness hangs on it.
+    SingleBlockingMember { member: String },
+}
+
+/// A finding of the qset lints, see `FbasAnalyzer::qset_lints`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct QsetLint {
+    /// The validator whose qset it is found in.
+    pub validator: String,
+    pub severity: LintSeverity,
+    pub kind: QsetLintKind,
+}
+
+impl QsetLintKind {
+    pub fn severity(&self) -> LintSeverity {
+        match self {
+            QsetLintKind::ThresholdTooLow { threshold, .. } if *threshold > 0 => {
+                LintSeverity::Warning
+            }
+            QsetLintKind::ThresholdTooLow { .. }
+            | QsetLintKind::ThresholdTooHigh { .. }
+            | QsetLintKind::DuplicateMember { .. }
+            | QsetLintKind::ExcessiveDepth { .. } => LintSeverity::Error,
+            QsetLintKind::SingleBlockingMember { .. } => LintSeverity::Warning,
+            QsetLintKind::MissingSelf => LintSeverity::Info,
+        }
+    }
+}
+
+impl std::fmt::Display for QsetLint {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{:?}: qset of {}: ", self.severity, self.validator)?;
+        match &self.kind {
+            QsetLintKind::ThresholdTooLow { threshold, members } => write!(
+                f,
+                "threshold {} is below a majority of its {} members",
+                threshold, members
+            ),
+            QsetLintKind::ThresholdTooHigh { threshold, members } => {
+                write!(f, "threshold {} exceeds its {} members", threshold, members)
+            }
+            QsetLintKind::DuplicateMember { member } => {
+                write!(f, "{} is listed more than once", member)
+            }
+            QsetLintKind::ExcessiveDepth { depth, max } => {
+                write!(f, "nested {} levels deep, more than {}", depth, max)
+            }
+            QsetLintKind::MissingSelf => write!(f, "the validator is not in it"),
+            QsetLintKind::SingleBlockingMember { member } => {
+                write!(f, "{} alone can block it", member)
+            }
+        }
+    }
+}
+
+// Whether `qset` can no longer be satisfied without `failed`.
+fn is_blocked(qset: &InternalScpQuorumSet, failed: Option<&str>) -> bool {
+    let members = qset.validators.len() + qset.inner_sets.len();
+    let blocked = qset
+        .validators
+        .iter()
+        .filter(|v| Some(v.as_str()) == failed)
+        .count()
+        + qset
+            .inner_sets
+            .iter()
+            .filter(|inner| is_blocked(inner, failed))
+            .count();
+    members - blocked < qset.threshold as usize
+}
+
+// The number of nesting levels of `qset`, counting the top level.
+fn depth(qset: &InternalScpQuorumSet) -> u32 {
+    1 + qset.inner_sets.iter().map(depth).max().unwrap_or(0)
+}
+
+// Collects the threshold findings of `qset` and its inner sets.
+fn lint_thresholds(qset: &InternalScpQuorumSet, out: &mut Vec<QsetLintKind>) {
+    let members = qset.validators.len() + qset.inner_sets.len();
+    let threshold = qset.threshold;
+    if threshold as usize > members {
+        out.push(QsetLintKind::ThresholdTooHigh { threshold, members });
+    } else if threshold == 0 || (threshold as usize) * 2 <= members {
+        out.push(QsetLintKind::ThresholdTooLow { threshold, members });
+    }
+    for inner in qset.inner_sets.iter() {
+        lint_thresholds(inner, out);
+    }
+}
+
+// The findings of a top-level qset that don't depend on whose it is.
+fn lint_qset(qset: &InternalScpQuorumSet) -> Vec<QsetLintKind> {
+    let mut out = vec![];
+    let depth = depth(qset);
+    if depth > QUORUM_SET_MAX_DEPTH {
+        out.push(QsetLintKind::ExcessiveDepth {
+            depth,
+            max: QUORUM_SET_MAX_DEPTH,
+        });
+    }
+    lint_thresholds(qset, &mut out);
+
+    let mut seen = BTreeSet::new();
+    let mut duplicates = BTreeSet::new();
+    let mut stack = vec![qset];
+    while let Some(q) = stack.pop() {
+        for v in q.validators.iter() {
+            if !seen.insert(v.as_str()) {
+                duplicates.insert(v.as_str());
+            }
+        }
+        stack.extend(q.inner_sets.iter());
+    }
+    out.extend(
+        duplicates
+            .into_iter()
+            .map(|v| QsetLintKind::DuplicateMember {
+                member: v.to_string(),
+            }),
+    );
+
+    // an unsatisfiable qset is blocked by anyone, which says nothing more
+    if !is_blocked(qset, None) {
+        out.extend(seen.iter().filter(|v| is_blocked(qset, Some(v))).map(|v| {
+            QsetLintKind::SingleBlockingMember {
+                member: v.to_string(),
+            }
+        }));
+    }
+    out
+}
+
+/// Lints the qsets of `qsm` as written, in validator order. Unlike building
+/// the network, this doesn't stop at qsets nested too deeply, which are
+/// reported as `QsetLintKind::ExcessiveDepth`.
+pub fn lint_quorum_set_map(qsm: &QuorumSetMap) -> Vec<QsetLint> {
+    let mut by_qset: BTreeMap<&InternalScpQuorumSet, Vec<QsetLintKind>> = BTreeMap::new();
+    let mut lints = vec![];
+    for (validator, qset) in qsm.iter() {
+        let kinds = by_qset
+            .entry(qset.as_ref())
+            .or_insert_with(|| lint_qset(qset));
+        let mut in_own_qset = false;
+        let mut stack = vec![qset.as_ref()];
+        while let Some(q) = stack.pop() {
+            in_own_qset |= q.validators.contains(validator);
+            stack.extend(q.inner_sets.iter());
+        }
+        // a validator blocking its own qset only stops itself
+        let others = kinds.iter().filter(|kind| {
+            !matches!(kind, QsetLintKind::SingleBlockingMember { member } if member == validator)
+        });
+        let missing_self = (!in_own_qset).then_some(QsetLintKind::MissingSelf);
+        lints.extend(others.cloned().chain(missing_self).map(|kind| QsetLint {
+            validator: validator.clone(),
+            severity: kind.severity(),
+            kind,
+        }));
+    }
+    lints
+}
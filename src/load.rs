@@ -0,0 +1,102 @@
+use crate::{
+    fbas::{Fbas, FbasError},
+    json_parser::{is_stellar_core_json, node_records_from_json_value},
+    FbasAnalyzer,
+};
+use batsat::Callbacks;
+use serde_json::Value;
+
+// The format is told from the first non-blank byte, then the shape of the
+// document:
+//
+// - `{`: a repro bundle if it has `nodes` and `solver_seed`, the output of
+//   stellar-core's `quorum` or `scp` endpoint if it has `transitive` or
+//   `scp`, otherwise the regular json format.
+// - `[`: the stellarbeats json format.
+// - anything else: the compact text syntax.
+//
+// stellar-core's TOML config may start either way, and is told apart by not
+// parsing as either, and having a table header such as `[QUORUM_SET]`.
+//
+// Formats that can't be read (TOML configs, compressed files) are recognized
+// anyway, to report them as such rather than as a malformed DSL. XDR has no
+// file format of its own to detect: it comes as node and qset buffers, a
+// directory or a stream, see `FbasAnalyzer::from_quorum_set_map_buf` and the
+// constructors following it.
+
+/// The formats `FbasAnalyzer::load` tells apart.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum InputFormat {
+    /// The regular json format, see `FbasAnalyzer::from_json_path`.
+    Json,
+    /// The stellarbeats json format, see `FbasAnalyzer::from_json_path`.
+    Stellarbeats,
+    /// A repro bundle, see `FbasAnalyzer::capture_repro`.
+    ReproBundle,
+    /// The output of stellar-core's `quorum?transitive=true` or `scp` HTTP
+    /// endpoint, see `FbasAnalyzer::from_json_path`.
+    StellarCore,
+    /// The compact text syntax, see `FbasAnalyzer::from_dsl`.
+    Dsl,
+}
+
+const GZIP_MAGIC: &[u8] = &[0x1f, 0x8b];
+const ZSTD_MAGIC: &[u8] = &[0x28, 0xb5, 0x2f, 0xfd];
+
+// Whether `line` is a TOML table header, e.g. `[QUORUM_SET]` or
+// `[[VALIDATORS]]`.
+fn is_toml_header(line: &str) -> bool {
+    let name = line.trim().trim_start_matches('[').trim_end_matches(']');
+    line.trim().ends_with(']')
+        && !name.is_empty()
+        && name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, '_' | '.' | '-'))
+}
+
+impl<Cb: Callbacks> FbasAnalyzer<Cb> {
+    /// Constructs the analyzer from a network in any of the supported
+    /// formats, detected from the content, and returns the format it was
+    /// read as. Meant for front ends taking whatever file they are given.
+    pub fn load(input: &[u8], cb: Cb) -> Result<(Self, InputFormat), FbasError> {
+        if input.starts_with(GZIP_MAGIC) || input.starts_with(ZSTD_MAGIC) {
+            return Err(FbasError::ParseError("Compressed input is not supported"));
+        }
+        let text = std::str::from_utf8(input)
+            .map_err(|_| FbasError::ParseError("Input is neither json nor DSL text"))?;
+        let trimmed = text.trim_start();
+        let is_toml = || text.lines().any(is_toml_header);
+        if !trimmed.starts_with(['{', '[']) {
+            return match Fbas::from_dsl(text) {
+                Ok(fbas) => Ok((Self::from_fbas(fbas, cb)?, InputFormat::Dsl)),
+                Err(_) if is_toml() => Err(FbasError::ParseError("TOML configs are not supported")),
+                Err(e) => Err(e),
+            };
+        }
+        match serde_json::from_str(text) {
+            Ok(doc) => Self::from_json_doc(doc, cb),
+            Err(_) if is_toml() => Err(FbasError::ParseError("TOML configs are not supported")),
+            Err(_) => Err(FbasError::ParseError("fail to parse to json")),
+        }
+    }
+
+    /// Same as `load`, reading the input from `path`.
+    pub fn load_path(path: &str, cb: Cb) -> Result<(Self, InputFormat), FbasError> {
+        let input = std::fs::read(path).map_err(|_| FbasError::IoError("fail to read file"))?;
+        Self::load(&input, cb)
+    }
+
+    fn from_json_doc(doc: Value, cb: Cb) -> Result<(Self, InputFormat), FbasError> {
+        if doc.get("nodes").is_some() && doc.get("solver_seed").is_some() {
+            return Ok((Self::from_repro_json(&doc, cb)?, InputFormat::ReproBundle));
+        }
+        let format = match &doc {
+            Value::Array(_) => InputFormat::Stellarbeats,
+            Value::Object(root) if is_stellar_core_json(root) => InputFormat::StellarCore,
+            _ => InputFormat::Json,
+        };
+        let records = node_records_from_json_value(doc)?;
+        let fbas = Fbas::from_node_records(&records)?;
+        Ok((Self::from_fbas(fbas, cb)?, format))
+    }
+}
@@ -0,0 +1,122 @@
+use crate::fbas::{Fbas, FbasError, QuorumSetMap, Vertex};
+use petgraph::graph::NodeIndex;
+use std::collections::{BTreeSet, VecDeque};
+
+// Whether a validator can be in a quorum only depends on its transitive qset:
+// the validators reachable from it through qsets. Any quorum containing it,
+// restricted to these, is still a quorum. Likewise, whether a set is
+// v-blocking only depends on the qset of `v` itself. Node-local queries thus
+// only need the graph of the transitive qset, and no formula at all.
+
+/// Node-local queries on a network, built from the transitive qset of a
+/// single validator rather than the whole network. The input is still
+/// parsed in full, but the graph only holds the validators the queried one
+/// (transitively) depends on, and nothing is encoded, so single-node checks
+/// stay fast on large crawls.
+#[derive(Debug)]
+pub struct LocalView {
+    fbas: Fbas,
+    node: NodeIndex,
+}
+
+// Restricts `qsm` to the validators reachable from `node` through qsets,
+// including inner sets.
+fn transitive_quorum_set_map(qsm: &QuorumSetMap, node: &str) -> Result<QuorumSetMap, FbasError> {
+    if !qsm.contains_key(node) {
+        return Err(FbasError::ParseError("Unknown validator"));
+    }
+    let mut reachable = QuorumSetMap::new();
+    let mut queue = VecDeque::from([node.to_string()]);
+    while let Some(v) = queue.pop_front() {
+        // validators without a qset stay unknown
+        let Some(qset) = qsm.get(&v) else {
+            continue;
+        };
+        if reachable.insert(v, qset.clone()).is_some() {
+            continue;
+        }
+        let mut stack = vec![qset.as_ref()];
+        while let Some(qset) = stack.pop() {
+            queue.extend(
+                qset.validators
+                    .iter()
+                    .filter(|w| !reachable.contains_key(*w))
+                    .cloned(),
+            );
+            stack.extend(qset.inner_sets.iter());
+        }
+    }
+    Ok(reachable)
+}
+
+impl LocalView {
+    fn new(qsm: &QuorumSetMap, validator: &str) -> Result<Self, FbasError> {
+        let local = transitive_quorum_set_map(qsm, validator)?;
+        let fbas = Fbas::from_quorum_set_map(&local)?;
+        let node = fbas.try_get_validator_index(validator)?;
+        Ok(Self { fbas, node })
+    }
+
+    /// Builds the view of `validator` from the compact text syntax, see
+    /// `FbasAnalyzer::from_dsl`.
+    pub fn from_dsl(dsl: &str, validator: &str) -> Result<Self, FbasError> {
+        Self::new(&crate::dsl::quorum_set_map_from_dsl(dsl)?, validator)
+    }
+
+    #[cfg(any(feature = "json", test))]
+    pub fn from_json_path(path: &str, validator: &str) -> Result<Self, FbasError> {
+        Self::new(
+            &crate::json_parser::quorum_set_map_from_json(path)?,
+            validator,
+        )
+    }
+
+    #[cfg(feature = "xdr")]
+    pub fn from_quorum_set_map_buf<T: AsRef<[u8]>, I: ExactSizeIterator<Item = T>>(
+        nodes: I,
+        quorum_set: I,
+        validator: &str,
+    ) -> Result<Self, FbasError> {
+        let qsm = crate::fbas::quorum_set_map_from_buf(nodes, quorum_set, &mut vec![])?;
+        Self::new(&qsm, validator)
+    }
+
+    /// The number of validators in the transitive qset of the validator,
+    /// including itself.
+    pub fn num_validators(&self) -> usize {
+        self.fbas.validators.len()
+    }
+
+    /// Returns whether the validator is in some quorum, i.e. could
+    /// externalize with every validator it depends on available.
+    pub fn can_externalize(&self) -> bool {
+        self.fbas.max_quorum(&BTreeSet::new()).contains(&self.node)
+    }
+
+    /// Returns whether `validators` are v-blocking for the validator, i.e.
+    /// they intersect every slice of its qset, so that it can't be satisfied
+    /// without them. Unknown validators count as unavailable, as in the
+    /// analysis.
+    pub fn is_v_blocking(&self, validators: &[&str]) -> bool {
+        let blocking: BTreeSet<&str> = validators.iter().copied().collect();
+        self.fbas
+            .graph
+            .neighbors(self.node)
+            .next()
+            .is_some_and(|qi| !self.is_satisfied_without(qi, &blocking))
+    }
+
+    fn is_satisfied_without(&self, qi: NodeIndex, blocking: &BTreeSet<&str>) -> bool {
+        let available = self
+            .fbas
+            .graph
+            .neighbors(qi)
+            .filter(|ni| match &self.fbas.graph[*ni] {
+                Vertex::Validator(v) => !blocking.contains(v.as_str()),
+                Vertex::QSet(_) => self.is_satisfied_without(*ni, blocking),
+                Vertex::Watcher(_) => false,
+            })
+            .count();
+        available >= self.fbas.graph[qi].get_threshold() as usize
+    }
+}
@@ -0,0 +1,124 @@
+use crate::{
+    fbas::{Fbas, FbasError},
+    Callbacks, FbasAnalyzer,
+};
+use std::collections::{BTreeSet, VecDeque};
+
+// The trust graph has an edge from each validator to every validator in its
+// qset, inner sets included, see `Fbas::trust_graph`.
+//
+// Betweenness follows Brandes' algorithm for unweighted directed graphs: a
+// breadth-first search from every validator counts the shortest paths to the
+// others, and the dependencies are accumulated back in reverse order of
+// distance. That is O(validators * edges), fine for networks of hundreds of
+// validators.
+
+/// Importance metrics of one validator, see
+/// `FbasAnalyzer::validator_metrics`.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ValidatorMetrics {
+    pub validator: String,
+    /// The fraction of minimal quorums containing the validator, `None` if
+    /// their enumeration was interrupted by the callbacks.
+    pub minimal_quorum_frequency: Option<f64>,
+    /// The number of other validators having it in their qset.
+    pub trust_in_degree: usize,
+    /// The fraction of shortest paths between two other validators of the
+    /// trust graph passing through the validator, averaged over all such
+    /// pairs connected by a path.
+    pub betweenness: f64,
+}
+
+// The validators in the qset of each validator, by position in
+// `fbas.validators`, see `Fbas::trust_graph`.
+fn trust_graph(fbas: &Fbas) -> Result<Vec<BTreeSet<usize>>, FbasError> {
+    let trust = fbas.trust_graph()?;
+    Ok(trust
+        .node_indices()
+        .map(|ni| trust.neighbors(ni).map(|t| t.index()).collect())
+        .collect())
+}
+
+// Brandes' betweenness, normalized by the number of ordered pairs of other
+// validators connected by a path.
+fn betweenness(edges: &[BTreeSet<usize>]) -> Vec<f64> {
+    let n = edges.len();
+    let mut centrality = vec![0.0; n];
+    let mut connected_pairs = vec![0usize; n];
+    for s in 0..n {
+        let mut order = vec![];
+        let mut predecessors = vec![vec![]; n];
+        let mut paths = vec![0.0; n];
+        let mut distance = vec![usize::MAX; n];
+        paths[s] = 1.0;
+        distance[s] = 0;
+        let mut queue = VecDeque::from([s]);
+        while let Some(v) = queue.pop_front() {
+            order.push(v);
+            for &w in edges[v].iter() {
+                if distance[w] == usize::MAX {
+                    distance[w] = distance[v] + 1;
+                    queue.push_back(w);
+                }
+                if distance[w] == distance[v] + 1 {
+                    paths[w] += paths[v];
+                    predecessors[w].push(v);
+                }
+            }
+        }
+        // for every v other than s, the targets other than v reached from s
+        let reached = order.len() - 1;
+        for (v, pairs) in connected_pairs.iter_mut().enumerate() {
+            if v != s {
+                *pairs += reached - usize::from(distance[v] != usize::MAX);
+            }
+        }
+        let mut dependency = vec![0.0; n];
+        for &w in order.iter().rev() {
+            for &v in predecessors[w].iter() {
+                dependency[v] += paths[v] / paths[w] * (1.0 + dependency[w]);
+            }
+            if w != s {
+                centrality[w] += dependency[w];
+            }
+        }
+    }
+    centrality
+        .iter()
+        .zip(connected_pairs)
+        .map(|(c, pairs)| if pairs == 0 { 0.0 } else { c / pairs as f64 })
+        .collect()
+}
+
+impl<Cb: Callbacks> FbasAnalyzer<Cb> {
+    /// Computes importance metrics for every validator, in validator order,
+    /// to rank them on dashboards: how often it is in a minimal quorum, how
+    /// many validators trust it, and how central it is to the trust graph.
+    /// The minimal quorums are shared with `enumerate_minimal_quorums`, each
+    /// size searched by a separate solver driven by callbacks from `new_cb`.
+    pub fn validator_metrics<C: Callbacks>(
+        &self,
+        new_cb: impl FnMut() -> C,
+    ) -> Result<Vec<ValidatorMetrics>, FbasError> {
+        let fbas = &self.fbas;
+        let complete = self.enumerate_minimal_quorums(usize::MAX, new_cb)?.complete;
+        let quorums = self.minimal_quorums.get().filter(|_| complete);
+        let edges = trust_graph(fbas)?;
+        let betweenness = betweenness(&edges);
+        fbas.validators
+            .iter()
+            .enumerate()
+            .map(|(i, vi)| {
+                Ok(ValidatorMetrics {
+                    validator: fbas.try_get_validator_string(vi)?,
+                    minimal_quorum_frequency: quorums.map(|quorums| {
+                        let containing = quorums.iter().filter(|q| q.contains(vi)).count();
+                        containing as f64 / quorums.len().max(1) as f64
+                    }),
+                    trust_in_degree: edges.iter().filter(|trusted| trusted.contains(&i)).count(),
+                    betweenness: betweenness[i],
+                })
+            })
+            .collect()
+    }
+}
@@ -0,0 +1,122 @@
+use crate::{
+    fbas::FbasError,
+    fbas_analyze::{
+        default_num_threads, encode_qset_relations, enumerate_minimal_sets, CardinalityEncoding,
+        FbasLitsWrapper,
+    },
+    FbasAnalyzer,
+};
+use batsat::{Callbacks, Solver, SolverInterface};
+use petgraph::graph::NodeIndex;
+use std::collections::BTreeSet;
+
+// The validators in quorum A of a model of the qset relations (with quorum A
+// non-empty) form a quorum, and every quorum is the validators of some model.
+// Minimal quorums are thus the minimal sets of validators in quorum A over all
+// models, see `enumerate_minimal_sets`. Quorum B is left unconstrained.
+//
+// A complete enumeration is kept in the analyzer and reused by the analyses
+// deriving from it: the top tier is their union, and a set is blocking iff it
+// intersects all of them. Capped or interrupted enumerations aren't kept.
+
+/// Outcome of `FbasAnalyzer::enumerate_minimal_quorums`.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct MinimalQuorums {
+    /// The minimal quorums found, ordered by size and then by validator
+    /// order.
+    pub quorums: Vec<Vec<String>>,
+    /// Whether `quorums` are all the minimal quorums, i.e. neither the cap
+    /// was reached nor a solver interrupted by its callbacks.
+    pub complete: bool,
+}
+
+impl<Cb: Callbacks> FbasAnalyzer<Cb> {
+    /// Lists the minimal quorums of the network, i.e. the quorums none of
+    /// whose proper subsets is a quorum, stopping at `max_quorums` of them.
+    /// If stopped, the quorums of the largest size found are not necessarily
+    /// the first ones in validator order.
+    /// The network has quorum intersection iff every two of them intersect.
+    /// Each size of quorums is searched by a separate solver driven by
+    /// callbacks from `new_cb`. Once enumerated in full, the minimal quorums
+    /// are remembered, and later calls take the first `max_quorums` of them
+    /// without solving.
+    pub fn enumerate_minimal_quorums<C: Callbacks>(
+        &self,
+        max_quorums: usize,
+        new_cb: impl FnMut() -> C,
+    ) -> Result<MinimalQuorums, FbasError> {
+        let found = match self.minimal_quorums.get() {
+            Some(all) => all,
+            None => {
+                let (found, complete) = self.solve_minimal_quorums(max_quorums, new_cb)?;
+                if !complete {
+                    return Ok(MinimalQuorums {
+                        quorums: self.validator_names(&found)?,
+                        complete,
+                    });
+                }
+                // another thread may have finished first, with the same result
+                self.minimal_quorums.get_or_init(|| found)
+            }
+        };
+        Ok(MinimalQuorums {
+            quorums: self.validator_names(&found[..found.len().min(max_quorums)])?,
+            complete: found.len() <= max_quorums,
+        })
+    }
+
+    /// Returns the top tier of the network, i.e. the validators in some
+    /// minimal quorum, in validator order. These are the validators whose
+    /// qsets matter for quorum intersection and liveness, the others only
+    /// follow them. `None` if a solver was interrupted by its callbacks.
+    /// Shares the minimal quorums with `enumerate_minimal_quorums`.
+    pub fn top_tier<C: Callbacks>(
+        &self,
+        new_cb: impl FnMut() -> C,
+    ) -> Result<Option<Vec<String>>, FbasError> {
+        if self.enumerate_minimal_quorums(usize::MAX, new_cb)?.complete {
+            let quorums = self.minimal_quorums.get().into_iter().flatten();
+            let top_tier: BTreeSet<_> = quorums.flatten().copied().collect();
+            let mut names = self.validator_names(&[top_tier])?;
+            return Ok(names.pop());
+        }
+        Ok(None)
+    }
+
+    // Returns the minimal quorums, up to `max_quorums`, and whether they are
+    // all of them.
+    fn solve_minimal_quorums<C: Callbacks>(
+        &self,
+        max_quorums: usize,
+        mut new_cb: impl FnMut() -> C,
+    ) -> Result<(Vec<BTreeSet<NodeIndex>>, bool), FbasError> {
+        let fbas = &self.fbas;
+        let encode = || {
+            let fbas_lits = FbasLitsWrapper::new(fbas.graph.node_count());
+            let mut solver = Solver::new(Default::default(), new_cb());
+            for _ in 0..2 * fbas.graph.node_count() {
+                solver.new_var_default();
+            }
+            encode_qset_relations(
+                fbas,
+                &mut solver,
+                |_| false,
+                default_num_threads(),
+                CardinalityEncoding::default(),
+            )?;
+            let members: Vec<_> = fbas
+                .validators
+                .iter()
+                .map(|vi| fbas_lits.in_quorum_a(vi))
+                .collect();
+            solver.add_clause_reuse(&mut members.clone());
+            Ok((solver, members))
+        };
+        let (found, complete) = enumerate_minimal_sets(fbas.validators.len(), max_quorums, encode)?;
+        let found = found
+            .iter()
+            .map(|quorum| quorum.iter().map(|i| fbas.validators[*i]).collect())
+            .collect();
+        Ok((found, complete))
+    }
+}
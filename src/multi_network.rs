@@ -0,0 +1,87 @@
+use crate::{AnalysisReport, SolveStatus};
+use std::collections::BTreeMap;
+
+/// Summary of the analyses of several networks, e.g. the private networks of
+/// different tenants, keyed by network name.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct MultiNetworkReport {
+    pub networks: BTreeMap<String, AnalysisReport>,
+}
+
+// Severity of a status for the roll-up: a potential split is worse than not
+// knowing, which is worse than quorum intersection.
+fn severity(status: &SolveStatus) -> u8 {
+    match status {
+        SolveStatus::UNSAT => 0,
+        SolveStatus::UNKNOWN => 1,
+        SolveStatus::SAT(_) => 2,
+    }
+}
+
+impl MultiNetworkReport {
+    pub fn add(&mut self, network: impl Into<String>, report: AnalysisReport) {
+        self.networks.insert(network.into(), report);
+    }
+
+    /// The worst status across all networks, UNSAT if there are none.
+    pub fn worst_status(&self) -> SolveStatus {
+        self.networks
+            .values()
+            .map(|report| &report.status)
+            .max_by_key(|status| severity(status))
+            .cloned()
+            .unwrap_or(SolveStatus::UNSAT)
+    }
+
+    /// The networks for which disjoint quorums were found.
+    pub fn split_networks(&self) -> Vec<&str> {
+        self.networks_with(|status| matches!(status, SolveStatus::SAT(_)))
+    }
+
+    /// The networks whose analysis did not reach a conclusion.
+    pub fn unknown_networks(&self) -> Vec<&str> {
+        self.networks_with(|status| *status == SolveStatus::UNKNOWN)
+    }
+
+    fn networks_with(&self, pred: impl Fn(&SolveStatus) -> bool) -> Vec<&str> {
+        self.networks
+            .iter()
+            .filter(|(_, report)| pred(&report.status))
+            .map(|(network, _)| network.as_str())
+            .collect()
+    }
+
+    /// Total number of warnings across all networks.
+    pub fn num_warnings(&self) -> usize {
+        self.networks
+            .values()
+            .map(|report| report.warnings.len())
+            .sum()
+    }
+}
+
+impl std::fmt::Display for MultiNetworkReport {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let verdict = |status: &SolveStatus| match status {
+            SolveStatus::SAT(_) => "SPLIT",
+            SolveStatus::UNSAT => "OK",
+            SolveStatus::UNKNOWN => "UNKNOWN",
+        };
+        writeln!(
+            f,
+            "{} networks, overall {}",
+            self.networks.len(),
+            verdict(&self.worst_status())
+        )?;
+        for (network, report) in &self.networks {
+            writeln!(
+                f,
+                "  {}: {} ({} warnings)",
+                network,
+                verdict(&report.status),
+                report.warnings.len()
+            )?;
+        }
+        Ok(())
+    }
+}
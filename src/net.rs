@@ -0,0 +1,102 @@
+use crate::{
+    fbas::{Fbas, FbasError},
+    json_parser::nodes_from_api_response,
+    Callbacks, FbasAnalyzer,
+};
+use std::time::Duration;
+
+// The node list is fetched, and reports posted to webhooks, with a blocking
+// HTTP client, TLS included, so that the rest of the crate stays free of
+// network dependencies unless `net` is enabled. Transient failures (connection
+// errors, timeouts, 429 and 5xx responses) are retried with exponential
+// backoff; anything else, including a response in an unknown schema, fails
+// right away since retrying wouldn't help.
+
+/// How many times a request is attempted before giving up.
+const MAX_ATTEMPTS: u32 = 4;
+/// The wait before the first retry, doubled after each.
+const INITIAL_BACKOFF: Duration = Duration::from_millis(500);
+/// The time allowed for one attempt, from connecting to reading the body.
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(60);
+/// The largest response accepted. The full node list of the public network
+/// is a few megabytes.
+const MAX_RESPONSE_SIZE: u64 = 128 * 1024 * 1024;
+
+fn is_transient(error: &ureq::Error) -> bool {
+    match error {
+        ureq::Error::StatusCode(status) => *status == 429 || *status >= 500,
+        ureq::Error::Io(_)
+        | ureq::Error::Timeout(_)
+        | ureq::Error::HostNotFound
+        | ureq::Error::ConnectionFailed => true,
+        _ => false,
+    }
+}
+
+// Sends a request with `send` until it succeeds, retrying transient
+// failures, and returns the body of the response.
+fn with_retries(
+    send: impl Fn(&ureq::Agent) -> Result<ureq::http::Response<ureq::Body>, ureq::Error>,
+) -> Result<String, FbasError> {
+    let agent: ureq::Agent = ureq::Agent::config_builder()
+        .timeout_global(Some(REQUEST_TIMEOUT))
+        .build()
+        .into();
+    let mut backoff = INITIAL_BACKOFF;
+    let mut attempt = 1;
+    loop {
+        let result = send(&agent).and_then(|mut response| {
+            response
+                .body_mut()
+                .with_config()
+                .limit(MAX_RESPONSE_SIZE)
+                .read_to_string()
+        });
+        match result {
+            Ok(body) => return Ok(body),
+            Err(e) if attempt < MAX_ATTEMPTS && is_transient(&e) => {
+                std::thread::sleep(backoff);
+                backoff *= 2;
+                attempt += 1;
+            }
+            Err(ureq::Error::StatusCode(_)) => {
+                return Err(FbasError::IoError("HTTP request rejected"))
+            }
+            Err(_) => return Err(FbasError::IoError("HTTP request failed")),
+        }
+    }
+}
+
+// Fetches `url` as text, retrying transient failures.
+fn fetch(url: &str) -> Result<String, FbasError> {
+    with_retries(|agent| agent.get(url).call())
+}
+
+// Posts `body` as JSON to `url`, retrying transient failures. The response
+// body is ignored.
+pub(crate) fn post_json(url: &str, body: &str) -> Result<(), FbasError> {
+    with_retries(|agent| agent.post(url).content_type("application/json").send(body)).map(|_| ())
+}
+
+impl Fbas {
+    /// Builds the `Fbas` from the current node list of a stellarbeats-like
+    /// API, e.g. `https://api.stellaratlas.io/v1/node`, with node metadata
+    /// and attributes as for a saved snapshot (see `from_json_path`).
+    /// Transient failures are retried a few times with backoff. Both the
+    /// bare node list and the newer responses wrapping it in an object are
+    /// accepted; other schemas are rejected with a parse error.
+    pub fn from_stellarbeats_api(url: &str) -> Result<Self, FbasError> {
+        let body = fetch(url)?;
+        let doc: serde_json::Value = serde_json::from_str(&body)
+            .map_err(|_| FbasError::ParseError("fail to parse to json"))?;
+        Self::from_node_records(&nodes_from_api_response(doc)?)
+    }
+}
+
+impl<Cb: Callbacks> FbasAnalyzer<Cb> {
+    /// Constructs the analyzer from the current node list of a
+    /// stellarbeats-like API, see `Fbas::from_stellarbeats_api`.
+    pub fn from_stellarbeats_api(url: &str, cb: Cb) -> Result<Self, FbasError> {
+        Self::from_fbas(Fbas::from_stellarbeats_api(url)?, cb)
+    }
+}
@@ -0,0 +1,82 @@
+use crate::{AnalysisReport, MultiNetworkReport, Organizations, TrustReport};
+use std::{cmp::Ordering, collections::BTreeMap};
+
+/// How validator lists in results are ordered, so that related validators are
+/// listed together. Apply it with e.g. `AnalysisReport::sort_validators`.
+#[derive(Debug, Clone, Copy)]
+pub enum ValidatorOrder<'a> {
+    /// By public key (or whatever identifies validators in the input).
+    Key,
+    /// By display name from the given aliases, then by key. Validators
+    /// without an alias go by their key.
+    Name(&'a BTreeMap<String, String>),
+    /// Grouped by organization, ordered by organization name then key.
+    /// Validators without an organization come last.
+    Org(&'a Organizations),
+}
+
+impl ValidatorOrder<'_> {
+    pub fn compare(&self, a: &str, b: &str) -> Ordering {
+        match self {
+            ValidatorOrder::Key => a.cmp(b),
+            ValidatorOrder::Name(aliases) => {
+                let name = |v: &str| aliases.get(v).map_or(v, |alias| alias.as_str()).to_string();
+                name(a).cmp(&name(b)).then_with(|| a.cmp(b))
+            }
+            ValidatorOrder::Org(orgs) => {
+                // `None` sorts first, so compare the negation to put it last
+                let org = |v: &str| (orgs.org_of(v).is_none(), orgs.org_of(v));
+                org(a).cmp(&org(b)).then_with(|| a.cmp(b))
+            }
+        }
+    }
+
+    pub fn sort(&self, validators: &mut [String]) {
+        validators.sort_by(|a, b| self.compare(a, b));
+    }
+
+    /// Sorts `validators` and splits them into runs of the same organization
+    /// (`None` for validators without one). For orders other than `Org`,
+    /// everything is a single run.
+    pub fn group(&self, validators: &[String]) -> Vec<(Option<String>, Vec<String>)> {
+        let mut sorted = validators.to_vec();
+        self.sort(&mut sorted);
+        let ValidatorOrder::Org(orgs) = self else {
+            return vec![(None, sorted)];
+        };
+        let mut groups: Vec<(Option<String>, Vec<String>)> = vec![];
+        for v in sorted {
+            let org = orgs.org_of(&v).map(str::to_string);
+            match groups.last_mut() {
+                Some((last, members)) if *last == org => members.push(v),
+                _ => groups.push((org, vec![v])),
+            }
+        }
+        groups
+    }
+}
+
+impl AnalysisReport {
+    /// Orders the validators of the potential split.
+    pub fn sort_validators(&mut self, order: ValidatorOrder) {
+        order.sort(&mut self.potential_split.0);
+        order.sort(&mut self.potential_split.1);
+    }
+}
+
+impl TrustReport {
+    /// Orders the asymmetric relations by truster, then by trusted validator.
+    pub fn sort_validators(&mut self, order: ValidatorOrder) {
+        self.asymmetric
+            .sort_by(|(a, b), (c, d)| order.compare(a, c).then_with(|| order.compare(b, d)));
+    }
+}
+
+impl MultiNetworkReport {
+    /// Orders the validators in the report of every network.
+    pub fn sort_validators(&mut self, order: ValidatorOrder) {
+        for report in self.networks.values_mut() {
+            report.sort_validators(order);
+        }
+    }
+}
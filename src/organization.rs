@@ -0,0 +1,201 @@
+use crate::fbas::{FbasError, InternalScpQuorumSet, QuorumSetMap};
+use std::{collections::BTreeMap, sync::Arc};
+
+/// Mapping of organizations to the validators they run.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Organizations {
+    orgs: BTreeMap<String, Vec<String>>,
+    validator_orgs: BTreeMap<String, String>,
+}
+
+impl Organizations {
+    pub fn new(orgs: BTreeMap<String, Vec<String>>) -> Self {
+        let validator_orgs = orgs
+            .iter()
+            .flat_map(|(org, validators)| validators.iter().map(|v| (v.clone(), org.clone())))
+            .collect();
+        Self {
+            orgs,
+            validator_orgs,
+        }
+    }
+
+    /// Groups the validators of a network in the stellarbeats format (see
+    /// `FbasAnalyzer::from_json_path`) by their `homeDomain`, the usual proxy
+    /// for the organization running them. Validators without one belong to no
+    /// organization.
+    #[cfg(any(feature = "json", test))]
+    pub fn from_home_domains_json(path: &str) -> Result<Self, FbasError> {
+        crate::json_parser::organizations_from_home_domains(path)
+    }
+
+    pub fn org_of(&self, validator: &str) -> Option<&str> {
+        self.validator_orgs.get(validator).map(|org| org.as_str())
+    }
+
+    pub fn validators_of(&self, org: &str) -> Option<&[String]> {
+        self.orgs.get(org).map(|validators| validators.as_slice())
+    }
+
+    pub fn orgs(&self) -> impl Iterator<Item = &str> {
+        self.orgs.keys().map(|org| org.as_str())
+    }
+
+    // Adds a single-validator organization, named after its validator, for
+    // each of `validators` without an organization. Names already taken by
+    // an organization are skipped.
+    pub(crate) fn with_singletons<'a>(&self, validators: impl Iterator<Item = &'a String>) -> Self {
+        let mut orgs = self.orgs.clone();
+        for v in validators {
+            if self.org_of(v).is_none() {
+                orgs.entry(v.clone()).or_insert_with(|| vec![v.clone()]);
+            }
+        }
+        Self::new(orgs)
+    }
+
+    /// Groups validators by the `home_domain` of their metadata, as
+    /// `from_home_domains_json` does. Validators without one belong to no
+    /// organization.
+    pub fn from_metadata(metadata: &BTreeMap<String, NodeMetadata>) -> Self {
+        let mut orgs: BTreeMap<String, Vec<String>> = BTreeMap::new();
+        for (validator, meta) in metadata {
+            if let Some(domain) = &meta.home_domain {
+                orgs.entry(domain.clone())
+                    .or_default()
+                    .push(validator.clone());
+            }
+        }
+        Self::new(orgs)
+    }
+}
+
+/// What the stellarbeats format tells about a node besides its qset, see
+/// `FbasAnalyzer::node_metadata`.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct NodeMetadata {
+    /// The domain the node's operator publishes its `stellar.toml` on, the
+    /// usual proxy for its organization.
+    pub home_domain: Option<String>,
+    /// The stellarbeats identifier of the node's organization.
+    pub organization_id: Option<String>,
+    /// The display name of the node.
+    pub name: Option<String>,
+}
+
+/// A quorum set over organizations instead of validators. An organization is
+/// referenced whenever the validator-level qset contains an inner set made up
+/// solely of that organization's validators, whatever its threshold is: at
+/// the organization level, the organization either takes part in a quorum as
+/// a whole or it doesn't.
+#[derive(Debug, Clone, Default, PartialEq, Eq, PartialOrd, Ord)]
+pub struct OrgQuorumSet {
+    pub threshold: u32,
+    pub orgs: Vec<String>,
+    pub inner_sets: Vec<OrgQuorumSet>,
+}
+
+impl OrgQuorumSet {
+    // Returns the organization `qset` stands for, if it is a flat set of
+    // validators all belonging to the same organization.
+    fn as_single_org<'a>(qset: &InternalScpQuorumSet, orgs: &'a Organizations) -> Option<&'a str> {
+        if !qset.inner_sets.is_empty() {
+            return None;
+        }
+        let mut validator_orgs = qset.validators.iter().map(|v| orgs.org_of(v));
+        let first = validator_orgs.next()??;
+        validator_orgs
+            .all(|org| org == Some(first))
+            .then_some(first)
+    }
+
+    /// Converts a validator-level qset to the organization level. Validators
+    /// listed directly (rather than through their organization's inner set)
+    /// are only accepted for single-validator organizations.
+    pub(crate) fn from_validator_qset(
+        qset: &InternalScpQuorumSet,
+        orgs: &Organizations,
+    ) -> Result<Self, FbasError> {
+        if let Some(org) = Self::as_single_org(qset, orgs) {
+            return Ok(OrgQuorumSet {
+                threshold: 1,
+                orgs: vec![org.to_string()],
+                inner_sets: vec![],
+            });
+        }
+
+        let mut org_qset = OrgQuorumSet {
+            threshold: qset.threshold,
+            ..Default::default()
+        };
+        for v in qset.validators.iter() {
+            match orgs.org_of(v) {
+                Some(org) if orgs.validators_of(org).is_some_and(|vs| vs.len() == 1) => {
+                    org_qset.orgs.push(org.to_string())
+                }
+                _ => {
+                    return Err(FbasError::ParseError(
+                        "validator does not map to a single-validator organization",
+                    ))
+                }
+            }
+        }
+        for inner in qset.inner_sets.iter() {
+            match Self::as_single_org(inner, orgs) {
+                Some(org) => org_qset.orgs.push(org.to_string()),
+                None => org_qset
+                    .inner_sets
+                    .push(Self::from_validator_qset(inner, orgs)?),
+            }
+        }
+        Ok(org_qset)
+    }
+}
+
+impl From<&OrgQuorumSet> for InternalScpQuorumSet {
+    fn from(qset: &OrgQuorumSet) -> Self {
+        InternalScpQuorumSet {
+            threshold: qset.threshold,
+            threshold_percent: None,
+            validators: qset.orgs.clone(),
+            inner_sets: qset.inner_sets.iter().map(|qs| qs.into()).collect(),
+        }
+    }
+}
+
+// Builds the organization-level quorum set map, in which every organization is
+// a node with the organization-level qset of its validators. All validators of
+// an organization must agree on that qset, validators without an organization
+// are left out and reported in `diagnostics`.
+pub(crate) fn org_quorum_set_map(
+    qsm: &QuorumSetMap,
+    orgs: &Organizations,
+    diagnostics: &mut Vec<String>,
+) -> Result<QuorumSetMap, FbasError> {
+    let mut org_qsets: BTreeMap<&str, OrgQuorumSet> = BTreeMap::new();
+    for (validator, qset) in qsm.iter() {
+        let Some(org) = orgs.org_of(validator) else {
+            diagnostics.push(format!(
+                "Validator {} does not belong to any organization",
+                validator
+            ));
+            continue;
+        };
+        let org_qset = OrgQuorumSet::from_validator_qset(qset, orgs)?;
+        match org_qsets.get(org) {
+            Some(existing) if *existing != org_qset => {
+                return Err(FbasError::ParseError(
+                    "validators of an organization declare different quorum sets",
+                ))
+            }
+            Some(_) => {}
+            None => {
+                org_qsets.insert(org, org_qset);
+            }
+        }
+    }
+    Ok(org_qsets
+        .into_iter()
+        .map(|(org, qset)| (org.to_string(), Arc::new((&qset).into())))
+        .collect())
+}
@@ -0,0 +1,108 @@
+use crate::{fbas::FbasError, AnalysisReport, Deadline, FbasAnalyzer};
+use std::{
+    collections::VecDeque,
+    sync::{Condvar, Mutex, MutexGuard},
+    time::Instant,
+};
+
+// Requests wait in a FIFO queue for one of the slots. A waiter whose deadline
+// passes leaves the queue, so the queue only ever holds requests that can
+// still be served. The analyzer of a request is built and solved within its
+// slot and consumed into a report, so its memory is released before the slot
+// is handed to the next request.
+
+#[derive(Debug, Default)]
+struct PoolState {
+    running: usize,
+    next_ticket: u64,
+    queue: VecDeque<u64>,
+}
+
+/// Runs analyses for concurrent requests, e.g. of a server, with at most a
+/// fixed number of them solving at once. Each request has a deadline
+/// covering both its time in the queue and its analysis.
+#[derive(Debug)]
+pub struct AnalyzerPool {
+    max_concurrent: usize,
+    state: Mutex<PoolState>,
+    changed: Condvar,
+}
+
+// Frees the slot of a request, even if its analysis panics.
+struct Slot<'a>(&'a AnalyzerPool);
+
+impl Drop for Slot<'_> {
+    fn drop(&mut self) {
+        self.0.lock().running -= 1;
+        self.0.changed.notify_all();
+    }
+}
+
+impl AnalyzerPool {
+    pub fn new(max_concurrent: usize) -> Self {
+        Self {
+            max_concurrent: max_concurrent.max(1),
+            state: Default::default(),
+            changed: Condvar::new(),
+        }
+    }
+
+    fn lock(&self) -> MutexGuard<'_, PoolState> {
+        self.state.lock().expect("analyzer pool poisoned")
+    }
+
+    /// Number of requests currently being analyzed.
+    pub fn running(&self) -> usize {
+        self.lock().running
+    }
+
+    /// Number of requests waiting for a slot.
+    pub fn queued(&self) -> usize {
+        self.lock().queue.len()
+    }
+
+    // Waits for a slot until `deadline`, in order of arrival.
+    fn acquire(&self, deadline: Deadline) -> Option<Slot<'_>> {
+        let mut state = self.lock();
+        let ticket = state.next_ticket;
+        state.next_ticket += 1;
+        state.queue.push_back(ticket);
+        loop {
+            if state.running < self.max_concurrent && state.queue.front() == Some(&ticket) {
+                state.queue.pop_front();
+                state.running += 1;
+                // the next in line may fit as well
+                self.changed.notify_all();
+                return Some(Slot(self));
+            }
+            let timeout = deadline.instant().saturating_duration_since(Instant::now());
+            if timeout.is_zero() {
+                state.queue.retain(|t| *t != ticket);
+                self.changed.notify_all();
+                return None;
+            }
+            state = self
+                .changed
+                .wait_timeout(state, timeout)
+                .expect("analyzer pool poisoned")
+                .0;
+        }
+    }
+
+    /// Analyzes the network built by `build`, once a slot is free and within
+    /// `deadline`. `build` gets the deadline to pass as the analyzer's
+    /// callbacks. If the deadline passes while queued, the network isn't
+    /// built and the report is UNKNOWN with `SolvePhase::NotSolved`.
+    pub fn analyze(
+        &self,
+        deadline: Deadline,
+        build: impl FnOnce(Deadline) -> Result<FbasAnalyzer<Deadline>, FbasError>,
+    ) -> Result<AnalysisReport, FbasError> {
+        let Some(_slot) = self.acquire(deadline) else {
+            return Ok(AnalysisReport::default());
+        };
+        let mut analyzer = build(deadline)?;
+        analyzer.try_solve()?;
+        analyzer.into_report(None)
+    }
+}
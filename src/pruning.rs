@@ -0,0 +1,63 @@
+use crate::fbas::{Fbas, Vertex};
+use petgraph::{algo::tarjan_scc, graph::NodeIndex};
+use std::collections::BTreeSet;
+
+// Pruning runs once before encoding. Pruned vertices keep their variables but
+// their qset relation is left out and they are fixed out of both quorums, so
+// a model of the reduced formula is directly a pair of quorums of the
+// original graph: witnesses need no translation, whatever the strategy.
+// What a strategy must get right is not to prune a vertex needed by every
+// pair of disjoint quorums, or a split is missed.
+
+/// A reduction of the network applied before encoding it, see
+/// `FbasAnalyzer::from_fbas_with_pruning`.
+pub trait PruningStrategy {
+    /// Returns the vertices (validators or qsets) to leave out of both
+    /// quorums. If the network has two disjoint quorums, it must still have
+    /// two disjoint quorums without the returned vertices.
+    fn prune(&self, fbas: &Fbas) -> BTreeSet<NodeIndex>;
+}
+
+/// The pruning used by every constructor of `FbasAnalyzer`:
+/// - Vertices that can't be satisfied even with every validator available are
+///   in no quorum.
+/// - Every minimal quorum lies within a strongly connected component of the
+///   graph. If a single component contains a quorum, any two disjoint quorums
+///   contain disjoint minimal quorums within it, so only the vertices that
+///   can be satisfied by its validators are kept. If several do, the network
+///   is split anyway and nothing more is pruned.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct DefaultPruning;
+
+// Returns the validators of the main strongly connected component, the only
+// one containing a quorum, or `None` if none or several do.
+pub(crate) fn main_component(fbas: &Fbas) -> Option<BTreeSet<NodeIndex>> {
+    let is_validator = |ni: &NodeIndex| matches!(fbas.graph[*ni], Vertex::Validator(_));
+    let alive = fbas.alive_vertices(&BTreeSet::new());
+    let mut with_quorum = tarjan_scc(&fbas.graph).into_iter().filter_map(|scc| {
+        if !scc.iter().any(|ni| is_validator(ni) && alive.contains(ni)) {
+            return None;
+        }
+        let validators: BTreeSet<NodeIndex> = scc.into_iter().filter(is_validator).collect();
+        let outside = fbas.validators_outside(&validators);
+        let has_quorum = !fbas.max_quorum(&outside).is_empty();
+        has_quorum.then_some(validators)
+    });
+    match (with_quorum.next(), with_quorum.next()) {
+        (Some(validators), None) => Some(validators),
+        _ => None,
+    }
+}
+
+impl PruningStrategy for DefaultPruning {
+    fn prune(&self, fbas: &Fbas) -> BTreeSet<NodeIndex> {
+        let kept = match main_component(fbas) {
+            Some(validators) => fbas.alive_vertices(&fbas.validators_outside(&validators)),
+            None => fbas.alive_vertices(&BTreeSet::new()),
+        };
+        fbas.graph
+            .node_indices()
+            .filter(|ni| !kept.contains(ni))
+            .collect()
+    }
+}
@@ -0,0 +1,137 @@
+use crate::fbas::FbasError;
+use std::collections::{BTreeMap, BTreeSet};
+use stellar_xdr::curr::{Hash, NodeId, ScpEnvelope, ScpQuorumSet, ScpStatementPledges};
+
+// Experimental: infers the qsets of validators whose configuration isn't
+// crawled from the SCP envelopes they send. Envelopes only carry the hash of
+// the sender's qset, so there are two sources of evidence:
+//
+// - A validator advertising the same qset hash as a validator whose qset is
+//   known has that same qset. This is exact.
+// - Otherwise, a validator only externalizes a slot once a slice of it has
+//   confirmed. The validators it heard from in a slot before externalizing
+//   therefore contain one of its slices. The validators present in most such
+//   observations are taken as the members of a flat qset, with the smallest
+//   number of them ever present as threshold. This is a heuristic, whose
+//   confidence grows with the number of slots observed.
+
+// Number of observed slots at which the heuristic's confidence reaches 0.5.
+const HALF_CONFIDENCE_SLOTS: f64 = 10.0;
+
+/// How a qset was inferred, see `infer_quorum_sets`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum InferenceMethod {
+    /// The validator advertised the same qset hash as `node`, whose qset is
+    /// known.
+    SharedHash { node: NodeId },
+    /// From the validators heard from before externalizing, over `slots`
+    /// slots.
+    ObservedVotes { slots: usize },
+}
+
+/// A best-effort qset of a validator, with a confidence between 0 and 1.
+#[derive(Debug, Clone, PartialEq)]
+pub struct InferredQset {
+    pub node: NodeId,
+    pub qset: ScpQuorumSet,
+    pub confidence: f64,
+    pub method: InferenceMethod,
+}
+
+fn qset_hash(pledges: &ScpStatementPledges) -> &Hash {
+    match pledges {
+        ScpStatementPledges::Prepare(p) => &p.quorum_set_hash,
+        ScpStatementPledges::Confirm(c) => &c.quorum_set_hash,
+        ScpStatementPledges::Externalize(e) => &e.commit_quorum_set_hash,
+        ScpStatementPledges::Nominate(n) => &n.quorum_set_hash,
+    }
+}
+
+/// **Experimental.** Infers the qsets of the validators sending `envelopes`
+/// (in the order they were observed) that are not in `known`. Validators
+/// without enough evidence are left out. The result is sorted by validator
+/// and can be included in an analysis along with the known qsets, e.g. with
+/// `FbasAnalyzer::from_quorum_set_map_buf`.
+pub fn infer_quorum_sets(
+    envelopes: &[ScpEnvelope],
+    known: &[(NodeId, ScpQuorumSet)],
+) -> Result<Vec<InferredQset>, FbasError> {
+    let known: BTreeMap<&NodeId, &ScpQuorumSet> = known.iter().map(|(n, q)| (n, q)).collect();
+    let mut hashes: BTreeMap<&NodeId, &Hash> = BTreeMap::new();
+    // validators heard from so far, per slot
+    let mut heard: BTreeMap<u64, BTreeSet<&NodeId>> = BTreeMap::new();
+    let mut observations: BTreeMap<&NodeId, BTreeMap<u64, BTreeSet<&NodeId>>> = BTreeMap::new();
+    for envelope in envelopes {
+        let statement = &envelope.statement;
+        let node = &statement.node_id;
+        hashes.insert(node, qset_hash(&statement.pledges));
+        let slot_heard = heard.entry(statement.slot_index).or_default();
+        slot_heard.insert(node);
+        if matches!(statement.pledges, ScpStatementPledges::Externalize(_)) {
+            observations
+                .entry(node)
+                .or_default()
+                .entry(statement.slot_index)
+                .or_insert_with(|| slot_heard.clone());
+        }
+    }
+
+    let mut by_hash: BTreeMap<&Hash, &NodeId> = BTreeMap::new();
+    for (node, hash) in &hashes {
+        if known.contains_key(node) {
+            by_hash.entry(hash).or_insert(node);
+        }
+    }
+
+    let mut inferred = vec![];
+    for (node, hash) in &hashes {
+        if known.contains_key(node) {
+            continue;
+        }
+        if let Some(source) = by_hash.get(hash) {
+            inferred.push(InferredQset {
+                node: (*node).clone(),
+                qset: known[source].clone(),
+                confidence: 1.0,
+                method: InferenceMethod::SharedHash {
+                    node: (*source).clone(),
+                },
+            });
+            continue;
+        }
+        let Some(slots) = observations.get(node) else {
+            continue;
+        };
+        let mut presence: BTreeMap<&NodeId, usize> = BTreeMap::new();
+        for validator in slots.values().flatten() {
+            *presence.entry(validator).or_default() += 1;
+        }
+        let members: BTreeSet<&NodeId> = presence
+            .into_iter()
+            .filter(|(_, count)| 2 * count > slots.len())
+            .map(|(validator, _)| validator)
+            .collect();
+        let threshold = slots
+            .values()
+            .map(|heard| heard.intersection(&members).count())
+            .min()
+            .unwrap_or_default()
+            .max(1);
+        inferred.push(InferredQset {
+            node: (*node).clone(),
+            qset: ScpQuorumSet {
+                threshold: threshold as u32,
+                validators: members
+                    .into_iter()
+                    .cloned()
+                    .collect::<Vec<_>>()
+                    .try_into()
+                    .map_err(|_| FbasError::InternalError("Too many validators in qset"))?,
+                inner_sets: Default::default(),
+            },
+            confidence: slots.len() as f64 / (slots.len() as f64 + HALF_CONFIDENCE_SLOTS),
+            method: InferenceMethod::ObservedVotes { slots: slots.len() },
+        });
+    }
+    Ok(inferred)
+}
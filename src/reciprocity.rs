@@ -0,0 +1,82 @@
+use crate::{fbas::FbasError, organization::Organizations, FbasAnalyzer};
+use batsat::Callbacks;
+use std::collections::{BTreeMap, BTreeSet};
+
+/// Summary of how mutual the trust relationships of a network are. `a` trusts
+/// `b` if `b` appears anywhere in `a`'s qset (including inner sets); the
+/// relationship is reciprocated if `b` also trusts `a`.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct TrustReport {
+    /// Number of (truster, trusted) pairs.
+    pub num_trust_relations: usize,
+    /// Number of pairs whose trust is returned.
+    pub num_reciprocated: usize,
+    /// The (truster, trusted) pairs whose trust is not returned.
+    pub asymmetric: Vec<(String, String)>,
+}
+
+impl TrustReport {
+    fn from_trust(trust: &BTreeMap<String, BTreeSet<String>>) -> Self {
+        let mut report = TrustReport::default();
+        for (truster, trusted) in trust {
+            for t in trusted {
+                report.num_trust_relations += 1;
+                if trust.get(t).is_some_and(|back| back.contains(truster)) {
+                    report.num_reciprocated += 1;
+                } else {
+                    report.asymmetric.push((truster.clone(), t.clone()));
+                }
+            }
+        }
+        report
+    }
+
+    /// Fraction of the trust relations that are reciprocated. A network
+    /// without any trust relation is considered fully reciprocal.
+    pub fn reciprocity(&self) -> f64 {
+        if self.num_trust_relations == 0 {
+            return 1.0;
+        }
+        self.num_reciprocated as f64 / self.num_trust_relations as f64
+    }
+}
+
+impl<Cb: Callbacks> FbasAnalyzer<Cb> {
+    fn validator_trust(&self) -> Result<BTreeMap<String, BTreeSet<String>>, FbasError> {
+        let fbas = &self.fbas;
+        fbas.validators
+            .iter()
+            .map(|vi| {
+                let trusted = fbas
+                    .trusted_validators(*vi)
+                    .iter()
+                    .map(|ni| fbas.try_get_validator_string(ni))
+                    .collect::<Result<_, _>>()?;
+                Ok((fbas.try_get_validator_string(vi)?, trusted))
+            })
+            .collect()
+    }
+
+    /// Lists the trust relationships between validators that are not mutual.
+    pub fn trust_reciprocity(&self) -> Result<TrustReport, FbasError> {
+        Ok(TrustReport::from_trust(&self.validator_trust()?))
+    }
+
+    /// Same as `trust_reciprocity`, with the validators grouped into
+    /// organizations: an organization trusts another if any of its validators
+    /// trusts any of the other's. Validators without an organization stand
+    /// for themselves, and trust within an organization is ignored.
+    pub fn trust_reciprocity_org_level(
+        &self,
+        orgs: &Organizations,
+    ) -> Result<TrustReport, FbasError> {
+        let org_of = |v: &String| orgs.org_of(v).map_or(v.clone(), |org| org.to_string());
+        let mut org_trust: BTreeMap<String, BTreeSet<String>> = BTreeMap::new();
+        for (truster, trusted) in self.validator_trust()? {
+            let truster = org_of(&truster);
+            let entry = org_trust.entry(truster.clone()).or_default();
+            entry.extend(trusted.iter().map(org_of).filter(|org| *org != truster));
+        }
+        Ok(TrustReport::from_trust(&org_trust))
+    }
+}
@@ -0,0 +1,124 @@
+use crate::{
+    fbas::FbasError,
+    fbas_analyze::{
+        add_at_most_clauses, add_threshold_clauses, encode_fbas, FbasLitsWrapper, SliceMemo,
+    },
+    FbasAnalyzer,
+};
+use batsat::{interface::SolveResult, lbool, theory, Callbacks, Lit, Solver, SolverInterface};
+
+// Red-team analysis models a sybil-insertion attack: `k` new attacker-controlled
+// validators are inserted into the (top-level) qsets of up to `m` existing
+// validators. Attackers are byzantine and declare whatever qsets suit them, so
+// they can be assumed to be in both quorums at once. Inserting them into the
+// qset of validator `v` with threshold `t` therefore means `v` only needs `t -
+// k` of its original members to be in a quorum.
+//
+// The formula is the regular encoding, except for the qset relation of each
+// validator `v`, which is replaced by
+//
+//     v -> q_v OR r_v,    r_v -> s_v,    r_v -> (t - k) out of members(q_v)
+//
+// where `q_v` is the original qset vertex of `v`, `s_v` is a selector for the
+// attackers being inserted into `v`'s qset, and `r_v` is the relaxed qset
+// being satisfied. At most `m` selectors may be true. Disjointness and
+// non-emptiness only concern honest validators, since the attackers are not
+// part of the graph.
+
+/// Outcome of `FbasAnalyzer::red_team`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RedTeamResult {
+    /// No injection within the given budget can split the honest validators.
+    Safe,
+    /// Two disjoint quorums of honest validators, made possible by inserting
+    /// the attackers into the qsets of the `injected_into` validators.
+    Split {
+        quorum_a: Vec<String>,
+        quorum_b: Vec<String>,
+        injected_into: Vec<String>,
+    },
+    /// The solver was interrupted before reaching a conclusion.
+    Unknown,
+}
+
+impl<Cb: Callbacks> FbasAnalyzer<Cb> {
+    /// Determines whether `attackers` new validators, each declaring an
+    /// arbitrary qset and appearing in the top-level qsets of up to
+    /// `max_injections` existing validators, could cause a split among the
+    /// honest validators. The analysis builds its own formula, solved with a
+    /// separate solver driven by `cb`.
+    pub fn red_team<C: Callbacks>(
+        &self,
+        attackers: u32,
+        max_injections: u32,
+        cb: C,
+    ) -> Result<RedTeamResult, FbasError> {
+        let fbas = &self.fbas;
+        let fbas_lits = FbasLitsWrapper::new(fbas.graph.node_count());
+        let mut solver = Solver::new(Default::default(), cb);
+        encode_fbas(fbas, &mut solver, |ni| fbas.validators.contains(ni))?;
+
+        let mut memo = SliceMemo::default();
+        let mut selectors = vec![];
+        for vi in fbas.validators.iter() {
+            let qi = fbas
+                .graph
+                .neighbors(*vi)
+                .next()
+                .ok_or(FbasError::InternalError("Validator has no qset"))?;
+            let threshold = fbas
+                .graph
+                .node_weight(qi)
+                .ok_or(FbasError::InternalError("Node index not found"))?
+                .get_threshold();
+            let s_v = Lit::new(solver.new_var_default(), true);
+            for in_quorum in [FbasLitsWrapper::in_quorum_a, FbasLitsWrapper::in_quorum_b] {
+                let v = in_quorum(&fbas_lits, vi);
+                let r_v = Lit::new(solver.new_var_default(), true);
+                solver.add_clause_reuse(&mut vec![!v, in_quorum(&fbas_lits, &qi), r_v]);
+                solver.add_clause_reuse(&mut vec![!r_v, s_v]);
+                let elems: Vec<Lit> = fbas
+                    .graph
+                    .neighbors(qi)
+                    .map(|ni| in_quorum(&fbas_lits, &ni))
+                    .collect();
+                add_threshold_clauses(
+                    &mut solver,
+                    r_v,
+                    threshold.saturating_sub(attackers),
+                    &elems,
+                    &mut memo,
+                );
+            }
+            selectors.push(s_v);
+        }
+        add_at_most_clauses(&mut solver, &selectors, max_injections as usize);
+
+        let mut th = theory::EmptyTheory::new();
+        let model = match solver.solve_limited_th_full(&mut th, &[]) {
+            SolveResult::Sat(model) => model,
+            SolveResult::Unsat(_) => return Ok(RedTeamResult::Safe),
+            SolveResult::Unknown(_) => return Ok(RedTeamResult::Unknown),
+        };
+        let mut quorum_a = vec![];
+        let mut quorum_b = vec![];
+        let mut injected_into = vec![];
+        for (vi, s_v) in fbas.validators.iter().zip(selectors) {
+            let name = fbas.try_get_validator_string(vi)?;
+            if model.value_lit(fbas_lits.in_quorum_a(vi)) == lbool::TRUE {
+                quorum_a.push(name.clone());
+            }
+            if model.value_lit(fbas_lits.in_quorum_b(vi)) == lbool::TRUE {
+                quorum_b.push(name.clone());
+            }
+            if model.value_lit(s_v) == lbool::TRUE {
+                injected_into.push(name);
+            }
+        }
+        Ok(RedTeamResult::Split {
+            quorum_a,
+            quorum_b,
+            injected_into,
+        })
+    }
+}
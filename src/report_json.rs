@@ -0,0 +1,219 @@
+use crate::{
+    fbas::FbasError, AnalysisReport, BranchingHeuristic, FbasWarning, ReportProvenance,
+    RobustnessScore, SolvePhase, SolveStats, SolveStatus, REPORT_SCHEMA_VERSION,
+};
+use petgraph::graph::NodeIndex;
+use serde_json::{json, Value};
+
+fn as_u32(value: &Value) -> Option<u32> {
+    value.as_u64()?.try_into().ok()
+}
+
+fn as_usize(value: &Value) -> Option<usize> {
+    value.as_u64()?.try_into().ok()
+}
+
+fn members(value: &Value) -> impl Iterator<Item = &Value> {
+    value.as_array().into_iter().flatten()
+}
+
+fn parse_strings(value: &Value) -> Result<Vec<String>, FbasError> {
+    members(value)
+        .map(|v| {
+            v.as_str()
+                .map(str::to_string)
+                .ok_or(FbasError::ParseError("Expected a string"))
+        })
+        .collect()
+}
+
+fn parse_indices(value: &Value) -> Result<Vec<NodeIndex>, FbasError> {
+    members(value)
+        .map(|v| {
+            as_usize(v)
+                .map(NodeIndex::new)
+                .ok_or(FbasError::ParseError("Expected a node index"))
+        })
+        .collect()
+}
+
+fn warning_to_json(warning: &FbasWarning) -> Value {
+    match warning {
+        FbasWarning::ThresholdExceedsMembers {
+            threshold,
+            threshold_percent,
+            members,
+            validators,
+        } => json!({
+            "kind": "ThresholdExceedsMembers",
+            "threshold": threshold,
+            "threshold_percent": threshold_percent,
+            "members": members,
+            "validators": validators,
+        }),
+        FbasWarning::UnsatisfiableQset { validator } => json!({
+            "kind": "UnsatisfiableQset",
+            "validator": validator,
+        }),
+    }
+}
+
+// `None` for kinds of warnings added in later versions
+fn parse_warning(value: &Value) -> Result<Option<FbasWarning>, FbasError> {
+    let warning = match value["kind"].as_str() {
+        Some("ThresholdExceedsMembers") => FbasWarning::ThresholdExceedsMembers {
+            threshold: as_u32(&value["threshold"]).ok_or(FbasError::ParseError(
+                "Missing or invalid warning threshold",
+            ))?,
+            threshold_percent: as_u32(&value["threshold_percent"]),
+            members: as_usize(&value["members"])
+                .ok_or(FbasError::ParseError("Missing or invalid warning members"))?,
+            validators: parse_strings(&value["validators"])?,
+        },
+        Some("UnsatisfiableQset") => FbasWarning::UnsatisfiableQset {
+            validator: value["validator"]
+                .as_str()
+                .ok_or(FbasError::ParseError("Missing warning validator"))?
+                .to_string(),
+        },
+        _ => return Ok(None),
+    };
+    Ok(Some(warning))
+}
+
+fn parse_robustness(value: &Value) -> Option<RobustnessScore> {
+    if value.is_null() {
+        return None;
+    }
+    Some(RobustnessScore {
+        score: value["score"].as_f64().unwrap_or_default(),
+        intersects: value["intersects"].as_bool().unwrap_or_default(),
+        top_tier_size: as_usize(&value["top_tier_size"]).unwrap_or_default(),
+        top_tier_orgs: as_usize(&value["top_tier_orgs"]).unwrap_or_default(),
+        blocking_set_size: as_usize(&value["blocking_set_size"]).unwrap_or_default(),
+        splitting_set_size: as_usize(&value["splitting_set_size"]),
+        // earlier reports searched sets of any size
+        max_set_size: as_usize(&value["max_set_size"]).unwrap_or(usize::MAX),
+    })
+}
+
+impl AnalysisReport {
+    /// Serializes the report as JSON, see `REPORT_SCHEMA_VERSION` for how the
+    /// format evolves.
+    pub fn to_json(&self) -> String {
+        let status = match &self.status {
+            SolveStatus::SAT(_) => "SAT",
+            SolveStatus::UNSAT => "UNSAT",
+            SolveStatus::UNKNOWN => "UNKNOWN",
+        };
+        let mut report = json!({
+            "schema_version": self.schema_version,
+            "status": status,
+            "potential_split": [&self.potential_split.0, &self.potential_split.1],
+            "stats": {
+                "phase": format!("{:?}", self.stats.phase),
+                "num_vars": self.stats.num_vars,
+                "num_clauses": self.stats.num_clauses,
+                "num_conflicts": self.stats.num_conflicts,
+                "num_decisions": self.stats.num_decisions,
+                "num_propagations": self.stats.num_propagations,
+            },
+            "warnings": self.warnings.iter().map(warning_to_json).collect::<Vec<_>>(),
+            "provenance": {
+                "crate_version": self.provenance.crate_version,
+                "solver": self.provenance.solver,
+                "solver_seed": self.provenance.solver_seed,
+                "branching_heuristic": format!("{:?}", self.provenance.branching_heuristic),
+                "symmetry_breaking": self.provenance.symmetry_breaking,
+                "interrupted": self.provenance.interrupted,
+                "pruned_vertices": self.provenance.pruned_vertices,
+                "unknown_validators": self.provenance.unknown_validators,
+            },
+        });
+        if let SolveStatus::SAT((quorum_a, quorum_b)) = &self.status {
+            let indices = |q: &[NodeIndex]| q.iter().map(|ni| ni.index()).collect::<Vec<_>>();
+            report["split_indices"] = json!([indices(quorum_a), indices(quorum_b)]);
+        }
+        if let Some(robustness) = &self.robustness {
+            report["robustness"] = json!({
+                "score": robustness.score,
+                "intersects": robustness.intersects,
+                "top_tier_size": robustness.top_tier_size,
+                "top_tier_orgs": robustness.top_tier_orgs,
+                "blocking_set_size": robustness.blocking_set_size,
+                "splitting_set_size": robustness.splitting_set_size,
+                "max_set_size": robustness.max_set_size,
+            });
+        }
+        report.to_string()
+    }
+
+    /// Deserializes a report written by `to_json` of this or any earlier
+    /// version of the crate.
+    pub fn from_json(data: &str) -> Result<Self, FbasError> {
+        let report: Value = serde_json::from_str(data)
+            .map_err(|_| FbasError::ParseError("fail to parse to json"))?;
+        let schema_version = as_u32(&report["schema_version"])
+            .ok_or(FbasError::ParseError("Missing or invalid schema_version"))?;
+        if schema_version > REPORT_SCHEMA_VERSION {
+            return Err(FbasError::ParseError("Report schema version is too new"));
+        }
+        let status = match report["status"].as_str() {
+            Some("SAT") => {
+                let indices = &report["split_indices"];
+                SolveStatus::SAT((parse_indices(&indices[0])?, parse_indices(&indices[1])?))
+            }
+            Some("UNSAT") => SolveStatus::UNSAT,
+            Some("UNKNOWN") => SolveStatus::UNKNOWN,
+            _ => return Err(FbasError::ParseError("Missing or invalid status")),
+        };
+        let split = &report["potential_split"];
+        let stats = &report["stats"];
+        let phase = match stats["phase"].as_str() {
+            Some("PreCheck") => SolvePhase::PreCheck,
+            Some("Search") => SolvePhase::Search,
+            _ => SolvePhase::NotSolved,
+        };
+        let provenance = &report["provenance"];
+        let branching_heuristic = match provenance["branching_heuristic"].as_str() {
+            Some("TopTier") => BranchingHeuristic::TopTier,
+            _ => BranchingHeuristic::Solver,
+        };
+        let warnings = members(&report["warnings"])
+            .filter_map(|w| parse_warning(w).transpose())
+            .collect::<Result<_, _>>()?;
+        Ok(AnalysisReport {
+            schema_version,
+            status,
+            potential_split: (parse_strings(&split[0])?, parse_strings(&split[1])?),
+            stats: SolveStats {
+                phase,
+                num_vars: as_u32(&stats["num_vars"]).unwrap_or_default(),
+                num_clauses: stats["num_clauses"].as_u64().unwrap_or_default(),
+                num_conflicts: stats["num_conflicts"].as_u64().unwrap_or_default(),
+                num_decisions: stats["num_decisions"].as_u64().unwrap_or_default(),
+                num_propagations: stats["num_propagations"].as_u64().unwrap_or_default(),
+            },
+            warnings,
+            provenance: ReportProvenance {
+                crate_version: provenance["crate_version"]
+                    .as_str()
+                    .unwrap_or_default()
+                    .to_string(),
+                solver: provenance["solver"]
+                    .as_str()
+                    .unwrap_or_default()
+                    .to_string(),
+                solver_seed: provenance["solver_seed"].as_f64().unwrap_or_default(),
+                branching_heuristic,
+                symmetry_breaking: provenance["symmetry_breaking"]
+                    .as_bool()
+                    .unwrap_or_default(),
+                interrupted: provenance["interrupted"].as_bool().unwrap_or_default(),
+                pruned_vertices: as_usize(&provenance["pruned_vertices"]).unwrap_or_default(),
+                unknown_validators: as_usize(&provenance["unknown_validators"]).unwrap_or_default(),
+            },
+            robustness: parse_robustness(&report["robustness"]),
+        })
+    }
+}
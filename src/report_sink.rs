@@ -0,0 +1,121 @@
+use crate::{fbas::FbasError, AnalysisReport, TopTierAlert};
+use std::{
+    fs::OpenOptions,
+    io::Write,
+    path::{Path, PathBuf},
+};
+
+// Sinks deliver reports in their JSON form, see `AnalysisReport::to_json`,
+// so whatever produces them (a monitoring service, a scheduled job) can send
+// them to stdout, a log file, an alerting webhook or its own code alike,
+// without writing the delivery itself. Alerts on the top tier (see
+// `Timeline::send_top_tier_alerts`) go the same way, told apart from reports
+// by their `alert` field. Reports and alerts are one line each, so a file or
+// stream of them is JSON Lines.
+
+/// Where analysis reports go once produced, e.g. by a service analyzing each
+/// new snapshot of a network. Closures are sinks too, for any delivery not
+/// covered by the sinks of this crate.
+pub trait ReportSink {
+    /// Delivers `report`.
+    fn send(&mut self, report: &AnalysisReport) -> Result<(), FbasError>;
+
+    /// Delivers `alert`. Sinks taking only reports, such as closures, drop
+    /// alerts.
+    fn send_alert(&mut self, alert: &TopTierAlert) -> Result<(), FbasError> {
+        let _ = alert;
+        Ok(())
+    }
+}
+
+impl<F: FnMut(&AnalysisReport) -> Result<(), FbasError>> ReportSink for F {
+    fn send(&mut self, report: &AnalysisReport) -> Result<(), FbasError> {
+        self(report)
+    }
+}
+
+/// Prints each report and alert to stdout as a line of JSON.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct StdoutSink;
+
+impl StdoutSink {
+    fn write_line(&self, line: &str) -> Result<(), FbasError> {
+        writeln!(std::io::stdout().lock(), "{}", line)
+            .map_err(|_| FbasError::IoError("fail to write report"))
+    }
+}
+
+impl ReportSink for StdoutSink {
+    fn send(&mut self, report: &AnalysisReport) -> Result<(), FbasError> {
+        self.write_line(&report.to_json())
+    }
+
+    fn send_alert(&mut self, alert: &TopTierAlert) -> Result<(), FbasError> {
+        self.write_line(&alert.to_json())
+    }
+}
+
+/// Appends each report and alert to a file as a line of JSON, creating the file if it
+/// doesn't exist.
+#[derive(Debug, Clone)]
+pub struct FileSink {
+    path: PathBuf,
+}
+
+impl FileSink {
+    pub fn new(path: impl AsRef<Path>) -> Self {
+        Self {
+            path: path.as_ref().to_path_buf(),
+        }
+    }
+}
+
+impl FileSink {
+    fn write_line(&self, line: &str) -> Result<(), FbasError> {
+        let mut file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.path)
+            .map_err(|_| FbasError::IoError("fail to open report file"))?;
+        writeln!(file, "{}", line).map_err(|_| FbasError::IoError("fail to write report"))
+    }
+}
+
+impl ReportSink for FileSink {
+    fn send(&mut self, report: &AnalysisReport) -> Result<(), FbasError> {
+        self.write_line(&report.to_json())
+    }
+
+    fn send_alert(&mut self, alert: &TopTierAlert) -> Result<(), FbasError> {
+        self.write_line(&alert.to_json())
+    }
+}
+
+/// Posts each report (and alert) as JSON to a URL, e.g. an alerting webhook. Transient
+/// failures are retried a few times with backoff, as when fetching a network
+/// with `FbasAnalyzer::from_stellarbeats_api`.
+#[cfg(feature = "net")]
+#[derive(Debug, Clone)]
+pub struct WebhookSink {
+    url: String,
+}
+
+#[cfg(feature = "net")]
+impl WebhookSink {
+    pub fn new(url: &str) -> Self {
+        Self {
+            url: url.to_string(),
+        }
+    }
+}
+
+#[cfg(feature = "net")]
+impl ReportSink for WebhookSink {
+    fn send(&mut self, report: &AnalysisReport) -> Result<(), FbasError> {
+        crate::net::post_json(&self.url, &report.to_json())
+    }
+
+    fn send_alert(&mut self, alert: &TopTierAlert) -> Result<(), FbasError> {
+        crate::net::post_json(&self.url, &alert.to_json())
+    }
+}
@@ -0,0 +1,78 @@
+use crate::{
+    fbas::{Fbas, FbasError},
+    fbas_analyze::solver_seed,
+    json_parser::{nodes_to_json, quorum_set_map_from_nodes_json},
+    FbasAnalyzer, SolveStatus,
+};
+use batsat::Callbacks;
+use serde_json::Value;
+use std::fs;
+
+// A repro bundle is a single json file with everything needed to rerun an
+// analysis elsewhere:
+//
+//     {
+//       "version": "0.1.0",        // crate version that captured it
+//       "solver_seed": 91648253,   // random seed of the SAT solver
+//       "status": "SAT",           // result at capture time, if solved
+//       "nodes": [...]             // the network, in the stellarbeats format
+//     }
+//
+// The network is the one seen by the analysis, so unknown validators are
+// already left out of the qsets.
+
+impl<Cb: Callbacks> FbasAnalyzer<Cb> {
+    /// Writes a self-contained repro bundle of this analysis to `path`, to be
+    /// attached to bug reports and loaded with `from_repro`.
+    pub fn capture_repro(&self, path: &str) -> Result<(), FbasError> {
+        let status = match self.status {
+            SolveStatus::SAT(_) => "SAT",
+            SolveStatus::UNSAT => "UNSAT",
+            SolveStatus::UNKNOWN => "UNKNOWN",
+        };
+        let bundle = serde_json::json!({
+            "version": env!("CARGO_PKG_VERSION"),
+            "solver_seed": solver_seed(),
+            "status": status,
+            "nodes": nodes_to_json(&self.fbas)?,
+        });
+        let bundle = serde_json::to_string_pretty(&bundle)
+            .map_err(|e| FbasError::JsonError(e.to_string()))?;
+        fs::write(path, bundle).map_err(|_| FbasError::IoError("fail to write repro bundle"))
+    }
+
+    /// Constructs the analyzer from a repro bundle written by `capture_repro`.
+    /// A bundle captured by a different crate version or solver seed is still
+    /// loaded, with a diagnostic, since results may then differ.
+    pub fn from_repro(path: &str, cb: Cb) -> Result<Self, FbasError> {
+        let data = fs::read_to_string(path)
+            .map_err(|_| FbasError::IoError("fail to read repro bundle"))?;
+        let bundle = serde_json::from_str(&data)
+            .map_err(|_| FbasError::ParseError("fail to parse to json"))?;
+        Self::from_repro_json(&bundle, cb)
+    }
+
+    // Same as `from_repro`, for an already parsed bundle.
+    pub(crate) fn from_repro_json(bundle: &Value, cb: Cb) -> Result<Self, FbasError> {
+        let mut watchers = vec![];
+        let qsm = quorum_set_map_from_nodes_json(&bundle["nodes"], &mut watchers)?;
+        let mut fbas = Fbas::from_quorum_set_map(&qsm)?.with_watchers(watchers);
+
+        let version = bundle["version"].as_str().unwrap_or("unknown");
+        if version != env!("CARGO_PKG_VERSION") {
+            fbas.diagnostics.push(format!(
+                "Repro bundle was captured by version {}, this is version {}",
+                version,
+                env!("CARGO_PKG_VERSION")
+            ));
+        }
+        if bundle["solver_seed"].as_f64() != Some(solver_seed()) {
+            fbas.diagnostics.push(format!(
+                "Repro bundle was captured with solver seed {}, this solver uses {}",
+                bundle["solver_seed"],
+                solver_seed()
+            ));
+        }
+        Self::from_fbas(fbas, cb)
+    }
+}
@@ -0,0 +1,296 @@
+use crate::{
+    fbas::{Fbas, FbasError, InternalScpQuorumSet, QuorumSetMap},
+    fbas_analyze::{
+        default_num_threads, encode_qset_relations, CardinalityEncoding, FbasLitsWrapper,
+    },
+    Callbacks, DiagnosticsSink, FbasAnalyzer, Organizations, Quorum, SolveStatus,
+};
+use batsat::{interface::SolveResult, lbool, theory, Lit, Solver, SolverInterface};
+use itertools::Itertools;
+use std::{collections::BTreeSet, sync::Arc};
+
+// Every combination of up to `k` failed organizations is checked for two
+// properties, each with its own failure model:
+//
+// - Availability: the failed validators crashed, so a quorum must exist among
+//   the others. This is a graph-level check, see `Fbas::max_quorum`.
+// - Intersection: the failed validators are byzantine. Quorums then only need
+//   to intersect in a correct validator, which is quorum intersection of the
+//   network with the failed validators deleted: they are removed from every
+//   qset, each lowering the threshold by one, as they can vouch for whatever
+//   helps. This needs a solve per combination.
+//
+// For a single set of byzantine validators, `solve_excluding` encodes the
+// deletion on the original graph instead of rebuilding it: the deleted
+// validators are fixed in both quorums, counting toward every qset as if
+// present, while their own qsets, disjointness and non-emptiness only apply
+// to the other validators.
+
+/// The outcomes of the combinations of `failed_orgs` failed organizations,
+/// see `FbasAnalyzer::org_failure_resilience`.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ResilienceRow {
+    pub failed_orgs: usize,
+    pub combinations: usize,
+    /// Combinations leaving a quorum among the other validators.
+    pub available: usize,
+    /// Combinations under which quorums still intersect in a correct
+    /// validator.
+    pub intersecting: usize,
+    /// Combinations for which the solver didn't reach a conclusion, counted
+    /// as not intersecting.
+    pub unknown: usize,
+}
+
+impl ResilienceRow {
+    pub fn available_fraction(&self) -> f64 {
+        self.available as f64 / self.combinations.max(1) as f64
+    }
+
+    pub fn intersecting_fraction(&self) -> f64 {
+        self.intersecting as f64 / self.combinations.max(1) as f64
+    }
+}
+
+/// The organizations whose failure alone breaks the network, see
+/// `FbasAnalyzer::critical_organizations`. Each list is in organization name
+/// order.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct CriticalOrganizations {
+    /// Organizations whose crash leaves no quorum among the other
+    /// validators.
+    pub availability: Vec<String>,
+    /// Organizations whose byzantine validators allow two quorums
+    /// intersecting in no correct validator.
+    pub intersection: Vec<String>,
+    /// Organizations for which the solver didn't reach a conclusion on
+    /// intersection.
+    pub unknown: Vec<String>,
+}
+
+/// Resilience of a network to organization failures, one row per number of
+/// failed organizations from 0 up.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ResilienceReport {
+    pub rows: Vec<ResilienceRow>,
+}
+
+impl std::fmt::Display for ResilienceReport {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        writeln!(f, "failed orgs  combinations  available  intersecting")?;
+        for row in &self.rows {
+            writeln!(
+                f,
+                "{:>11}  {:>12}  {:>8.1}%  {:>11.1}%",
+                row.failed_orgs,
+                row.combinations,
+                row.available_fraction() * 100.0,
+                row.intersecting_fraction() * 100.0
+            )?;
+        }
+        Ok(())
+    }
+}
+
+// Removes the `deleted` validators from `qset`, lowering its threshold for
+// each of them. Inner sets left with a threshold of 0 are always satisfied,
+// so they are removed as well rather than kept as identical members, which
+// the graph would merge into one.
+fn delete_validators(
+    qset: &InternalScpQuorumSet,
+    deleted: &BTreeSet<&str>,
+) -> InternalScpQuorumSet {
+    let validators: Vec<String> = qset
+        .validators
+        .iter()
+        .filter(|v| !deleted.contains(v.as_str()))
+        .cloned()
+        .collect();
+    let inner_sets: Vec<InternalScpQuorumSet> = qset
+        .inner_sets
+        .iter()
+        .map(|inner| delete_validators(inner, deleted))
+        .filter(|inner| inner.threshold > 0)
+        .collect();
+    let removed =
+        qset.validators.len() - validators.len() + qset.inner_sets.len() - inner_sets.len();
+    InternalScpQuorumSet {
+        threshold: qset.threshold.saturating_sub(removed as u32),
+        // no longer the given percentage once members are removed
+        threshold_percent: qset.threshold_percent.filter(|_| removed == 0),
+        validators,
+        inner_sets,
+    }
+}
+
+impl<Cb: Callbacks> FbasAnalyzer<Cb> {
+    /// Checks every combination of up to `max_failed` failed organizations of
+    /// `orgs` for the availability of a quorum (with the failed validators
+    /// crashed) and for quorum intersection (with them byzantine). Each
+    /// intersection check is solved by a separate solver driven by callbacks
+    /// from `new_cb`, so the number of solves grows with the binomial
+    /// coefficients of the number of organizations.
+    pub fn org_failure_resilience<C: Callbacks>(
+        &self,
+        orgs: &Organizations,
+        max_failed: usize,
+        mut new_cb: impl FnMut() -> C,
+    ) -> Result<ResilienceReport, FbasError> {
+        let fbas = &self.fbas;
+        let qsm = fbas.quorum_set_map()?;
+        let org_names: Vec<&str> = orgs.orgs().collect();
+        let mut report = ResilienceReport::default();
+        for k in 0..=max_failed.min(org_names.len()) {
+            let mut row = ResilienceRow {
+                failed_orgs: k,
+                ..Default::default()
+            };
+            for failed_orgs in org_names.iter().copied().combinations(k) {
+                row.combinations += 1;
+                let (available, intersection) =
+                    self.check_failed_orgs(&qsm, orgs, &failed_orgs, new_cb())?;
+                if available {
+                    row.available += 1;
+                }
+                match intersection {
+                    SolveStatus::UNSAT => row.intersecting += 1,
+                    SolveStatus::UNKNOWN => row.unknown += 1,
+                    SolveStatus::SAT(_) => {}
+                }
+            }
+            report.rows.push(row);
+        }
+        Ok(report)
+    }
+
+    /// Finds the organizations of `orgs` whose failure alone breaks the
+    /// network: the availability of a quorum (with their validators crashed)
+    /// or quorum intersection (with them byzantine). These are the rows for
+    /// one failed organization of `org_failure_resilience`, broken down by
+    /// organization. Each intersection check is solved by a separate solver
+    /// driven by callbacks from `new_cb`.
+    pub fn critical_organizations<C: Callbacks>(
+        &self,
+        orgs: &Organizations,
+        mut new_cb: impl FnMut() -> C,
+    ) -> Result<CriticalOrganizations, FbasError> {
+        let qsm = self.fbas.quorum_set_map()?;
+        let mut critical = CriticalOrganizations::default();
+        for org in orgs.orgs() {
+            let (available, intersection) = self.check_failed_orgs(&qsm, orgs, &[org], new_cb())?;
+            if !available {
+                critical.availability.push(org.to_string());
+            }
+            match intersection {
+                SolveStatus::SAT(_) => critical.intersection.push(org.to_string()),
+                SolveStatus::UNKNOWN => critical.unknown.push(org.to_string()),
+                SolveStatus::UNSAT => {}
+            }
+        }
+        Ok(critical)
+    }
+
+    /// Checks quorum intersection after deleting the `faulty` validators,
+    /// i.e. whether the others can still be split by two quorums when the
+    /// faulty ones are byzantine and vouch for whatever helps. A potential
+    /// split only lists correct validators. Solved by a separate solver
+    /// driven by `cb`, leaving the analyzer's own result untouched. Returns
+    /// an error for validators unknown to the network.
+    pub fn solve_excluding<C: Callbacks>(
+        &self,
+        faulty: &[&str],
+        cb: C,
+    ) -> Result<SolveStatus, FbasError> {
+        let fbas = &self.fbas;
+        let faulty = faulty
+            .iter()
+            .map(|v| fbas.try_get_validator_index(v))
+            .collect::<Result<BTreeSet<_>, _>>()?;
+        let fbas_lits = FbasLitsWrapper::new(fbas.graph.node_count());
+        let mut solver = Solver::new(Default::default(), cb);
+        for _ in 0..2 * fbas.graph.node_count() {
+            solver.new_var_default();
+        }
+        encode_qset_relations(
+            fbas,
+            &mut solver,
+            |ni| faulty.contains(ni),
+            default_num_threads(),
+            CardinalityEncoding::default(),
+        )?;
+        let correct: Vec<_> = fbas
+            .validators
+            .iter()
+            .filter(|ni| !faulty.contains(ni))
+            .collect();
+        let (mut in_a, mut in_b): (Vec<Lit>, Vec<Lit>) = correct
+            .iter()
+            .map(|ni| (fbas_lits.in_quorum_a(ni), fbas_lits.in_quorum_b(ni)))
+            .unzip();
+        solver.add_clause_reuse(&mut in_a);
+        solver.add_clause_reuse(&mut in_b);
+        for ni in correct.iter() {
+            solver.add_clause_reuse(&mut vec![
+                !fbas_lits.in_quorum_a(ni),
+                !fbas_lits.in_quorum_b(ni),
+            ]);
+        }
+        for ni in faulty.iter() {
+            solver.add_clause_reuse(&mut vec![fbas_lits.in_quorum_a(ni)]);
+            solver.add_clause_reuse(&mut vec![fbas_lits.in_quorum_b(ni)]);
+        }
+
+        let mut th = theory::EmptyTheory::new();
+        let status = match solver.solve_limited_th_full(&mut th, &[]) {
+            SolveResult::Sat(model) => {
+                let members = |quorum| {
+                    correct
+                        .iter()
+                        .filter(|ni| {
+                            model.value_lit(fbas_lits.in_quorum(ni, quorum)) == lbool::TRUE
+                        })
+                        .map(|ni| **ni)
+                        .collect()
+                };
+                SolveStatus::SAT((members(Quorum::A), members(Quorum::B)))
+            }
+            SolveResult::Unsat(_) => SolveStatus::UNSAT,
+            SolveResult::Unknown(_) => SolveStatus::UNKNOWN,
+        };
+        Ok(status)
+    }
+
+    // Returns whether a quorum is available with the validators of
+    // `failed_orgs` crashed, and the outcome of the intersection check with
+    // them byzantine.
+    fn check_failed_orgs<C: Callbacks>(
+        &self,
+        qsm: &QuorumSetMap,
+        orgs: &Organizations,
+        failed_orgs: &[&str],
+        cb: C,
+    ) -> Result<(bool, SolveStatus), FbasError> {
+        let fbas = &self.fbas;
+        let failed: BTreeSet<&str> = failed_orgs
+            .iter()
+            .flat_map(|org| orgs.validators_of(org).unwrap_or_default())
+            .map(String::as_str)
+            .collect();
+
+        let crashed = failed
+            .iter()
+            .filter_map(|v| fbas.try_get_validator_index(v).ok())
+            .collect();
+        let available = !fbas.max_quorum(&crashed).is_empty();
+
+        let deleted = qsm
+            .iter()
+            .filter(|(v, _)| !failed.contains(v.as_str()))
+            .map(|(v, qset)| (v.clone(), Arc::new(delete_validators(qset, &failed))))
+            .collect();
+        let deleted = Fbas::from_quorum_set_map(&deleted)?;
+        let mut analyzer =
+            FbasAnalyzer::from_fbas(deleted, cb)?.with_diagnostics_sink(DiagnosticsSink::Silent);
+        Ok((available, analyzer.solve()))
+    }
+}
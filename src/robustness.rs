@@ -0,0 +1,151 @@
+use crate::{fbas::FbasError, FbasAnalyzer, Organizations};
+use batsat::Callbacks;
+use std::collections::BTreeSet;
+
+// The score is a weighted mean of four components in [0, 1], each measured
+// against the top tier `T` (the validators of minimal quorums), as only its
+// validators matter for safety and liveness:
+//
+//     intersection = 1 if the network enjoys quorum intersection, else 0
+//     blocking     = (size of the smallest blocking set) / |T|
+//     splitting    = (size of the smallest splitting set) / |T|, 1 if none
+//     diversity    = 1 - 1 / (number of organizations in T)
+//
+// The whole top tier is blocking, so `blocking` is at most 1. Validators
+// without an organization count as one each. A network without any quorum
+// scores 0 on everything.
+//
+// Both set searches are exponential in the set size, so they stop at a
+// caller given size `k`. When no set of at most `k` validators exists, the
+// size is recorded as `k + 1`, i.e. "more than `k`", and the score is a lower
+// bound of the one an unbounded search would give.
+
+/// Weights of the components of `RobustnessScore::score`. Only their ratios
+/// matter.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct RobustnessWeights {
+    pub intersection: f64,
+    pub blocking: f64,
+    pub splitting: f64,
+    pub diversity: f64,
+}
+
+impl Default for RobustnessWeights {
+    fn default() -> Self {
+        Self {
+            intersection: 0.4,
+            blocking: 0.2,
+            splitting: 0.2,
+            diversity: 0.2,
+        }
+    }
+}
+
+/// A composite measure of a network's robustness, see
+/// `FbasAnalyzer::robustness_score`, with the measurements it is computed
+/// from. Meant for tracking trends across snapshots rather than comparing
+/// unrelated networks.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct RobustnessScore {
+    /// The weighted mean of the components, from 0 (no quorum) to 1.
+    pub score: f64,
+    pub intersects: bool,
+    pub top_tier_size: usize,
+    /// The number of organizations in the top tier.
+    pub top_tier_orgs: usize,
+    /// `max_set_size + 1` if every blocking set is larger than that.
+    pub blocking_set_size: usize,
+    /// `None` if no set of validators can split the network,
+    /// `Some(max_set_size + 1)` if every splitting set is larger than that.
+    pub splitting_set_size: Option<usize>,
+    /// The largest blocking and splitting sets searched for.
+    pub max_set_size: usize,
+}
+
+impl RobustnessScore {
+    fn with_score(mut self, weights: &RobustnessWeights) -> Self {
+        let top_tier = self.top_tier_size.max(1) as f64;
+        let components = [
+            (
+                weights.intersection,
+                if self.intersects { 1.0 } else { 0.0 },
+            ),
+            (
+                weights.blocking,
+                (self.blocking_set_size as f64 / top_tier).min(1.0),
+            ),
+            (
+                weights.splitting,
+                self.splitting_set_size
+                    .map_or(1.0, |size| (size as f64 / top_tier).min(1.0)),
+            ),
+            (
+                weights.diversity,
+                1.0 - 1.0 / self.top_tier_orgs.max(1) as f64,
+            ),
+        ];
+        let total: f64 = components.iter().map(|(weight, _)| weight).sum();
+        if total > 0.0 {
+            self.score = components.iter().map(|(w, c)| w * c).sum::<f64>() / total;
+        }
+        self
+    }
+}
+
+impl<Cb: Callbacks> FbasAnalyzer<Cb> {
+    /// Computes the robustness score of the network with `weights`. `orgs`
+    /// maps validators to organizations for the diversity of the top tier;
+    /// without it, every validator is its own organization. This runs the
+    /// top tier, blocking set and splitting set analyses, which are
+    /// exponential in the worst case. The latter two only look for sets of
+    /// at most `max_set_size` validators, larger ones being reported as
+    /// `max_set_size + 1`. Each search is driven by callbacks from `new_cb`.
+    /// `None` if one of them was interrupted.
+    pub fn robustness_score<C: Callbacks>(
+        &self,
+        orgs: Option<&Organizations>,
+        weights: &RobustnessWeights,
+        max_set_size: usize,
+        mut new_cb: impl FnMut() -> C,
+    ) -> Result<Option<RobustnessScore>, FbasError> {
+        let Some(top_tier) = self.top_tier(&mut new_cb)? else {
+            return Ok(None);
+        };
+        if top_tier.is_empty() {
+            return Ok(Some(RobustnessScore {
+                max_set_size,
+                ..Default::default()
+            }));
+        }
+        let Some(intersects) = self.solve_excluding(&[], new_cb())?.intersecting() else {
+            return Ok(None);
+        };
+        let more_than_max = max_set_size.saturating_add(1);
+        let splitting = self.minimal_splitting_sets_limited(max_set_size, 1, &mut new_cb)?;
+        let splitting_set_size = match splitting.sets.first() {
+            Some(set) => Some(set.len()),
+            None if !splitting.complete => return Ok(None),
+            None if max_set_size >= self.fbas.validators.len() => None,
+            None => Some(more_than_max),
+        };
+        let Some(blocking) = self.find_minimal_blocking_sets(max_set_size, new_cb())? else {
+            return Ok(None);
+        };
+        let blocking_set_size = blocking.first().map_or(more_than_max, Vec::len);
+        let top_tier_orgs = top_tier
+            .iter()
+            .map(|v| orgs.and_then(|orgs| orgs.org_of(v)).unwrap_or(v))
+            .collect::<BTreeSet<_>>()
+            .len();
+        let score = RobustnessScore {
+            score: 0.0,
+            intersects,
+            top_tier_size: top_tier.len(),
+            top_tier_orgs,
+            blocking_set_size,
+            splitting_set_size,
+            max_set_size,
+        };
+        Ok(Some(score.with_score(weights)))
+    }
+}
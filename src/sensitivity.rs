@@ -0,0 +1,91 @@
+use crate::{
+    fbas::{Fbas, FbasError, Vertex},
+    Callbacks, DiagnosticsSink, FbasAnalyzer,
+};
+use petgraph::{graph::NodeIndex, Direction};
+use std::collections::BTreeMap;
+
+// Each perturbation lowers the threshold of one qset vertex of the graph by
+// one. Identical qsets share a vertex, so a qset used by several validators
+// (or nested in several qsets) is lowered everywhere at once, as when the
+// operators sharing a configuration all change it. The network is rebuilt
+// from its qsets for every perturbation, so vertices are those of the
+// rebuilt graph rather than of the analyzer's.
+
+/// The outcome of lowering the threshold of one qset by one, see
+/// `FbasAnalyzer::threshold_sensitivity`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ThresholdPerturbation {
+    /// The qset before the change, as rendered by
+    /// `InternalScpQuorumSet::pretty`.
+    pub qset: String,
+    /// The validators whose qset it is or contains, in validator order.
+    pub validators: Vec<String>,
+    /// Whether quorums still intersect with the lowered threshold, `None`
+    /// if the solver didn't reach a conclusion.
+    pub intersecting: Option<bool>,
+    /// Two disjoint quorums if the change breaks quorum intersection, as in
+    /// `get_potential_split`.
+    pub potential_split: (Vec<String>, Vec<String>),
+}
+
+// The validators whose qset is or contains `qi`.
+fn qset_users(fbas: &Fbas, qi: NodeIndex) -> Vec<NodeIndex> {
+    let mut users = vec![];
+    let mut stack = vec![qi];
+    while let Some(ni) = stack.pop() {
+        for parent in fbas.graph.neighbors_directed(ni, Direction::Incoming) {
+            match fbas.graph[parent] {
+                Vertex::QSet(_) => stack.push(parent),
+                _ => users.push(parent),
+            }
+        }
+    }
+    users.sort();
+    users.dedup();
+    users
+}
+
+impl<Cb: Callbacks> FbasAnalyzer<Cb> {
+    /// Lowers the threshold of each qset in turn by one, and checks quorum
+    /// intersection of the resulting network, to show how much safety margin
+    /// the configuration has: perturbations breaking it are one careless
+    /// change away from a potential split. Qsets with a threshold of 1 are
+    /// skipped, as lowering it to 0 makes them vacuous rather than looser.
+    /// Only meaningful if the network enjoys quorum intersection to begin
+    /// with. Every check is solved by a separate solver driven by callbacks
+    /// from `new_cb`.
+    pub fn threshold_sensitivity<C: Callbacks>(
+        &self,
+        mut new_cb: impl FnMut() -> C,
+    ) -> Result<Vec<ThresholdPerturbation>, FbasError> {
+        let qsm = self.fbas.quorum_set_map()?;
+        let build = || Fbas::from_quorum_set_map(&qsm);
+        let base = build()?;
+        let aliases = BTreeMap::new();
+        let mut perturbations = vec![];
+        for qi in base.graph.node_indices() {
+            if !matches!(base.graph[qi], Vertex::QSet(_)) || base.graph[qi].get_threshold() <= 1 {
+                continue;
+            }
+            let mut perturbed = build()?;
+            if let Vertex::QSet(qset) = &mut perturbed.graph[qi] {
+                qset.threshold -= 1;
+                qset.threshold_percent = None;
+            }
+            let mut analyzer = FbasAnalyzer::from_fbas(perturbed, new_cb())?
+                .with_diagnostics_sink(DiagnosticsSink::Silent);
+            let intersecting = analyzer.solve().intersecting();
+            perturbations.push(ThresholdPerturbation {
+                qset: base.internal_qset(qi)?.pretty(&aliases),
+                validators: qset_users(&base, qi)
+                    .iter()
+                    .map(|vi| base.try_get_validator_string(vi))
+                    .collect::<Result<_, _>>()?,
+                intersecting,
+                potential_split: analyzer.get_potential_split()?,
+            });
+        }
+        Ok(perturbations)
+    }
+}
@@ -0,0 +1,113 @@
+use crate::{
+    fbas::{FbasError, Vertex},
+    FbasAnalyzer, Quorum,
+};
+use batsat::Callbacks;
+use petgraph::graph::NodeIndex;
+use std::fmt::Write;
+
+// SMT-LIB2 export of the same problem as the SAT encoding, except that the qset
+// relation is stated directly as a cardinality constraint (in linear integer
+// arithmetic) instead of the expansion of all threshold combinations:
+//
+//     A_v -> (ite A_m1 1 0) + ... + (ite A_mk 1 0) >= t
+//
+// so that SMT solvers and optimization backends can reason about it natively.
+//
+// Each vertex gets a quoted symbol per quorum, `|A v:<name>|` for validators
+// and watchers and `|A q:<index>|` for qsets, the prefixes keeping the two
+// apart whatever the validators are called.
+
+// `|` and `\` are the only characters not allowed in quoted symbols. They are
+// percent-encoded, as is `%` itself, so that distinct names stay distinct.
+fn smtlib_escape(name: &str) -> String {
+    name.replace('%', "%25")
+        .replace('|', "%7C")
+        .replace('\\', "%5C")
+}
+
+impl<Cb: Callbacks> FbasAnalyzer<Cb> {
+    fn smtlib_symbol(&self, ni: NodeIndex, quorum: Quorum) -> String {
+        let q = match quorum {
+            Quorum::A => "A",
+            Quorum::B => "B",
+        };
+        match &self.fbas.graph[ni] {
+            Vertex::Validator(name) | Vertex::Watcher(name) => {
+                format!("|{} v:{}|", q, smtlib_escape(name))
+            }
+            Vertex::QSet(_) => format!("|{} q:{}|", q, ni.index()),
+        }
+    }
+
+    /// Exports the problem solved by `solve` as an SMT-LIB2 script: it is
+    /// satisfiable iff there are two disjoint quorums. Qset thresholds are kept
+    /// as cardinality constraints.
+    pub fn to_smtlib(&self) -> Result<String, FbasError> {
+        let fmt_err = |_| FbasError::InternalError("Failed to format SMT-LIB");
+        let graph = &self.fbas.graph;
+        let validators = &self.fbas.validators;
+        let mut out = String::new();
+        writeln!(out, "(set-logic QF_LIA)").map_err(fmt_err)?;
+        for quorum in [Quorum::A, Quorum::B] {
+            for ni in graph.node_indices() {
+                writeln!(
+                    out,
+                    "(declare-const {} Bool)",
+                    self.smtlib_symbol(ni, quorum)
+                )
+                .map_err(fmt_err)?;
+            }
+        }
+
+        // both quorums are non-empty
+        for quorum in [Quorum::A, Quorum::B] {
+            let members: Vec<String> = validators
+                .iter()
+                .map(|ni| self.smtlib_symbol(*ni, quorum))
+                .collect();
+            match members.len() {
+                0 => writeln!(out, "(assert false)"),
+                1 => writeln!(out, "(assert {})", members[0]),
+                _ => writeln!(out, "(assert (or {}))", members.join(" ")),
+            }
+            .map_err(fmt_err)?;
+        }
+
+        // the quorums are disjoint
+        for ni in validators {
+            writeln!(
+                out,
+                "(assert (not (and {} {})))",
+                self.smtlib_symbol(*ni, Quorum::A),
+                self.smtlib_symbol(*ni, Quorum::B)
+            )
+            .map_err(fmt_err)?;
+        }
+
+        // qset relation of every vertex
+        for quorum in [Quorum::A, Quorum::B] {
+            for ni in graph.node_indices() {
+                let terms: Vec<String> = graph
+                    .neighbors(ni)
+                    .map(|m| format!("(ite {} 1 0)", self.smtlib_symbol(m, quorum)))
+                    .collect();
+                let sum = match terms.len() {
+                    0 => "0".to_string(),
+                    1 => terms[0].clone(),
+                    _ => format!("(+ {})", terms.join(" ")),
+                };
+                writeln!(
+                    out,
+                    "(assert (=> {} (>= {} {})))",
+                    self.smtlib_symbol(ni, quorum),
+                    sum,
+                    graph[ni].get_threshold()
+                )
+                .map_err(fmt_err)?;
+            }
+        }
+        writeln!(out, "(check-sat)").map_err(fmt_err)?;
+        Ok(out)
+    }
+}
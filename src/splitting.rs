@@ -0,0 +1,118 @@
+use crate::{
+    fbas::FbasError,
+    fbas_analyze::{
+        default_num_threads, encode_qset_relations, enumerate_minimal_sets, CardinalityEncoding,
+        FbasLitsWrapper,
+    },
+    FbasAnalyzer,
+};
+use batsat::{Callbacks, Lit, Solver, SolverInterface};
+
+// A splitting set is a set of validators which, if byzantine, can make two
+// quorums diverge: the faulty validators vouch for whatever helps, so they
+// count toward the qsets of both quorums, and the quorums only need to be
+// disjoint in their correct validators, each having at least one. Being
+// splitting is upward closed, like being blocking.
+//
+// The formula is the regular encoding with a selector `f_v` per validator
+// marking it faulty. The qset relation, disjointness and non-emptiness are
+// replaced by
+//
+//     v -> q_v OR f_v,    f_v -> v,    NOT (v_A AND v_B) OR f_v,
+//     OR (v AND NOT f_v) over all validators v
+//
+// for each quorum, where `q_v` is `v`'s qset vertex. With at most `k`
+// selectors true, a model's faulty validators are a splitting set of at most
+// `k` validators, and the minimal ones are enumerated by increasing `k`, see
+// `enumerate_minimal_sets`.
+
+/// Outcome of `FbasAnalyzer::minimal_splitting_sets`.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SplittingSets {
+    /// The minimal splitting sets found, ordered by size and then by
+    /// validator order.
+    pub sets: Vec<Vec<String>>,
+    /// Whether `sets` are all the minimal splitting sets up to the requested
+    /// size, i.e. no solver was interrupted by its callbacks.
+    pub complete: bool,
+}
+
+impl<Cb: Callbacks> FbasAnalyzer<Cb> {
+    /// Returns all minimal splitting sets with at most `max_size` validators:
+    /// the sets of validators whose byzantine behavior allows two quorums
+    /// that intersect in no correct validator. The empty set is the only one
+    /// if the network lacks quorum intersection to begin with. Each size is
+    /// searched by a separate solver driven by callbacks from `new_cb`.
+    pub fn minimal_splitting_sets<C: Callbacks>(
+        &self,
+        max_size: usize,
+        new_cb: impl FnMut() -> C,
+    ) -> Result<SplittingSets, FbasError> {
+        self.minimal_splitting_sets_limited(max_size, usize::MAX, new_cb)
+    }
+
+    // Same as `minimal_splitting_sets`, stopping once `max_sets` sets are
+    // found, in which case the result is incomplete.
+    pub(crate) fn minimal_splitting_sets_limited<C: Callbacks>(
+        &self,
+        max_size: usize,
+        max_sets: usize,
+        mut new_cb: impl FnMut() -> C,
+    ) -> Result<SplittingSets, FbasError> {
+        let fbas = &self.fbas;
+        let encode = || {
+            let fbas_lits = FbasLitsWrapper::new(fbas.graph.node_count());
+            let mut solver = Solver::new(Default::default(), new_cb());
+            for _ in 0..2 * fbas.graph.node_count() {
+                solver.new_var_default();
+            }
+            encode_qset_relations(
+                fbas,
+                &mut solver,
+                |ni| fbas.validators.contains(ni),
+                default_num_threads(),
+                CardinalityEncoding::default(),
+            )?;
+
+            let mut faulty = vec![];
+            let mut correct_members = (vec![], vec![]);
+            for vi in fbas.validators.iter() {
+                let qi = fbas
+                    .graph
+                    .neighbors(*vi)
+                    .next()
+                    .ok_or(FbasError::InternalError("Validator has no qset"))?;
+                let f_v = Lit::new(solver.new_var_default(), true);
+                let (a, b) = (fbas_lits.in_quorum_a(vi), fbas_lits.in_quorum_b(vi));
+                solver.add_clause_reuse(&mut vec![!a, !b, f_v]);
+                for (v, q, members) in [
+                    (a, fbas_lits.in_quorum_a(&qi), &mut correct_members.0),
+                    (b, fbas_lits.in_quorum_b(&qi), &mut correct_members.1),
+                ] {
+                    solver.add_clause_reuse(&mut vec![!v, q, f_v]);
+                    solver.add_clause_reuse(&mut vec![!f_v, v]);
+                    let c_v = Lit::new(solver.new_var_default(), true);
+                    solver.add_clause_reuse(&mut vec![!c_v, v]);
+                    solver.add_clause_reuse(&mut vec![!c_v, !f_v]);
+                    members.push(c_v);
+                }
+                faulty.push(f_v);
+            }
+            solver.add_clause_reuse(&mut correct_members.0);
+            solver.add_clause_reuse(&mut correct_members.1);
+            Ok((solver, faulty))
+        };
+        // by positions in `fbas.validators`
+        let (found, complete) =
+            enumerate_minimal_sets(max_size.min(fbas.validators.len()), max_sets, encode)?;
+        let sets = found
+            .iter()
+            .map(|set| {
+                set.iter()
+                    .map(|i| fbas.try_get_validator_string(&fbas.validators[*i]))
+                    .collect()
+            })
+            .collect::<Result<_, _>>()?;
+        Ok(SplittingSets { sets, complete })
+    }
+}
@@ -0,0 +1,174 @@
+use crate::{fbas::FbasError, AnalysisReport, SolveStatus};
+use rusqlite::{params, Connection, OptionalExtension};
+use std::{
+    path::Path,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+// Reports are stored whole, as their JSON (see `AnalysisReport::to_json`), so
+// that the store keeps up with the report format by itself, next to the few
+// columns the queries need: the network, the hash of the snapshot analyzed,
+// when it was recorded, the status and the size of the top tier. Rows are
+// only ever appended, and their ids give the order they were recorded in.
+
+const SCHEMA: &str = "
+    CREATE TABLE IF NOT EXISTS reports (
+        id INTEGER PRIMARY KEY AUTOINCREMENT,
+        network TEXT NOT NULL,
+        snapshot_hash TEXT NOT NULL,
+        recorded_at INTEGER NOT NULL,
+        status TEXT NOT NULL,
+        top_tier_size INTEGER,
+        report TEXT NOT NULL
+    );
+    CREATE INDEX IF NOT EXISTS reports_by_network ON reports (network, id);
+";
+
+fn store_error(_: rusqlite::Error) -> FbasError {
+    FbasError::IoError("report store query failed")
+}
+
+/// A report as recorded in a `ReportStore`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct StoredReport {
+    pub network: String,
+    /// The hash of the snapshot analyzed, see `FbasAnalyzer::snapshot_hash`.
+    pub snapshot_hash: String,
+    /// When the report was recorded, in seconds since the Unix epoch.
+    pub recorded_at: u64,
+    pub report: AnalysisReport,
+}
+
+/// The size of the top tier of a network at some point, see
+/// `ReportStore::top_tier_size_history`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TopTierSize {
+    pub snapshot_hash: String,
+    /// When the report was recorded, in seconds since the Unix epoch.
+    pub recorded_at: u64,
+    pub size: usize,
+}
+
+/// Analysis reports of one or more networks over time, persisted in a SQLite
+/// database, e.g. as the storage of a small monitoring deployment.
+pub struct ReportStore {
+    conn: Connection,
+}
+
+impl ReportStore {
+    /// Opens the store in the database file at `path`, creating it if needed.
+    pub fn open(path: impl AsRef<Path>) -> Result<Self, FbasError> {
+        Self::with_connection(Connection::open(path).map_err(store_error)?)
+    }
+
+    /// Opens a store held in memory, gone once dropped.
+    pub fn open_in_memory() -> Result<Self, FbasError> {
+        Self::with_connection(Connection::open_in_memory().map_err(store_error)?)
+    }
+
+    fn with_connection(conn: Connection) -> Result<Self, FbasError> {
+        conn.execute_batch(SCHEMA).map_err(store_error)?;
+        Ok(Self { conn })
+    }
+
+    /// Records `report` of the snapshot of `network` with `snapshot_hash`.
+    /// The top tier size comes from `report.robustness`, if it was computed.
+    pub fn insert(
+        &self,
+        network: &str,
+        snapshot_hash: &str,
+        report: &AnalysisReport,
+    ) -> Result<(), FbasError> {
+        let recorded_at = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |elapsed| elapsed.as_secs());
+        let status = match report.status {
+            SolveStatus::SAT(_) => "SAT",
+            SolveStatus::UNSAT => "UNSAT",
+            SolveStatus::UNKNOWN => "UNKNOWN",
+        };
+        let top_tier_size = report.robustness.as_ref().map(|r| r.top_tier_size as i64);
+        self.conn
+            .execute(
+                "INSERT INTO reports
+                    (network, snapshot_hash, recorded_at, status, top_tier_size, report)
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+                params![
+                    network,
+                    snapshot_hash,
+                    recorded_at as i64,
+                    status,
+                    top_tier_size,
+                    report.to_json()
+                ],
+            )
+            .map_err(store_error)?;
+        Ok(())
+    }
+
+    /// The latest report of each network, in network order.
+    pub fn latest_reports(&self) -> Result<Vec<StoredReport>, FbasError> {
+        let mut statement = self
+            .conn
+            .prepare(
+                "SELECT network, snapshot_hash, recorded_at, report FROM reports
+                 WHERE id IN (SELECT MAX(id) FROM reports GROUP BY network)
+                 ORDER BY network",
+            )
+            .map_err(store_error)?;
+        let rows = statement
+            .query_map([], |row| {
+                Ok((row.get(0)?, row.get(1)?, row.get(2)?, row.get(3)?))
+            })
+            .map_err(store_error)?;
+        rows.map(|row| {
+            let (network, snapshot_hash, recorded_at, report): (String, String, i64, String) =
+                row.map_err(store_error)?;
+            Ok(StoredReport {
+                network,
+                snapshot_hash,
+                recorded_at: recorded_at as u64,
+                report: AnalysisReport::from_json(&report)?,
+            })
+        })
+        .collect()
+    }
+
+    /// The hash of the latest snapshot of `network` recorded, `None` if there
+    /// is none. Comparing it with the hash of a new snapshot tells whether it
+    /// needs to be analyzed again.
+    pub fn latest_snapshot_hash(&self, network: &str) -> Result<Option<String>, FbasError> {
+        self.conn
+            .query_row(
+                "SELECT snapshot_hash FROM reports WHERE network = ?1
+                 ORDER BY id DESC LIMIT 1",
+                params![network],
+                |row| row.get(0),
+            )
+            .optional()
+            .map_err(store_error)
+    }
+
+    /// The size of the top tier of `network` in the order the reports were
+    /// recorded, skipping reports without it.
+    pub fn top_tier_size_history(&self, network: &str) -> Result<Vec<TopTierSize>, FbasError> {
+        let mut statement = self
+            .conn
+            .prepare(
+                "SELECT snapshot_hash, recorded_at, top_tier_size FROM reports
+                 WHERE network = ?1 AND top_tier_size IS NOT NULL
+                 ORDER BY id",
+            )
+            .map_err(store_error)?;
+        let rows = statement
+            .query_map(params![network], |row| {
+                Ok(TopTierSize {
+                    snapshot_hash: row.get(0)?,
+                    recorded_at: row.get::<_, i64>(1)? as u64,
+                    size: row.get::<_, i64>(2)? as usize,
+                })
+            })
+            .map_err(store_error)?;
+        rows.collect::<Result<_, _>>().map_err(store_error)
+    }
+}
@@ -0,0 +1,149 @@
+use crate::{
+    fbas::{Fbas, FbasError, QuorumSetMap},
+    Callbacks, DiagnosticsSink, FbasAnalyzer, SolveStatus,
+};
+use std::{collections::BTreeSet, sync::Arc};
+
+// Raising the threshold of a qset only removes slices, so every quorum of the
+// network with the higher threshold is a quorum with the lower one. Hence
+// quorum intersection, once reached, holds for every higher threshold, and
+// the availability of a quorum, once lost, for none. Availability is a cheap
+// graph check done for every threshold, while intersection is found by a
+// binary search for the threshold where it flips, so that a sweep takes a
+// logarithmic number of solves.
+
+/// One threshold of a `ThresholdSweep`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ThresholdSweepRow {
+    pub threshold: u32,
+    /// Whether a quorum exists.
+    pub available: bool,
+    /// Whether the network enjoys quorum intersection, `None` if the solver
+    /// didn't reach a conclusion for this threshold.
+    pub intersecting: Option<bool>,
+}
+
+/// Safety and availability of the network over the thresholds of a qset, see
+/// `FbasAnalyzer::threshold_sweep`.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ThresholdSweep {
+    /// The validators using the qset, in name order.
+    pub validators: Vec<String>,
+    /// One row per threshold, from 1 to the number of members of the qset.
+    pub rows: Vec<ThresholdSweepRow>,
+}
+
+impl ThresholdSweep {
+    /// The smallest threshold with quorum intersection, if any and known.
+    pub fn intersection_threshold(&self) -> Option<u32> {
+        let first = self
+            .rows
+            .iter()
+            .find(|row| row.intersecting != Some(false))?;
+        (first.intersecting == Some(true)).then_some(first.threshold)
+    }
+
+    /// The largest threshold with a quorum, if any.
+    pub fn availability_threshold(&self) -> Option<u32> {
+        self.rows
+            .iter()
+            .rev()
+            .find(|row| row.available)
+            .map(|row| row.threshold)
+    }
+}
+
+impl std::fmt::Display for ThresholdSweep {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        writeln!(f, "threshold  available  intersecting")?;
+        for row in &self.rows {
+            let intersecting = match row.intersecting {
+                Some(true) => "yes",
+                Some(false) => "no",
+                None => "unknown",
+            };
+            let available = if row.available { "yes" } else { "no" };
+            writeln!(
+                f,
+                "{:>9}  {:>9}  {:>12}",
+                row.threshold, available, intersecting
+            )?;
+        }
+        Ok(())
+    }
+}
+
+// Sets the threshold of the qset of `validators` to `threshold`.
+fn with_threshold(qsm: &QuorumSetMap, validators: &[String], threshold: u32) -> QuorumSetMap {
+    let mut swept = qsm.clone();
+    for v in validators {
+        if let Some(qset) = swept.get_mut(v) {
+            let qset = Arc::make_mut(qset);
+            qset.threshold = threshold;
+            qset.threshold_percent = None;
+        }
+    }
+    swept
+}
+
+impl<Cb: Callbacks> FbasAnalyzer<Cb> {
+    /// Varies the threshold of the top-level qset of `validator`, and of
+    /// every validator with the same qset, from 1 to its number of members,
+    /// and reports for each whether a quorum exists and whether quorums
+    /// intersect: the data of a threshold vs safety plot. Both are monotone
+    /// in the threshold, so intersection is only solved for a logarithmic
+    /// number of thresholds, each by a separate solver driven by callbacks
+    /// from `new_cb`. Unknown validators are left out of the qset, as in the
+    /// analysis.
+    pub fn threshold_sweep<C: Callbacks>(
+        &self,
+        validator: &str,
+        mut new_cb: impl FnMut() -> C,
+    ) -> Result<ThresholdSweep, FbasError> {
+        let fbas = &self.fbas;
+        let qsm = fbas.quorum_set_map()?;
+        let qset = fbas.validator_qset(fbas.try_get_validator_index(validator)?)?;
+        let validators: Vec<String> = qsm
+            .iter()
+            .filter(|(_, other)| ***other == qset)
+            .map(|(v, _)| v.clone())
+            .collect();
+        let members = (qset.validators.len() + qset.inner_sets.len()) as u32;
+
+        let build = |threshold| {
+            let swept = with_threshold(&qsm, &validators, threshold);
+            Fbas::from_quorum_set_map(&swept)
+        };
+        let mut rows = (1..=members)
+            .map(|threshold| {
+                Ok(ThresholdSweepRow {
+                    threshold,
+                    available: !build(threshold)?.max_quorum(&BTreeSet::new()).is_empty(),
+                    intersecting: None,
+                })
+            })
+            .collect::<Result<Vec<_>, FbasError>>()?;
+
+        // thresholds below `lo` are known not to intersect, those above `hi`
+        // to intersect
+        let (mut lo, mut hi) = (1, members);
+        while lo <= hi {
+            let mid = lo + (hi - lo) / 2;
+            let mut analyzer = FbasAnalyzer::from_fbas(build(mid)?, new_cb())?
+                .with_diagnostics_sink(DiagnosticsSink::Silent);
+            match analyzer.solve() {
+                SolveStatus::UNSAT => hi = mid - 1,
+                SolveStatus::SAT(_) => lo = mid + 1,
+                SolveStatus::UNKNOWN => break,
+            }
+        }
+        for row in rows.iter_mut() {
+            if row.threshold < lo {
+                row.intersecting = Some(false);
+            } else if row.threshold > hi {
+                row.intersecting = Some(true);
+            }
+        }
+        Ok(ThresholdSweep { validators, rows })
+    }
+}
@@ -0,0 +1,123 @@
+use crate::fbas::Fbas;
+
+// Spans for the phases of an analysis, started on the global OpenTelemetry
+// tracer with the `otel` feature and no-ops otherwise, so the call sites don't
+// need to be gated. As for any instrumented library, installing a tracer
+// provider and exporter is up to the application; without one the global
+// tracer drops everything.
+
+#[cfg(feature = "otel")]
+const TRACER: &str = "stellar-quorum-analyzer";
+
+/// A span ending when dropped.
+pub(crate) struct Span {
+    #[cfg(feature = "otel")]
+    inner: opentelemetry::global::BoxedSpan,
+}
+
+impl Span {
+    pub(crate) fn start(name: &'static str) -> Self {
+        #[cfg(feature = "otel")]
+        {
+            use opentelemetry::trace::Tracer;
+            Self {
+                inner: opentelemetry::global::tracer(TRACER).start(name),
+            }
+        }
+        #[cfg(not(feature = "otel"))]
+        {
+            let _ = name;
+            Self {}
+        }
+    }
+
+    pub(crate) fn set_int(&mut self, key: &'static str, value: u64) {
+        #[cfg(feature = "otel")]
+        {
+            use opentelemetry::trace::Span as _;
+            let value = i64::try_from(value).unwrap_or(i64::MAX);
+            self.inner
+                .set_attribute(opentelemetry::KeyValue::new(key, value));
+        }
+        #[cfg(not(feature = "otel"))]
+        let _ = (key, value);
+    }
+
+    pub(crate) fn set_str(&mut self, key: &'static str, value: impl FnOnce() -> String) {
+        #[cfg(feature = "otel")]
+        {
+            use opentelemetry::trace::Span as _;
+            self.inner
+                .set_attribute(opentelemetry::KeyValue::new(key, value()));
+        }
+        #[cfg(not(feature = "otel"))]
+        let _ = (key, value);
+    }
+
+    /// Records the sizes of a network and the hash identifying it.
+    pub(crate) fn set_network(&mut self, network: &NetworkTag) {
+        self.set_str("network.hash", || network.hash.clone());
+        self.set_int("network.validators", network.validators as u64);
+        self.set_int("network.vertices", network.vertices as u64);
+    }
+}
+
+/// What spans record about a network: its sizes and a hash identifying it,
+/// the same for networks with the same validators and qsets. Hashing goes
+/// through the whole network, so it is done once per analyzer, when it is
+/// constructed, rather than by every span; in particular not between entering
+/// `solve` and starting the search, where an interrupt could be missed.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct NetworkTag {
+    hash: String,
+    validators: usize,
+    vertices: usize,
+}
+
+impl NetworkTag {
+    pub(crate) fn of(fbas: &Fbas) -> Self {
+        Self {
+            hash: network_hash(fbas),
+            validators: fbas.validators.len(),
+            vertices: fbas.graph.node_count(),
+        }
+    }
+}
+
+impl Drop for Span {
+    fn drop(&mut self) {
+        #[cfg(feature = "otel")]
+        {
+            use opentelemetry::trace::Span as _;
+            self.inner.end();
+        }
+    }
+}
+
+// Hashes the qsets of the network as seen by the analysis, keyed by
+// validator. Only stable within a build of the crate, which is enough to
+// correlate the spans of one service.
+#[cfg(feature = "otel")]
+fn network_hash(fbas: &Fbas) -> String {
+    use std::hash::{Hash, Hasher};
+    #[cfg(test)]
+    NETWORK_HASHES.with(|count| count.set(count.get() + 1));
+    let mut hasher = std::collections::hash_map::DefaultHasher::new();
+    match fbas.quorum_set_map() {
+        Ok(qsm) => qsm.hash(&mut hasher),
+        Err(_) => fbas.validators.len().hash(&mut hasher),
+    }
+    format!("{:016x}", hasher.finish())
+}
+
+// The number of networks hashed on this thread, for tests to check that it
+// happens once per analyzer.
+#[cfg(all(feature = "otel", test))]
+thread_local! {
+    pub(crate) static NETWORK_HASHES: std::cell::Cell<usize> = const { std::cell::Cell::new(0) };
+}
+
+#[cfg(not(feature = "otel"))]
+fn network_hash(_: &Fbas) -> String {
+    String::new()
+}
@@ -1,4 +1,5 @@
 mod analyze;
+mod differential;
 
 #[cfg(any(feature = "json", test))]
 mod parse;
@@ -1,6 +1,25 @@
-use crate::{FbasAnalyzer, SolveStatus};
+use crate::fbas::Fbas;
+use crate::fbas_analyze::{
+    encode_fbas_with, for_each_combination, CardinalityEncoding, ClauseArena,
+};
+use crate::json_parser::organizations_from_json;
+use crate::pruning::{DefaultPruning, PruningStrategy};
+use crate::{
+    AnalyzerPool, Availability, BranchingHeuristic, Deadline, DiagnosticsSink, FbasAnalyzer,
+    FbasError, FbasWarning, InputFormat, LimitedStatus, LocalView, MultiNetworkReport,
+    QsetLintKind, QsetMembership, Quorum, RedTeamResult, ResourceLimits, SnapshotChange,
+    SolvePhase, SolveStatus, TimeBudget, TrustEdge, UncertainQsets, ValidatorOrder,
+};
 use batsat::callbacks::{AsyncInterrupt, Basic};
-use std::{io::BufRead, str::FromStr};
+use batsat::{intmap::AsIndex, lbool, Lit, Solver, SolverInterface, Var};
+use itertools::Itertools;
+use petgraph::graph::NodeIndex;
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    io::BufRead,
+    str::FromStr,
+    time::{Duration, Instant},
+};
 
 #[test]
 fn test_solver_interrupt() -> Result<(), Box<dyn std::error::Error>> {
@@ -97,3 +116,3473 @@ fn test_random_data() -> std::io::Result<()> {
     }
     Ok(())
 }
+
+#[test]
+fn test_literal_for() -> Result<(), Box<dyn std::error::Error>> {
+    let mut solver =
+        FbasAnalyzer::from_json_path("./tests/test_data/conflicted_3.json", Basic::default())?;
+    let res = solver.solve();
+    assert!(matches!(res, SolveStatus::SAT(_)));
+
+    let (qa, qb) = solver.get_potential_split()?;
+    for v in qa.iter() {
+        let la = solver.literal_for(v, Quorum::A)?;
+        let lb = solver.literal_for(v, Quorum::B)?;
+        assert_ne!(la, lb);
+        assert!(!qb.contains(v));
+    }
+    assert!(solver.literal_for("PK_UNKNOWN", Quorum::A).is_err());
+    Ok(())
+}
+
+#[test]
+fn test_solve_with_unreachable() -> Result<(), Box<dyn std::error::Error>> {
+    let mut solver =
+        FbasAnalyzer::from_json_path("./tests/test_data/conflicted_3.json", Basic::default())?;
+    assert_eq!(solver.solve_with_unreachable(&["PK21"]), SolveStatus::UNSAT);
+    // the configured topology is unaffected by the overlay
+    assert!(matches!(solver.solve(), SolveStatus::SAT(_)));
+    Ok(())
+}
+
+#[test]
+fn test_red_team() -> Result<(), Box<dyn std::error::Error>> {
+    let mut solver =
+        FbasAnalyzer::from_json_path("./tests/test_data/symmetric_4.json", Basic::default())?;
+    assert_eq!(solver.solve(), SolveStatus::UNSAT);
+
+    // a single attacker needs to be in every qset to bring all thresholds down
+    // to two honest validators
+    let res = solver.red_team(1, 3, Basic::default())?;
+    assert_eq!(res, RedTeamResult::Safe);
+    match solver.red_team(1, 4, Basic::default())? {
+        RedTeamResult::Split {
+            quorum_a,
+            quorum_b,
+            injected_into,
+        } => {
+            assert!(!quorum_a.is_empty() && !quorum_b.is_empty());
+            assert!(quorum_a.iter().all(|v| !quorum_b.contains(v)));
+            assert_eq!(injected_into.len(), 4);
+        }
+        res => panic!("expected a split, got {:?}", res),
+    }
+    Ok(())
+}
+
+#[test]
+fn test_solve_phase() -> Result<(), Box<dyn std::error::Error>> {
+    // neither validator's qset can be satisfied, so no quorum exists and the
+    // search is skipped entirely
+    let mut solver =
+        FbasAnalyzer::from_json_path("./tests/test_data/missing_1.json", Basic::default())?;
+    assert_eq!(solver.get_stats().phase, SolvePhase::NotSolved);
+    assert_eq!(solver.solve(), SolveStatus::UNSAT);
+    assert_eq!(solver.get_stats().phase, SolvePhase::PreCheck);
+    assert_eq!(solver.get_stats().num_decisions, 0);
+
+    let mut solver =
+        FbasAnalyzer::from_json_path("./tests/test_data/symmetric_4.json", Basic::default())?;
+    assert_eq!(solver.solve(), SolveStatus::UNSAT);
+    assert_eq!(solver.get_stats().phase, SolvePhase::Search);
+    Ok(())
+}
+
+#[test]
+fn test_shared_slice_propositions() -> Result<(), Box<dyn std::error::Error>> {
+    // all four validators point to the same qset, so the slice proposition
+    // for "qset is in the quorum" is shared between them: 2 * 5 vertex
+    // variables, 2 * 4 slices of the 3-of-4 qset, plus 2 shared slices
+    let mut solver =
+        FbasAnalyzer::from_json_path("./tests/test_data/symmetric_4.json", Basic::default())?;
+    assert_eq!(solver.get_stats().num_vars, 20);
+    assert_eq!(solver.solve(), SolveStatus::UNSAT);
+    Ok(())
+}
+
+#[test]
+fn test_threshold_exceeds_members_warning() -> Result<(), Box<dyn std::error::Error>> {
+    // both validators only trust an unknown validator, leaving their shared
+    // qset without any members
+    let solver =
+        FbasAnalyzer::from_json_path("./tests/test_data/missing_1.json", Basic::default())?;
+    assert_eq!(
+        solver.get_warnings(),
+        &[FbasWarning::ThresholdExceedsMembers {
+            threshold: 1,
+            threshold_percent: None,
+            members: 0,
+            validators: vec!["PK1".to_string(), "PK2".to_string()],
+        }]
+    );
+
+    let solver =
+        FbasAnalyzer::from_json_path("./tests/test_data/symmetric_4.json", Basic::default())?;
+    assert!(solver.get_warnings().is_empty());
+    Ok(())
+}
+
+#[test]
+fn test_brute_force_matches_sat() -> Result<(), Box<dyn std::error::Error>> {
+    let mut paths = vec![];
+    for dir in ["./tests/test_data/", "./tests/test_data/random/"] {
+        for entry in std::fs::read_dir(dir)? {
+            let path = entry?.path();
+            // skip the org mappings, which are not networks
+            let is_orgs = path.to_str().is_some_and(|p| p.ends_with("_orgs.json"));
+            if path.extension().is_some_and(|ext| ext == "json") && !is_orgs {
+                paths.push(path);
+            }
+        }
+    }
+
+    let mut checked = 0;
+    for path in paths {
+        let path = path.as_os_str().to_str().unwrap();
+        if Fbas::from_json_path(path)?.validators.len() > 12 {
+            continue;
+        }
+        let mut solver = FbasAnalyzer::from_json_path(path, Basic::default())?;
+        let expected = matches!(solver.solve(), SolveStatus::SAT(_));
+        let res = solver.solve_brute_force()?;
+        assert_eq!(matches!(res, SolveStatus::SAT(_)), expected, "{}", path);
+        checked += 1;
+    }
+    assert!(checked >= 10);
+    Ok(())
+}
+
+#[test]
+fn test_org_level_analysis() -> Result<(), Box<dyn std::error::Error>> {
+    let dir = "./tests/test_data/random/";
+    for case in [
+        "almost_symmetric_network_6_orgs_delete_prob_factor_2",
+        "almost_symmetric_network_8_orgs_delete_prob_factor_1",
+    ] {
+        let path = format!("{}{}.json", dir, case);
+        let orgs_path = format!("{}{}_orgs.json", dir, case);
+        let mut org_solver =
+            FbasAnalyzer::from_json_path_org_level(&path, &orgs_path, Basic::default())?;
+        // one node per organization
+        assert!(org_solver.fbas.validators.len() <= 8);
+        let mut solver = FbasAnalyzer::from_json_path(&path, Basic::default())?;
+        let is_sat = matches!(solver.solve(), SolveStatus::SAT(_));
+        assert_eq!(
+            matches!(org_solver.solve(), SolveStatus::SAT(_)),
+            is_sat,
+            "{}",
+            case
+        );
+        if is_sat {
+            let (qa, _) = org_solver.get_potential_split()?;
+            assert!(qa.iter().all(|org| org.starts_with("org")));
+        }
+    }
+    Ok(())
+}
+
+#[test]
+fn test_collapse_organizations() -> Result<(), Box<dyn std::error::Error>> {
+    let mut orgs = BTreeMap::new();
+    for org in ["a", "b", "c"] {
+        orgs.insert(
+            org.to_string(),
+            (1..=3).map(|i| format!("{org}{i}")).collect(),
+        );
+    }
+    let orgs = crate::Organizations::new(orgs);
+    // d has no organization and stays as it is
+    let solver = FbasAnalyzer::from_dsl(
+        "a1, a2, a3, b1, b2, b3, c1, c2, c3: \
+         2-of(2-of(a1, a2, a3), 2-of(b1, b2, b3), 2-of(c1, c2, c3))\n\
+         d: 2-of(2-of(a1, a2, a3), 3-of(b1, b2, b3))",
+        Basic::default(),
+    )?;
+    let mut collapsed = solver.collapse_organizations(&orgs, Basic::default())?;
+    assert_eq!(collapsed.fbas.validators.len(), 4);
+    assert_eq!(
+        collapsed.describe_qset("a", &BTreeMap::new())?,
+        "2 of: a, b, c\n"
+    );
+    assert_eq!(
+        collapsed.describe_qset("d", &BTreeMap::new())?,
+        "2 of: a, b\n"
+    );
+    assert_eq!(collapsed.solve(), SolveStatus::UNSAT);
+
+    // a split among organizations is one of the collapsed network
+    let solver = FbasAnalyzer::from_dsl(
+        "a1, a2, a3: 2-of(a1, a2, a3)\n\
+         b1, b2, b3, c1, c2, c3: 2-of(2-of(b1, b2, b3), 2-of(c1, c2, c3))",
+        Basic::default(),
+    )?;
+    let mut collapsed = solver.collapse_organizations(&orgs, Basic::default())?;
+    assert!(matches!(collapsed.solve(), SolveStatus::SAT(_)));
+    let (qa, qb) = collapsed.get_potential_split()?;
+    let mut split = [qa, qb];
+    split.sort();
+    assert_eq!(split, [vec!["a"], vec!["b", "c"]]);
+
+    // the validators of an organization must agree, thresholds aside
+    let solver = FbasAnalyzer::from_dsl(
+        "a1, a2: 2-of(a1, a2, a3)\n\
+         a3: 2-of(2-of(a1, a2, a3), 2-of(b1, b2, b3))\n\
+         b1, b2, b3: 2-of(b1, b2, b3)",
+        Basic::default(),
+    )?;
+    assert!(solver
+        .collapse_organizations(&orgs, Basic::default())
+        .is_err());
+    Ok(())
+}
+
+#[test]
+fn test_trust_graph() -> Result<(), Box<dyn std::error::Error>> {
+    // x is unknown, and c trusts itself
+    let solver = FbasAnalyzer::from_dsl(
+        "a: 2-of(b, 1-of(c, x))\nb: 1-of(a)\nc: 2-of(a, c)",
+        Basic::default(),
+    )?;
+    let trust = solver.trust_graph()?;
+    let names: Vec<&str> = trust.node_weights().map(String::as_str).collect();
+    assert_eq!(names, vec!["a", "b", "c"]);
+    let mut edges: Vec<(&str, &str)> = trust
+        .edge_indices()
+        .filter_map(|e| trust.edge_endpoints(e))
+        .map(|(from, to)| (names[from.index()], names[to.index()]))
+        .collect();
+    edges.sort();
+    assert_eq!(edges, vec![("a", "b"), ("a", "c"), ("b", "a"), ("c", "a")]);
+    Ok(())
+}
+
+#[test]
+fn test_diff() -> Result<(), Box<dyn std::error::Error>> {
+    let before =
+        FbasAnalyzer::from_dsl("a, b, c: 2-of(a, b, c)\nd: 2-of(a, b, x)", Basic::default())?;
+    // reordering members and unknown validators make no difference
+    let same =
+        FbasAnalyzer::from_dsl("a, b, c: 2-of(c, b, a)\nd: 2-of(b, a, y)", Basic::default())?;
+    let diff = before.diff(&same, Basic::default)?;
+    assert!(diff.is_empty());
+    assert!(!diff.breaks_intersection());
+    assert_eq!(diff.intersecting_before, Some(true));
+
+    // d leaves, e joins, and c lowers its threshold
+    let after = FbasAnalyzer::from_dsl(
+        "a, b: 2-of(a, b, c)\nc: 1-of(c, e)\ne: 1-of(e)",
+        Basic::default(),
+    )?;
+    let diff = before.diff(&after, Basic::default)?;
+    assert_eq!(diff.added, vec!["e"]);
+    assert_eq!(diff.removed, vec!["d"]);
+    assert_eq!(diff.changed.len(), 1);
+    assert_eq!(diff.changed[0].validator, "c");
+    assert_eq!(diff.changed[0].after, "1 of: c, e\n");
+    assert_eq!(diff.intersecting_after, Some(false));
+    assert!(diff.breaks_intersection());
+    // c splits off only with both e joining and its own change
+    assert!(diff.culprits.is_empty());
+    assert_eq!(after.fbas.diff(&before.fbas)?.removed, vec!["e"]);
+
+    // of the two validators joining, only e splits off
+    let after = FbasAnalyzer::from_dsl(
+        "a, b, c: 2-of(a, b, c)\nd: 2-of(a, b, x)\ne: 1-of(e)\nf: 2-of(a, b, c)",
+        Basic::default(),
+    )?;
+    let diff = before.diff(&after, Basic::default)?;
+    assert_eq!(diff.added, vec!["e", "f"]);
+    assert_eq!(diff.culprits, vec![SnapshotChange::Added("e".into())]);
+    assert_eq!(diff.culprits[0].to_string(), "e added");
+    // nothing to explain when intersection holds
+    assert!(after.diff(&before, Basic::default)?.culprits.is_empty());
+    Ok(())
+}
+
+#[test]
+fn test_timeline() -> Result<(), Box<dyn std::error::Error>> {
+    let snapshots = [
+        ("t0", "a, b, c: 2-of(a, b, c)"),
+        ("t1", "a, b, c: 2-of(c, b, a)"),
+        // d joins the top tier, which now tolerates one failure
+        ("t2", "a, b, c, d: 3-of(a, b, c, d)"),
+        // and c splits off
+        ("t3", "a, b, d: 2-of(a, b, d)\nc: 1-of(c)"),
+    ];
+    let analyzers = snapshots
+        .iter()
+        .map(|(label, dsl)| {
+            Ok((
+                label.to_string(),
+                FbasAnalyzer::from_dsl(dsl, Basic::default())?,
+            ))
+        })
+        .collect::<Result<Vec<_>, FbasError>>()?;
+    let timeline = crate::Timeline::from_snapshots(analyzers, 2, Basic::default)?;
+    let entries = &timeline.entries;
+    assert_eq!(entries.len(), 4);
+    assert!(entries[0].changes.is_empty());
+    assert_eq!(entries[0].intersecting, Some(true));
+    assert_eq!(
+        entries[0].top_tier,
+        Some(vec!["a".into(), "b".into(), "c".into()])
+    );
+    // t1 only reorders the qset
+    assert!(entries[1].changes.is_empty());
+    assert_eq!(entries[1].top_tier, entries[0].top_tier);
+    assert_eq!(entries[2].changes.added, vec!["d"]);
+    assert_eq!(entries[2].top_tier_joined, vec!["d"]);
+    assert!(entries[2].critical_sets_changed);
+    assert_eq!(
+        entries[2]
+            .blocking_sets
+            .iter()
+            .flatten()
+            .map(Vec::len)
+            .min(),
+        Some(2)
+    );
+    assert_eq!(entries[3].intersecting, Some(false));
+    assert!(entries[3].changes.breaks_intersection());
+    // every qset changes, but intersection is back with only c's reverted
+    assert_eq!(entries[3].changes.changed.len(), 4);
+    assert_eq!(
+        entries[3].changes.culprits,
+        vec![SnapshotChange::QsetChanged("c".into())]
+    );
+    // c is a minimal quorum on its own, so it stays in the top tier
+    assert!(entries[3].top_tier_left.is_empty());
+    let rendered = timeline.to_string();
+    assert_eq!(rendered.lines().count(), 4);
+    assert!(rendered.lines().nth(2).unwrap().contains("+d"));
+    assert!(rendered.lines().nth(3).unwrap().starts_with("t3: SPLIT"));
+    assert!(rendered
+        .lines()
+        .nth(3)
+        .unwrap()
+        .contains("split by qset of c changed"));
+
+    // the same from files
+    let dir = std::env::temp_dir().join(format!("timeline-{}", std::process::id()));
+    std::fs::create_dir_all(&dir)?;
+    for (label, dsl) in snapshots {
+        std::fs::write(dir.join(format!("{label}.json")), dsl)?;
+    }
+    std::fs::write(dir.join("notes.txt"), "not a snapshot")?;
+    let from_dir = crate::Timeline::from_json_dir(dir.to_str().unwrap(), 2, Basic::default);
+    std::fs::remove_dir_all(&dir)?;
+    let from_dir = from_dir?;
+    assert_eq!(from_dir.entries.len(), 4);
+    assert_eq!(from_dir.entries[0].snapshot, "t0.json");
+    assert_eq!(from_dir.entries[3].intersecting, Some(false));
+
+    // expired callbacks interrupt every search but the analyzer's own
+    let analyzer = FbasAnalyzer::from_dsl(snapshots[2].1, Basic::default())?;
+    let interrupted = crate::Timeline::from_snapshots([("t2".into(), analyzer)], 2, || {
+        Deadline::after(Duration::ZERO)
+    })?;
+    let entry = &interrupted.entries[0];
+    assert_eq!(entry.intersecting, Some(true));
+    assert_eq!(entry.top_tier, None);
+    assert_eq!(entry.blocking_sets, None);
+    assert!(!entry.splitting_sets.complete);
+    Ok(())
+}
+
+#[test]
+fn test_top_tier_alerts() -> Result<(), Box<dyn std::error::Error>> {
+    use crate::{AnalysisReport, ReportSink, Timeline, TopTierAlert};
+
+    // validators with their home domain and qset
+    type Node<'a> = (&'a str, &'a str, usize, &'a [&'a str]);
+    let snapshot = |nodes: &[Node]| {
+        let nodes: Vec<_> = nodes
+            .iter()
+            .map(|(v, domain, threshold, qset)| {
+                serde_json::json!({
+                    "publicKey": v,
+                    "homeDomain": domain,
+                    "quorumSet": { "threshold": threshold, "validators": qset, "innerQuorumSets": [] },
+                })
+            })
+            .collect();
+        FbasAnalyzer::from_json_str(
+            &serde_json::Value::from(nodes).to_string(),
+            Basic::default(),
+        )
+    };
+    let abc: &[&str] = &["a", "b", "c"];
+    let t0 = snapshot(&[
+        ("a", "x.org", 2, abc),
+        ("b", "x.org", 2, abc),
+        ("c", "y.org", 2, abc),
+    ])?;
+    // d of a new organization joins the top tier, e only watches it
+    let abcd: &[&str] = &["a", "b", "c", "d"];
+    let t1 = snapshot(&[
+        ("a", "x.org", 3, abcd),
+        ("b", "x.org", 3, abcd),
+        ("c", "y.org", 3, abcd),
+        ("d", "z.org", 3, abcd),
+        ("e", "w.org", 1, &["a"]),
+    ])?;
+    let timeline =
+        Timeline::from_snapshots([("t0".into(), t0), ("t1".into(), t1)], 2, Basic::default)?;
+    let entry = &timeline.entries[1];
+    assert!(entry.top_tier_changed());
+    assert!(!timeline.entries[0].top_tier_changed());
+    assert_eq!(entry.changes.added, vec!["d", "e"]);
+    assert_eq!(
+        entry.top_tier_alert(),
+        Some(TopTierAlert {
+            snapshot: "t1".into(),
+            joined: vec!["d".into()],
+            left: vec![],
+            orgs_joined: vec!["z.org".into()],
+            orgs_left: vec![],
+            culprits: vec![SnapshotChange::Added("d".into())],
+        })
+    );
+    assert!(timeline
+        .to_string()
+        .lines()
+        .nth(1)
+        .unwrap()
+        .contains("+d by d added"));
+
+    struct Alerts(Vec<String>);
+    impl ReportSink for Alerts {
+        fn send(&mut self, _: &AnalysisReport) -> Result<(), FbasError> {
+            Ok(())
+        }
+
+        fn send_alert(&mut self, alert: &TopTierAlert) -> Result<(), FbasError> {
+            self.0.push(alert.to_json());
+            Ok(())
+        }
+    }
+    let mut alerts = Alerts(vec![]);
+    assert_eq!(timeline.send_top_tier_alerts(&mut alerts)?, 1);
+    let alert: serde_json::Value = serde_json::from_str(&alerts.0[0])?;
+    assert_eq!(
+        alert,
+        serde_json::json!({
+            "alert": "top_tier_changed",
+            "snapshot": "t1",
+            "joined": ["d"],
+            "left": [],
+            "orgs_joined": ["z.org"],
+            "orgs_left": [],
+            "culprits": [{ "change": "added", "validator": "d" }],
+        })
+    );
+    // closures only take reports
+    let mut reports = 0;
+    let mut closure = |_: &AnalysisReport| -> Result<(), FbasError> {
+        reports += 1;
+        Ok(())
+    };
+    assert_eq!(timeline.send_top_tier_alerts(&mut closure)?, 1);
+    assert_eq!(reports, 0);
+    Ok(())
+}
+
+#[test]
+fn test_subnetwork() -> Result<(), Box<dyn std::error::Error>> {
+    // the core intersects, the rest of the network doesn't
+    let solver = FbasAnalyzer::from_dsl(
+        "a, b, c, d: 3-of(a, b, c, d)\ne: 1-of(a, f)\nf: 1-of(f)",
+        Basic::default(),
+    )?;
+    let mut sub = solver.subnetwork(&["a", "b", "c", "d"], Basic::default())?;
+    assert_eq!(sub.fbas.validators.len(), 4);
+    assert_eq!(sub.solve(), SolveStatus::UNSAT);
+
+    // without d, thresholds stay as they are and b, c alone are no quorum
+    let mut sub = solver
+        .subnetwork(&["a", "b", "c", "e", "f"], Basic::default())?
+        .with_diagnostics_sink(DiagnosticsSink::Collected);
+    assert_eq!(sub.describe_qset("e", &BTreeMap::new())?, "1 of: a, f\n");
+    assert!(sub.is_quorum(&["a", "b", "c"])?);
+    assert!(!sub.contains_quorum(&["b", "c"])?);
+    assert!(matches!(sub.solve(), SolveStatus::SAT(_)));
+    assert!(sub
+        .take_diagnostics()
+        .contains(&"Validator d is unknown".to_string()));
+    assert!(solver.subnetwork(&["a", "x"], Basic::default()).is_err());
+    Ok(())
+}
+
+#[test]
+fn test_with_replaced_qset() -> Result<(), Box<dyn std::error::Error>> {
+    let solver = FbasAnalyzer::from_dsl(
+        "a: 2-of(a, 2-of(b, c, d))\nb, c, d: 3-of(a, b, c, d)",
+        Basic::default(),
+    )?;
+    // a's old qset and its inner set are no longer used and go away
+    let mut edited = solver.with_replaced_qset("a", "3-of(d, c, b, a)", Basic::default())?;
+    edited.check_invariants()?;
+    let rebuilt = FbasAnalyzer::from_dsl("a, b, c, d: 3-of(a, b, c, d)", Basic::default())?;
+    assert_eq!(
+        edited.fbas.graph.node_count(),
+        rebuilt.fbas.graph.node_count()
+    );
+    assert!(edited.fbas.diff(&rebuilt.fbas)?.is_empty());
+    assert_eq!(edited.solve(), SolveStatus::UNSAT);
+
+    // b trusting only itself splits it from the others
+    let mut edited = edited.with_replaced_qset("b", "1-of(b, x)", Basic::default())?;
+    edited.check_invariants()?;
+    assert!(matches!(edited.solve(), SolveStatus::SAT(_)));
+    assert!(edited
+        .qset_lints()
+        .iter()
+        .any(|lint| lint.validator == "b"
+            && matches!(lint.kind, QsetLintKind::ThresholdTooLow { .. })));
+
+    // the original is left as it was
+    assert_eq!(
+        solver.describe_qset("a", &BTreeMap::new())?,
+        "2 of:\n  a\n  2 of: b, c, d\n"
+    );
+    assert!(solver
+        .with_replaced_qset("x", "1-of(a)", Basic::default())
+        .is_err());
+    assert!(solver
+        .with_replaced_qset("a", "1-of(a", Basic::default())
+        .is_err());
+
+    // numbered as if loaded with the new qset
+    let solver = FbasAnalyzer::from_dsl(
+        "a: 1-of(a)\nb: 1-of(b)\nc: 1-of(c)\nd: 1-of(d)",
+        Basic::default(),
+    )?;
+    let edited = solver.with_replaced_qset("a", "1-of(b)", Basic::default())?;
+    let rebuilt =
+        FbasAnalyzer::from_dsl("a, b: 1-of(b)\nc: 1-of(c)\nd: 1-of(d)", Basic::default())?;
+    let vertices = |solver: &FbasAnalyzer<Basic>| {
+        format!("{:?}", solver.fbas.graph.node_weights().collect::<Vec<_>>())
+    };
+    assert_eq!(vertices(&edited), vertices(&rebuilt));
+    Ok(())
+}
+
+#[test]
+fn test_trust_reciprocity() -> Result<(), Box<dyn std::error::Error>> {
+    let analyzer =
+        FbasAnalyzer::from_json_path("./tests/test_data/symmetric_4.json", Basic::default())?;
+    let report = analyzer.trust_reciprocity()?;
+    assert_eq!(report.num_trust_relations, 12);
+    assert!(report.asymmetric.is_empty());
+    assert_eq!(report.reciprocity(), 1.0);
+
+    // PKX trusts PK11 and PK21, neither of which trusts it back
+    let analyzer =
+        FbasAnalyzer::from_json_path("./tests/test_data/conflicted_3.json", Basic::default())?;
+    let report = analyzer.trust_reciprocity()?;
+    assert_eq!(report.num_reciprocated, 0);
+    assert_eq!(
+        report.asymmetric,
+        vec![
+            ("PKX".to_string(), "PK11".to_string()),
+            ("PKX".to_string(), "PK21".to_string())
+        ]
+    );
+
+    let case = "./tests/test_data/random/almost_symmetric_network_6_orgs_delete_prob_factor_2";
+    let analyzer = FbasAnalyzer::from_json_path(&format!("{}.json", case), Basic::default())?;
+    let orgs = organizations_from_json(&format!("{}_orgs.json", case))?;
+    let report = analyzer.trust_reciprocity_org_level(&orgs)?;
+    assert!(report.num_trust_relations <= 6 * 5);
+    assert!(report
+        .asymmetric
+        .iter()
+        .all(|(a, b)| a != b && orgs.validators_of(a).is_some()));
+    Ok(())
+}
+
+#[test]
+fn test_influence_scores() -> Result<(), Box<dyn std::error::Error>> {
+    // PK11 and PK21 are each a quorum on their own, PKX needs both
+    let analyzer =
+        FbasAnalyzer::from_json_path("./tests/test_data/conflicted_3.json", Basic::default())?;
+    let scores = analyzer.influence_scores(1000, 42)?;
+    assert_eq!(scores.last(), Some(&("PKX".to_string(), 0.0)));
+    assert!(scores[..2].iter().all(|(_, s)| (0.4..0.6).contains(s)));
+    assert_eq!(scores.iter().map(|(_, s)| s).sum::<f64>(), 1.0);
+    assert_eq!(scores, analyzer.influence_scores(1000, 42)?);
+
+    let analyzer =
+        FbasAnalyzer::from_json_path("./tests/test_data/missing_1.json", Basic::default())?;
+    assert!(analyzer
+        .influence_scores(100, 0)?
+        .iter()
+        .all(|(_, s)| *s == 0.0));
+    Ok(())
+}
+
+#[test]
+fn test_shrink_and_into_report() -> Result<(), Box<dyn std::error::Error>> {
+    let path = "./tests/test_data/conflicted_3.json";
+    let mut solver = FbasAnalyzer::from_json_path(path, Basic::default())?;
+    let res = solver.solve();
+    assert!(matches!(res, SolveStatus::SAT(_)));
+    let split = solver.get_potential_split()?;
+    solver.shrink();
+    assert_eq!(solver.solve(), res);
+    assert_eq!(solver.get_stats().num_vars, 0);
+    assert_eq!(solver.get_stats().phase, SolvePhase::Search);
+    assert_eq!(solver.trust_reciprocity()?.num_trust_relations, 2);
+
+    let report = solver.into_report(None)?;
+    assert_eq!(report.status, res);
+    assert_eq!(report.potential_split, split);
+
+    let mut solver = FbasAnalyzer::from_json_path(path, Basic::default())?;
+    solver.solve();
+    let report = solver.into_report(None)?;
+    assert_eq!(report.potential_split, split);
+    assert!(report.stats.num_vars > 0);
+    Ok(())
+}
+
+#[cfg(feature = "xdr")]
+#[test]
+fn test_from_xdr_buf() -> Result<(), Box<dyn std::error::Error>> {
+    use crate::{Limits, NodeId, ScpQuorumSet};
+    use stellar_xdr::curr::{PublicKey, Uint256, WriteXdr};
+
+    let node_ids: Vec<NodeId> = (1..=4)
+        .map(|i| NodeId(PublicKey::PublicKeyTypeEd25519(Uint256([i; 32]))))
+        .collect();
+    let qset = ScpQuorumSet {
+        threshold: 3,
+        validators: node_ids.clone().try_into()?,
+        inner_sets: vec![].try_into()?,
+    };
+    let nodes = node_ids
+        .iter()
+        .map(|n| n.to_xdr(Limits::none()))
+        .collect::<Result<Vec<_>, _>>()?;
+    let qsets = vec![qset.to_xdr(Limits::none())?; nodes.len()];
+    let mut solver =
+        FbasAnalyzer::from_quorum_set_map_buf(nodes.iter(), qsets.iter(), Basic::default())?;
+    assert_eq!(solver.solve(), SolveStatus::UNSAT);
+    Ok(())
+}
+
+#[cfg(feature = "xdr")]
+#[test]
+fn test_from_xdr_dir_and_stream() -> Result<(), Box<dyn std::error::Error>> {
+    use crate::{Limits, NodeId, ScpQuorumSet};
+    use stellar_xdr::curr::{PublicKey, Uint256, WriteXdr};
+
+    let node_ids: Vec<NodeId> = (1..=4)
+        .map(|i| NodeId(PublicKey::PublicKeyTypeEd25519(Uint256([i; 32]))))
+        .collect();
+    let keys: Vec<String> = node_ids
+        .iter()
+        .map(|n| match &n.0 {
+            PublicKey::PublicKeyTypeEd25519(key) => {
+                stellar_strkey::ed25519::PublicKey(key.0).to_string()
+            }
+        })
+        .collect();
+    let qset = ScpQuorumSet {
+        threshold: 3,
+        validators: node_ids.clone().try_into()?,
+        inner_sets: vec![].try_into()?,
+    };
+    let expected = format!("3 of: {}\n", keys.join(", "));
+
+    // a directory of binary and base64 files, the last validator unknown
+    let dir = std::env::temp_dir().join(format!("sqa-xdr-dir-{}", std::process::id()));
+    std::fs::create_dir_all(&dir)?;
+    std::fs::write(
+        dir.join(format!("{}.xdr", keys[0])),
+        qset.to_xdr(Limits::none())?,
+    )?;
+    for key in &keys[1..3] {
+        let b64 = qset.to_xdr_base64(Limits::none())? + "\n";
+        std::fs::write(dir.join(format!("{}.b64", key)), b64)?;
+    }
+    std::fs::write(dir.join(format!("{}.xdr", keys[3])), "")?;
+    std::fs::write(dir.join(".DS_Store"), "junk")?;
+    let solver = FbasAnalyzer::from_quorum_set_dir(dir.to_str().unwrap(), Basic::default());
+    std::fs::remove_dir_all(&dir)?;
+    let mut solver = solver?;
+    // unknown validators are left out
+    assert_eq!(
+        solver.describe_qset(&keys[1], &Default::default())?,
+        format!("3 of: {}\n", keys[..3].join(", "))
+    );
+    assert_eq!(solver.solve(), SolveStatus::UNSAT);
+
+    // a text stream, one validator per line
+    let text: String = keys
+        .iter()
+        .map(|key| format!("{} {}\n", key, qset.to_xdr_base64(Limits::none()).unwrap()))
+        .collect();
+    let mut solver = FbasAnalyzer::from_quorum_set_stream(text.as_bytes(), Basic::default())?;
+    assert_eq!(
+        solver.describe_qset(&keys[3], &Default::default())?,
+        expected
+    );
+    assert_eq!(solver.solve(), SolveStatus::UNSAT);
+
+    // a binary stream of node and qset pairs
+    let mut binary = vec![];
+    for node in &node_ids {
+        binary.extend(node.to_xdr(Limits::none())?);
+        binary.extend(qset.to_xdr(Limits::none())?);
+    }
+    let mut solver = FbasAnalyzer::from_quorum_set_stream(binary.as_slice(), Basic::default())?;
+    assert_eq!(
+        solver.describe_qset(&keys[0], &Default::default())?,
+        expected
+    );
+    assert_eq!(solver.solve(), SolveStatus::UNSAT);
+
+    assert!(matches!(
+        FbasAnalyzer::from_quorum_set_stream(&binary[..binary.len() - 1], Basic::default()),
+        Err(FbasError::XdrDecodingError(_))
+    ));
+    assert!(matches!(
+        FbasAnalyzer::from_quorum_set_stream("not-a-key AAAA\n".as_bytes(), Basic::default()),
+        Err(FbasError::ParseError(_))
+    ));
+    Ok(())
+}
+
+#[test]
+fn test_from_dsl() -> Result<(), Box<dyn std::error::Error>> {
+    let mut solver = FbasAnalyzer::from_dsl("a, b, c, d: 3-of(a, b, c, d)", Basic::default())?;
+    assert_eq!(solver.solve(), SolveStatus::UNSAT);
+
+    let mut solver = FbasAnalyzer::from_dsl(
+        "a, b: 2-of(a, b)\n\
+         c, d: 2-of(c, d)\n\
+         e: 2-of(1-of(a, b), 1-of(c, d))",
+        Basic::default(),
+    )?;
+    assert!(matches!(solver.solve(), SolveStatus::SAT(_)));
+    let (mut qa, mut qb) = solver.get_potential_split()?;
+    qa.sort();
+    qb.sort();
+    assert!(qa.len() == 2 && qb.len() == 2 && qa != qb);
+    Ok(())
+}
+
+#[test]
+fn test_diagnostics_sink() -> Result<(), Box<dyn std::error::Error>> {
+    let dsl = "a, b: 2-of(a, b, c)";
+    let mut solver = FbasAnalyzer::from_dsl(dsl, Basic::default())?
+        .with_diagnostics_sink(DiagnosticsSink::Collected);
+    solver.solve_with_unreachable(&["d"]);
+    assert_eq!(
+        solver.take_diagnostics(),
+        vec!["Validator c is unknown", "Validator d is unknown"]
+    );
+    assert!(solver.take_diagnostics().is_empty());
+
+    let mut solver = FbasAnalyzer::from_dsl(dsl, Basic::default())?
+        .with_diagnostics_sink(DiagnosticsSink::Silent);
+    solver.solve();
+    assert!(solver.take_diagnostics().is_empty());
+    Ok(())
+}
+
+#[test]
+fn test_parallel_encoding() -> Result<(), Box<dyn std::error::Error>> {
+    let fbas = Fbas::from_json_path(
+        "./tests/test_data/random/almost_symmetric_network_8_orgs_delete_prob_factor_1.json",
+    )?;
+    let mut serial = Solver::new(Default::default(), Basic::default());
+    encode_fbas_with(
+        &fbas,
+        &mut serial,
+        |_| false,
+        1,
+        Default::default(),
+        |_| lbool::UNDEF,
+    )?;
+    let mut parallel = Solver::new(Default::default(), Basic::default());
+    encode_fbas_with(
+        &fbas,
+        &mut parallel,
+        |_| false,
+        4,
+        Default::default(),
+        |_| lbool::UNDEF,
+    )?;
+    assert_eq!(serial.num_vars(), parallel.num_vars());
+    assert_eq!(serial.num_clauses(), parallel.num_clauses());
+    assert_eq!(serial.solve_limited(&[]), parallel.solve_limited(&[]));
+    Ok(())
+}
+
+#[test]
+fn test_clause_arena() -> Result<(), Box<dyn std::error::Error>> {
+    let lits: Vec<Lit> = (0..4)
+        .map(|i| Lit::new(Var::from_index(i), i % 2 == 0))
+        .collect();
+    let clauses = vec![vec![lits[0], lits[1]], vec![], vec![!lits[2]], lits.clone()];
+    let mut arena = ClauseArena::default();
+    for clause in clauses.iter() {
+        arena.push(clause.iter().copied());
+    }
+    assert_eq!(
+        arena.iter().map(<[Lit]>::to_vec).collect::<Vec<_>>(),
+        clauses
+    );
+
+    // the encoding is the same as when each clause had its own vector
+    for (path, num_vars, num_clauses, intersects) in [
+        ("symmetric_4.json", 20, 50, true),
+        ("conflicted.json", 40, 79, false),
+        ("conflicted_3.json", 24, 43, false),
+        ("circular_2.json", 17, 19, true),
+        ("top_tier.json", 162, 529, true),
+        (
+            "random/almost_symmetric_network_8_orgs_delete_prob_factor_1.json",
+            220,
+            870,
+            true,
+        ),
+    ] {
+        let path = format!("./tests/test_data/{}", path);
+        let mut solver = FbasAnalyzer::from_json_path(&path, Basic::default())?;
+        let stats = solver.get_stats();
+        assert_eq!(
+            (stats.num_vars, stats.num_clauses),
+            (num_vars, num_clauses),
+            "{}",
+            path
+        );
+        assert_eq!(solver.solve() == SolveStatus::UNSAT, intersects, "{}", path);
+    }
+    Ok(())
+}
+
+#[cfg(feature = "otel")]
+#[test]
+fn test_otel_spans() -> Result<(), Box<dyn std::error::Error>> {
+    use crate::telemetry::NETWORK_HASHES;
+    use opentelemetry::{
+        trace::{self, SpanBuilder, SpanContext, Status},
+        Context, InstrumentationScope, KeyValue,
+    };
+    use std::{
+        borrow::Cow,
+        sync::{Arc, Mutex},
+        thread::ThreadId,
+        time::SystemTime,
+    };
+
+    // The spans started, with the thread that started them and the attributes
+    // set on them. The tracer provider is global, so other tests running at
+    // the same time record theirs too.
+    type Recorded = Arc<Mutex<Vec<(ThreadId, String, BTreeMap<String, String>)>>>;
+    struct Provider(Recorded);
+    struct Tracer(Recorded);
+    struct Recording {
+        spans: Recorded,
+        index: usize,
+        context: SpanContext,
+    }
+    impl trace::TracerProvider for Provider {
+        type Tracer = Tracer;
+        fn tracer_with_scope(&self, _: InstrumentationScope) -> Tracer {
+            Tracer(self.0.clone())
+        }
+    }
+    impl trace::Tracer for Tracer {
+        type Span = Recording;
+        fn build_with_context(&self, builder: SpanBuilder, _: &Context) -> Recording {
+            let mut spans = self.0.lock().unwrap();
+            let name = builder.name.to_string();
+            spans.push((std::thread::current().id(), name, BTreeMap::new()));
+            Recording {
+                spans: self.0.clone(),
+                index: spans.len() - 1,
+                context: SpanContext::empty_context(),
+            }
+        }
+    }
+    impl trace::Span for Recording {
+        fn add_event_with_timestamp<T>(&mut self, _: T, _: SystemTime, _: Vec<KeyValue>)
+        where
+            T: Into<Cow<'static, str>>,
+        {
+        }
+        fn span_context(&self) -> &SpanContext {
+            &self.context
+        }
+        fn is_recording(&self) -> bool {
+            true
+        }
+        fn set_attribute(&mut self, attribute: KeyValue) {
+            let mut spans = self.spans.lock().unwrap();
+            let value = attribute.value.to_string();
+            spans[self.index].2.insert(attribute.key.to_string(), value);
+        }
+        fn set_status(&mut self, _: Status) {}
+        fn update_name<T>(&mut self, _: T)
+        where
+            T: Into<Cow<'static, str>>,
+        {
+        }
+        fn add_link(&mut self, _: SpanContext, _: Vec<KeyValue>) {}
+        fn end_with_timestamp(&mut self, _: SystemTime) {}
+    }
+
+    let recorded = Recorded::default();
+    opentelemetry::global::set_tracer_provider(Provider(recorded.clone()));
+    let hashes = NETWORK_HASHES.with(|count| count.get());
+    let mut solver = FbasAnalyzer::from_dsl("a, b, c: 2-of(a, b, c)", Basic::default())?
+        .with_symmetry_breaking(true)?;
+    assert_eq!(solver.solve(), SolveStatus::UNSAT);
+    // once when parsing, not again when encoding or solving
+    assert_eq!(NETWORK_HASHES.with(|count| count.get()), hashes + 1);
+
+    let this_thread = std::thread::current().id();
+    let spans: Vec<_> = recorded
+        .lock()
+        .unwrap()
+        .iter()
+        .filter(|(thread, _, _)| *thread == this_thread)
+        .map(|(_, name, attributes)| (name.clone(), attributes.clone()))
+        .collect();
+    let names: Vec<_> = spans.iter().map(|(name, _)| name.as_str()).collect();
+    assert_eq!(names, ["parse", "encode", "encode", "solve"]);
+    let hash = &spans[0].1["network.hash"];
+    assert_eq!(hash.len(), 16);
+    for (name, attributes) in spans.iter() {
+        assert_eq!(&attributes["network.hash"], hash, "{}", name);
+        assert_eq!(attributes["network.validators"], "3", "{}", name);
+        assert_eq!(attributes["network.vertices"], "4", "{}", name);
+    }
+    assert_eq!(spans[0].1["entry_point"], "from_dsl");
+    assert!(spans[1].1.contains_key("num_clauses"));
+    let solve = &spans[3].1;
+    assert_eq!(solve["status"], "UNSAT");
+    assert_eq!(solve["phase"], "Search");
+    assert!(solve.contains_key("num_conflicts") && solve.contains_key("num_decisions"));
+    Ok(())
+}
+
+#[test]
+fn test_describe_qset() -> Result<(), Box<dyn std::error::Error>> {
+    let solver = FbasAnalyzer::from_dsl("a, b, c: 2-of(a, b, 1-of(c, d))", Basic::default())?;
+    let aliases = [("a".to_string(), "Alice".to_string())].into();
+    assert_eq!(
+        solver.describe_qset("b", &aliases)?,
+        "2 of:\n  Alice, b\n  1 of: c\n"
+    );
+    assert!(solver.describe_qset("d", &aliases).is_err());
+    Ok(())
+}
+
+#[test]
+fn test_multi_network_report() -> Result<(), Box<dyn std::error::Error>> {
+    let mut report = MultiNetworkReport::default();
+    assert_eq!(report.worst_status(), SolveStatus::UNSAT);
+    for (network, dsl) in [
+        ("safe", "a, b, c: 2-of(a, b, c)"),
+        ("split", "a: 1-of(a)\nb: 1-of(b)"),
+    ] {
+        let mut solver = FbasAnalyzer::from_dsl(dsl, Basic::default())?;
+        solver.solve();
+        report.add(network, solver.into_report(None)?);
+    }
+    assert!(matches!(report.worst_status(), SolveStatus::SAT(_)));
+    assert_eq!(report.split_networks(), vec!["split"]);
+    assert!(report.unknown_networks().is_empty());
+    assert_eq!(
+        report.to_string(),
+        "2 networks, overall SPLIT\n  safe: OK (0 warnings)\n  split: SPLIT (0 warnings)\n"
+    );
+    Ok(())
+}
+
+#[test]
+fn test_to_smtlib() -> Result<(), Box<dyn std::error::Error>> {
+    let solver = FbasAnalyzer::from_dsl("a, b: 2-of(a, b, c)", Basic::default())?;
+    let smt = solver.to_smtlib()?;
+    let lines: Vec<&str> = smt.lines().collect();
+    assert_eq!(lines.first(), Some(&"(set-logic QF_LIA)"));
+    assert_eq!(lines.last(), Some(&"(check-sat)"));
+    // 3 vertices in 2 quorums
+    assert_eq!(
+        lines
+            .iter()
+            .filter(|l| l.starts_with("(declare-const"))
+            .count(),
+        6
+    );
+    for expected in [
+        "(assert (or |A v:a| |A v:b|))",
+        "(assert (not (and |A v:b| |B v:b|)))",
+        "(assert (=> |A v:a| (>= (ite |A q:2| 1 0) 1)))",
+        "(assert (=> |B q:2| (>= (+ (ite |B v:b| 1 0) (ite |B v:a| 1 0)) 2)))",
+    ] {
+        assert!(lines.contains(&expected), "{}", expected);
+    }
+
+    // symbols stay distinct whatever the validators are called
+    let solver = FbasAnalyzer::from_dsl(
+        "qset4, a|b, a_b, a%7Cb: 2-of(qset4, a|b, a_b, a%7Cb)",
+        Basic::default(),
+    )?;
+    let smt = solver.to_smtlib()?;
+    let declared: BTreeSet<&str> = smt
+        .lines()
+        .filter(|l| l.starts_with("(declare-const"))
+        .collect();
+    assert_eq!(declared.len(), 2 * solver.fbas.graph.node_count());
+    assert!(declared.contains("(declare-const |A v:a%7Cb| Bool)"));
+    Ok(())
+}
+
+#[test]
+fn test_write_dimacs() -> Result<(), Box<dyn std::error::Error>> {
+    let solver = FbasAnalyzer::from_dsl("a, b: 2-of(a, b, c)", Basic::default())?;
+    let mut out = vec![];
+    solver.write_dimacs(&mut out)?;
+    let dimacs = String::from_utf8(out)?;
+    let lines: Vec<&str> = dimacs.lines().collect();
+    // 3 vertices in 2 quorums
+    for expected in ["c 1 A a", "c 3 A qset 2 (2 of 2)", "c 6 B qset 2 (2 of 2)"] {
+        assert!(lines.contains(&expected), "{}", expected);
+    }
+    let header = lines.iter().find(|l| l.starts_with("p cnf")).unwrap();
+    let clauses = lines.iter().filter(|l| l.ends_with(" 0")).count();
+    assert_eq!(
+        *header,
+        format!("p cnf {} {}", solver.get_stats().num_vars, clauses)
+    );
+
+    // the export solves the same as the analyzer, for external solvers too
+    for path in [
+        "./tests/test_data/conflicted_3.json",
+        "./tests/test_data/top_tier.json",
+    ] {
+        let mut analyzer = FbasAnalyzer::from_json_path(path, Basic::default())?;
+        let mut out = vec![];
+        analyzer.write_dimacs(&mut out)?;
+        let mut external = Solver::new(Default::default(), Basic::default());
+        batsat::dimacs::parse(&mut out.as_slice(), &mut external, true, false)?;
+        let expected = match analyzer.solve() {
+            SolveStatus::SAT(_) => lbool::TRUE,
+            _ => lbool::FALSE,
+        };
+        assert_eq!(external.solve_limited(&[]), expected, "{}", path);
+    }
+    Ok(())
+}
+
+#[test]
+fn test_solve_dimacs() -> Result<(), Box<dyn std::error::Error>> {
+    use crate::solve_dimacs;
+
+    let path = "./tests/test_data/random/almost_symmetric_network_10_orgs_.dimacs";
+    let reader = std::io::BufReader::new(std::fs::File::open(path)?);
+    let solution = solve_dimacs(reader, Basic::default())?;
+    assert_eq!(solution.satisfiable, Some(false));
+    assert!(solution.model.is_empty());
+    assert_eq!(solution.stats.num_vars, 1058);
+
+    // a model of an exported formula maps back to a split
+    let analyzer =
+        FbasAnalyzer::from_json_path("./tests/test_data/conflicted_3.json", Basic::default())?;
+    let mut out = vec![];
+    analyzer.write_dimacs(&mut out)?;
+    let solution = solve_dimacs(out.as_slice(), Basic::default())?;
+    assert_eq!(solution.satisfiable, Some(true));
+    let validators: Vec<String> = analyzer
+        .fbas
+        .validators
+        .iter()
+        .map(|ni| analyzer.fbas.try_get_validator_string(ni))
+        .collect::<Result<_, _>>()?;
+    let quorum = |q: Quorum| -> Result<Vec<&str>, FbasError> {
+        let mut members = vec![];
+        for v in validators.iter() {
+            let var = analyzer.literal_for(v, q)?.var().idx() as usize;
+            if solution.model[var] {
+                members.push(v.as_str());
+            }
+        }
+        Ok(members)
+    };
+    let (qa, qb) = (quorum(Quorum::A)?, quorum(Quorum::B)?);
+    assert!(!qa.is_empty() && !qb.is_empty());
+    assert!(qa.iter().all(|v| !qb.contains(v)));
+    assert!(analyzer.is_quorum(&qa)? && analyzer.is_quorum(&qb)?);
+
+    // the callbacks bound the search
+    let path =
+        "./tests/test_data/random/almost_symmetric_network_16_orgs_delete_prob_factor_1.dimacs";
+    let reader = std::io::BufReader::new(std::fs::File::open(path)?);
+    let solution = solve_dimacs(reader, Deadline::after(Duration::ZERO))?;
+    assert_eq!(solution.satisfiable, None);
+
+    assert!(matches!(
+        solve_dimacs("p cnf 2 1\n1 x 0\n".as_bytes(), Basic::default()),
+        Err(FbasError::ParseError(_))
+    ));
+    Ok(())
+}
+
+#[cfg(feature = "fbas_analyzer")]
+#[test]
+fn test_fbas_analyzer_compat() -> Result<(), Box<dyn std::error::Error>> {
+    use crate::fbas_analyzer_compat::{from_node_id_set, to_node_id_set};
+
+    let mut solver = FbasAnalyzer::from_dsl(
+        "a, b: 2-of(a, b)\n\
+         c, d: 2-of(c, d)\n\
+         e: 2-of(1-of(a, b), 1-of(c, d), x)",
+        Basic::default(),
+    )?;
+    let fbas = solver.to_fbas_analyzer()?;
+    assert_eq!(fbas.number_of_nodes(), 5);
+    assert!(matches!(solver.solve(), SolveStatus::SAT(_)));
+    let (qa, qb) = solver.get_potential_split()?;
+    let (qa_ids, qb_ids) = (to_node_id_set(&qa, &fbas), to_node_id_set(&qb, &fbas));
+    assert!(fbas.is_quorum(&qa_ids) && fbas.is_quorum(&qb_ids));
+    assert!(qa_ids.is_disjoint(&qb_ids));
+    assert_eq!(from_node_id_set(&qa_ids, &fbas), qa);
+
+    let mut round_trip = FbasAnalyzer::from_fbas_analyzer(&fbas, Basic::default())?;
+    assert!(matches!(round_trip.solve(), SolveStatus::SAT(_)));
+    assert_eq!(
+        round_trip.describe_qset("e", &Default::default())?,
+        solver.describe_qset("e", &Default::default())?
+    );
+    Ok(())
+}
+
+#[test]
+fn test_unsatisfiable_nested_qset() -> Result<(), Box<dyn std::error::Error>> {
+    let mut solver = FbasAnalyzer::from_dsl(
+        "a, b: 3-of(a, b, 3-of(c, d))\n\
+         c, d: 2-of(c, d)",
+        Basic::default(),
+    )?;
+    assert_eq!(
+        solver.get_warnings(),
+        &[
+            FbasWarning::ThresholdExceedsMembers {
+                threshold: 3,
+                threshold_percent: None,
+                members: 2,
+                validators: vec![],
+            },
+            FbasWarning::UnsatisfiableQset {
+                validator: "a".to_string()
+            },
+            FbasWarning::UnsatisfiableQset {
+                validator: "b".to_string()
+            },
+        ]
+    );
+    assert_eq!(solver.solve(), SolveStatus::UNSAT);
+    Ok(())
+}
+
+#[test]
+fn test_threshold_percent_in_warnings() -> Result<(), Box<dyn std::error::Error>> {
+    let qset = crate::QuorumSetJson {
+        threshold_percent: Some(100),
+        validators: vec!["PK1".into(), "PK2".into(), "X".into()],
+        ..Default::default()
+    };
+    let records: Vec<_> = ["PK1", "PK2"]
+        .into_iter()
+        .map(|v| crate::NodeRecord {
+            public_key: v.into(),
+            quorum_set: Some(qset.clone()),
+            ..Default::default()
+        })
+        .collect();
+    let mut solver = FbasAnalyzer::from_node_records(&records, Basic::default())?
+        .with_diagnostics_sink(DiagnosticsSink::Silent);
+    let warning = FbasWarning::ThresholdExceedsMembers {
+        threshold: 3,
+        threshold_percent: Some(100),
+        members: 2,
+        validators: vec!["PK1".to_string(), "PK2".to_string()],
+    };
+    assert_eq!(solver.get_warnings().to_vec(), vec![warning.clone()]);
+    assert!(warning
+        .to_string()
+        .starts_with("Qset threshold 100% (3) exceeds"));
+    solver.solve();
+    let report = solver.into_report(None)?;
+    assert_eq!(report.warnings, vec![warning]);
+    assert_eq!(crate::AnalysisReport::from_json(&report.to_json())?, report);
+    Ok(())
+}
+
+#[test]
+fn test_threshold_percent_not_in_identity() -> Result<(), Box<dyn std::error::Error>> {
+    // 67% of 3 is the same threshold as 3
+    let qset = |threshold, threshold_percent| crate::QuorumSetJson {
+        threshold,
+        threshold_percent,
+        validators: vec!["PK1".into(), "PK2".into(), "PK3".into()],
+        ..Default::default()
+    };
+    let records = |first: crate::QuorumSetJson| -> Vec<_> {
+        ["PK1", "PK2", "PK3"]
+            .into_iter()
+            .enumerate()
+            .map(|(i, v)| crate::NodeRecord {
+                public_key: v.into(),
+                quorum_set: Some(if i == 0 {
+                    first.clone()
+                } else {
+                    qset(Some(3), None)
+                }),
+                ..Default::default()
+            })
+            .collect()
+    };
+    let percent =
+        FbasAnalyzer::from_node_records(&records(qset(None, Some(67))), Basic::default())?;
+    let plain = FbasAnalyzer::from_node_records(&records(qset(Some(3), None)), Basic::default())?;
+    // the three qsets share one vertex
+    assert_eq!(percent.fbas.graph.node_count(), 4);
+    assert_eq!(
+        percent.fbas.graph.node_count(),
+        plain.fbas.graph.node_count()
+    );
+    assert!(percent.fbas.diff(&plain.fbas)?.changed.is_empty());
+    Ok(())
+}
+
+#[test]
+fn test_repro_round_trip() -> Result<(), Box<dyn std::error::Error>> {
+    let dsl = "a, b: 2-of(a, b)\n\
+               c, d: 2-of(c, d, x)\n\
+               e: 2-of(1-of(a, b), 1-of(c, d))";
+    let path = std::env::temp_dir().join(format!("repro-{}.json", std::process::id()));
+    let path = path.to_str().unwrap();
+    let mut solver = FbasAnalyzer::from_dsl(dsl, Basic::default())?;
+    let status = solver.solve();
+    solver.capture_repro(path)?;
+
+    let mut loaded = FbasAnalyzer::from_repro(path, Basic::default())?
+        .with_diagnostics_sink(DiagnosticsSink::Collected);
+    std::fs::remove_file(path)?;
+    assert_eq!(loaded.solve(), status);
+    assert!(loaded.take_diagnostics().is_empty());
+    let aliases = BTreeMap::new();
+    for v in ["a", "c", "e"] {
+        assert_eq!(
+            loaded.describe_qset(v, &aliases)?,
+            solver.describe_qset(v, &aliases)?
+        );
+    }
+    Ok(())
+}
+
+#[test]
+fn test_load() -> Result<(), Box<dyn std::error::Error>> {
+    for (path, format) in [
+        ("./tests/test_data/top_tier.json", InputFormat::Stellarbeats),
+        (
+            "./tests/test_data/random/almost_symmetric_network_13_orgs_delete_prob_factor_1_for_stellar_core.json",
+            InputFormat::Json,
+        ),
+    ] {
+        let (mut loaded, detected) = FbasAnalyzer::load_path(path, Basic::default())?;
+        assert_eq!(detected, format, "{}", path);
+        let mut solver = FbasAnalyzer::from_json_path(path, Basic::default())?;
+        assert_eq!(loaded.solve(), solver.solve(), "{}", path);
+    }
+
+    let dsl = "  # leading comment\na, b: 2-of(a, b)\nc, d: 2-of(c, d)";
+    let (mut loaded, format) = FbasAnalyzer::load(dsl.as_bytes(), Basic::default())?;
+    assert_eq!(format, InputFormat::Dsl);
+    assert!(matches!(loaded.solve(), SolveStatus::SAT(_)));
+
+    let path = std::env::temp_dir().join(format!("load-{}.json", std::process::id()));
+    let path = path.to_str().unwrap();
+    loaded.capture_repro(path)?;
+    let loaded = FbasAnalyzer::load_path(path, Basic::default());
+    std::fs::remove_file(path)?;
+    assert_eq!(loaded?.1, InputFormat::ReproBundle);
+
+    for (input, message) in [
+        (
+            &b"\x1f\x8b\x08\x00"[..],
+            "Compressed input is not supported",
+        ),
+        (
+            b"NETWORK_PASSPHRASE=\"x\"\n[QUORUM_SET]\nTHRESHOLD_PERCENT=67",
+            "TOML configs are not supported",
+        ),
+        (
+            b"[QUORUM_SET]\nTHRESHOLD_PERCENT=67",
+            "TOML configs are not supported",
+        ),
+        (b"[{\"publicKey\": ", "fail to parse to json"),
+        (b"a: 1-of(\xff)", "Input is neither json nor DSL text"),
+    ] {
+        match FbasAnalyzer::load(input, Basic::default()) {
+            Err(FbasError::ParseError(m)) => assert_eq!(m, message),
+            other => panic!("{:?}", other.map(|(_, format)| format)),
+        }
+    }
+    Ok(())
+}
+
+#[test]
+fn test_qset_membership() -> Result<(), Box<dyn std::error::Error>> {
+    let mut solver = FbasAnalyzer::from_dsl(
+        "a, b: 2-of(a, b)\n\
+         c, d: 2-of(c, d)\n\
+         e: 2-of(e, 1-of(a, b), 1-of(c, d))",
+        Basic::default(),
+    )?;
+    assert_eq!(solver.qset_membership("a", Quorum::A)?, None);
+    assert!(matches!(solver.solve(), SolveStatus::SAT(_)));
+    let (qa, _) = solver.get_potential_split()?;
+
+    // every qset of a quorum member is satisfied by the quorum
+    fn check(qset: &QsetMembership) {
+        if !qset.in_quorum {
+            return;
+        }
+        let satisfied = qset.validators.iter().filter(|(_, in_q)| *in_q).count()
+            + qset.inner_sets.iter().filter(|q| q.in_quorum).count();
+        assert!(satisfied >= qset.threshold as usize);
+        qset.inner_sets.iter().for_each(check);
+    }
+    for v in &qa {
+        let qset = solver.qset_membership(v, Quorum::A)?.unwrap();
+        assert!(qset.in_quorum);
+        check(&qset);
+    }
+    let e = solver.qset_membership("e", Quorum::A)?.unwrap();
+    assert_eq!(e.threshold, 2);
+    assert_eq!(
+        e.validators,
+        vec![("e".to_string(), qa.contains(&"e".to_string()))]
+    );
+    assert_eq!(e.inner_sets.len(), 2);
+    assert!(solver.qset_membership("x", Quorum::A).is_err());
+    Ok(())
+}
+
+#[test]
+fn test_chunked_cardinality() -> Result<(), Box<dyn std::error::Error>> {
+    // the chunked counter agrees with the regular encoding
+    for path in [
+        "./tests/test_data/symmetric_4.json",
+        "./tests/test_data/conflicted.json",
+        "./tests/test_data/conflicted_3.json",
+        "./tests/test_data/circular_2.json",
+        "./tests/test_data/random/almost_symmetric_network_8_orgs_delete_prob_factor_1.json",
+    ] {
+        let fbas = Fbas::from_json_path(path)?;
+        let mut regular = Solver::new(Default::default(), Basic::default());
+        encode_fbas_with(
+            &fbas,
+            &mut regular,
+            |_| false,
+            1,
+            Default::default(),
+            |_| lbool::UNDEF,
+        )?;
+        let expected = regular.solve_limited(&[]);
+        for chunk_size in [1, 2, 3, 100] {
+            let cardinality = CardinalityEncoding {
+                min_members: 0,
+                chunk_size,
+            };
+            let mut chunked = Solver::new(Default::default(), Basic::default());
+            encode_fbas_with(
+                &fbas,
+                &mut chunked,
+                |_| false,
+                1,
+                cardinality,
+                |_| lbool::UNDEF,
+            )?;
+            assert_eq!(chunked.solve_limited(&[]), expected, "{}", path);
+        }
+    }
+
+    // flat qsets far too large to expand into combinations
+    let validators: Vec<String> = (0..40).map(|i| format!("v{}", i)).collect();
+    let flat = |threshold: usize| {
+        format!(
+            "{}: {}-of({})",
+            validators.join(", "),
+            threshold,
+            validators.join(", ")
+        )
+    };
+    let mut solver = FbasAnalyzer::from_dsl(&flat(27), Basic::default())?;
+    assert_eq!(solver.solve(), SolveStatus::UNSAT);
+    let mut solver = FbasAnalyzer::from_dsl(&flat(20), Basic::default())?;
+    assert!(matches!(solver.solve(), SolveStatus::SAT(_)));
+    let (qa, qb) = solver.get_potential_split()?;
+    assert!(qa.len() >= 20 && qb.len() >= 20);
+    Ok(())
+}
+
+#[test]
+fn test_time_budget() -> Result<(), Box<dyn std::error::Error>> {
+    // the budget is shared evenly among the pending analyses
+    let mut budget = TimeBudget::new(Duration::from_secs(100), 4);
+    let deadline = budget.next_deadline().unwrap();
+    let share = deadline.instant() - Instant::now();
+    assert!(share <= Duration::from_secs(25));
+    assert!(share > Duration::from_secs(24));
+    assert_eq!(budget.pending(), 3);
+
+    // quick analyses leave their time to the next ones
+    let mut budget = TimeBudget::new(Duration::from_secs(60), 2);
+    let mut solver = FbasAnalyzer::from_json_path(
+        "./tests/test_data/symmetric_4.json",
+        budget.next_deadline().unwrap(),
+    )?;
+    assert_eq!(solver.solve(), SolveStatus::UNSAT);
+    assert!(budget.next_deadline().unwrap().instant() - Instant::now() > Duration::from_secs(50));
+    assert!(budget.next_deadline().is_none());
+
+    // an expired deadline leaves the search inconclusive, and an exhausted
+    // budget skips the remaining analyses
+    let mut solver = FbasAnalyzer::from_json_path(
+        "./tests/test_data/random/almost_symmetric_network_16_orgs_delete_prob_factor_1.json",
+        Deadline::after(Duration::ZERO),
+    )?;
+    assert_eq!(solver.solve(), SolveStatus::UNKNOWN);
+    let mut budget = TimeBudget::new(Duration::ZERO, 2);
+    assert!(budget.next_deadline().is_none());
+    assert_eq!(budget.pending(), 1);
+
+    // no deadline at all, and more analyses than a share can be split into
+    let deadline = Deadline::after(Duration::MAX);
+    assert!(deadline.instant() - Instant::now() > Duration::from_secs(365 * 24 * 60 * 60));
+    let mut budget = TimeBudget::new(Duration::MAX, 3);
+    assert!(budget.next_deadline().is_some());
+    let mut budget = TimeBudget::new(Duration::from_secs(1), 1 << 32);
+    assert!(budget.next_deadline().is_none());
+    assert_eq!(budget.pending(), (1 << 32) - 1);
+    Ok(())
+}
+
+#[test]
+fn test_estimate_encoding() -> Result<(), Box<dyn std::error::Error>> {
+    // without unit clauses, which the solver doesn't keep, the estimate is
+    // exactly what the solver holds
+    for path in [
+        "./tests/test_data/symmetric_4.json",
+        "./tests/test_data/conflicted_3.json",
+        "./tests/test_data/random/almost_symmetric_network_8_orgs_delete_prob_factor_1.json",
+    ] {
+        let solver = FbasAnalyzer::from_json_path(path, Basic::default())?;
+        let estimate = solver.estimate_encoding()?;
+        let stats = solver.get_stats();
+        assert_eq!(estimate.num_vars, stats.num_vars as u64, "{}", path);
+        assert_eq!(estimate.num_clauses, stats.num_clauses, "{}", path);
+        assert!(estimate.memory_bytes > 0);
+    }
+
+    // dead vertices and the chunked counter add unit clauses
+    let validators: Vec<String> = (0..40).map(|i| format!("v{}", i)).collect();
+    let flat = format!(
+        "{}: 27-of({})",
+        validators.join(", "),
+        validators.join(", ")
+    );
+    for solver in [
+        FbasAnalyzer::from_json_path("./tests/test_data/missing_1.json", Basic::default())?,
+        FbasAnalyzer::from_dsl(&flat, Basic::default())?,
+    ] {
+        let estimate = solver.estimate_encoding()?;
+        let stats = solver.get_stats();
+        assert_eq!(estimate.num_vars, stats.num_vars as u64);
+        assert!(estimate.num_clauses > stats.num_clauses);
+    }
+    Ok(())
+}
+
+#[cfg(feature = "rayon")]
+#[test]
+fn test_parallel_blocking_matches_sequential() -> Result<(), Box<dyn std::error::Error>> {
+    use crate::blocking::{parallel_blocking_in_batch, sequential_blocking_in_batch};
+
+    for network in ["conflicted", "symmetric_4"] {
+        let fbas = Fbas::from_json_path(&format!("./tests/test_data/{network}.json"))?;
+        let batch: Vec<BTreeSet<NodeIndex>> = fbas
+            .validators
+            .iter()
+            .copied()
+            .powerset()
+            .map(|candidate| candidate.into_iter().collect())
+            .collect();
+        let is_blocking = |candidate: &BTreeSet<NodeIndex>| fbas.max_quorum(candidate).is_empty();
+        let all = sequential_blocking_in_batch(is_blocking, &batch, usize::MAX);
+        assert!(!all.is_empty());
+        for limit in [0, 1, 2, all.len() / 2, all.len(), usize::MAX] {
+            assert_eq!(
+                parallel_blocking_in_batch(is_blocking, &batch, limit),
+                sequential_blocking_in_batch(is_blocking, &batch, limit),
+            );
+        }
+    }
+    Ok(())
+}
+
+#[test]
+fn test_rank_blocking_sets_by_uptime() -> Result<(), Box<dyn std::error::Error>> {
+    // any two of the four members (d, e forming one) are blocking
+    let solver =
+        FbasAnalyzer::from_dsl("a, b, c, d, e: 3-of(a, b, c, 2-of(d, e))", Basic::default())?;
+    let sets = solver.minimal_blocking_sets(3)?;
+    let expected: Vec<Vec<String>> = ["ab", "ac", "ad", "ae", "bc", "bd", "be", "cd", "ce"]
+        .into_iter()
+        .map(|set| set.chars().map(String::from).collect())
+        .collect();
+    assert_eq!(sets, expected);
+    assert!(solver.minimal_blocking_sets(1)?.is_empty());
+    assert_eq!(solver.minimal_blocking_sets_limited(3, 4)?, expected[..4]);
+    assert!(solver.minimal_blocking_sets_limited(3, 0)?.is_empty());
+    assert_eq!(
+        solver.find_minimal_blocking_sets(usize::MAX, Basic::default())?,
+        Some(expected.clone())
+    );
+    // none small enough, or stopped by the callbacks
+    assert_eq!(
+        solver.find_minimal_blocking_sets(1, Basic::default())?,
+        Some(vec![])
+    );
+    assert_eq!(
+        solver.find_minimal_blocking_sets(3, Deadline::after(Duration::ZERO))?,
+        None
+    );
+
+    // d and e only block together with one of a, b and c
+    let weak_de =
+        FbasAnalyzer::from_dsl("a, b, c, d, e: 3-of(a, b, c, 1-of(d, e))", Basic::default())?;
+    assert_eq!(weak_de.minimal_blocking_sets(3)?.len(), 6);
+    assert_eq!(
+        weak_de
+            .find_minimal_blocking_sets(usize::MAX, Basic::default())?
+            .unwrap(),
+        vec![vec!["a", "b"], vec!["a", "c"], vec!["b", "c"]]
+    );
+
+    // the search on a network of 48 validators is bounded by the callbacks
+    let large = FbasAnalyzer::from_json_path(
+        "./tests/test_data/random/almost_symmetric_network_16_orgs_delete_prob_factor_1.json",
+        Basic::default(),
+    )?;
+    let start = Instant::now();
+    let deadline = Deadline::after(Duration::from_millis(200));
+    assert_eq!(
+        large.find_minimal_blocking_sets(usize::MAX, deadline)?,
+        None
+    );
+    assert!(start.elapsed() < Duration::from_secs(10));
+
+    // validators outside the main component are in no minimal quorum, so in
+    // no minimal blocking set
+    let with_follower = FbasAnalyzer::from_dsl(
+        "a, b, c: 2-of(a, b, c)\nx: 3-of(a, b, c, x)",
+        Basic::default(),
+    )?;
+    assert_eq!(
+        with_follower.minimal_blocking_sets(4)?,
+        vec![vec!["a", "b"], vec!["a", "c"], vec!["b", "c"]]
+    );
+
+    let uptime: BTreeMap<String, f64> = [("a", 0.99), ("b", 0.9), ("c", 0.99), ("d", 0.5)]
+        .into_iter()
+        .map(|(v, u)| (v.to_string(), u))
+        .collect();
+    let ranked = solver.rank_blocking_sets_by_uptime(3, &uptime, 0.99)?;
+    assert_eq!(ranked[0].0, vec!["b", "d"]);
+    assert!((ranked[0].1 - 0.05).abs() < 1e-9);
+    assert!((ranked.last().unwrap().1 - 1e-4).abs() < 1e-9);
+    assert!(ranked.windows(2).all(|w| w[0].1 >= w[1].1));
+
+    // without any quorum, nothing needs to fail
+    let solver =
+        FbasAnalyzer::from_json_path("./tests/test_data/missing_1.json", Basic::default())?;
+    assert_eq!(solver.minimal_blocking_sets(2)?, vec![Vec::<String>::new()]);
+    assert_eq!(
+        solver.find_minimal_blocking_sets(usize::MAX, Basic::default())?,
+        Some(vec![Vec::<String>::new()])
+    );
+    Ok(())
+}
+
+#[cfg(feature = "xdr")]
+#[test]
+fn test_infer_quorum_sets() -> Result<(), Box<dyn std::error::Error>> {
+    use crate::{infer_quorum_sets, InferenceMethod, NodeId, ScpQuorumSet};
+    use stellar_xdr::curr::{
+        Hash, PublicKey, ScpBallot, ScpEnvelope, ScpNomination, ScpStatement,
+        ScpStatementExternalize, ScpStatementPledges, Uint256,
+    };
+
+    let node = |i: u8| NodeId(PublicKey::PublicKeyTypeEd25519(Uint256([i; 32])));
+    let envelope = |i: u8, slot: u64, hash: u8, externalize: bool| {
+        let quorum_set_hash = Hash([hash; 32]);
+        let pledges = if externalize {
+            ScpStatementPledges::Externalize(ScpStatementExternalize {
+                commit: ScpBallot {
+                    counter: 1,
+                    value: Default::default(),
+                },
+                n_h: 1,
+                commit_quorum_set_hash: quorum_set_hash,
+            })
+        } else {
+            ScpStatementPledges::Nominate(ScpNomination {
+                quorum_set_hash,
+                votes: Default::default(),
+                accepted: Default::default(),
+            })
+        };
+        ScpEnvelope {
+            statement: ScpStatement {
+                node_id: node(i),
+                slot_index: slot,
+                pledges,
+            },
+            signature: Default::default(),
+        }
+    };
+
+    let known_qset = ScpQuorumSet {
+        threshold: 2,
+        validators: vec![node(1), node(2), node(3)].try_into()?,
+        inner_sets: Default::default(),
+    };
+    // 1 is crawled and 4 shares its qset; 5 externalizes after hearing from
+    // 2 and 3 in every slot, and from 6 only once
+    let mut envelopes = vec![envelope(1, 1, 10, false), envelope(4, 1, 10, false)];
+    for slot in 1..=4 {
+        if slot == 1 {
+            envelopes.push(envelope(6, slot, 30, false));
+        }
+        envelopes.push(envelope(2, slot, 30, false));
+        envelopes.push(envelope(3, slot, 30, false));
+        envelopes.push(envelope(5, slot, 20, true));
+    }
+    let inferred = infer_quorum_sets(&envelopes, &[(node(1), known_qset.clone())])?;
+    // 2, 3 and 6 share a hash nobody known advertises and never externalize
+    assert_eq!(inferred.len(), 2);
+
+    let shared = inferred.iter().find(|q| q.node == node(4)).unwrap();
+    assert_eq!(shared.qset, known_qset);
+    assert_eq!(shared.confidence, 1.0);
+    assert_eq!(shared.method, InferenceMethod::SharedHash { node: node(1) });
+
+    let observed = inferred.iter().find(|q| q.node == node(5)).unwrap();
+    assert_eq!(observed.qset.threshold, 3);
+    assert_eq!(
+        observed.qset.validators.to_vec(),
+        vec![node(2), node(3), node(5)]
+    );
+    assert_eq!(observed.method, InferenceMethod::ObservedVotes { slots: 4 });
+    assert!(observed.confidence > 0.0 && observed.confidence < 1.0);
+
+    Ok(())
+}
+
+#[test]
+fn test_validator_order() -> Result<(), Box<dyn std::error::Error>> {
+    let orgs = crate::Organizations::new(
+        [("org1", vec!["d", "a"]), ("org2", vec!["b"])]
+            .into_iter()
+            .map(|(org, vs)| (org.to_string(), vs.into_iter().map(String::from).collect()))
+            .collect(),
+    );
+    let aliases: BTreeMap<String, String> = [("a", "zeta"), ("c", "alpha")]
+        .into_iter()
+        .map(|(v, alias)| (v.to_string(), alias.to_string()))
+        .collect();
+    let validators: Vec<String> = ["e", "d", "c", "b", "a"].map(String::from).to_vec();
+    let sorted = |order: ValidatorOrder| {
+        let mut vs = validators.clone();
+        order.sort(&mut vs);
+        vs.concat()
+    };
+    assert_eq!(sorted(ValidatorOrder::Key), "abcde");
+    assert_eq!(sorted(ValidatorOrder::Name(&aliases)), "cbdea");
+    assert_eq!(sorted(ValidatorOrder::Org(&orgs)), "adbce");
+    let groups = ValidatorOrder::Org(&orgs).group(&validators);
+    let groups: Vec<(Option<&str>, String)> = groups
+        .iter()
+        .map(|(org, vs)| (org.as_deref(), vs.concat()))
+        .collect();
+    assert_eq!(
+        groups,
+        vec![
+            (Some("org1"), "ad".to_string()),
+            (Some("org2"), "b".to_string()),
+            (None, "ce".to_string())
+        ]
+    );
+
+    // applied to reports
+    let mut solver = FbasAnalyzer::from_dsl(
+        "a, b: 2-of(a, b)\n\
+         c, d: 2-of(c, d)",
+        Basic::default(),
+    )?;
+    solver.solve();
+    let mut report = solver.into_report(None)?;
+    report.sort_validators(ValidatorOrder::Org(&orgs));
+    let (qa, qb) = &report.potential_split;
+    let mut split = vec![qa.concat(), qb.concat()];
+    split.sort();
+    assert_eq!(split, vec!["ab", "dc"]);
+    Ok(())
+}
+
+#[test]
+fn test_report_json_schema() -> Result<(), Box<dyn std::error::Error>> {
+    use crate::{AnalysisReport, REPORT_SCHEMA_VERSION};
+    use petgraph::graph::NodeIndex;
+
+    // fixtures of every schema version keep deserializing
+    let v1 = std::fs::read_to_string("./tests/test_data/reports/v1.json")?;
+    let report = AnalysisReport::from_json(&v1)?;
+    assert_eq!(report.schema_version, 1);
+    assert_eq!(
+        report.status,
+        SolveStatus::SAT((
+            vec![NodeIndex::new(0), NodeIndex::new(2)],
+            vec![NodeIndex::new(4), NodeIndex::new(6)]
+        ))
+    );
+    assert_eq!(
+        report.potential_split,
+        (
+            vec!["a".to_string(), "b".to_string()],
+            vec!["c".to_string(), "d".to_string()]
+        )
+    );
+    assert_eq!(report.stats.phase, SolvePhase::Search);
+    assert_eq!(report.stats.num_clauses, 52);
+    assert_eq!(
+        report.warnings,
+        vec![
+            FbasWarning::ThresholdExceedsMembers {
+                threshold: 3,
+                threshold_percent: None,
+                members: 2,
+                validators: vec!["e".to_string()]
+            },
+            FbasWarning::UnsatisfiableQset {
+                validator: "f".to_string()
+            }
+        ]
+    );
+
+    // reports round-trip
+    let mut solver =
+        FbasAnalyzer::from_json_path("./tests/test_data/conflicted_3.json", Basic::default())?;
+    solver.solve();
+    let report = solver.into_report(None)?;
+    assert_eq!(report.schema_version, REPORT_SCHEMA_VERSION);
+    assert_eq!(report.provenance.crate_version, env!("CARGO_PKG_VERSION"));
+    assert!(!report.provenance.interrupted);
+    assert_eq!(AnalysisReport::from_json(&report.to_json())?, report);
+    // reports written before provenance was recorded
+    assert_eq!(
+        AnalysisReport::from_json(&v1)?.provenance,
+        Default::default()
+    );
+
+    // lossy preprocessing is recorded
+    let mut solver = FbasAnalyzer::from_dsl("a, b: 2-of(a, b, x, y)", Basic::default())?;
+    solver.solve();
+    let provenance = solver.into_report(None)?.provenance;
+    assert_eq!(provenance.unknown_validators, 2);
+
+    // unknown fields and warning kinds are ignored, newer versions rejected
+    let mut value: serde_json::Value = serde_json::from_str(&v1)?;
+    value["new_field"] = 1.into();
+    value["warnings"]
+        .as_array_mut()
+        .ok_or("warnings is not an array")?
+        .push(serde_json::json!({"kind": "NewWarning"}));
+    assert_eq!(
+        AnalysisReport::from_json(&value.to_string())?
+            .warnings
+            .len(),
+        2
+    );
+    value["schema_version"] = (REPORT_SCHEMA_VERSION + 1).into();
+    assert!(AnalysisReport::from_json(&value.to_string()).is_err());
+    Ok(())
+}
+
+#[test]
+fn test_report_provenance() -> Result<(), Box<dyn std::error::Error>> {
+    // d is outside the component of the quorums and pruned (its qset, which a
+    // satisfies, is kept), x and y are unknown
+    let dsl = "a, b, c: 2-of(a, b, c, x)\nd: 1-of(a, y)";
+    let mut solver = FbasAnalyzer::from_dsl(dsl, Basic::default())?;
+    solver.solve();
+    let report = solver.into_report(None)?;
+    let expected = crate::ReportProvenance {
+        crate_version: env!("CARGO_PKG_VERSION").to_string(),
+        solver: "batsat".to_string(),
+        solver_seed: batsat::SolverOpts::default().random_seed,
+        branching_heuristic: BranchingHeuristic::Solver,
+        symmetry_breaking: false,
+        interrupted: false,
+        pruned_vertices: 1,
+        unknown_validators: 2,
+    };
+    assert_eq!(report.provenance, expected);
+    let json: serde_json::Value = serde_json::from_str(&report.to_json())?;
+    assert_eq!(
+        json["provenance"],
+        serde_json::json!({
+            "crate_version": env!("CARGO_PKG_VERSION"),
+            "solver": "batsat",
+            "solver_seed": expected.solver_seed,
+            "branching_heuristic": "Solver",
+            "symmetry_breaking": false,
+            "interrupted": false,
+            "pruned_vertices": 1,
+            "unknown_validators": 2,
+        })
+    );
+
+    // the solver options
+    let mut solver = FbasAnalyzer::from_dsl(dsl, Basic::default())?
+        .with_branching_heuristic(BranchingHeuristic::TopTier)?
+        .with_symmetry_breaking(true)?;
+    solver.solve();
+    let provenance = solver.into_report(None)?.provenance;
+    assert_eq!(provenance.branching_heuristic, BranchingHeuristic::TopTier);
+    assert!(provenance.symmetry_breaking);
+
+    // the deadline was hit
+    let mut solver = FbasAnalyzer::from_json_path(
+        "./tests/test_data/random/almost_symmetric_network_16_orgs_delete_prob_factor_1.json",
+        Deadline::after(Duration::ZERO),
+    )?;
+    assert_eq!(solver.solve(), SolveStatus::UNKNOWN);
+    let provenance = solver.into_report(None)?.provenance;
+    assert!(provenance.interrupted);
+    assert_eq!(provenance.unknown_validators, 0);
+    Ok(())
+}
+
+#[test]
+fn test_analysis_registry() -> Result<(), Box<dyn std::error::Error>> {
+    use crate::{Analysis, AnalysisInput, AnalysisRegistry};
+    use serde_json::{json, Value};
+
+    // a third-party analysis, configured by its own settings
+    struct LargeQsets;
+    impl Analysis for LargeQsets {
+        fn name(&self) -> &str {
+            "large_qsets"
+        }
+
+        fn run(
+            &self,
+            analyzer: &mut FbasAnalyzer<Basic>,
+            config: &Value,
+        ) -> Result<Value, FbasError> {
+            let min_size = config["min_size"].as_u64().unwrap_or(1) as usize;
+            let mut large = vec![];
+            for v in analyzer.fbas.quorum_set_map()?.keys() {
+                if analyzer.transitive_dependencies(v)?.len() >= min_size {
+                    large.push(v.clone());
+                }
+            }
+            Ok(json!(large))
+        }
+    }
+
+    let mut registry = AnalysisRegistry::with_builtins();
+    registry.register(LargeQsets);
+    assert_eq!(
+        registry.names().collect::<Vec<_>>(),
+        vec![
+            "critical_organizations",
+            "intersection",
+            "large_qsets",
+            "minimal_blocking_sets",
+            "minimal_splitting_sets",
+            "top_tier"
+        ]
+    );
+    assert_eq!(
+        registry
+            .get("critical_organizations")
+            .unwrap()
+            .required_inputs(),
+        &[AnalysisInput::Organizations]
+    );
+
+    let mut analyzer =
+        FbasAnalyzer::from_dsl("a, b, c: 2-of(a, b, c)\nd: 1-of(d)", Basic::default())?;
+    let mut results = registry.run_all(
+        &mut analyzer,
+        &json!({ "minimal_blocking_sets": { "max_size": 3 }, "large_qsets": { "min_size": 2 } }),
+    )?;
+    let intersection = results["intersection"].take();
+    assert_eq!(intersection["status"], "SAT");
+    let mut split: Vec<Vec<String>> = serde_json::from_value(intersection["split"].clone())?;
+    split.sort();
+    assert_eq!(split, vec![vec!["a", "b"], vec!["d"]]);
+    // the network has no organizations, so critical_organizations is skipped
+    assert_eq!(
+        results,
+        json!({
+            "intersection": null,
+            "large_qsets": ["a", "b", "c"],
+            "minimal_blocking_sets": [["a", "b", "d"], ["a", "c", "d"], ["b", "c", "d"]],
+            "minimal_splitting_sets": { "sets": [[]], "complete": true },
+            "top_tier": ["a", "b", "c", "d"],
+        })
+    );
+    let bad_config = json!({ "max_size": "two" });
+    for (name, config) in [
+        ("critical_organizations", &Value::Null),
+        ("unknown", &Value::Null),
+        ("minimal_splitting_sets", &bad_config),
+    ] {
+        assert!(
+            matches!(
+                registry.run(name, &mut analyzer, config),
+                Err(FbasError::InvalidAnalysis(_))
+            ),
+            "{}",
+            name
+        );
+    }
+
+    // registering under the same name replaces the analysis
+    let mut registry = AnalysisRegistry::new();
+    registry.register(LargeQsets);
+    registry.register(LargeQsets);
+    assert_eq!(registry.names().count(), 1);
+
+    let mut analyzer =
+        FbasAnalyzer::from_json_path("./tests/test_data/top_tier.json", Basic::default())?;
+    let registry = AnalysisRegistry::with_builtins();
+    assert_eq!(
+        registry.run("critical_organizations", &mut analyzer, &Value::Null)?,
+        json!({ "availability": [], "intersection": [], "unknown": [] })
+    );
+    assert_eq!(
+        registry.run("intersection", &mut analyzer, &Value::Null)?,
+        json!({ "status": "UNSAT", "split": [[], []] })
+    );
+    Ok(())
+}
+
+#[test]
+fn test_report_sinks() -> Result<(), Box<dyn std::error::Error>> {
+    use crate::{FileSink, ReportSink};
+
+    let mut reports = vec![];
+    for dsl in ["a, b, c: 2-of(a, b, c)", "a: 1-of(a)\nb: 1-of(b)"] {
+        let mut solver = FbasAnalyzer::from_dsl(dsl, Basic::default())?;
+        solver.solve();
+        reports.push(solver.into_report(None)?);
+    }
+
+    let mut received = vec![];
+    let mut closure = |report: &crate::AnalysisReport| {
+        received.push(report.clone());
+        Ok(())
+    };
+    let path = std::env::temp_dir().join(format!("report_sink_{}.jsonl", std::process::id()));
+    let _ = std::fs::remove_file(&path);
+    let mut sinks: Vec<Box<dyn ReportSink + '_>> =
+        vec![Box::new(FileSink::new(&path)), Box::new(&mut closure)];
+    for report in reports.iter() {
+        for sink in sinks.iter_mut() {
+            sink.send(report)?;
+        }
+    }
+    drop(sinks);
+    assert_eq!(received, reports);
+    // appended, one report per line
+    let written = std::fs::read_to_string(&path)?;
+    std::fs::remove_file(&path)?;
+    let read: Vec<_> = written
+        .lines()
+        .map(crate::AnalysisReport::from_json)
+        .collect::<Result<_, _>>()?;
+    assert_eq!(read, reports);
+
+    let mut failing = |_: &crate::AnalysisReport| Err(FbasError::IoError("unreachable"));
+    assert!(failing.send(&reports[0]).is_err());
+    let missing_dir = std::env::temp_dir()
+        .join("no_such_dir")
+        .join("reports.jsonl");
+    assert!(FileSink::new(missing_dir).send(&reports[0]).is_err());
+    Ok(())
+}
+
+#[cfg(feature = "net")]
+#[test]
+fn test_webhook_sink() -> Result<(), Box<dyn std::error::Error>> {
+    use crate::{ReportSink, WebhookSink};
+    use std::io::{Read, Write};
+
+    // a server answering each request with `status`, returning the body posted
+    let serve = |status: &'static str| -> std::io::Result<_> {
+        let listener = std::net::TcpListener::bind("127.0.0.1:0")?;
+        let url = format!("http://{}/hook", listener.local_addr()?);
+        let server = std::thread::spawn(move || -> std::io::Result<String> {
+            let (mut stream, _) = listener.accept()?;
+            let mut request = vec![];
+            let mut buf = [0; 4096];
+            let body = loop {
+                let n = stream.read(&mut buf)?;
+                request.extend_from_slice(&buf[..n]);
+                let text = String::from_utf8_lossy(&request).to_string();
+                let Some((head, body)) = text.split_once("\r\n\r\n") else {
+                    continue;
+                };
+                let length: usize = head
+                    .lines()
+                    .find_map(|line| {
+                        line.to_lowercase()
+                            .strip_prefix("content-length: ")
+                            .map(str::to_string)
+                    })
+                    .and_then(|length| length.parse().ok())
+                    .unwrap_or_default();
+                if body.len() >= length || n == 0 {
+                    assert!(head.starts_with("POST /hook"));
+                    assert!(head
+                        .to_lowercase()
+                        .contains("content-type: application/json"));
+                    break body.to_string();
+                }
+            };
+            write!(stream, "HTTP/1.1 {}\r\nContent-Length: 0\r\n\r\n", status)?;
+            Ok(body)
+        });
+        Ok((url, server))
+    };
+
+    let mut solver = FbasAnalyzer::from_dsl("a, b, c: 2-of(a, b, c)", Basic::default())?;
+    solver.solve();
+    let report = solver.into_report(None)?;
+    let (url, server) = serve("200 OK")?;
+    WebhookSink::new(&url).send(&report)?;
+    let posted = server.join().unwrap()?;
+    assert_eq!(crate::AnalysisReport::from_json(&posted)?, report);
+
+    // rejected, not retried
+    let (url, server) = serve("404 Not Found")?;
+    assert!(WebhookSink::new(&url).send(&report).is_err());
+    server.join().unwrap()?;
+    Ok(())
+}
+
+#[test]
+fn test_watcher() -> Result<(), Box<dyn std::error::Error>> {
+    use crate::{AnalysisReport, Watcher};
+
+    // delays are spread around the interval, within the jitter
+    let interval = Duration::from_secs(60);
+    let jitter = Duration::from_secs(10);
+    let mut watcher = Watcher::new(interval, jitter);
+    let delays: BTreeSet<_> = (0..100).map(|_| watcher.next_delay()).collect();
+    assert!(delays.len() > 90);
+    assert!(delays
+        .iter()
+        .all(|d| *d >= interval - jitter && *d <= interval + jitter));
+    assert!(delays.iter().any(|d| *d < interval) && delays.iter().any(|d| *d > interval));
+    let mut watcher = Watcher::new(interval, Duration::ZERO);
+    assert_eq!(watcher.next_delay(), interval);
+
+    // only snapshots differing from the previous one are reported
+    let snapshots = [
+        "a, b, c: 2-of(a, b, c)",
+        "a, b, c: 2-of(c, b, a)",
+        "a: 1-of(a)\nb: 1-of(b)",
+        "a: 1-of(a)\nb: 1-of(b)",
+        "a, b, c: 2-of(a, b, c)",
+    ];
+    let mut fetched = snapshots.iter();
+    let mut reports: Vec<AnalysisReport> = vec![];
+    let mut sink = |report: &AnalysisReport| {
+        reports.push(report.clone());
+        Ok(())
+    };
+    let mut watcher = Watcher::new(Duration::ZERO, Duration::ZERO);
+    assert_eq!(watcher.last_snapshot_hash(), None);
+    let fetch = || FbasAnalyzer::from_dsl(fetched.next().unwrap(), Basic::default());
+    assert_eq!(watcher.run(snapshots.len(), fetch, &mut sink)?, 3);
+    let statuses: Vec<_> = reports.iter().map(|r| r.status.to_string()).collect();
+    assert_eq!(statuses[0], "UNSAT");
+    assert!(statuses[1].starts_with("SAT"));
+    assert_eq!(statuses[2], "UNSAT");
+    let last = FbasAnalyzer::from_dsl(snapshots[4], Basic::default())?;
+    assert_eq!(
+        watcher.last_snapshot_hash(),
+        Some(last.snapshot_hash()?.as_str())
+    );
+
+    // a restarted watcher picks up from the last snapshot reported
+    let mut watcher =
+        Watcher::new(Duration::ZERO, Duration::ZERO).with_last_snapshot_hash(last.snapshot_hash()?);
+    let mut unreachable = |_: &AnalysisReport| Err(FbasError::InternalError("reported again"));
+    assert!(!watcher.analyze(last, &mut unreachable)?);
+    Ok(())
+}
+
+#[cfg(feature = "store")]
+#[test]
+fn test_report_store() -> Result<(), Box<dyn std::error::Error>> {
+    use crate::{ReportStore, RobustnessWeights};
+
+    // order and unknown validators aside, as for `Fbas::diff`
+    let hash = |dsl| FbasAnalyzer::from_dsl(dsl, Basic::default())?.snapshot_hash();
+    let before = hash("a, b, c: 2-of(a, b, c)")?;
+    assert_eq!(before, hash("c, b, a: 2-of(c, x, b, a)")?);
+    assert_ne!(before, hash("a, b, c: 3-of(a, b, c)")?);
+    // the same whatever the build
+    assert_eq!(before, "d0e49c5f11756be3");
+
+    let analyze = |dsl, with_top_tier: bool| -> Result<_, Box<dyn std::error::Error>> {
+        let mut solver = FbasAnalyzer::from_dsl(dsl, Basic::default())?;
+        let robustness = if with_top_tier {
+            solver.robustness_score(None, &RobustnessWeights::default(), 2, Basic::default)?
+        } else {
+            None
+        };
+        solver.solve();
+        let hash = solver.snapshot_hash()?;
+        Ok((hash, solver.into_report(robustness)?))
+    };
+    let path = std::env::temp_dir().join(format!("report_store_{}.db", std::process::id()));
+    let _ = std::fs::remove_file(&path);
+    let store = ReportStore::open(&path)?;
+    assert!(store.latest_reports()?.is_empty());
+    assert_eq!(store.latest_snapshot_hash("public")?, None);
+    let snapshots = [
+        ("public", "a, b, c: 2-of(a, b, c)", true),
+        ("test", "a: 1-of(a)\nb: 1-of(b)", false),
+        ("public", "a: 1-of(a)\nb, c: 1-of(b, c)", false),
+        ("public", "a, b, c, d: 3-of(a, b, c, d)", true),
+    ];
+    let mut recorded = vec![];
+    for (network, dsl, with_top_tier) in snapshots {
+        let (hash, report) = analyze(dsl, with_top_tier)?;
+        store.insert(network, &hash, &report)?;
+        recorded.push((hash, report));
+    }
+    drop(store);
+
+    let store = ReportStore::open(&path)?;
+    let latest = store.latest_reports()?;
+    std::fs::remove_file(&path)?;
+    let networks: Vec<_> = latest.iter().map(|r| r.network.as_str()).collect();
+    assert_eq!(networks, ["public", "test"]);
+    assert_eq!(latest[0].snapshot_hash, recorded[3].0);
+    assert_eq!(latest[0].report, recorded[3].1);
+    assert_eq!(latest[0].report.status, SolveStatus::UNSAT);
+    assert_eq!(latest[1].report, recorded[1].1);
+    assert_eq!(
+        store.latest_snapshot_hash("public")?,
+        Some(recorded[3].0.clone())
+    );
+
+    let history = store.top_tier_size_history("public")?;
+    let sizes: Vec<_> = history.iter().map(|entry| entry.size).collect();
+    assert_eq!(sizes, [3, 4]);
+    assert_eq!(history[0].snapshot_hash, recorded[0].0);
+    assert!(history[0].recorded_at <= history[1].recorded_at);
+    assert!(store.top_tier_size_history("test")?.is_empty());
+
+    let in_memory = ReportStore::open_in_memory()?;
+    in_memory.insert("public", &recorded[0].0, &recorded[0].1)?;
+    assert_eq!(in_memory.latest_reports()?.len(), 1);
+    Ok(())
+}
+
+#[test]
+fn test_pruning_strategy() -> Result<(), Box<dyn std::error::Error>> {
+    struct Prune(Vec<&'static str>);
+    impl PruningStrategy for Prune {
+        fn prune(&self, fbas: &Fbas) -> BTreeSet<NodeIndex> {
+            self.0
+                .iter()
+                .map(|v| fbas.try_get_validator_index(v).unwrap())
+                .collect()
+        }
+    }
+
+    // d and e depend on the top tier, which doesn't depend on them
+    let dsl = "a, b, c: 2-of(a, b, c)\nd, e: 3-of(a, b, c, d, e)";
+    let fbas = Fbas::from_dsl(dsl)?;
+    let pruned: BTreeSet<String> = DefaultPruning
+        .prune(&fbas)
+        .iter()
+        .filter_map(|ni| fbas.try_get_validator_string(ni).ok())
+        .collect();
+    assert_eq!(pruned, BTreeSet::from(["d".to_string(), "e".to_string()]));
+    for strategy in [&DefaultPruning as &dyn PruningStrategy, &Prune(vec![])] {
+        let mut solver = FbasAnalyzer::from_fbas_with_strategy(
+            Fbas::from_dsl(dsl)?,
+            strategy,
+            Basic::default(),
+        )?;
+        assert_eq!(solver.solve(), SolveStatus::UNSAT);
+    }
+
+    // {a, b} and {c, d} are disjoint quorums, as are {b, c} and {d, e} without a
+    let dsl = "a, b, c, d, e: 2-of(a, b, c, d, e)";
+    let mut solver = FbasAnalyzer::from_fbas_with_strategy(
+        Fbas::from_dsl(dsl)?,
+        &Prune(vec!["a"]),
+        Basic::default(),
+    )?;
+    let SolveStatus::SAT((quorum_a, quorum_b)) = solver.solve() else {
+        panic!("expected a split");
+    };
+    for quorum in [quorum_a, quorum_b] {
+        let quorum: BTreeSet<NodeIndex> = quorum.into_iter().collect();
+        assert!(solver.fbas.is_quorum(&quorum));
+        let a = solver.fbas.try_get_validator_index("a")?;
+        assert!(!quorum.contains(&a));
+    }
+
+    struct OutOfGraph;
+    impl PruningStrategy for OutOfGraph {
+        fn prune(&self, fbas: &Fbas) -> BTreeSet<NodeIndex> {
+            BTreeSet::from([NodeIndex::new(fbas.graph.node_count())])
+        }
+    }
+    assert!(FbasAnalyzer::from_fbas_with_strategy(
+        Fbas::from_dsl(dsl)?,
+        &OutOfGraph,
+        Basic::default()
+    )
+    .is_err());
+    Ok(())
+}
+
+#[test]
+fn test_solve_under_uncertainty() -> Result<(), Box<dyn std::error::Error>> {
+    let solver = FbasAnalyzer::from_dsl("a, b, c: 2-of(a, b, c)", Basic::default())?;
+
+    // alone, a would be a quorum disjoint from {b, c}
+    let uncertain = UncertainQsets::from_dsl("a: 1-of(a)\na: 2-of(a, b, c)")?;
+    let report = solver.solve_under_uncertainty(&uncertain, Basic::default)?;
+    assert!(!report.is_robust());
+    assert_eq!(report.optimistic, SolveStatus::UNSAT);
+    assert_eq!(
+        report.optimistic_resolution,
+        BTreeMap::from([("a".to_string(), 1)])
+    );
+    assert!(matches!(report.pessimistic, SolveStatus::SAT(_)));
+    assert_eq!(
+        report.pessimistic_resolution,
+        BTreeMap::from([("a".to_string(), 0)])
+    );
+
+    let uncertain = UncertainQsets::from_dsl("a: 2-of(a, b, c)\na, b: 3-of(a, b, c)")?;
+    let report = solver.solve_under_uncertainty(&uncertain, Basic::default)?;
+    assert!(report.is_robust());
+    assert_eq!(report.pessimistic, SolveStatus::UNSAT);
+    assert_eq!(report.resolutions, 2);
+
+    // without uncertainty, this is a regular analysis
+    let report = solver.solve_under_uncertainty(&UncertainQsets::default(), Basic::default)?;
+    assert_eq!(report.resolutions, 1);
+    assert_eq!(report.optimistic, SolveStatus::UNSAT);
+
+    let uncertain = UncertainQsets::from_dsl("x: 1-of(a)")?;
+    assert!(solver
+        .solve_under_uncertainty(&uncertain, Basic::default)
+        .is_err());
+
+    let validators: Vec<String> = (0..11).map(|i| format!("v{}", i)).collect();
+    let solver = FbasAnalyzer::from_dsl(
+        &format!("{}: 6-of({})", validators.join(", "), validators.join(", ")),
+        Basic::default(),
+    )?;
+    let alternatives = format!(
+        "{}: 1-of(v0)\n{}: 2-of(v0, v1)",
+        validators.join(", "),
+        validators.join(", ")
+    );
+    let uncertain = UncertainQsets::from_dsl(&alternatives)?;
+    assert!(solver
+        .solve_under_uncertainty(&uncertain, Basic::default)
+        .is_err());
+    Ok(())
+}
+
+#[test]
+fn test_analyzer_pool() -> Result<(), Box<dyn std::error::Error>> {
+    let dsl = "a, b, c: 2-of(a, b, c)";
+    let pool = &AnalyzerPool::new(1);
+    let (release, released) = std::sync::mpsc::channel::<()>();
+    std::thread::scope(|scope| -> Result<(), Box<dyn std::error::Error>> {
+        let first = scope.spawn(move || {
+            pool.analyze(Deadline::after(Duration::from_secs(60)), |cb| {
+                released.recv().unwrap();
+                FbasAnalyzer::from_dsl(dsl, cb)
+            })
+        });
+        while pool.running() == 0 {
+            std::thread::sleep(Duration::from_millis(1));
+        }
+
+        // the only slot is taken until the deadline
+        let report = pool.analyze(Deadline::after(Duration::from_millis(20)), |cb| {
+            FbasAnalyzer::from_dsl(dsl, cb)
+        })?;
+        assert_eq!(report.status, SolveStatus::UNKNOWN);
+        assert_eq!(report.stats.phase, SolvePhase::NotSolved);
+        assert_eq!(pool.queued(), 0);
+
+        release.send(())?;
+        let report = first.join().unwrap()?;
+        assert_eq!(report.status, SolveStatus::UNSAT);
+        Ok(())
+    })?;
+    assert_eq!(pool.running(), 0);
+    let report = pool.analyze(Deadline::after(Duration::from_secs(60)), |cb| {
+        FbasAnalyzer::from_dsl(dsl, cb)
+    })?;
+    assert_eq!(report.status, SolveStatus::UNSAT);
+    Ok(())
+}
+
+#[test]
+fn test_for_each_combination() {
+    // slice propositions are numbered in this order, which must not change
+    for n in 0..7 {
+        for k in 0..=n + 1 {
+            let mut combinations = vec![];
+            for_each_combination(n, k, |j, positions| {
+                assert_eq!(j, combinations.len());
+                combinations.push(positions.to_vec());
+            });
+            let expected: Vec<Vec<usize>> = (0..n).combinations(k).collect();
+            assert_eq!(combinations, expected, "{} out of {}", k, n);
+        }
+    }
+}
+
+#[test]
+fn test_branching_heuristic() -> Result<(), Box<dyn std::error::Error>> {
+    for path in [
+        "./tests/test_data/conflicted.json",
+        "./tests/test_data/top_tier.json",
+        "./tests/test_data/random/almost_symmetric_network_12_orgs_delete_prob_factor_1.json",
+    ] {
+        let mut solver = FbasAnalyzer::from_json_path(path, Basic::default())?;
+        let expected = solver.solve();
+        let num_vars = solver.get_stats().num_vars;
+        let mut solver = solver.with_branching_heuristic(BranchingHeuristic::TopTier)?;
+        assert_eq!(solver.get_stats().phase, SolvePhase::NotSolved);
+        assert_eq!(solver.get_stats().num_vars, num_vars);
+        let status = solver.solve();
+        assert_eq!(
+            matches!(status, SolveStatus::SAT(_)),
+            matches!(expected, SolveStatus::SAT(_)),
+            "{}",
+            path
+        );
+        if let SolveStatus::SAT((quorum_a, quorum_b)) = status {
+            for quorum in [quorum_a, quorum_b] {
+                assert!(solver.fbas.is_quorum(&quorum.into_iter().collect()));
+            }
+        }
+    }
+    Ok(())
+}
+
+#[test]
+fn test_org_failure_resilience() -> Result<(), Box<dyn std::error::Error>> {
+    let solver = FbasAnalyzer::from_dsl(
+        "a1, a2, a3, b1, b2, b3, c1, c2, c3: \
+         2-of(2-of(a1, a2, a3), 2-of(b1, b2, b3), 2-of(c1, c2, c3))",
+        Basic::default(),
+    )?;
+    let orgs = crate::Organizations::new(
+        ["a", "b", "c"]
+            .into_iter()
+            .map(|org| {
+                let validators = (1..=3).map(|i| format!("{}{}", org, i)).collect();
+                (org.to_string(), validators)
+            })
+            .collect(),
+    );
+    let report = solver.org_failure_resilience(&orgs, 5, Basic::default)?;
+    // a crashed organization is tolerated, a byzantine one isn't
+    let summary: Vec<_> = report
+        .rows
+        .iter()
+        .map(|row| {
+            (
+                row.failed_orgs,
+                row.combinations,
+                row.available,
+                row.intersecting,
+            )
+        })
+        .collect();
+    assert_eq!(
+        summary,
+        vec![(0, 1, 1, 1), (1, 3, 3, 0), (2, 3, 0, 0), (3, 1, 0, 1)]
+    );
+    assert_eq!(report.rows[1].available_fraction(), 1.0);
+    assert!(report.to_string().contains("100.0%"));
+    Ok(())
+}
+
+#[test]
+fn test_try_solve_catches_panics() -> Result<(), Box<dyn std::error::Error>> {
+    struct Panicking;
+    impl batsat::Callbacks for Panicking {
+        fn on_start(&mut self) {
+            panic!("callback failed");
+        }
+    }
+
+    let mut solver = FbasAnalyzer::from_dsl("a, b, c: 2-of(a, b, c)", Panicking)?;
+    let Err(crate::FbasError::Internal(failure)) = solver.try_solve() else {
+        panic!("expected the panic to be caught");
+    };
+    assert_eq!(failure.entry_point, "solve");
+    assert_eq!(failure.stats.phase, SolvePhase::Search);
+    assert!(failure.stats.num_vars > 0);
+    assert_eq!(failure.num_validators, 3);
+    assert_eq!(failure.message, "callback failed");
+    // the solver is released, so solving again doesn't panic
+    assert_eq!(solver.try_solve()?, SolveStatus::UNKNOWN);
+    Ok(())
+}
+
+#[test]
+fn test_check_invariants() -> Result<(), Box<dyn std::error::Error>> {
+    let solver = FbasAnalyzer::from_dsl(
+        "a, b: 2-of(a, b, 1-of(c, d))\n\
+         c, d: 2-of(c, d, 1-of(a, b))",
+        Basic::default(),
+    )?;
+    solver.check_invariants()?;
+    let solver =
+        FbasAnalyzer::from_json_path("./tests/test_data/others/watchers.json", Basic::default())?;
+    solver.check_invariants()?;
+
+    // a validator with a second qset
+    let mut fbas = Fbas::from_dsl("a: 1-of(a)\nb: 1-of(b)")?;
+    let qsets: Vec<_> = fbas
+        .validators
+        .iter()
+        .map(|vi| fbas.graph.neighbors(*vi).next().unwrap())
+        .collect();
+    fbas.graph.add_edge(fbas.validators[0], qsets[1], ());
+    assert!(fbas.check_invariants().is_err());
+    Ok(())
+}
+
+#[test]
+fn test_minimal_splitting_sets() -> Result<(), Box<dyn std::error::Error>> {
+    // two faulty validators leave 1-of-2 qsets to the others
+    let solver = FbasAnalyzer::from_dsl("a, b, c, d: 3-of(a, b, c, d)", Basic::default())?;
+    let splitting = solver.minimal_splitting_sets(4, Basic::default)?;
+    assert!(splitting.complete);
+    let expected: Vec<Vec<String>> = ["ab", "ac", "ad", "bc", "bd", "cd"]
+        .into_iter()
+        .map(|set| set.chars().map(String::from).collect())
+        .collect();
+    assert_eq!(splitting.sets, expected);
+    assert!(solver
+        .minimal_splitting_sets(1, Basic::default)?
+        .sets
+        .is_empty());
+
+    // a k-subset is splitting iff it contains a minimal splitting set, which
+    // is checked against intersection with one organization per validator
+    let solver = FbasAnalyzer::from_dsl(
+        "a1, a2, a3, b1, b2, b3, c1, c2, c3: \
+         2-of(2-of(a1, a2, a3), 2-of(b1, b2, b3), 2-of(c1, c2, c3))",
+        Basic::default(),
+    )?;
+    let splitting = solver.minimal_splitting_sets(3, Basic::default)?;
+    assert!(splitting.sets.windows(2).all(|w| w[0].len() <= w[1].len()));
+    let validators: Vec<String> = ["a", "b", "c"]
+        .into_iter()
+        .flat_map(|org| (1..=3).map(move |i| format!("{}{}", org, i)))
+        .collect();
+    let orgs = crate::Organizations::new(
+        validators
+            .iter()
+            .map(|v| (v.clone(), vec![v.clone()]))
+            .collect(),
+    );
+    let report = solver.org_failure_resilience(&orgs, 3, Basic::default)?;
+    for row in &report.rows {
+        let splitting_subsets = validators
+            .iter()
+            .combinations(row.failed_orgs)
+            .filter(|subset| {
+                splitting
+                    .sets
+                    .iter()
+                    .any(|set| set.iter().all(|v| subset.contains(&v)))
+            })
+            .count();
+        assert_eq!(splitting_subsets, row.combinations - row.intersecting);
+    }
+
+    // without quorum intersection, nothing needs to be faulty
+    let solver = FbasAnalyzer::from_dsl("a: 1-of(a)\nb: 1-of(b)", Basic::default())?;
+    assert_eq!(
+        solver.minimal_splitting_sets(2, Basic::default)?.sets,
+        vec![Vec::<String>::new()]
+    );
+    Ok(())
+}
+
+#[test]
+fn test_enumerate_minimal_quorums() -> Result<(), Box<dyn std::error::Error>> {
+    let solver = FbasAnalyzer::from_dsl("a, b, c, d: 3-of(a, b, c, d)", Basic::default())?;
+    let minimal = solver.enumerate_minimal_quorums(usize::MAX, Basic::default)?;
+    assert!(minimal.complete);
+    let expected: Vec<Vec<String>> = ["abc", "abd", "acd", "bcd"]
+        .into_iter()
+        .map(|set| set.chars().map(String::from).collect())
+        .collect();
+    assert_eq!(minimal.quorums, expected);
+
+    // two validators of each of two organizations, and {x} on its own
+    let solver = FbasAnalyzer::from_dsl(
+        "a1, a2, a3, b1, b2, b3, c1, c2, c3: \
+         2-of(2-of(a1, a2, a3), 2-of(b1, b2, b3), 2-of(c1, c2, c3))\n\
+         x: 1-of(x)",
+        Basic::default(),
+    )?;
+    let minimal = solver.enumerate_minimal_quorums(usize::MAX, Basic::default)?;
+    assert!(minimal.complete);
+    assert_eq!(minimal.quorums.len(), 28);
+    assert_eq!(minimal.quorums[0], vec!["x"]);
+    assert!(minimal.quorums[1..].iter().all(|q| q.len() == 4));
+    let capped = solver.enumerate_minimal_quorums(5, Basic::default)?;
+    assert!(!capped.complete);
+    assert_eq!(capped.quorums.len(), 5);
+    assert!(capped.quorums.iter().all(|q| minimal.quorums.contains(q)));
+
+    let solver =
+        FbasAnalyzer::from_json_path("./tests/test_data/missing_1.json", Basic::default())?;
+    let minimal = solver.enumerate_minimal_quorums(usize::MAX, Basic::default)?;
+    assert!(minimal.complete && minimal.quorums.is_empty());
+    Ok(())
+}
+
+#[test]
+fn test_minimize_quorum() -> Result<(), Box<dyn std::error::Error>> {
+    let solver = FbasAnalyzer::from_dsl(
+        "a1, a2, a3, b1, b2, b3, c1, c2, c3: \
+         2-of(2-of(a1, a2, a3), 2-of(b1, b2, b3), 2-of(c1, c2, c3))",
+        Basic::default(),
+    )?;
+    let all = ["a1", "a2", "a3", "b1", "b2", "b3", "c1", "c2", "c3"];
+    assert!(!solver.is_minimal_quorum(&all)?);
+    let minimal = solver.minimize_quorum(&all)?;
+    let minimal: Vec<&str> = minimal.iter().map(String::as_str).collect();
+    // members are dropped in validator order while a quorum remains
+    assert_eq!(minimal, vec!["b2", "b3", "c2", "c3"]);
+    assert!(solver.is_minimal_quorum(&minimal)?);
+    // not a quorum at all
+    assert!(!solver.is_minimal_quorum(&["a1", "a2", "b1"])?);
+    assert!(solver.minimize_quorum(&["a1", "a2", "b1"])?.is_empty());
+
+    // agrees with the enumeration
+    for quorum in solver
+        .enumerate_minimal_quorums(usize::MAX, Basic::default)?
+        .quorums
+    {
+        let quorum: Vec<&str> = quorum.iter().map(String::as_str).collect();
+        assert!(solver.is_minimal_quorum(&quorum)?);
+        assert_eq!(solver.minimize_quorum(&quorum)?, quorum);
+    }
+    Ok(())
+}
+
+#[test]
+fn test_is_quorum() -> Result<(), Box<dyn std::error::Error>> {
+    let solver = FbasAnalyzer::from_dsl(
+        "a1, a2, a3: 2-of(a1, a2, a3)\nb: 2-of(b, a1)\nc: 1-of(x)",
+        Basic::default(),
+    )?;
+    assert!(solver.is_quorum(&["a1", "a2"])?);
+    assert!(solver.is_quorum(&["a1", "a2", "b"])?);
+    // b's qset isn't satisfied without a1
+    assert!(!solver.is_quorum(&["a2", "a3", "b"])?);
+    assert!(solver.contains_quorum(&["a2", "a3", "b"])?);
+    assert!(!solver.is_quorum(&["a1", "a2", "a3", "c"])?);
+    assert!(!solver.contains_quorum(&["a1", "b", "c"])?);
+    assert!(!solver.is_quorum(&[])? && !solver.contains_quorum(&[])?);
+    assert!(solver.is_quorum(&["x"]).is_err());
+
+    // both sides of a potential split are quorums
+    let mut solver = FbasAnalyzer::from_dsl(
+        "a, b: 2-of(a, b)\nc, d: 2-of(c, d)\ne: 2-of(1-of(a, b), 1-of(c, d))",
+        Basic::default(),
+    )?;
+    assert!(matches!(solver.solve(), SolveStatus::SAT(_)));
+    let (qa, qb) = solver.get_potential_split()?;
+    for quorum in [qa, qb] {
+        let quorum: Vec<&str> = quorum.iter().map(String::as_str).collect();
+        assert!(solver.is_quorum(&quorum)? && solver.contains_quorum(&quorum)?);
+    }
+    Ok(())
+}
+
+#[test]
+fn test_transitive_dependencies() -> Result<(), Box<dyn std::error::Error>> {
+    let dsl = "a, b: 2-of(a, b)\nc: 2-of(c, 1-of(a, d))\nd: 1-of(e)\ne: 1-of(x)";
+    let solver = FbasAnalyzer::from_dsl(dsl, Basic::default())?;
+    let deps = |v| -> Result<Vec<String>, FbasError> {
+        Ok(solver.transitive_dependencies(v)?.into_iter().collect())
+    };
+    assert_eq!(deps("a")?, vec!["a", "b"]);
+    // through the inner set, and on through d's qset
+    assert_eq!(deps("c")?, vec!["a", "b", "c", "d", "e"]);
+    // d doesn't depend on itself, and e only on an unknown validator
+    assert_eq!(deps("d")?, vec!["e"]);
+    assert!(deps("e")?.is_empty());
+    assert!(solver.transitive_dependencies("x").is_err());
+    Ok(())
+}
+
+#[test]
+fn test_validators_outside_main_scc() -> Result<(), Box<dyn std::error::Error>> {
+    // d follows the main component, e and f form a cycle without a quorum
+    let solver = FbasAnalyzer::from_dsl(
+        "a, b, c: 2-of(a, b, c)\nd: 2-of(a, b, c)\ne: 3-of(e, f, x)\nf: 1-of(e)",
+        Basic::default(),
+    )?;
+    assert_eq!(
+        solver.validators_outside_main_scc()?,
+        Some(vec!["d".to_string(), "e".to_string(), "f".to_string()])
+    );
+    // no main component if two of them contain a quorum
+    let solver = FbasAnalyzer::from_dsl("a, b: 2-of(a, b)\nc, d: 2-of(c, d)", Basic::default())?;
+    assert_eq!(solver.validators_outside_main_scc()?, None);
+    // every validator outside it is pruned
+    let solver = FbasAnalyzer::from_json_path("./tests/test_data/top_tier.json", Basic::default())?;
+    let outside = solver.validators_outside_main_scc()?.unwrap();
+    let pruned = DefaultPruning.prune(&solver.fbas);
+    for v in outside {
+        assert!(pruned.contains(&solver.fbas.try_get_validator_index(&v)?));
+    }
+    Ok(())
+}
+
+#[test]
+fn test_validator_metrics() -> Result<(), Box<dyn std::error::Error>> {
+    // a trusts b, which trusts c, which only trusts itself
+    let solver = FbasAnalyzer::from_dsl("a: 1-of(b)\nb: 1-of(c)\nc: 1-of(c)", Basic::default())?;
+    let metrics = solver.validator_metrics(Basic::default)?;
+    let summary: Vec<_> = metrics
+        .iter()
+        .map(|m| {
+            (
+                m.validator.as_str(),
+                m.minimal_quorum_frequency,
+                m.trust_in_degree,
+                m.betweenness,
+            )
+        })
+        .collect();
+    assert_eq!(
+        summary,
+        vec![
+            ("a", Some(0.0), 0, 0.0),
+            ("b", Some(0.0), 1, 1.0),
+            ("c", Some(1.0), 1, 0.0),
+        ]
+    );
+
+    // all validators of a symmetric network are alike, each in 12 of the 27
+    // minimal quorums of 4 validators
+    let solver = FbasAnalyzer::from_dsl(
+        "a1, a2, a3, b1, b2, b3, c1, c2, c3: \
+         2-of(2-of(a1, a2, a3), 2-of(b1, b2, b3), 2-of(c1, c2, c3))",
+        Basic::default(),
+    )?;
+    for m in solver.validator_metrics(Basic::default)? {
+        assert_eq!(m.minimal_quorum_frequency, Some(12.0 / 27.0));
+        assert_eq!(m.trust_in_degree, 8);
+        // every pair of others is adjacent
+        assert_eq!(m.betweenness, 0.0);
+    }
+    Ok(())
+}
+
+#[test]
+fn test_local_view() -> Result<(), Box<dyn std::error::Error>> {
+    let dsl = "a, b: 2-of(a, b)\nc: 2-of(c, a)\nd, e: 2-of(d, e)\nf: 1-of(x)";
+    let c = LocalView::from_dsl(dsl, "c")?;
+    // only c and what it depends on, a and b
+    assert_eq!(c.num_validators(), 3);
+    assert!(c.can_externalize());
+    assert!(c.is_v_blocking(&["a"]));
+    assert!(c.is_v_blocking(&["c"]));
+    assert!(!c.is_v_blocking(&["b"]));
+    assert!(!c.is_v_blocking(&[]));
+
+    let d = LocalView::from_dsl(dsl, "d")?;
+    assert_eq!(d.num_validators(), 2);
+    assert!(d.can_externalize());
+    assert!(d.is_v_blocking(&["e"]));
+
+    // x is unknown
+    let f = LocalView::from_dsl(dsl, "f")?;
+    assert!(!f.can_externalize());
+    assert!(f.is_v_blocking(&[]));
+    assert!(LocalView::from_dsl(dsl, "x").is_err());
+
+    // agrees with the full network
+    let fbas = Fbas::from_dsl(dsl)?;
+    let quorum = fbas.max_quorum(&BTreeSet::new());
+    for v in ["a", "b", "c", "d", "e", "f"] {
+        assert_eq!(
+            LocalView::from_dsl(dsl, v)?.can_externalize(),
+            quorum.contains(&fbas.try_get_validator_index(v)?)
+        );
+    }
+    Ok(())
+}
+
+#[test]
+fn test_minimal_quorums_are_shared() -> Result<(), Box<dyn std::error::Error>> {
+    let dsl = "a1, a2, a3, b1, b2, b3, c1, c2, c3: \
+               2-of(2-of(a1, a2, a3), 2-of(b1, b2, b3), 2-of(c1, c2, c3))\n\
+               d: 1-of(a1)";
+    let fresh = FbasAnalyzer::from_dsl(dsl, Basic::default())?;
+    let blocking = fresh.minimal_blocking_sets(4)?;
+
+    let solver = FbasAnalyzer::from_dsl(dsl, Basic::default())?;
+    // capped enumerations aren't remembered
+    let capped = solver.enumerate_minimal_quorums(2, Basic::default)?;
+    assert!(!capped.complete);
+    assert!(solver.minimal_quorums.get().is_none());
+
+    let all = solver.enumerate_minimal_quorums(usize::MAX, Basic::default)?;
+    assert!(all.complete);
+    assert_eq!(all.quorums.len(), 27);
+    // answered from the remembered quorums, the callbacks are never created
+    let first = solver.enumerate_minimal_quorums(2, || -> Basic { unreachable!() })?;
+    assert_eq!(first.quorums, all.quorums[..2]);
+    assert!(!first.complete);
+    let top_tier = solver.top_tier(|| -> Basic { unreachable!() })?;
+    assert_eq!(
+        top_tier,
+        Some(
+            ["a1", "a2", "a3", "b1", "b2", "b3", "c1", "c2", "c3"]
+                .map(String::from)
+                .to_vec()
+        )
+    );
+    // blocking sets checked against the minimal quorums agree
+    assert_eq!(solver.minimal_blocking_sets(4)?, blocking);
+    assert_eq!(
+        solver.find_minimal_blocking_sets(usize::MAX, Basic::default())?,
+        fresh.find_minimal_blocking_sets(usize::MAX, Basic::default())?
+    );
+    Ok(())
+}
+
+#[test]
+fn test_availability() -> Result<(), Box<dyn std::error::Error>> {
+    let solver = FbasAnalyzer::from_dsl(
+        "a1, a2, a3, b1, b2, b3, c1, c2, c3: \
+         2-of(2-of(a1, a2, a3), 2-of(b1, b2, b3), 2-of(c1, c2, c3))\n\
+         d: 1-of(a1)",
+        Basic::default(),
+    )?;
+    let names = |names: &[&str]| names.iter().map(|v| v.to_string()).collect::<Vec<_>>();
+
+    let none = solver.availability(&[])?;
+    assert!(none.quorum_exists);
+    assert_eq!(none.blocked, Vec::<String>::new());
+    assert_eq!(none.available.len(), 10);
+
+    // org a is down, b and c carry on, and so does a3 with them
+    let org_a = solver.availability(&["a1", "a2"])?;
+    assert!(org_a.quorum_exists);
+    assert_eq!(
+        org_a.available,
+        names(&["a3", "b1", "b2", "b3", "c1", "c2", "c3"])
+    );
+    assert_eq!(org_a.blocked, names(&["d"]));
+
+    let two_orgs = solver.availability(&["a1", "a2", "b1", "b2"])?;
+    assert_eq!(
+        two_orgs,
+        Availability {
+            quorum_exists: false,
+            available: vec![],
+            blocked: names(&["a3", "b3", "c1", "c2", "c3", "d"]),
+        }
+    );
+    assert!(solver.availability(&["x"]).is_err());
+    Ok(())
+}
+
+#[test]
+fn test_symmetry_breaking() -> Result<(), Box<dyn std::error::Error>> {
+    let mut paths = vec![];
+    for dir in ["./tests/test_data/", "./tests/test_data/random/"] {
+        for entry in std::fs::read_dir(dir)? {
+            let path = entry?.path();
+            let is_orgs = path.to_str().is_some_and(|p| p.ends_with("_orgs.json"));
+            if path.extension().is_some_and(|ext| ext == "json") && !is_orgs {
+                paths.push(path);
+            }
+        }
+    }
+
+    let (mut sat, mut unsat) = (0, 0);
+    for path in paths {
+        let path = path.as_os_str().to_str().unwrap();
+        if Fbas::from_json_path(path)?.validators.len() > 40 {
+            continue;
+        }
+        let mut solver = FbasAnalyzer::from_json_path(path, Basic::default())?;
+        let mut broken =
+            FbasAnalyzer::from_json_path(path, Basic::default())?.with_symmetry_breaking(true)?;
+        assert!(broken.get_stats().num_vars > solver.get_stats().num_vars);
+        match (solver.solve(), broken.solve()) {
+            (SolveStatus::UNSAT, SolveStatus::UNSAT) => unsat += 1,
+            (SolveStatus::SAT(_), SolveStatus::SAT((qa, qb))) => {
+                let fbas = &broken.fbas;
+                let (qa, qb): (BTreeSet<_>, BTreeSet<_>) =
+                    (qa.into_iter().collect(), qb.into_iter().collect());
+                assert!(fbas.is_quorum(&qa) && fbas.is_quorum(&qb), "{}", path);
+                assert!(qa.is_disjoint(&qb), "{}", path);
+                let first = fbas
+                    .validators
+                    .iter()
+                    .find(|ni| qa.contains(ni) || qb.contains(ni));
+                assert!(qa.contains(first.unwrap()), "{}", path);
+                sat += 1;
+            }
+            (expected, actual) => panic!("{}: {:?} vs {:?}", path, expected, actual),
+        }
+        assert!(broken.into_report(None)?.provenance.symmetry_breaking);
+    }
+    assert!(sat >= 3 && unsat >= 3);
+    Ok(())
+}
+
+#[test]
+fn test_critical_organizations() -> Result<(), Box<dyn std::error::Error>> {
+    let solver = FbasAnalyzer::from_dsl(
+        "a1, a2, a3, b1, b2, b3, c1, c2, c3: \
+         2-of(2-of(a1, a2, a3), 2-of(b1, b2, b3), 2-of(c1, c2, c3))",
+        Basic::default(),
+    )?;
+    let orgs = crate::Organizations::new(
+        ["a", "b", "c"]
+            .into_iter()
+            .map(|org| {
+                let validators = (1..=3).map(|i| format!("{}{}", org, i)).collect();
+                (org.to_string(), validators)
+            })
+            .collect(),
+    );
+    let critical = solver.critical_organizations(&orgs, Basic::default)?;
+    // the network survives any one crashed organization, not a byzantine one
+    assert_eq!(critical.intersection, vec!["a", "b", "c"]);
+    assert!(critical.availability.is_empty());
+    assert!(critical.unknown.is_empty());
+
+    let path = "./tests/test_data/top_tier.json";
+    let orgs = crate::Organizations::from_home_domains_json(path)?;
+    assert_eq!(orgs.orgs().count(), 7);
+    assert_eq!(orgs.validators_of("lobstr.co").map(<[_]>::len), Some(5));
+    let solver = FbasAnalyzer::from_json_path(path, Basic::default())?;
+    let critical = solver.critical_organizations(&orgs, Basic::default)?;
+    assert_eq!(critical, crate::CriticalOrganizations::default());
+    Ok(())
+}
+
+#[test]
+fn test_group_by_attribute() -> Result<(), Box<dyn std::error::Error>> {
+    let names = |names: &[&str]| names.iter().map(|v| v.to_string()).collect::<Vec<_>>();
+    let mut solver = FbasAnalyzer::from_dsl(
+        "a1, a2, a3, b1, b2, b3, c1, c2, c3: \
+         2-of(2-of(a1, a2, a3), 2-of(b1, b2, b3), 2-of(c1, c2, c3))",
+        Basic::default(),
+    )?;
+    // c's validators are mostly in FI, the others' in the US
+    let countries = [
+        ("DE", &["a1", "b1", "c1"][..]),
+        ("FI", &["c2", "c3"]),
+        ("US", &["a2", "a3", "b2", "b3"]),
+    ];
+    for (country, validators) in countries {
+        for v in validators {
+            solver.set_node_attribute(v, "country", country)?;
+        }
+    }
+    assert_eq!(solver.node_attribute("c3", "country"), Some("FI"));
+    assert_eq!(solver.node_attribute("c3", "isp"), None);
+    assert!(solver.set_node_attribute("x", "country", "US").is_err());
+    let countries = solver.group_by_attribute("country");
+    assert_eq!(countries.orgs().collect::<Vec<_>>(), vec!["DE", "FI", "US"]);
+    assert_eq!(
+        countries.validators_of("FI").map(<[_]>::to_vec),
+        Some(names(&["c2", "c3"]))
+    );
+
+    // losing the US takes both a and b, while c alone can be done without
+    let critical = solver.critical_groups("country", Basic::default)?;
+    assert_eq!(critical.availability, vec!["US"]);
+    assert!(critical.intersection.contains(&"US".to_string()));
+    let report = solver.group_failure_resilience("country", 1, Basic::default)?;
+    assert_eq!(report.rows[1].combinations, 3);
+    assert_eq!(report.rows[1].available, 2);
+    assert!(solver.group_by_attribute("isp").orgs().next().is_none());
+
+    // stellarbeats networks come with ISPs and countries
+    let solver = FbasAnalyzer::from_json_path("./tests/test_data/top_tier.json", Basic::default())?;
+    let key = "GD6SZQV3WEJUH352NTVLKEV2JM2RH266VPEM7EH5QLLI7ZZAALMLNUVN";
+    assert_eq!(solver.node_attribute(key, "country"), Some("DE"));
+    assert_eq!(
+        solver.node_attribute(key, "isp"),
+        Some("Hetzner Online Gmbh")
+    );
+    Ok(())
+}
+
+#[test]
+fn test_aliases() -> Result<(), Box<dyn std::error::Error>> {
+    let mut solver =
+        FbasAnalyzer::from_dsl("a, b: 2-of(a, b)\nc, d: 2-of(c, d)", Basic::default())?;
+    assert!(solver.aliases().is_empty());
+    assert!(matches!(solver.solve(), SolveStatus::SAT(_)));
+    let aliases = [("a", "alice"), ("d", "dave")]
+        .into_iter()
+        .map(|(k, v)| (k.to_string(), v.to_string()))
+        .collect();
+    let (qa, qb) = solver.get_potential_split()?;
+    let (named_a, named_b) = solver.get_potential_split_aliased(&aliases)?;
+    let named: Vec<_> = named_a.iter().chain(named_b.iter()).collect();
+    assert_eq!(named.len(), qa.len() + qb.len());
+    for v in ["alice", "b", "c", "dave"] {
+        assert!(named.contains(&&v.to_string()), "{v}");
+    }
+    let mut report = solver.into_report(None)?;
+    report.apply_aliases(&aliases);
+    assert_eq!(report.potential_split, (named_a, named_b));
+
+    // stellarbeats networks come with names
+    let solver = FbasAnalyzer::from_json_path("./tests/test_data/top_tier.json", Basic::default())?;
+    let aliases = solver.aliases();
+    assert_eq!(aliases.len(), 23);
+    assert_eq!(
+        aliases["GD6SZQV3WEJUH352NTVLKEV2JM2RH266VPEM7EH5QLLI7ZZAALMLNUVN"],
+        "Whalestack (Germany)"
+    );
+    Ok(())
+}
+
+#[test]
+fn test_solve_excluding() -> Result<(), Box<dyn std::error::Error>> {
+    let mut solver = FbasAnalyzer::from_dsl("a, b, c, d: 3-of(a, b, c, d)", Basic::default())?;
+    assert_eq!(
+        solver.solve_excluding(&["a"], Basic::default())?,
+        SolveStatus::UNSAT
+    );
+    // 1-of-2 qsets are left to c and d
+    let SolveStatus::SAT((qa, qb)) = solver.solve_excluding(&["a", "b"], Basic::default())? else {
+        panic!("expected a split");
+    };
+    let names = |q: &[NodeIndex]| {
+        q.iter()
+            .map(|ni| solver.fbas.try_get_validator_string(ni))
+            .collect::<Result<BTreeSet<_>, _>>()
+    };
+    let split = [names(&qa)?, names(&qb)?];
+    assert!(split.contains(&BTreeSet::from(["c".to_string()])));
+    assert!(split.contains(&BTreeSet::from(["d".to_string()])));
+    assert!(solver.solve_excluding(&["x"], Basic::default()).is_err());
+    // the analyzer's own result is untouched
+    assert_eq!(solver.solve(), SolveStatus::UNSAT);
+
+    // a set is splitting iff it contains a minimal splitting set
+    let solver = FbasAnalyzer::from_dsl(
+        "a1, a2, a3, b1, b2, b3, c1, c2, c3: \
+         2-of(2-of(a1, a2, a3), 2-of(b1, b2, b3), 2-of(c1, c2, c3))\n\
+         d: 2-of(a1, b1)",
+        Basic::default(),
+    )?;
+    assert_eq!(
+        solver.solve_excluding(&[], Basic::default())?,
+        SolveStatus::UNSAT
+    );
+    let splitting = solver.minimal_splitting_sets(2, Basic::default)?.sets;
+    let validators = ["a1", "a2", "a3", "b1", "b2", "b3", "c1", "c2", "c3", "d"];
+    for faulty in (0..=2).flat_map(|k| validators.into_iter().combinations(k)) {
+        let expected = splitting
+            .iter()
+            .any(|set| set.iter().all(|v| faulty.contains(&v.as_str())));
+        let status = solver.solve_excluding(&faulty, Basic::default())?;
+        assert_eq!(
+            matches!(status, SolveStatus::SAT(_)),
+            expected,
+            "{:?}",
+            faulty
+        );
+        if let SolveStatus::SAT((qa, qb)) = status {
+            assert!(qa.iter().chain(qb.iter()).all(|ni| {
+                let v = solver.fbas.try_get_validator_string(ni).unwrap();
+                !faulty.contains(&v.as_str())
+            }));
+        }
+    }
+    Ok(())
+}
+
+#[test]
+fn test_intact_validators() -> Result<(), Box<dyn std::error::Error>> {
+    let names = |names: &[&str]| names.iter().map(|v| v.to_string()).collect::<Vec<_>>();
+    let solver = FbasAnalyzer::from_dsl(
+        "a, b, c, d: 3-of(a, b, c, d)\ne: 2-of(a, b)\nf: 1-of(b)",
+        Basic::default(),
+    )?;
+    assert_eq!(
+        solver.is_dispensable_set(&[], Basic::default())?,
+        Some(true)
+    );
+    assert_eq!(
+        solver.is_dispensable_set(&["a"], Basic::default())?,
+        Some(false)
+    );
+    assert_eq!(
+        solver.is_dispensable_set(&["a", "e"], Basic::default())?,
+        Some(true)
+    );
+    // e depends on a, the others tolerate one failure
+    let split = solver.intact_validators(&["a"], Basic::default)?.unwrap();
+    assert_eq!(split.intact, names(&["b", "c", "d", "f"]));
+    assert_eq!(split.befouled, names(&["a", "e"]));
+    // two are too many
+    let split = solver
+        .intact_validators(&["a", "c"], Basic::default)?
+        .unwrap();
+    assert!(split.intact.is_empty());
+    assert!(solver.intact_validators(&["x"], Basic::default).is_err());
+
+    // the befouled validators are the intersection of all DSets containing
+    // the ill-behaved ones
+    let solver = FbasAnalyzer::from_dsl(
+        "a1, a2, a3, b1, b2, b3, c1, c2, c3: \
+         2-of(2-of(a1, a2, a3), 2-of(b1, b2, b3), 2-of(c1, c2, c3))\n\
+         d: 2-of(a1, b1)",
+        Basic::default(),
+    )?;
+    let validators = ["a1", "a2", "a3", "b1", "b2", "b3", "c1", "c2", "c3", "d"];
+    let mut dsets = vec![];
+    for set in validators.into_iter().powerset() {
+        if solver.is_dispensable_set(&set, Basic::default())? == Some(true) {
+            dsets.push(set);
+        }
+    }
+    for ill_behaved in [vec![], vec!["d"], vec!["a1"], vec!["a1", "b2"]] {
+        let expected: Vec<&str> = validators
+            .into_iter()
+            .filter(|v| {
+                dsets
+                    .iter()
+                    .filter(|set| ill_behaved.iter().all(|i| set.contains(i)))
+                    .all(|set| set.contains(v))
+            })
+            .collect();
+        let split = solver
+            .intact_validators(&ill_behaved, Basic::default)?
+            .unwrap();
+        assert_eq!(split.befouled, names(&expected), "{:?}", ill_behaved);
+    }
+    Ok(())
+}
+
+#[test]
+fn test_befoulment_cascade() -> Result<(), Box<dyn std::error::Error>> {
+    let names = |names: &[&str]| names.iter().map(|v| v.to_string()).collect::<Vec<_>>();
+    // g depends on e, which depends on a, and h on both f and g
+    let solver = FbasAnalyzer::from_dsl(
+        "a, b, c, d: 3-of(a, b, c, d)\ne: 2-of(a, b)\nf: 1-of(b)\n\
+         g: 1-of(e)\nh: 2-of(f, g)",
+        Basic::default(),
+    )?;
+    let cascade = solver.befoulment_cascade(&[])?;
+    assert!(cascade.befouled.is_empty());
+    let cascade = solver.befoulment_cascade(&["a"])?;
+    assert_eq!(cascade.befouled, names(&["a", "e", "g", "h"]));
+    assert_eq!(cascade.intact, names(&["b", "c", "d", "f"]));
+    // b v-blocks f, and with a everything else
+    let cascade = solver.befoulment_cascade(&["a", "b"])?;
+    assert!(cascade.intact.is_empty());
+    assert!(solver.befoulment_cascade(&["x"]).is_err());
+
+    // the survivors contain the intact validators
+    let solver = FbasAnalyzer::from_dsl(
+        "a1, a2, a3, b1, b2, b3, c1, c2, c3: \
+         2-of(2-of(a1, a2, a3), 2-of(b1, b2, b3), 2-of(c1, c2, c3))\n\
+         d: 2-of(a1, b1)",
+        Basic::default(),
+    )?;
+    for faulty in [vec![], vec!["d"], vec!["a1"], vec!["a1", "b2"]] {
+        let cascade = solver.befoulment_cascade(&faulty)?;
+        let split = solver.intact_validators(&faulty, Basic::default)?.unwrap();
+        assert!(split.intact.iter().all(|v| cascade.intact.contains(v)));
+    }
+    let cascade = solver.befoulment_cascade(&["a1", "b2"])?;
+    assert_eq!(cascade.befouled, names(&["a1", "b2", "d"]));
+    Ok(())
+}
+
+#[test]
+fn test_robustness_score() -> Result<(), Box<dyn std::error::Error>> {
+    let mut solver = FbasAnalyzer::from_dsl(
+        "a1, a2, a3, b1, b2, b3, c1, c2, c3: \
+         2-of(2-of(a1, a2, a3), 2-of(b1, b2, b3), 2-of(c1, c2, c3))",
+        Basic::default(),
+    )?;
+    let orgs = crate::Organizations::new(
+        ["a", "b", "c"]
+            .into_iter()
+            .map(|org| {
+                let validators = (1..=3).map(|i| format!("{}{}", org, i)).collect();
+                (org.to_string(), validators)
+            })
+            .collect(),
+    );
+    let weights = crate::RobustnessWeights::default();
+    let robustness = solver
+        .robustness_score(Some(&orgs), &weights, 9, Basic::default)?
+        .unwrap();
+    assert!(robustness.intersects);
+    assert_eq!(robustness.top_tier_size, 9);
+    assert_eq!(robustness.top_tier_orgs, 3);
+    // two validators of each of two organizations
+    assert_eq!(robustness.blocking_set_size, 4);
+    // one byzantine validator leaves its organization at 1-of-2
+    assert_eq!(robustness.splitting_set_size, Some(1));
+    let expected = 0.4 + 0.2 * 4.0 / 9.0 + 0.2 / 9.0 + 0.2 * 2.0 / 3.0;
+    assert!((robustness.score - expected).abs() < 1e-9);
+    // without organizations, every validator is its own
+    let by_validator = solver
+        .robustness_score(None, &weights, 9, Basic::default)?
+        .unwrap();
+    assert_eq!(by_validator.top_tier_orgs, 9);
+    assert!(by_validator.score > robustness.score);
+    // sets larger than the limit count as one more than it
+    let limited = solver
+        .robustness_score(Some(&orgs), &weights, 3, Basic::default)?
+        .unwrap();
+    let expected = crate::RobustnessScore {
+        max_set_size: 3,
+        ..robustness.clone()
+    };
+    assert_eq!(limited, expected);
+    let limited = solver
+        .robustness_score(Some(&orgs), &weights, 0, Basic::default)?
+        .unwrap();
+    assert_eq!(limited.blocking_set_size, 1);
+    assert_eq!(limited.splitting_set_size, Some(1));
+    assert!(limited.score < robustness.score);
+    let interrupted =
+        solver.robustness_score(Some(&orgs), &weights, 9, || Deadline::after(Duration::ZERO))?;
+    assert_eq!(interrupted, None);
+
+    // exposed in reports
+    solver.solve();
+    let report = solver.into_report(Some(robustness.clone()))?;
+    assert_eq!(report.robustness, Some(robustness));
+    assert_eq!(crate::AnalysisReport::from_json(&report.to_json())?, report);
+
+    let split = FbasAnalyzer::from_dsl("a: 1-of(a)\nb: 1-of(b)", Basic::default())?
+        .robustness_score(None, &weights, 2, Basic::default)?
+        .unwrap();
+    assert!(!split.intersects);
+    assert_eq!(split.splitting_set_size, Some(0));
+    assert_eq!(split.blocking_set_size, 2);
+    let no_quorum = FbasAnalyzer::from_dsl("a: 1-of(x)", Basic::default())?.robustness_score(
+        None,
+        &weights,
+        1,
+        Basic::default,
+    )?;
+    assert_eq!(
+        no_quorum,
+        Some(crate::RobustnessScore {
+            max_set_size: 1,
+            ..Default::default()
+        })
+    );
+    Ok(())
+}
+
+#[test]
+fn test_enumerate_splits() -> Result<(), Box<dyn std::error::Error>> {
+    // every non-empty set is a quorum: 6 unordered pairs of disjoint ones
+    let mut solver =
+        FbasAnalyzer::from_dsl("a: 1-of(a)\nb: 1-of(b)\nc: 1-of(c)", Basic::default())?;
+    let splits = solver.enumerate_splits(usize::MAX)?;
+    assert!(splits.complete);
+    assert_eq!(splits.pairs.len(), 6);
+    let unordered: BTreeSet<_> = splits
+        .pairs
+        .iter()
+        .map(|(a, b)| BTreeSet::from([a.clone(), b.clone()]))
+        .collect();
+    assert_eq!(unordered.len(), 6);
+    for (a, b) in splits.pairs.iter() {
+        assert!(!a.is_empty() && !b.is_empty());
+        assert!(a.iter().all(|v| !b.contains(v)));
+    }
+    // the first pair is the potential split, which is kept
+    let (a, b) = solver.get_potential_split()?;
+    let sorted = |mut q: Vec<String>| {
+        q.sort();
+        q
+    };
+    assert_eq!(splits.pairs[0], (sorted(a), sorted(b)));
+    assert!(matches!(solver.solve(), SolveStatus::SAT(_)));
+
+    let capped = solver.enumerate_splits(2)?;
+    assert!(!capped.complete);
+    assert_eq!(capped.pairs.len(), 2);
+    // the excluded pairs don't linger
+    assert_eq!(solver.enumerate_splits(usize::MAX)?.pairs.len(), 6);
+    let none = solver.enumerate_splits(0)?;
+    assert!(!none.complete && none.pairs.is_empty());
+    assert!(matches!(solver.solve(), SolveStatus::SAT(_)));
+
+    let mut solver = FbasAnalyzer::from_dsl("a, b, c: 2-of(a, b, c)", Basic::default())?;
+    let splits = solver.enumerate_splits(10)?;
+    assert!(splits.complete && splits.pairs.is_empty());
+    let splits = solver.enumerate_splits(0)?;
+    assert!(splits.complete && splits.pairs.is_empty());
+    Ok(())
+}
+
+#[test]
+fn test_minimal_split() -> Result<(), Box<dyn std::error::Error>> {
+    let mut solver = FbasAnalyzer::from_dsl(
+        "a: 1-of(a)\nb: 1-of(b)\nc, d, e, f: 1-of(a, b)",
+        Basic::default(),
+    )?;
+    assert_eq!(solver.get_minimal_split()?, (vec![], vec![]));
+    assert!(matches!(solver.solve(), SolveStatus::SAT(_)));
+    let (qa, qb) = solver.get_potential_split()?;
+    let (ma, mb) = solver.get_minimal_split()?;
+    for (quorum, minimal) in [(&qa, &ma), (&qb, &mb)] {
+        assert!(minimal.iter().all(|v| quorum.contains(v)));
+        let minimal: Vec<_> = minimal.iter().map(String::as_str).collect();
+        assert!(solver.is_minimal_quorum(&minimal)?);
+    }
+    // only a and b are minimal quorums
+    let mut sides = [ma, mb];
+    sides.sort();
+    assert_eq!(sides, [vec!["a".to_string()], vec!["b".to_string()]]);
+    Ok(())
+}
+
+#[test]
+fn test_resource_limits() -> Result<(), Box<dyn std::error::Error>> {
+    let limits = ResourceLimits::default();
+    let intact = "a, b, c, d: 3-of(a, b, c, d)";
+    let outcome = FbasAnalyzer::solve_dsl_within(intact, &limits)?;
+    assert_eq!(outcome.status, LimitedStatus::Unsat);
+    assert_eq!(outcome.status.code(), 0);
+    assert!(outcome.estimate.num_clauses > 0);
+
+    let split = "a, b: 2-of(a, b)\nc, d: 2-of(c, d)";
+    let outcome = FbasAnalyzer::solve_dsl_within(split, &limits)?;
+    assert_eq!(outcome.status, LimitedStatus::Sat);
+    let (mut qa, mut qb) = outcome.potential_split;
+    qa.sort();
+    qb.sort();
+    let mut sides = [qa, qb];
+    sides.sort();
+    assert_eq!(sides, [vec!["a", "b"], vec!["c", "d"]]);
+
+    // limits are checked before encoding, against the same estimate
+    let estimate = FbasAnalyzer::from_dsl(intact, Basic::default())?.estimate_encoding()?;
+    for (limits, status) in [
+        (
+            ResourceLimits {
+                max_clauses: estimate.num_clauses - 1,
+                ..limits
+            },
+            LimitedStatus::ClauseLimit,
+        ),
+        (
+            ResourceLimits {
+                max_memory_bytes: estimate.memory_bytes - 1,
+                ..limits
+            },
+            LimitedStatus::MemoryLimit,
+        ),
+        (
+            ResourceLimits {
+                max_time: Duration::ZERO,
+                ..limits
+            },
+            LimitedStatus::TimeLimit,
+        ),
+        (
+            ResourceLimits {
+                max_clauses: estimate.num_clauses,
+                max_memory_bytes: estimate.memory_bytes,
+                ..limits
+            },
+            LimitedStatus::Unsat,
+        ),
+    ] {
+        let outcome = FbasAnalyzer::solve_dsl_within(intact, &limits)?;
+        assert_eq!(outcome.status, status);
+        assert_eq!(outcome.potential_split, Default::default());
+    }
+    assert!(FbasAnalyzer::solve_dsl_within("a: 2-of(", &limits).is_err());
+    Ok(())
+}
+
+#[test]
+fn test_bridging_edges() -> Result<(), Box<dyn std::error::Error>> {
+    let orgs = crate::Organizations::new(BTreeMap::from([
+        ("A".to_string(), vec!["a1".into(), "a2".into(), "a3".into()]),
+        ("B".to_string(), vec!["b1".into(), "b2".into(), "b3".into()]),
+    ]));
+    // each organization only trusts itself
+    let solver = FbasAnalyzer::from_dsl(
+        "a1, a2, a3: 2-of(a1, a2, a3)\nb1, b2, b3: 2-of(b1, b2, b3)",
+        Basic::default(),
+    )?;
+    // a single edge leaves a quorum of the two other validators of A
+    let bridging = solver.bridging_edges(&orgs, 1, Basic::default)?;
+    assert_eq!(bridging.edges, None);
+    assert!(bridging.complete);
+
+    let bridging = solver.bridging_edges(&orgs, 3, Basic::default)?;
+    assert!(bridging.complete);
+    let edges = bridging.edges.unwrap();
+    let edge = |validator: &str, org: &str| TrustEdge {
+        validator: validator.to_string(),
+        org: org.to_string(),
+    };
+    assert_eq!(edges, vec![edge("a1", "B"), edge("a2", "B")]);
+    let mut bridged = FbasAnalyzer::from_dsl(
+        "a1, a2: 2-of(2-of(a1, a2, a3), 2-of(b1, b2, b3))\n\
+         a3: 2-of(a1, a2, a3)\n\
+         b1, b2, b3: 2-of(b1, b2, b3)",
+        Basic::default(),
+    )?;
+    assert_eq!(bridged.solve(), SolveStatus::UNSAT);
+
+    // nothing to bridge
+    let solver = FbasAnalyzer::from_dsl("a1, a2, a3, b1: 3-of(a1, a2, a3, b1)", Basic::default())?;
+    let bridging = solver.bridging_edges(&orgs, 2, Basic::default)?;
+    assert_eq!(bridging.edges, Some(vec![]));
+    Ok(())
+}
+
+#[test]
+fn test_threshold_sweep() -> Result<(), Box<dyn std::error::Error>> {
+    let orgs = "a1, a2, a3, b1, b2, b3, c1, c2, c3: \
+                {}-of(2-of(a1, a2, a3), 2-of(b1, b2, b3), 2-of(c1, c2, c3))";
+    let solver = FbasAnalyzer::from_dsl(&orgs.replace("{}", "2"), Basic::default())?;
+    let sweep = solver.threshold_sweep("b2", Basic::default)?;
+    assert_eq!(sweep.validators.len(), 9);
+    assert_eq!(sweep.rows.len(), 3);
+    assert_eq!(sweep.intersection_threshold(), Some(2));
+    assert_eq!(sweep.availability_threshold(), Some(3));
+    // each row agrees with solving the network with that threshold
+    for row in sweep.rows.iter() {
+        let dsl = orgs.replace("{}", &row.threshold.to_string());
+        let mut swept = FbasAnalyzer::from_dsl(&dsl, Basic::default())?;
+        assert_eq!(row.intersecting, Some(swept.solve() == SolveStatus::UNSAT));
+        assert!(row.available);
+    }
+
+    // c can never be satisfied, so requiring it loses availability, and
+    // intersection holds vacuously
+    let solver = FbasAnalyzer::from_dsl("a, b: 2-of(a, b, c)\nc: 1-of(y)", Basic::default())?;
+    let sweep = solver.threshold_sweep("a", Basic::default)?;
+    assert_eq!(sweep.validators, vec!["a", "b"]);
+    let rows: Vec<_> = sweep
+        .rows
+        .iter()
+        .map(|row| (row.available, row.intersecting))
+        .collect();
+    assert_eq!(
+        rows,
+        vec![(true, Some(false)), (true, Some(true)), (false, Some(true))]
+    );
+    assert_eq!(sweep.availability_threshold(), Some(2));
+    assert!(sweep
+        .to_string()
+        .starts_with("threshold  available  intersecting\n"));
+    assert!(solver.threshold_sweep("y", Basic::default).is_err());
+    Ok(())
+}
+
+#[test]
+fn test_threshold_sensitivity() -> Result<(), Box<dyn std::error::Error>> {
+    // 3 of 5 still intersect, 2 of 4 don't
+    let solver = FbasAnalyzer::from_dsl("a, b, c, d, e: 4-of(a, b, c, d, e)", Basic::default())?;
+    let perturbations = solver.threshold_sensitivity(Basic::default)?;
+    assert_eq!(perturbations.len(), 1);
+    assert_eq!(perturbations[0].qset, "4 of: a, b, c, d, e\n");
+    assert_eq!(perturbations[0].validators.len(), 5);
+    assert_eq!(perturbations[0].intersecting, Some(true));
+    assert_eq!(perturbations[0].potential_split, (vec![], vec![]));
+
+    let solver = FbasAnalyzer::from_dsl(
+        "a, b, c, d: 3-of(a, b, c, d)\ne: 1-of(a, b)",
+        Basic::default(),
+    )?;
+    let perturbations = solver.threshold_sensitivity(Basic::default)?;
+    // e's qset has a threshold of 1 already
+    assert_eq!(perturbations.len(), 1);
+    assert_eq!(perturbations[0].intersecting, Some(false));
+    let (qa, qb) = &perturbations[0].potential_split;
+    assert!(qa.len() >= 2 && qb.len() >= 2);
+    assert!(qa.iter().all(|v| !qb.contains(v)));
+
+    // in the organizations, loosening any qset breaks intersection
+    let solver = FbasAnalyzer::from_dsl(
+        "a1, a2, a3, b1, b2, b3, c1, c2, c3: \
+         2-of(2-of(a1, a2, a3), 2-of(b1, b2, b3), 2-of(c1, c2, c3))",
+        Basic::default(),
+    )?;
+    let perturbations = solver.threshold_sensitivity(Basic::default)?;
+    assert_eq!(perturbations.len(), 4);
+    for perturbation in perturbations {
+        assert_eq!(perturbation.intersecting, Some(false));
+        assert_eq!(perturbation.validators.len(), 9);
+    }
+    Ok(())
+}
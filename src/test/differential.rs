@@ -0,0 +1,200 @@
+// Differential tests of the SAT-based analyses against brute-force oracles,
+// on random networks small enough to enumerate every set of validators. They
+// guard changes to the encoding: any disagreement is printed with the
+// network in the DSL, to be turned into a regular test.
+
+use crate::fbas::{Fbas, Vertex};
+use crate::influence::SplitMix64;
+use crate::{FbasAnalyzer, SolveStatus};
+use batsat::callbacks::Basic;
+use itertools::Itertools;
+use petgraph::graph::NodeIndex;
+use std::collections::BTreeSet;
+
+const NETWORKS: u64 = 40;
+const MAX_VALIDATORS: u64 = 12;
+// splitting sets need an enumeration per candidate set
+const MAX_VALIDATORS_SPLITTING: usize = 9;
+const MAX_SPLITTING_SIZE: usize = 2;
+
+// A random network in the DSL with 3 to `MAX_VALIDATORS` validators, each with
+// a qset of random members and threshold, sometimes with a nested inner set.
+fn random_network(rng: &mut SplitMix64) -> String {
+    let n = 3 + rng.next() % (MAX_VALIDATORS - 2);
+    let names: Vec<String> = (0..n).map(|i| format!("v{}", i)).collect();
+    let members = |rng: &mut SplitMix64, max: u64| {
+        let mut names = names.clone();
+        rng.shuffle(&mut names);
+        names.truncate(1 + (rng.next() % max) as usize);
+        names
+    };
+    let mut dsl = String::new();
+    for name in names.iter() {
+        let mut elems = members(rng, n.min(5));
+        if rng.next().is_multiple_of(3) {
+            let inner = members(rng, 3);
+            let threshold = 1 + rng.next() % inner.len() as u64;
+            elems.push(format!("{}-of({})", threshold, inner.join(", ")));
+        }
+        let threshold = 1 + rng.next() % elems.len() as u64;
+        dsl.push_str(&format!(
+            "{}: {}-of({})\n",
+            name,
+            threshold,
+            elems.join(", ")
+        ));
+    }
+    dsl
+}
+
+// Whether qset vertex `qi` is satisfied by the `available` validators.
+fn satisfied(fbas: &Fbas, qi: NodeIndex, available: &BTreeSet<NodeIndex>) -> bool {
+    let count = fbas
+        .graph
+        .neighbors(qi)
+        .filter(|ni| match &fbas.graph[*ni] {
+            Vertex::QSet(_) => satisfied(fbas, *ni, available),
+            _ => available.contains(ni),
+        })
+        .count();
+    count >= fbas.graph[qi].get_threshold() as usize
+}
+
+// Every set of validators, by bitmask over `fbas.validators`.
+fn all_sets(fbas: &Fbas) -> impl Iterator<Item = BTreeSet<NodeIndex>> + '_ {
+    (0u32..1 << fbas.validators.len()).map(|mask| {
+        fbas.validators
+            .iter()
+            .enumerate()
+            .filter(|(i, _)| mask & (1 << i) != 0)
+            .map(|(_, ni)| *ni)
+            .collect()
+    })
+}
+
+// Whether the qset of validator `vi` is satisfied by the `available` ones.
+fn validator_satisfied(fbas: &Fbas, vi: NodeIndex, available: &BTreeSet<NodeIndex>) -> bool {
+    let qi = fbas.graph.neighbors(vi).next();
+    qi.is_some_and(|qi| satisfied(fbas, qi, available))
+}
+
+// The largest quorum among `candidates` of the network with `faulty` deleted,
+// where each member has its qset satisfied counting the faulty as present.
+fn max_quorum_despite(
+    fbas: &Fbas,
+    faulty: &BTreeSet<NodeIndex>,
+    mut candidates: BTreeSet<NodeIndex>,
+) -> BTreeSet<NodeIndex> {
+    loop {
+        let available: BTreeSet<_> = candidates.union(faulty).copied().collect();
+        match candidates
+            .iter()
+            .find(|vi| !validator_satisfied(fbas, **vi, &available))
+        {
+            Some(vi) => {
+                let vi = *vi;
+                candidates.remove(&vi);
+            }
+            None => return candidates,
+        }
+    }
+}
+
+// Whether two quorums of the network with `faulty` deleted are disjoint.
+fn is_splitting(fbas: &Fbas, faulty: &BTreeSet<NodeIndex>) -> bool {
+    all_sets(fbas)
+        .filter(|set| !set.is_empty() && set.is_disjoint(faulty))
+        .filter(|set| max_quorum_despite(fbas, faulty, set.clone()) == *set)
+        .any(|quorum| {
+            let rest = fbas
+                .validators
+                .iter()
+                .filter(|ni| !quorum.contains(ni) && !faulty.contains(ni))
+                .copied()
+                .collect();
+            !max_quorum_despite(fbas, faulty, rest).is_empty()
+        })
+}
+
+fn names(fbas: &Fbas, sets: &[BTreeSet<NodeIndex>]) -> Vec<Vec<String>> {
+    sets.iter()
+        .map(|set| {
+            set.iter()
+                .map(|ni| fbas.try_get_validator_string(ni).unwrap())
+                .collect()
+        })
+        .collect()
+}
+
+// The minimal sets among `sets` of an upward closed family, ordered by size
+// and then by validator order like the analyses do.
+fn minimal(mut sets: Vec<BTreeSet<NodeIndex>>) -> Vec<BTreeSet<NodeIndex>> {
+    sets.sort_by(|a, b| a.len().cmp(&b.len()).then_with(|| a.iter().cmp(b.iter())));
+    let mut found: Vec<BTreeSet<NodeIndex>> = vec![];
+    for set in sets {
+        if !found.iter().any(|smaller| smaller.is_subset(&set)) {
+            found.push(set);
+        }
+    }
+    found
+}
+
+#[test]
+fn test_differential_against_brute_force() -> Result<(), Box<dyn std::error::Error>> {
+    let mut rng = SplitMix64(0x5eed);
+    let mut sat = 0;
+    for _ in 0..NETWORKS {
+        let dsl = random_network(&mut rng);
+        let mut solver = FbasAnalyzer::from_dsl(&dsl, Basic::default())?;
+        let fbas = Fbas::from_dsl(&dsl)?;
+
+        // intersection
+        let expected = solver.solve_brute_force()?;
+        let actual = solver.solve();
+        assert_eq!(
+            matches!(actual, SolveStatus::SAT(_)),
+            matches!(expected, SolveStatus::SAT(_)),
+            "intersection of\n{}",
+            dsl
+        );
+        if matches!(actual, SolveStatus::SAT(_)) {
+            sat += 1;
+        }
+
+        let quorums: Vec<_> = all_sets(&fbas).filter(|set| fbas.is_quorum(set)).collect();
+
+        // minimal quorums
+        let expected = names(&fbas, &minimal(quorums.clone()));
+        let actual = solver.enumerate_minimal_quorums(usize::MAX, Basic::default)?;
+        assert_eq!(actual.quorums, expected, "minimal quorums of\n{}", dsl);
+
+        // blocking sets: those intersecting every quorum
+        let blocking = all_sets(&fbas)
+            .filter(|set| quorums.iter().all(|quorum| !quorum.is_disjoint(set)))
+            .collect();
+        let expected = names(&fbas, &minimal(blocking));
+        let fresh = FbasAnalyzer::from_dsl(&dsl, Basic::default())?;
+        assert_eq!(
+            fresh.minimal_blocking_sets(usize::MAX)?,
+            expected,
+            "blocking sets of\n{}",
+            dsl
+        );
+
+        // splitting sets
+        if fbas.validators.len() > MAX_VALIDATORS_SPLITTING {
+            continue;
+        }
+        let splitting = (0..=MAX_SPLITTING_SIZE)
+            .flat_map(|k| fbas.validators.iter().copied().combinations(k))
+            .map(|set| set.into_iter().collect())
+            .filter(|set| is_splitting(&fbas, set))
+            .collect();
+        let expected = names(&fbas, &minimal(splitting));
+        let actual = solver.minimal_splitting_sets(MAX_SPLITTING_SIZE, Basic::default)?;
+        assert_eq!(actual.sets, expected, "splitting sets of\n{}", dsl);
+    }
+    // both verdicts are covered
+    assert!(sat > 0 && sat < NETWORKS);
+    Ok(())
+}
@@ -1,4 +1,6 @@
-use crate::json_parser::quorum_set_map_from_json;
+use crate::dsl::quorum_set_map_from_dsl;
+use crate::fbas::{Fbas, FbasError};
+use crate::json_parser::{quorum_set_map_from_json, quorum_set_map_from_json_with_min_uptime};
 use std::str::FromStr;
 use stellar_strkey::ed25519::PublicKey as StrKeyPublicKey;
 
@@ -59,3 +61,535 @@ fn test_parse_quorum_set_map_from_stellarbeats_json() {
     let expected_validator = "GAAV2GCVFLNN522ORUYFV33E76VPC22E72S75AQ6MBR5V45Z5DWVPWEU";
     assert_eq!(&first_inner.validators[0], expected_validator);
 }
+
+#[test]
+fn test_parse_threshold_percent() {
+    let quorum_map = quorum_set_map_from_json("./tests/test_data/threshold_percent.json").unwrap();
+
+    // 67% of 3 rounds up to 3
+    assert_eq!(quorum_map.get("PK1").unwrap().threshold, 3);
+    // 51% of 3 rounds up to 2
+    let qset = quorum_map.get("PK2").unwrap();
+    assert_eq!(qset.threshold, 2);
+    assert_eq!(qset.inner_sets[0].threshold, 1);
+    // absolute thresholds are still taken as-is
+    assert_eq!(quorum_map.get("PK3").unwrap().threshold, 2);
+
+    // the percentage is kept for display, in both forms
+    assert_eq!(qset.threshold_percent, Some(51));
+    assert_eq!(quorum_map.get("PK3").unwrap().threshold_percent, None);
+    assert_eq!(
+        qset.pretty(&Default::default()),
+        "51% (2) of:\n  PK1, PK2\n  100% (1) of: PK3\n"
+    );
+    let json = crate::QuorumSetJson::from_internal(qset);
+    assert_eq!(json.to_internal().unwrap(), **qset);
+}
+
+#[test]
+fn test_parse_with_min_uptime() {
+    let quorum_map = quorum_set_map_from_json("./tests/test_data/uptime.json").unwrap();
+    assert_eq!(quorum_map.len(), 4);
+
+    let mut diagnostics = vec![];
+    let quorum_map = quorum_set_map_from_json_with_min_uptime(
+        "./tests/test_data/uptime.json",
+        90,
+        &mut diagnostics,
+        &mut vec![],
+    )
+    .unwrap();
+    // PK3 is below the uptime threshold, PK4 has no statistics and is kept
+    assert_eq!(quorum_map.len(), 3);
+    assert_eq!(diagnostics.len(), 1);
+    assert!(diagnostics[0].starts_with("Validator PK3 excluded"));
+    assert!(!quorum_map.contains_key("PK3"));
+    assert!(quorum_map.contains_key("PK4"));
+}
+
+#[test]
+fn test_parse_watchers() {
+    // PK4 has a null qset and PK5 none at all
+    let fbas = Fbas::from_json_path("./tests/test_data/others/watchers.json").unwrap();
+    assert_eq!(fbas.validators.len(), 3);
+    assert_eq!(fbas.watchers().collect::<Vec<_>>(), vec!["PK4", "PK5"]);
+    assert!(fbas
+        .diagnostics
+        .contains(&"Validator PK4 is a watcher without a qset".to_string()));
+
+    let mut analyzer =
+        crate::FbasAnalyzer::from_fbas(fbas, batsat::callbacks::Basic::default()).unwrap();
+    assert_eq!(analyzer.get_watchers(), vec!["PK4", "PK5"]);
+    assert_eq!(analyzer.solve(), crate::SolveStatus::UNSAT);
+}
+
+#[test]
+fn test_parse_node_metadata() {
+    use crate::{FbasAnalyzer, NodeMetadata};
+    use batsat::callbacks::Basic;
+
+    let path = "./tests/test_data/top_tier.json";
+    let analyzer = FbasAnalyzer::from_json_path(path, Basic::default()).unwrap();
+    assert_eq!(
+        analyzer.node_metadata("GD6SZQV3WEJUH352NTVLKEV2JM2RH266VPEM7EH5QLLI7ZZAALMLNUVN"),
+        Some(&NodeMetadata {
+            home_domain: Some("whalestack.com".to_string()),
+            organization_id: Some("9860311160b56412668f572a6d9454d0".to_string()),
+            name: Some("Whalestack (Germany)".to_string()),
+        })
+    );
+    assert_eq!(
+        analyzer.organizations(),
+        crate::Organizations::from_home_domains_json(path).unwrap()
+    );
+    assert_eq!(analyzer.organizations().orgs().count(), 7);
+
+    // nodes without any metadata have none
+    let analyzer =
+        FbasAnalyzer::from_json_path("./tests/test_data/others/watchers.json", Basic::default())
+            .unwrap();
+    assert!(analyzer.node_metadata("PK1").is_none());
+    assert_eq!(analyzer.organizations().orgs().count(), 0);
+}
+
+#[test]
+fn test_json_snapshot_patch() {
+    use crate::{FbasAnalyzer, JsonSnapshot, SolveStatus};
+    use batsat::callbacks::Basic;
+
+    let mut snapshot =
+        JsonSnapshot::from_json_path("./tests/test_data/others/watchers.json").unwrap();
+    let original = snapshot.clone();
+    let solve = |snapshot: &JsonSnapshot| {
+        FbasAnalyzer::from_json_snapshot(snapshot, Basic::default())
+            .unwrap()
+            .solve()
+    };
+    assert_eq!(solve(&snapshot), SolveStatus::UNSAT);
+
+    // a failing test leaves the snapshot unchanged
+    let res = snapshot.apply_patch(
+        r#"[
+            {"op": "replace", "path": "/0/quorumSet/threshold", "value": 1},
+            {"op": "test", "path": "/0/publicKey", "value": "PK2"}
+        ]"#,
+    );
+    assert!(matches!(res, Err(FbasError::ParseError(_))));
+    assert_eq!(snapshot, original);
+    // so does a patch leaving an invalid network
+    assert!(snapshot
+        .apply_patch(r#"[{"op": "remove", "path": "/0/publicKey"}]"#)
+        .is_err());
+    assert_eq!(snapshot, original);
+
+    // PK1 alone becomes a quorum
+    snapshot
+        .apply_patch(
+            r#"[
+                {"op": "test", "path": "/0/publicKey", "value": "PK1"},
+                {"op": "replace", "path": "/0/quorumSet/threshold", "value": 1}
+            ]"#,
+        )
+        .unwrap();
+    assert!(matches!(solve(&snapshot), SolveStatus::SAT(_)));
+
+    // PK4 stops watching and trusts PK2 and PK3, PK5 leaves
+    snapshot
+        .apply_patch(
+            r#"[
+                {"op": "copy", "from": "/1/quorumSet", "path": "/3/quorumSet"},
+                {"op": "remove", "path": "/4"},
+                {"op": "replace", "path": "/0/quorumSet/threshold", "value": 2}
+            ]"#,
+        )
+        .unwrap();
+    let mut analyzer = FbasAnalyzer::from_json_snapshot(&snapshot, Basic::default()).unwrap();
+    assert!(analyzer.get_watchers().is_empty());
+    // {PK1, PK2} and {PK3, PK4} are now disjoint quorums
+    assert!(matches!(analyzer.solve(), SolveStatus::SAT(_)));
+    assert!(snapshot.to_json().contains("PK4"));
+}
+
+#[test]
+fn test_share_quorum_set_map_across_threads() {
+    let quorum_map = quorum_set_map_from_json(
+        "./tests/test_data/random/almost_symmetric_network_6_orgs_delete_prob_factor_3_for_stellar_core.json",
+    )
+    .unwrap();
+    let node_counts: Vec<usize> = std::thread::scope(|s| {
+        let handles: Vec<_> = (0..4)
+            .map(|_| {
+                s.spawn(|| {
+                    Fbas::from_quorum_set_map(&quorum_map)
+                        .unwrap()
+                        .validators
+                        .len()
+                })
+            })
+            .collect();
+        handles.into_iter().map(|h| h.join().unwrap()).collect()
+    });
+    assert_eq!(node_counts, vec![18; 4]);
+}
+
+#[test]
+fn test_parse_dsl() {
+    use crate::lint::lint_quorum_set_map;
+    use crate::{FbasAnalyzer, QsetLintKind};
+    use batsat::callbacks::Basic;
+
+    let qsm = quorum_set_map_from_dsl(
+        "# comment\n\
+         a, b: 2-of(a, b, 1-of(c, d))\n\
+         \n\
+         c:3-of( a,b , 2-of(c, d, e), 0-of() ) # trailing comment\n",
+    )
+    .unwrap();
+    assert_eq!(qsm.len(), 3);
+    assert_eq!(qsm["a"], qsm["b"]);
+    assert_eq!(qsm["a"].validators, vec!["a", "b"]);
+    assert_eq!(qsm["a"].inner_sets[0].threshold, 1);
+    assert_eq!(qsm["a"].inner_sets[0].validators, vec!["c", "d"]);
+    assert_eq!(qsm["c"].threshold, 3);
+    assert_eq!(qsm["c"].inner_sets.len(), 2);
+    assert!(qsm["c"].inner_sets[1].validators.is_empty());
+
+    for invalid in [
+        "a 1-of(a)",
+        "a: of(a)",
+        "a: 1-of(a",
+        "a: 1-of(a,)",
+        "a: 1-of(a) b",
+        "a: 1-of(a)\na: 1-of(b)",
+    ] {
+        assert!(
+            matches!(
+                quorum_set_map_from_dsl(invalid),
+                Err(FbasError::ParseError(_))
+            ),
+            "{}",
+            invalid
+        );
+    }
+
+    // nesting past the max depth still parses, to be linted
+    let too_deep = quorum_set_map_from_dsl("a: 1-of(1-of(1-of(1-of(1-of(a)))))").unwrap();
+    assert!(lint_quorum_set_map(&too_deep)
+        .iter()
+        .any(|lint| matches!(lint.kind, QsetLintKind::ExcessiveDepth { depth: 5, .. })));
+    // but not without bound
+    let nested = 100_000;
+    let dsl = format!("a: {}a{}", "1-of(".repeat(nested), ")".repeat(nested));
+    assert!(matches!(
+        quorum_set_map_from_dsl(&dsl),
+        Err(FbasError::MaxDepthExceeded)
+    ));
+    assert!(matches!(
+        FbasAnalyzer::load(dsl.as_bytes(), Basic::default()),
+        Err(FbasError::MaxDepthExceeded)
+    ));
+}
+
+#[test]
+fn test_canonical_numbering() {
+    let build = |dsl: &str| {
+        let fbas = Fbas::from_dsl(dsl).unwrap();
+        let names: Vec<_> = fbas
+            .validators
+            .iter()
+            .map(|ni| fbas.try_get_validator_string(ni).unwrap())
+            .collect();
+        (names, format!("{:?}", fbas.graph))
+    };
+    // the same network, listing validators, members and inner sets in
+    // different orders
+    let (names, graph) = build(
+        "c: 2-of(a, b, 1-of(d, c), 2-of(a, b, c))\n\
+         a, b: 2-of(a, b, c)\n\
+         d: 1-of(1-of(c, d), 2-of(b, c, a))",
+    );
+    let (other_names, other_graph) = build(
+        "b, a: 2-of(c, b, a)\n\
+         d: 1-of(2-of(a, b, c), 1-of(c, d))\n\
+         c: 2-of(2-of(c, a, b), 1-of(c, d), b, a)",
+    );
+    assert_eq!(names, vec!["a", "b", "c", "d"]);
+    assert_eq!(names, other_names);
+    assert_eq!(graph, other_graph);
+    // validators come first, in key order
+    let fbas = Fbas::from_dsl("z: 1-of(y)\ny: 1-of(z)\nx: 1-of(x)").unwrap();
+    for (i, ni) in fbas.validators.iter().enumerate() {
+        assert_eq!(ni.index(), i);
+    }
+}
+
+#[test]
+fn test_pretty_qset() {
+    let qsm = quorum_set_map_from_dsl(
+        "a: 3-of(v1, v2, 2-of(v3, v4, v5), 1-of(v6, 2-of(v7, v8)), 0-of())",
+    )
+    .unwrap();
+    let aliases = [("v1".to_string(), "alpha".to_string())].into();
+    assert_eq!(
+        qsm["a"].pretty(&aliases),
+        "3 of:\n  \
+           alpha, v2\n  \
+           2 of: v3, v4, v5\n  \
+           1 of:\n    \
+             v6\n    \
+             2 of: v7, v8\n  \
+           0 of:\n"
+    );
+}
+
+#[test]
+fn test_qset_lints() {
+    use crate::lint::lint_quorum_set_map;
+    use crate::{FbasAnalyzer, LintSeverity, QsetLintKind};
+    use batsat::callbacks::Basic;
+
+    let analyzer = FbasAnalyzer::from_dsl(
+        "a, b, c, d: 3-of(a, b, c, d)\n\
+         e: 2-of(a, b)\n\
+         f: 1-of(a, b, c, f)\n\
+         g: 2-of(g, a, a)\n\
+         h: 3-of(h, a)",
+        Basic::default(),
+    )
+    .unwrap();
+    let lints = |validator: &str| {
+        analyzer
+            .qset_lints()
+            .iter()
+            .filter(|lint| lint.validator == validator)
+            .map(|lint| (lint.severity, lint.kind.clone()))
+            .collect::<Vec<_>>()
+    };
+    let member = |v: &str| v.to_string();
+    assert!(lints("a").is_empty());
+    assert_eq!(
+        lints("e"),
+        vec![
+            (
+                LintSeverity::Warning,
+                QsetLintKind::SingleBlockingMember {
+                    member: member("a")
+                }
+            ),
+            (
+                LintSeverity::Warning,
+                QsetLintKind::SingleBlockingMember {
+                    member: member("b")
+                }
+            ),
+            (LintSeverity::Info, QsetLintKind::MissingSelf),
+        ]
+    );
+    assert_eq!(
+        lints("f"),
+        vec![(
+            LintSeverity::Warning,
+            QsetLintKind::ThresholdTooLow {
+                threshold: 1,
+                members: 4
+            }
+        )]
+    );
+    // a, listed twice, blocks g alone
+    assert_eq!(
+        lints("g"),
+        vec![
+            (
+                LintSeverity::Error,
+                QsetLintKind::DuplicateMember {
+                    member: member("a")
+                }
+            ),
+            (
+                LintSeverity::Warning,
+                QsetLintKind::SingleBlockingMember {
+                    member: member("a")
+                }
+            ),
+        ]
+    );
+    assert_eq!(
+        lints("h"),
+        vec![(
+            LintSeverity::Error,
+            QsetLintKind::ThresholdTooHigh {
+                threshold: 3,
+                members: 2
+            }
+        )]
+    );
+
+    // nesting too deep fails the build, but is linted
+    let qsm = quorum_set_map_from_json("./tests/test_data/others/too_deep.json").unwrap();
+    assert!(lint_quorum_set_map(&qsm)
+        .iter()
+        .any(|lint| matches!(lint.kind, QsetLintKind::ExcessiveDepth { max: 4, .. })));
+}
+
+#[test]
+fn test_parse_stellar_core_endpoints() {
+    use crate::{FbasAnalyzer, InputFormat};
+    use batsat::callbacks::Basic;
+
+    // quorum?transitive=true: PK4's qset hasn't been heard of
+    let path = "./tests/test_data/others/stellar_core_quorum.json";
+    let quorum_map = quorum_set_map_from_json(path).unwrap();
+    assert_eq!(quorum_map.len(), 3);
+    let qset = quorum_map.get("PK3").unwrap();
+    assert_eq!(qset.validators, vec!["PK2", "PK3"]);
+    assert_eq!(qset.inner_sets[0].validators, vec!["PK4"]);
+    let (mut analyzer, format) = FbasAnalyzer::load_path(path, Basic::default()).unwrap();
+    assert_eq!(format, InputFormat::StellarCore);
+    assert_eq!(analyzer.get_watchers(), vec!["PK4"]);
+    assert_eq!(analyzer.solve(), crate::SolveStatus::UNSAT);
+
+    // scp: the qset of the latest slot wins, and PK4's is unknown
+    let path = "./tests/test_data/others/stellar_core_scp.json";
+    let quorum_map = quorum_set_map_from_json(path).unwrap();
+    assert_eq!(
+        quorum_map.keys().collect::<Vec<_>>(),
+        vec!["PK1", "PK2", "PK3"]
+    );
+    assert_eq!(quorum_map.get("PK1").unwrap().threshold, 2);
+    assert_eq!(quorum_map.get("PK2").unwrap().threshold, 1);
+    assert_eq!(quorum_map.get("PK3").unwrap().inner_sets.len(), 1);
+    let (_, format) = FbasAnalyzer::load_path(path, Basic::default()).unwrap();
+    assert_eq!(format, InputFormat::StellarCore);
+}
+
+#[test]
+fn test_nodes_from_api_response() {
+    use crate::json_parser::nodes_from_api_response;
+    use serde_json::json;
+
+    let nodes = json!([{"publicKey": "PK1", "quorumSet": null}]);
+    let records = nodes_from_api_response(nodes.clone()).unwrap();
+    assert_eq!(records.len(), 1);
+    assert_eq!(records[0].public_key, "PK1");
+    let wrapped = json!({"nodes": nodes, "organizations": []});
+    assert_eq!(nodes_from_api_response(wrapped).unwrap(), records);
+
+    // the regular format, and objects without a node list
+    let regular = json!({"nodes": [{"node": "PK1", "qset": null}]});
+    assert!(nodes_from_api_response(regular).is_err());
+    assert!(nodes_from_api_response(json!({"data": []})).is_err());
+}
+
+#[test]
+fn test_node_records() {
+    use crate::{FbasAnalyzer, NodeRecord, QuorumSetJson};
+    use batsat::callbacks::Basic;
+
+    let qset = QuorumSetJson {
+        threshold_percent: Some(67),
+        validators: vec!["PK1".into(), "PK2".into(), "PK3".into()],
+        ..Default::default()
+    };
+    let mut records: Vec<NodeRecord> = ["PK1", "PK2", "PK3"]
+        .into_iter()
+        .map(|v| NodeRecord {
+            public_key: v.into(),
+            quorum_set: Some(qset.clone()),
+            isp: Some("isp-a".into()),
+            ..Default::default()
+        })
+        .collect();
+    records[0].name = Some("first".into());
+    let analyzer = FbasAnalyzer::from_node_records(&records, Basic::default()).unwrap();
+    assert_eq!(analyzer.fbas.validators.len(), 3);
+    assert_eq!(
+        analyzer.describe_qset("PK1", &Default::default()).unwrap(),
+        "67% (3) of: PK1, PK2, PK3\n"
+    );
+    assert_eq!(analyzer.aliases()["PK1"], "first");
+    assert_eq!(analyzer.node_attribute("PK2", "isp"), Some("isp-a"));
+
+    // records round-trip through the stellarbeats format
+    let json = serde_json::to_string(&records).unwrap();
+    assert!(json.contains(r#""thresholdPercent":67"#));
+    assert_eq!(
+        serde_json::from_str::<Vec<NodeRecord>>(&json).unwrap(),
+        records
+    );
+
+    // schema errors tell what is wrong
+    let doc = serde_json::json!([{"publicKey": "PK1", "quorumSet": {"threshold": "two"}}]);
+    match crate::json_parser::node_records_from_json_value(doc) {
+        Err(FbasError::JsonError(message)) => assert!(message.contains("invalid type")),
+        other => panic!("{:?}", other),
+    }
+}
+
+#[test]
+fn test_streaming_parse() {
+    use crate::json_parser::{
+        collect_nodes_from_reader, json_from_path, node_records_from_json_value,
+        quorum_set_map_from_node_records,
+    };
+
+    // streaming gives the same networks as reading the whole document, in
+    // each of the formats
+    for path in [
+        "./tests/test_data/random/almost_symmetric_network_12_orgs_delete_prob_factor_3.json",
+        "./tests/test_data/random/almost_symmetric_network_6_orgs_delete_prob_factor_3_for_stellar_core.json",
+        "./tests/test_data/others/watchers.json",
+        "./tests/test_data/others/stellar_core_scp.json",
+    ] {
+        let records = node_records_from_json_value(json_from_path(path).unwrap()).unwrap();
+        let (mut diagnostics, mut watchers) = (vec![], vec![]);
+        let expected =
+            quorum_set_map_from_node_records(&records, 0, &mut diagnostics, &mut watchers)
+                .unwrap();
+        let file = std::fs::File::open(path).unwrap();
+        let collector = collect_nodes_from_reader(file, 0).unwrap();
+        assert_eq!(collector.quorum_set_map, expected, "{}", path);
+        assert_eq!(collector.watchers, watchers, "{}", path);
+    }
+
+    // errors are reported as when reading the whole document
+    let doc = r#"[{"publicKey": "PK1", "quorumSet": {"threshold": "two"}}]"#;
+    match collect_nodes_from_reader(doc.as_bytes(), 0) {
+        Err(FbasError::JsonError(message)) => assert!(message.contains("invalid type")),
+        other => panic!("{:?}", other.map(|c| c.quorum_set_map)),
+    }
+    let doc = r#"{"nodes": [{"node": "PK1", "qset": {"t": 1, "v": ["PK1"]}}"#;
+    assert!(matches!(
+        collect_nodes_from_reader(doc.as_bytes(), 0).map(|c| c.quorum_set_map),
+        Err(FbasError::ParseError(_))
+    ));
+}
+
+#[test]
+fn test_from_json_reader_and_str() {
+    use crate::{FbasAnalyzer, SolveStatus};
+    use batsat::callbacks::Basic;
+
+    let path = "./tests/test_data/random/almost_symmetric_network_6_orgs_delete_prob_factor_3_for_stellar_core.json";
+    let from_path = Fbas::from_json_path(path).unwrap();
+    let from_reader = Fbas::from_json_reader(std::fs::File::open(path).unwrap()).unwrap();
+    assert!(from_path.diff(&from_reader).unwrap().is_empty());
+    let from_str = Fbas::from_json_str(&std::fs::read_to_string(path).unwrap()).unwrap();
+    assert!(from_path.diff(&from_str).unwrap().is_empty());
+
+    let json = r#"[
+        {"publicKey": "PK1", "name": "first", "quorumSet": {"threshold": 2, "validators": ["PK1", "PK2"]}},
+        {"publicKey": "PK2", "quorumSet": {"threshold": 2, "validators": ["PK1", "PK2"]}},
+        {"publicKey": "PK3"}
+    ]"#;
+    let mut analyzer = FbasAnalyzer::from_json_str(json, Basic::default()).unwrap();
+    assert_eq!(analyzer.solve(), SolveStatus::UNSAT);
+    assert_eq!(analyzer.aliases()["PK1"], "first");
+    assert_eq!(analyzer.fbas.watchers().collect::<Vec<_>>(), ["PK3"]);
+    let mut analyzer = FbasAnalyzer::from_json_reader(json.as_bytes(), Basic::default()).unwrap();
+    assert_eq!(analyzer.solve(), SolveStatus::UNSAT);
+
+    assert!(matches!(
+        Fbas::from_json_str("[{"),
+        Err(FbasError::ParseError(_))
+    ));
+}
@@ -0,0 +1,353 @@
+use crate::{
+    diff::{find_culprits, find_top_tier_culprits},
+    fbas::FbasError,
+    Callbacks, FbasAnalyzer, FbasDiff, SnapshotChange, SplittingSets,
+};
+use std::collections::BTreeSet;
+
+// Snapshots are analyzed in order, each compared to the previous one. Crawls
+// taken at regular intervals mostly repeat the previous snapshot, so a
+// snapshot without changes (see `Fbas::diff`) takes the results of the
+// previous one instead of being analyzed again. Otherwise every analysis runs
+// from scratch: the SAT solvers keep no state that carries over between
+// networks.
+//
+// The critical sets tracked are the minimal blocking sets and the minimal
+// splitting sets up to a given size, the smallest groups of validators that
+// can halt or split the network.
+//
+// A change of the top tier is silent drift in who the network depends on,
+// so it is attributed to the changes causing it (see `FbasDiff::culprits`)
+// and can be sent as an alert to a `ReportSink`.
+
+/// The analysis of one snapshot of a `Timeline`, and how it differs from the
+/// previous one. Each list is in validator order.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct TimelineEntry {
+    /// The label of the snapshot, e.g. its file name.
+    pub snapshot: String,
+    /// The changes since the previous snapshot, with the intersection
+    /// status of both. Empty for the first snapshot.
+    pub changes: FbasDiff,
+    /// Whether quorums intersect, `None` if the solver didn't reach a
+    /// conclusion.
+    pub intersecting: Option<bool>,
+    /// The top tier, `None` if its computation was interrupted, see
+    /// `FbasAnalyzer::top_tier`.
+    pub top_tier: Option<Vec<String>>,
+    /// Validators that joined the top tier since the previous snapshot.
+    pub top_tier_joined: Vec<String>,
+    /// Validators that left the top tier since the previous snapshot.
+    pub top_tier_left: Vec<String>,
+    /// Organizations (see `FbasAnalyzer::organizations`) with validators in
+    /// the top tier now but none before, in organization order.
+    pub top_tier_orgs_joined: Vec<String>,
+    /// Organizations with validators in the top tier before but none now.
+    pub top_tier_orgs_left: Vec<String>,
+    /// If the top tier changed, the changes that each bring back the
+    /// previous one when reverted alone, as for `FbasDiff::culprits`.
+    pub top_tier_culprits: Vec<SnapshotChange>,
+    /// The minimal blocking sets up to the requested size, `None` if their
+    /// search was interrupted by its callbacks.
+    pub blocking_sets: Option<Vec<Vec<String>>>,
+    /// The minimal splitting sets up to the requested size.
+    pub splitting_sets: SplittingSets,
+    /// Whether the blocking or splitting sets differ from the previous
+    /// snapshot's.
+    pub critical_sets_changed: bool,
+}
+
+/// The analyses of an ordered series of snapshots of a network, see
+/// `Timeline::from_snapshots`.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Timeline {
+    pub entries: Vec<TimelineEntry>,
+}
+
+// The validators of `a` not in `b`.
+fn difference(a: &Option<Vec<String>>, b: &Option<Vec<String>>) -> Vec<String> {
+    let (Some(a), Some(b)) = (a, b) else {
+        return vec![];
+    };
+    let b: BTreeSet<_> = b.iter().collect();
+    a.iter().filter(|v| !b.contains(v)).cloned().collect()
+}
+
+// The organizations with validators in `top_tier`, in organization order.
+fn top_tier_orgs<Cb: Callbacks>(
+    analyzer: &FbasAnalyzer<Cb>,
+    top_tier: &Option<Vec<String>>,
+) -> Option<Vec<String>> {
+    let orgs = analyzer.organizations();
+    let top_tier = top_tier.as_ref()?;
+    let top_tier_orgs: BTreeSet<_> = top_tier.iter().filter_map(|v| orgs.org_of(v)).collect();
+    Some(top_tier_orgs.into_iter().map(str::to_string).collect())
+}
+
+// Analyzes `analyzer`, the snapshot following `previous` if any.
+fn analyze_snapshot<Cb: Callbacks, C: Callbacks>(
+    previous: Option<(&FbasAnalyzer<Cb>, &TimelineEntry)>,
+    snapshot: String,
+    mut analyzer: FbasAnalyzer<Cb>,
+    max_set_size: usize,
+    mut new_cb: impl FnMut() -> C,
+) -> Result<(FbasAnalyzer<Cb>, TimelineEntry), FbasError> {
+    let changes = match previous {
+        Some((prev, _)) => prev.fbas.diff(&analyzer.fbas)?,
+        None => FbasDiff::default(),
+    };
+    if let Some((_, prev_entry)) = previous.filter(|_| changes.is_empty()) {
+        let entry = TimelineEntry {
+            snapshot,
+            changes: FbasDiff {
+                intersecting_before: prev_entry.intersecting,
+                intersecting_after: prev_entry.intersecting,
+                ..changes
+            },
+            top_tier_joined: vec![],
+            top_tier_left: vec![],
+            top_tier_orgs_joined: vec![],
+            top_tier_orgs_left: vec![],
+            top_tier_culprits: vec![],
+            critical_sets_changed: false,
+            ..prev_entry.clone()
+        };
+        return Ok((analyzer, entry));
+    }
+
+    let intersecting = analyzer.solve().intersecting();
+    let top_tier = analyzer.top_tier(&mut new_cb)?;
+    let blocking_sets = analyzer.minimal_blocking_sets_with(max_set_size, new_cb())?;
+    let splitting_sets = analyzer.minimal_splitting_sets(max_set_size, &mut new_cb)?;
+    let mut entry = TimelineEntry {
+        snapshot,
+        changes,
+        intersecting,
+        top_tier,
+        blocking_sets,
+        splitting_sets,
+        ..Default::default()
+    };
+    if let Some((prev, prev_entry)) = previous {
+        entry.changes.intersecting_before = prev_entry.intersecting;
+        entry.changes.intersecting_after = intersecting;
+        entry.changes.culprits =
+            find_culprits(&prev.fbas, &analyzer.fbas, &entry.changes, &mut new_cb)?;
+        entry.top_tier_joined = difference(&entry.top_tier, &prev_entry.top_tier);
+        entry.top_tier_left = difference(&prev_entry.top_tier, &entry.top_tier);
+        if let (Some(prev_top_tier), true) = (&prev_entry.top_tier, entry.top_tier_changed()) {
+            let orgs_before = top_tier_orgs(prev, &prev_entry.top_tier);
+            let orgs_after = top_tier_orgs(&analyzer, &entry.top_tier);
+            entry.top_tier_orgs_joined = difference(&orgs_after, &orgs_before);
+            entry.top_tier_orgs_left = difference(&orgs_before, &orgs_after);
+            entry.top_tier_culprits = find_top_tier_culprits(
+                &prev.fbas,
+                &analyzer.fbas,
+                &entry.changes,
+                prev_top_tier,
+                &mut new_cb,
+            )?;
+        }
+        entry.critical_sets_changed = entry.blocking_sets != prev_entry.blocking_sets
+            || entry.splitting_sets != prev_entry.splitting_sets;
+    }
+    Ok((analyzer, entry))
+}
+
+impl TimelineEntry {
+    /// Whether validators joined or left the top tier since the previous
+    /// snapshot.
+    pub fn top_tier_changed(&self) -> bool {
+        !self.top_tier_joined.is_empty() || !self.top_tier_left.is_empty()
+    }
+
+    /// The alert for the change of the top tier since the previous snapshot,
+    /// `None` if it didn't change.
+    pub fn top_tier_alert(&self) -> Option<TopTierAlert> {
+        self.top_tier_changed().then(|| TopTierAlert {
+            snapshot: self.snapshot.clone(),
+            joined: self.top_tier_joined.clone(),
+            left: self.top_tier_left.clone(),
+            orgs_joined: self.top_tier_orgs_joined.clone(),
+            orgs_left: self.top_tier_orgs_left.clone(),
+            culprits: self.top_tier_culprits.clone(),
+        })
+    }
+}
+
+/// A change of the top tier between two snapshots, see
+/// `TimelineEntry::top_tier_alert`.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct TopTierAlert {
+    /// The label of the later snapshot.
+    pub snapshot: String,
+    /// Validators that joined the top tier, in validator order.
+    pub joined: Vec<String>,
+    /// Validators that left the top tier, in validator order.
+    pub left: Vec<String>,
+    /// Organizations that joined the top tier, in organization order.
+    pub orgs_joined: Vec<String>,
+    /// Organizations that left the top tier, in organization order.
+    pub orgs_left: Vec<String>,
+    /// The changes of the snapshot that each undo the change of the top
+    /// tier when reverted alone.
+    pub culprits: Vec<SnapshotChange>,
+}
+
+#[cfg(any(feature = "json", test))]
+impl TopTierAlert {
+    /// Serializes the alert to a line of JSON, the form `ReportSink`s
+    /// deliver it in. Each culprit is an object with the `change` (`added`,
+    /// `removed` or `qset_changed`) and the `validator` it is about.
+    pub fn to_json(&self) -> String {
+        let culprits: Vec<_> = self
+            .culprits
+            .iter()
+            .map(|culprit| {
+                let (change, validator) = match culprit {
+                    SnapshotChange::Added(v) => ("added", v),
+                    SnapshotChange::Removed(v) => ("removed", v),
+                    SnapshotChange::QsetChanged(v) => ("qset_changed", v),
+                };
+                serde_json::json!({ "change": change, "validator": validator })
+            })
+            .collect();
+        serde_json::json!({
+            "alert": "top_tier_changed",
+            "snapshot": self.snapshot,
+            "joined": self.joined,
+            "left": self.left,
+            "orgs_joined": self.orgs_joined,
+            "orgs_left": self.orgs_left,
+            "culprits": culprits,
+        })
+        .to_string()
+    }
+}
+
+impl Timeline {
+    /// Sends the alert of each snapshot whose top tier changed (see
+    /// `TimelineEntry::top_tier_alert`) to `sink`, in order, and returns
+    /// the number of alerts sent.
+    #[cfg(any(feature = "json", test))]
+    pub fn send_top_tier_alerts(
+        &self,
+        sink: &mut impl crate::ReportSink,
+    ) -> Result<usize, FbasError> {
+        let mut sent = 0;
+        for alert in self
+            .entries
+            .iter()
+            .filter_map(TimelineEntry::top_tier_alert)
+        {
+            sink.send_alert(&alert)?;
+            sent += 1;
+        }
+        Ok(sent)
+    }
+
+    /// Analyzes a series of snapshots of a network, labelled and in
+    /// chronological order: quorum intersection, the top tier, and the
+    /// minimal blocking and splitting sets of at most `max_set_size`
+    /// validators, along with the changes from one snapshot to the next.
+    /// Snapshots identical to the previous one (see `Fbas::diff`) are not
+    /// analyzed again. Intersection is solved by each snapshot's analyzer,
+    /// the other analyses by separate solvers driven by callbacks from
+    /// `new_cb`.
+    pub fn from_snapshots<Cb: Callbacks, C: Callbacks>(
+        snapshots: impl IntoIterator<Item = (String, FbasAnalyzer<Cb>)>,
+        max_set_size: usize,
+        mut new_cb: impl FnMut() -> C,
+    ) -> Result<Self, FbasError> {
+        let mut timeline = Timeline::default();
+        let mut previous: Option<FbasAnalyzer<Cb>> = None;
+        for (snapshot, analyzer) in snapshots {
+            let prev = previous.as_ref().zip(timeline.entries.last());
+            let (analyzer, entry) =
+                analyze_snapshot(prev, snapshot, analyzer, max_set_size, &mut new_cb)?;
+            timeline.entries.push(entry);
+            previous = Some(analyzer);
+        }
+        Ok(timeline)
+    }
+
+    /// Same as `from_snapshots`, for the `.json` files of `dir` in file name
+    /// order (e.g. timestamped crawls), labelled by file name and read with
+    /// `FbasAnalyzer::load_path`. Only two snapshots are held at a time.
+    #[cfg(any(feature = "json", test))]
+    pub fn from_json_dir<C: Callbacks>(
+        dir: &str,
+        max_set_size: usize,
+        mut new_cb: impl FnMut() -> C,
+    ) -> Result<Self, FbasError> {
+        let mut paths: Vec<_> = std::fs::read_dir(dir)
+            .map_err(|_| FbasError::IoError("fail to read directory"))?
+            .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+            .filter(|path| path.is_file() && path.extension().is_some_and(|ext| ext == "json"))
+            .collect();
+        paths.sort();
+        let mut timeline = Timeline::default();
+        let mut previous: Option<FbasAnalyzer<C>> = None;
+        for path in paths {
+            let snapshot = path
+                .file_name()
+                .map(|name| name.to_string_lossy().into_owned())
+                .unwrap_or_default();
+            let path = path
+                .to_str()
+                .ok_or(FbasError::IoError("path is not valid UTF-8"))?;
+            let (analyzer, _) = FbasAnalyzer::load_path(path, new_cb())?;
+            let prev = previous.as_ref().zip(timeline.entries.last());
+            let (analyzer, entry) =
+                analyze_snapshot(prev, snapshot, analyzer, max_set_size, &mut new_cb)?;
+            timeline.entries.push(entry);
+            previous = Some(analyzer);
+        }
+        Ok(timeline)
+    }
+}
+
+impl std::fmt::Display for Timeline {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for entry in &self.entries {
+            let intersecting = match entry.intersecting {
+                Some(true) => "OK",
+                Some(false) => "SPLIT",
+                None => "UNKNOWN",
+            };
+            write!(f, "{}: {}", entry.snapshot, intersecting)?;
+            let changes = &entry.changes;
+            if !changes.is_empty() {
+                write!(
+                    f,
+                    ", {} added, {} removed, {} qsets changed",
+                    changes.added.len(),
+                    changes.removed.len(),
+                    changes.changed.len()
+                )?;
+            }
+            for (i, culprit) in changes.culprits.iter().enumerate() {
+                let sep = if i == 0 { ", split by" } else { " or" };
+                write!(f, "{} {}", sep, culprit)?;
+            }
+            if let Some(top_tier) = &entry.top_tier {
+                write!(f, ", top tier of {}", top_tier.len())?;
+            }
+            for v in &entry.top_tier_joined {
+                write!(f, ", +{}", v)?;
+            }
+            for v in &entry.top_tier_left {
+                write!(f, ", -{}", v)?;
+            }
+            for (i, culprit) in entry.top_tier_culprits.iter().enumerate() {
+                let sep = if i == 0 { " by" } else { " or" };
+                write!(f, "{} {}", sep, culprit)?;
+            }
+            if entry.critical_sets_changed {
+                write!(f, ", critical sets changed")?;
+            }
+            writeln!(f)?;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,158 @@
+use crate::{
+    fbas::{Fbas, FbasError, InternalScpQuorumSet},
+    Callbacks, DiagnosticsSink, FbasAnalyzer, SolveStatus,
+};
+use itertools::Itertools;
+use std::{collections::BTreeMap, sync::Arc};
+
+// The network is analyzed under every resolution of the uncertain qsets, i.e.
+// every combination of one alternative per uncertain validator, each with its
+// own solver. The optimistic verdict is the best one over all resolutions and
+// the pessimistic verdict the worst one. Whether a split exists under some
+// resolution could be asked in a single formula with selectors for the
+// alternatives (as in `red_team`), but whether the network intersects under
+// some resolution could not, so resolutions are simply enumerated.
+
+/// The largest number of resolutions `solve_under_uncertainty` accepts, i.e.
+/// the product of the numbers of alternatives of the uncertain validators.
+pub const UNCERTAINTY_MAX_RESOLUTIONS: usize = 1024;
+
+/// Alternative qsets of validators whose configuration is uncertain, e.g.
+/// because crawls disagree on them.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct UncertainQsets {
+    alternatives: BTreeMap<String, Vec<InternalScpQuorumSet>>,
+}
+
+impl UncertainQsets {
+    /// Parses the alternatives from the compact text syntax of
+    /// `FbasAnalyzer::from_dsl`, where a validator may appear on several
+    /// lines, one per alternative, e.g.
+    ///
+    /// ```text
+    /// d: 2-of(a, b, c)
+    /// d: 1-of(a, b, c)
+    /// ```
+    pub fn from_dsl(dsl: &str) -> Result<Self, FbasError> {
+        Ok(Self {
+            alternatives: crate::dsl::qset_alternatives_from_dsl(dsl)?,
+        })
+    }
+
+    /// The uncertain validators, in the order of `Resolution`s.
+    pub fn validators(&self) -> Vec<&str> {
+        self.alternatives.keys().map(String::as_str).collect()
+    }
+}
+
+/// The alternative picked for each uncertain validator, by its position among
+/// the validator's alternatives in the input.
+pub type Resolution = BTreeMap<String, usize>;
+
+/// Outcome of `FbasAnalyzer::solve_under_uncertainty`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct UncertaintyReport {
+    /// The best verdict over all resolutions: UNSAT if the network has quorum
+    /// intersection under any of them.
+    pub optimistic: SolveStatus,
+    /// A resolution leading to `optimistic`.
+    pub optimistic_resolution: Resolution,
+    /// The worst verdict over all resolutions: SAT if the network splits
+    /// under any of them. Its validator indices are the ones of the analyzer.
+    pub pessimistic: SolveStatus,
+    /// A resolution leading to `pessimistic`.
+    pub pessimistic_resolution: Resolution,
+    /// The number of resolutions analyzed, fewer than all of them if the
+    /// verdict was found not to be robust early.
+    pub resolutions: usize,
+}
+
+impl UncertaintyReport {
+    /// Whether the uncertainty doesn't matter, i.e. the network intersects
+    /// under every resolution, or splits under every one.
+    pub fn is_robust(&self) -> bool {
+        matches!(
+            (&self.optimistic, &self.pessimistic),
+            (SolveStatus::UNSAT, SolveStatus::UNSAT) | (SolveStatus::SAT(_), SolveStatus::SAT(_))
+        )
+    }
+}
+
+// Rank of a verdict from the network's point of view: intersection is best,
+// then not knowing, then a split.
+fn rank(status: &SolveStatus) -> u8 {
+    match status {
+        SolveStatus::UNSAT => 0,
+        SolveStatus::UNKNOWN => 1,
+        SolveStatus::SAT(_) => 2,
+    }
+}
+
+impl<Cb: Callbacks> FbasAnalyzer<Cb> {
+    /// Checks quorum intersection under every resolution of the `uncertain`
+    /// qsets, which replace the qsets the uncertain validators have in this
+    /// network (include it among the alternatives to keep it as a
+    /// possibility). Each resolution is solved by a separate solver driven by
+    /// callbacks from `new_cb`, and its diagnostics are dropped.
+    pub fn solve_under_uncertainty<C: Callbacks>(
+        &self,
+        uncertain: &UncertainQsets,
+        mut new_cb: impl FnMut() -> C,
+    ) -> Result<UncertaintyReport, FbasError> {
+        let mut qsm = self.fbas.quorum_set_map()?;
+        if uncertain.alternatives.keys().any(|v| !qsm.contains_key(v)) {
+            return Err(FbasError::ParseError(
+                "Uncertain qset of an unknown validator",
+            ));
+        }
+        let num_resolutions = uncertain
+            .alternatives
+            .values()
+            .try_fold(1usize, |n, qsets| n.checked_mul(qsets.len()));
+        if num_resolutions.is_none_or(|n| n > UNCERTAINTY_MAX_RESOLUTIONS) {
+            return Err(FbasError::NetworkTooLarge(
+                "at most 1024 resolutions of uncertain qsets are analyzed",
+            ));
+        }
+
+        let mut report: Option<UncertaintyReport> = None;
+        // with no uncertain validator, this is the single empty resolution
+        for picks in uncertain
+            .alternatives
+            .values()
+            .map(|qsets| 0..qsets.len())
+            .multi_cartesian_product()
+        {
+            let resolution: Resolution =
+                uncertain.alternatives.keys().cloned().zip(picks).collect();
+            for (v, i) in &resolution {
+                qsm.insert(v.clone(), Arc::new(uncertain.alternatives[v][*i].clone()));
+            }
+            let fbas = Fbas::from_quorum_set_map(&qsm)?;
+            let mut analyzer = FbasAnalyzer::from_fbas(fbas, new_cb())?
+                .with_diagnostics_sink(DiagnosticsSink::Silent);
+            let status = analyzer.solve();
+            let report = report.get_or_insert_with(|| UncertaintyReport {
+                optimistic: status.clone(),
+                optimistic_resolution: resolution.clone(),
+                pessimistic: status.clone(),
+                pessimistic_resolution: resolution.clone(),
+                resolutions: 0,
+            });
+            report.resolutions += 1;
+            if rank(&status) < rank(&report.optimistic) {
+                report.optimistic = status.clone();
+                report.optimistic_resolution = resolution.clone();
+            }
+            if rank(&status) > rank(&report.pessimistic) {
+                report.pessimistic = status;
+                report.pessimistic_resolution = resolution;
+            }
+            // the extremes are reached, further resolutions can't change them
+            if report.optimistic == SolveStatus::UNSAT && rank(&report.pessimistic) == 2 {
+                break;
+            }
+        }
+        report.ok_or(FbasError::InternalError("No resolution was analyzed"))
+    }
+}
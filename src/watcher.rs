@@ -0,0 +1,108 @@
+use crate::{fbas::FbasError, Callbacks, FbasAnalyzer, ReportSink};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+// A watcher analyzes the snapshots of a network taken one round after the
+// other, and reports only those that changed: a snapshot with the same hash
+// as the previous one (see `FbasAnalyzer::snapshot_hash`) isn't analyzed
+// again, so the sink only ever gets reports of new configurations.
+//
+// Rounds are spaced by the interval, shifted by a random jitter each time so
+// that watchers started together don't all query the same source at once.
+// The randomness doesn't need to be good, so it comes from a xorshift
+// generator seeded by the clock rather than from a dependency.
+
+/// Periodic re-analysis of a network, reporting each new snapshot to a
+/// `ReportSink`, see `Watcher::run`.
+#[derive(Debug, Clone)]
+pub struct Watcher {
+    interval: Duration,
+    jitter: Duration,
+    last_snapshot_hash: Option<String>,
+    rng: u64,
+}
+
+impl Watcher {
+    /// A watcher whose rounds are `interval` apart, give or take up to
+    /// `jitter`.
+    pub fn new(interval: Duration, jitter: Duration) -> Self {
+        let seed = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |elapsed| elapsed.as_nanos() as u64);
+        Self {
+            interval,
+            jitter,
+            last_snapshot_hash: None,
+            // xorshift gets stuck at 0
+            rng: seed | 1,
+        }
+    }
+
+    /// Takes `hash` as the hash of the last snapshot reported, e.g. from
+    /// `ReportStore::latest_snapshot_hash`, so that a restarted watcher
+    /// doesn't report the same snapshot again.
+    pub fn with_last_snapshot_hash(mut self, hash: String) -> Self {
+        self.last_snapshot_hash = Some(hash);
+        self
+    }
+
+    /// The hash of the last snapshot reported, `None` before the first.
+    pub fn last_snapshot_hash(&self) -> Option<&str> {
+        self.last_snapshot_hash.as_deref()
+    }
+
+    /// The time to wait before the next round: the interval shifted by a
+    /// random amount between minus and plus the jitter.
+    pub fn next_delay(&mut self) -> Duration {
+        self.rng ^= self.rng << 13;
+        self.rng ^= self.rng >> 7;
+        self.rng ^= self.rng << 17;
+        let shift = self
+            .jitter
+            .mul_f64((self.rng >> 11) as f64 / (1u64 << 53) as f64);
+        if self.rng & 1 == 0 {
+            self.interval.saturating_add(shift)
+        } else {
+            self.interval.saturating_sub(shift)
+        }
+    }
+
+    /// Analyzes `analyzer` (see `FbasAnalyzer::into_report`) and sends the
+    /// report to `sink`, unless its snapshot has the same hash as the last
+    /// one reported. Returns whether it was reported.
+    pub fn analyze<Cb: Callbacks>(
+        &mut self,
+        mut analyzer: FbasAnalyzer<Cb>,
+        sink: &mut impl ReportSink,
+    ) -> Result<bool, FbasError> {
+        let hash = analyzer.snapshot_hash()?;
+        if self.last_snapshot_hash.as_ref() == Some(&hash) {
+            return Ok(false);
+        }
+        analyzer.try_solve()?;
+        sink.send(&analyzer.into_report(None)?)?;
+        self.last_snapshot_hash = Some(hash);
+        Ok(true)
+    }
+
+    /// Runs `rounds` rounds, each analyzing the snapshot returned by `fetch`
+    /// (see `analyze`) and then sleeping for `next_delay`, except after the
+    /// last one. Returns the number of snapshots reported. Stops at the first
+    /// error of `fetch`, the analysis or `sink`.
+    pub fn run<Cb: Callbacks>(
+        &mut self,
+        rounds: usize,
+        mut fetch: impl FnMut() -> Result<FbasAnalyzer<Cb>, FbasError>,
+        sink: &mut impl ReportSink,
+    ) -> Result<usize, FbasError> {
+        let mut reported = 0;
+        for round in 0..rounds {
+            if round > 0 {
+                std::thread::sleep(self.next_delay());
+            }
+            if self.analyze(fetch()?, sink)? {
+                reported += 1;
+            }
+        }
+        Ok(reported)
+    }
+}